@@ -7,6 +7,9 @@
 
 mod filemaker;
 mod parser;
+mod unit;
+
+pub use unit::control_unit;
 
 use common::spec::{
     artifact::{Model, Package},
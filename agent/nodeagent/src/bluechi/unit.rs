@@ -0,0 +1,80 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Start and stop the systemd units generated from `.kube` files
+
+use common::nodeagent::WorkloadCommand;
+
+/// Start or stop the unit of a model on this node
+///
+/// ### Parametets
+/// * `command: WorkloadCommand` - start or stop
+/// * `unit: &str` - unit name such as `version-display.service`
+/// ### Description
+/// Reload systemd first so that the quadlet generator picks up `.kube`
+/// files written since the last reload, then start or stop the unit.
+pub async fn control_unit(command: WorkloadCommand, unit: &str) -> common::Result<()> {
+    let verb = match command {
+        WorkloadCommand::Start => "start",
+        WorkloadCommand::Stop => "stop",
+        WorkloadCommand::Unspecified => return Err("workload command is not specified".into()),
+    };
+    validate_unit(unit)?;
+
+    systemctl(&["daemon-reload"]).await?;
+    systemctl(&[verb, unit]).await
+}
+
+/// Unit names come from the network, only accept plain service names
+fn validate_unit(unit: &str) -> common::Result<()> {
+    let valid = unit.ends_with(".service")
+        && unit.len() > ".service".len()
+        && unit
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '@'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("invalid unit name '{}'", unit).into())
+    }
+}
+
+async fn systemctl(args: &[&str]) -> common::Result<()> {
+    let output = tokio::process::Command::new("systemctl")
+        .args(args)
+        .output()
+        .await?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "systemctl {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_unit() {
+        assert!(validate_unit("version-display.service").is_ok());
+        assert!(validate_unit("app_1@x.service").is_ok());
+        assert!(validate_unit(".service").is_err());
+        assert!(validate_unit("version-display").is_err());
+        assert!(validate_unit("../etc/passwd.service").is_err());
+        assert!(validate_unit("a b.service").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_control_unit_rejects_unspecified_command() {
+        let result = control_unit(WorkloadCommand::Unspecified, "app.service").await;
+        assert!(result.is_err());
+    }
+}
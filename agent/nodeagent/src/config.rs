@@ -0,0 +1,249 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Runtime configuration of NodeAgent
+//!
+//! Holds the settings that the API server can change while NodeAgent is
+//! running, pushed through `ReceiveConfig` or returned at registration.
+//! Background loops read the current values on every iteration, so a change
+//! takes effect without restarting the agent.
+
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+
+/// Interval in seconds between heartbeats sent to the API server
+pub const HEARTBEAT_INTERVAL: &str = "heartbeat_interval";
+/// Interval in seconds between two full container inspections, used when
+/// Podman events are not available
+pub const CONTAINER_POLL_INTERVAL: &str = "container_poll_interval";
+/// Interval in seconds between two collections of container stats
+pub const CONTAINER_STATS_INTERVAL: &str = "container_stats_interval";
+/// Interval in seconds between two node resource reports
+pub const NODE_INFO_INTERVAL: &str = "node_info_interval";
+/// Address of the monitoring server, `host:port` or a full URL
+pub const MONITORING_TARGET: &str = "monitoring_target";
+/// One of `error`, `warn`, `info` or `debug`
+pub const LOG_LEVEL: &str = "log_level";
+
+static CONFIG: OnceLock<RwLock<NodeConfig>> = OnceLock::new();
+
+/// Verbosity of NodeAgent output, ordered from least to most verbose
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl std::str::FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "error" => Ok(LogLevel::Error),
+            "warn" | "warning" => Ok(LogLevel::Warn),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            _ => Err(format!("unknown log level '{}'", s)),
+        }
+    }
+}
+
+/// Settings of NodeAgent that can be changed at runtime
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeConfig {
+    pub heartbeat_interval: Duration,
+    pub container_poll_interval: Duration,
+    pub container_stats_interval: Duration,
+    pub node_info_interval: Duration,
+    /// Monitoring server URL, `None` to use the address from settings.yaml
+    pub monitoring_target: Option<String>,
+    pub log_level: LogLevel,
+}
+
+impl Default for NodeConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval: Duration::from_secs(3),
+            container_poll_interval: Duration::from_secs(1),
+            container_stats_interval: Duration::from_secs(5),
+            node_info_interval: Duration::from_secs(1),
+            monitoring_target: None,
+            log_level: LogLevel::Info,
+        }
+    }
+}
+
+impl NodeConfig {
+    /// Build the configuration resulting from applying settings to `self`
+    ///
+    /// Returns the new configuration and the keys that are not supported by
+    /// NodeAgent. Fails without partial changes if a supported key has an
+    /// invalid value.
+    pub fn with_settings(
+        &self,
+        settings: &HashMap<String, String>,
+    ) -> Result<(NodeConfig, Vec<String>), String> {
+        let mut config = self.clone();
+        let mut ignored = Vec::new();
+
+        for (key, value) in settings {
+            let value = value.trim();
+            match key.as_str() {
+                HEARTBEAT_INTERVAL => config.heartbeat_interval = parse_interval(key, value)?,
+                CONTAINER_POLL_INTERVAL => {
+                    config.container_poll_interval = parse_interval(key, value)?
+                }
+                CONTAINER_STATS_INTERVAL => {
+                    config.container_stats_interval = parse_interval(key, value)?
+                }
+                NODE_INFO_INTERVAL => config.node_info_interval = parse_interval(key, value)?,
+                MONITORING_TARGET => config.monitoring_target = parse_target(value),
+                LOG_LEVEL => config.log_level = value.parse()?,
+                _ => ignored.push(key.clone()),
+            }
+        }
+
+        ignored.sort();
+        Ok((config, ignored))
+    }
+}
+
+fn parse_interval(key: &str, value: &str) -> Result<Duration, String> {
+    match value.parse::<u64>() {
+        Ok(secs) if secs > 0 => Ok(Duration::from_secs(secs)),
+        _ => Err(format!(
+            "{} must be a positive number of seconds, got '{}'",
+            key, value
+        )),
+    }
+}
+
+fn parse_target(value: &str) -> Option<String> {
+    if value.is_empty() {
+        None
+    } else if value.contains("://") {
+        Some(value.to_string())
+    } else {
+        Some(format!("{}://{}", common::transport::scheme(), value))
+    }
+}
+
+fn config() -> &'static RwLock<NodeConfig> {
+    CONFIG.get_or_init(|| RwLock::new(NodeConfig::default()))
+}
+
+/// Current runtime configuration
+pub fn get() -> NodeConfig {
+    config().read().map(|c| c.clone()).unwrap_or_default()
+}
+
+/// Apply settings received from the API server
+///
+/// ### Parameters
+/// * `settings` - cluster settings, unsupported keys are ignored
+/// ### Returns
+/// Keys that were ignored, or an error if a value is invalid. Nothing is
+/// applied in case of an error.
+pub fn apply(settings: &HashMap<String, String>) -> Result<Vec<String>, String> {
+    let mut current = config().write().map_err(|e| e.to_string())?;
+    let (updated, ignored) = current.with_settings(settings)?;
+
+    if *current != updated {
+        println!("NodeAgent configuration changed: {:?}", updated);
+        *current = updated;
+    }
+    Ok(ignored)
+}
+
+/// Check whether messages of the given level are printed
+pub fn log_enabled(level: LogLevel) -> bool {
+    level <= get().log_level
+}
+
+/// Monitoring server URL to send container and node information to
+pub fn monitoring_server() -> String {
+    get()
+        .monitoring_target
+        .unwrap_or_else(common::monitoringserver::connect_server)
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_with_settings_applies_supported_keys() {
+        let (config, ignored) = NodeConfig::default()
+            .with_settings(&settings(&[
+                (HEARTBEAT_INTERVAL, "10"),
+                (CONTAINER_POLL_INTERVAL, "5"),
+                (CONTAINER_STATS_INTERVAL, "15"),
+                (NODE_INFO_INTERVAL, "2"),
+                (MONITORING_TARGET, "10.0.0.5:47003"),
+                (LOG_LEVEL, "debug"),
+                ("cluster_id", "default-cluster"),
+            ]))
+            .unwrap();
+
+        assert_eq!(config.heartbeat_interval, Duration::from_secs(10));
+        assert_eq!(config.container_poll_interval, Duration::from_secs(5));
+        assert_eq!(config.container_stats_interval, Duration::from_secs(15));
+        assert_eq!(config.node_info_interval, Duration::from_secs(2));
+        assert_eq!(
+            config.monitoring_target,
+            Some("http://10.0.0.5:47003".to_string())
+        );
+        assert_eq!(config.log_level, LogLevel::Debug);
+        assert_eq!(ignored, vec!["cluster_id".to_string()]);
+    }
+
+    #[test]
+    fn test_with_settings_rejects_invalid_values() {
+        let base = NodeConfig::default();
+        assert!(base
+            .with_settings(&settings(&[(HEARTBEAT_INTERVAL, "0")]))
+            .is_err());
+        assert!(base
+            .with_settings(&settings(&[(CONTAINER_POLL_INTERVAL, "fast")]))
+            .is_err());
+        assert!(base
+            .with_settings(&settings(&[(LOG_LEVEL, "verbose")]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_empty_monitoring_target_restores_default() {
+        let (config, _) = NodeConfig::default()
+            .with_settings(&settings(&[(MONITORING_TARGET, "http://monitor:47003")]))
+            .unwrap();
+        assert_eq!(
+            config.monitoring_target,
+            Some("http://monitor:47003".to_string())
+        );
+
+        let (config, _) = config
+            .with_settings(&settings(&[(MONITORING_TARGET, "")]))
+            .unwrap();
+        assert!(config.monitoring_target.is_none());
+    }
+
+    #[test]
+    fn test_log_level_order() {
+        assert!(LogLevel::Error < LogLevel::Warn);
+        assert!(LogLevel::Info < LogLevel::Debug);
+        assert_eq!("WARN".parse::<LogLevel>().unwrap(), LogLevel::Warn);
+    }
+}
@@ -1,7 +1,8 @@
 use common::nodeagent::node_agent_connection_server::NodeAgentConnection;
 use common::nodeagent::{
-    ConfigRequest, ConfigResponse, HandleYamlRequest, HandleYamlResponse, HeartbeatRequest,
-    HeartbeatResponse, NodeRegistrationRequest, NodeRegistrationResponse, StatusAck, StatusReport,
+    ConfigRequest, ConfigResponse, HandleWorkloadRequest, HandleWorkloadResponse,
+    HandleYamlRequest, HandleYamlResponse, HeartbeatRequest, HeartbeatResponse,
+    NodeRegistrationRequest, NodeRegistrationResponse, StatusAck, StatusReport, WorkloadCommand,
 };
 use tokio::sync::mpsc;
 use tonic::{Request, Response, Status};
@@ -56,6 +57,31 @@ impl NodeAgentConnection for NodeAgentReceiver {
         }
     }
 
+    /// Start or stop the unit of a model on this node
+    ///
+    /// Used by ActionController to run and drain workloads on nodes that are
+    /// managed by NodeAgent instead of Bluechi.
+    async fn handle_workload(
+        &self,
+        request: Request<HandleWorkloadRequest>,
+    ) -> Result<Response<HandleWorkloadResponse>, Status> {
+        let req = request.into_inner();
+        let command = WorkloadCommand::try_from(req.command).unwrap_or_default();
+        println!("Got a workload request: {:?} {}", command, req.unit);
+
+        let response = match crate::bluechi::control_unit(command, &req.unit).await {
+            Ok(()) => HandleWorkloadResponse {
+                status: true,
+                desc: format!("{} {:?} done", req.unit, command),
+            },
+            Err(e) => HandleWorkloadResponse {
+                status: false,
+                desc: e.to_string(),
+            },
+        };
+        Ok(Response::new(response))
+    }
+
     /// Register this node with the API server
     async fn register_node(
         &self,
@@ -130,12 +156,24 @@ impl NodeAgentConnection for NodeAgentReceiver {
         println!("Processing ReceiveConfig request");
         let req = request.into_inner();
 
-        // TODO: Apply configuration changes
         println!("Received config with {} settings", req.config.len());
 
-        let response = ConfigResponse {
-            applied: true,
-            message: "Configuration applied successfully".to_string(),
+        let response = match crate::config::apply(&req.config) {
+            Ok(ignored) if ignored.is_empty() => ConfigResponse {
+                applied: true,
+                message: "Configuration applied successfully".to_string(),
+            },
+            Ok(ignored) => ConfigResponse {
+                applied: true,
+                message: format!(
+                    "Configuration applied, unsupported keys ignored: {}",
+                    ignored.join(", ")
+                ),
+            },
+            Err(e) => ConfigResponse {
+                applied: false,
+                message: format!("Configuration rejected: {}", e),
+            },
         };
 
         Ok(Response::new(response))
@@ -242,4 +280,46 @@ spec:
         assert_eq!(status.code(), tonic::Code::Unavailable);
         assert!(status.message().starts_with("cannot send condition:"));
     }
+
+    #[tokio::test]
+    async fn test_receive_config_rejects_invalid_value() {
+        let (tx, _rx) = mpsc::channel(1);
+        let receiver = NodeAgentReceiver::new(
+            tx,
+            "test-node".to_string(),
+            "test-host".to_string(),
+            "192.168.1.100".to_string(),
+        );
+
+        let mut config = std::collections::HashMap::new();
+        config.insert("heartbeat_interval".to_string(), "-1".to_string());
+        let request = Request::new(common::nodeagent::ConfigRequest { config });
+
+        let response = receiver.receive_config(request).await.unwrap().into_inner();
+        assert!(!response.applied);
+        assert!(response.message.contains("heartbeat_interval"));
+    }
+
+    #[tokio::test]
+    async fn test_handle_workload_rejects_invalid_unit() {
+        let (tx, _rx) = mpsc::channel(1);
+        let receiver = NodeAgentReceiver::new(
+            tx,
+            "test-node".to_string(),
+            "test-host".to_string(),
+            "192.168.1.100".to_string(),
+        );
+
+        let request = Request::new(common::nodeagent::HandleWorkloadRequest {
+            command: common::nodeagent::WorkloadCommand::Stop.into(),
+            unit: "../hellow-core".to_string(),
+        });
+        let response = receiver
+            .handle_workload(request)
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!response.status);
+        assert!(response.desc.contains("invalid unit name"));
+    }
 }
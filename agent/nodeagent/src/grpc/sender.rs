@@ -16,6 +16,7 @@ use common::statemanager::{
 };
 
 use common::monitoringserver::monitoring_server_connection_client::MonitoringServerConnectionClient;
+use common::transport::Component;
 use tonic::{Request, Status};
 
 /// Sender for making gRPC requests to Monitoring Server
@@ -29,7 +30,9 @@ impl NodeAgentSender {
         action: Action,
     ) -> Result<tonic::Response<Response>, Status> {
         let addr = common::statemanager::connect_server();
-        let client = StateManagerConnectionClient::connect(addr).await;
+        let client = common::transport::connect(Component::StateManager, addr)
+            .await
+            .map(StateManagerConnectionClient::new);
         match client {
             Ok(mut client) => {
                 // Send the action
@@ -50,9 +53,12 @@ impl NodeAgentSender {
         &mut self,
         container_list: ContainerList,
     ) -> Result<tonic::Response<SendContainerListResponse>, Status> {
-        let client =
-            MonitoringServerConnectionClient::connect(common::monitoringserver::connect_server())
-                .await;
+        let client = common::transport::connect(
+            Component::MonitoringServer,
+            crate::config::monitoring_server(),
+        )
+        .await
+        .map(MonitoringServerConnectionClient::new);
 
         match client {
             Ok(mut client) => {
@@ -73,9 +79,12 @@ impl NodeAgentSender {
         &mut self,
         node_info: NodeInfo,
     ) -> Result<tonic::Response<SendNodeInfoResponse>, Status> {
-        let client =
-            MonitoringServerConnectionClient::connect(common::monitoringserver::connect_server())
-                .await;
+        let client = common::transport::connect(
+            Component::MonitoringServer,
+            crate::config::monitoring_server(),
+        )
+        .await
+        .map(MonitoringServerConnectionClient::new);
 
         match client {
             Ok(mut client) => {
@@ -94,8 +103,12 @@ impl NodeAgentSender {
         &mut self,
         container_list: ContainerList,
     ) -> Result<tonic::Response<SendContainerListResponse>, Status> {
-        let client =
-            StateManagerConnectionClient::connect(common::statemanager::connect_server()).await;
+        let client = common::transport::connect(
+            Component::StateManager,
+            common::statemanager::connect_server(),
+        )
+        .await
+        .map(StateManagerConnectionClient::new);
 
         match client {
             Ok(mut client) => {
@@ -117,7 +130,9 @@ impl NodeAgentSender {
         registration_request: NodeRegistrationRequest,
     ) -> Result<tonic::Response<NodeRegistrationResponse>, Status> {
         let addr = common::apiserver::connect_grpc_server();
-        let client = ApiServerConnectionClient::connect(addr).await;
+        let client = common::transport::connect(Component::ApiServer, addr)
+            .await
+            .map(ApiServerConnectionClient::new);
 
         match client {
             Ok(mut client) => {
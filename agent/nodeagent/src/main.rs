@@ -5,11 +5,13 @@
 
 use common::nodeagent::HandleYamlRequest;
 mod bluechi;
+pub mod config;
 pub mod grpc;
 pub mod manager;
 pub mod resource;
 
 use common::nodeagent::node_agent_connection_server::NodeAgentConnectionServer;
+use common::transport::Component;
 use tokio::sync::mpsc::{channel, Receiver, Sender};
 
 /// Launches the NodeAgentManager in an asynchronous task.
@@ -32,14 +34,21 @@ async fn launch_manager(rx_grpc: Receiver<HandleYamlRequest>, hostname: String)
                 node_id: node_id.clone(),
                 hostname: hostname.clone(),
                 ip_address: config.host.ip.clone(),
-                metadata: std::collections::HashMap::new(), // Add empty metadata
+                metadata: registration_metadata(&hostname),
                 resources: None, // Use None if NodeResources doesn't exist, or create the correct struct
                 role: 0,         // Use integer instead of string (0 = worker, 1 = master, etc.)
             };
 
             // Register with API server
             match sender.register_with_api_server(registration_request).await {
-                Ok(_) => println!("Successfully registered with API server"),
+                Ok(response) => {
+                    println!("Successfully registered with API server");
+                    if let Some(cluster_config) = response.into_inner().cluster_config {
+                        if let Err(e) = config::apply(&cluster_config.settings) {
+                            eprintln!("Failed to apply cluster configuration: {}", e);
+                        }
+                    }
+                }
                 Err(e) => eprintln!("Failed to register with API server: {:?}", e),
             }
 
@@ -47,9 +56,7 @@ async fn launch_manager(rx_grpc: Receiver<HandleYamlRequest>, hostname: String)
             let mut sender_clone = sender.clone();
             let node_id_clone = node_id.clone();
             tokio::spawn(async move {
-                let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3));
                 loop {
-                    interval.tick().await;
                     let heartbeat_request = common::nodeagent::HeartbeatRequest {
                         node_id: node_id_clone.clone(),
                         timestamp: std::time::SystemTime::now()
@@ -59,8 +66,12 @@ async fn launch_manager(rx_grpc: Receiver<HandleYamlRequest>, hostname: String)
                     };
                     // Fix: call on instance, not static method
                     if let Err(e) = sender_clone.send_heartbeat(heartbeat_request).await {
-                        eprintln!("Failed to send heartbeat: {:?}", e);
+                        if config::log_enabled(config::LogLevel::Warn) {
+                            eprintln!("Failed to send heartbeat: {:?}", e);
+                        }
                     }
+                    // Read on every tick so that a pushed interval applies at once
+                    tokio::time::sleep(config::get().heartbeat_interval).await;
                 }
             });
 
@@ -75,12 +86,33 @@ async fn launch_manager(rx_grpc: Receiver<HandleYamlRequest>, hostname: String)
     }
 }
 
+/// Builds the metadata sent with the node registration.
+///
+/// The `runtime` entry tells the cluster whether workloads on this node are
+/// managed by Bluechi or by NodeAgent, as configured in settings.yaml.
+fn registration_metadata(hostname: &str) -> std::collections::HashMap<String, String> {
+    let config = common::setting::get_config();
+    let runtime = if hostname.trim().eq_ignore_ascii_case(&config.host.name) {
+        config.host.r#type.clone()
+    } else {
+        config
+            .guest
+            .iter()
+            .flatten()
+            .find(|guest| hostname.trim().eq_ignore_ascii_case(&guest.name))
+            .map(|guest| guest.r#type.clone())
+            .unwrap_or_else(|| "nodeagent".to_string())
+    };
+
+    let mut metadata = std::collections::HashMap::new();
+    metadata.insert("runtime".to_string(), runtime);
+    metadata
+}
+
 /// Initializes the NodeAgent gRPC server.
 ///
 /// Sets up the gRPC service and starts listening for incoming requests.
 async fn initialize(tx_grpc: Sender<HandleYamlRequest>, hostname: String) {
-    use tonic::transport::Server;
-
     let config = common::setting::get_config();
     let node_id = format!("{}-{}", hostname, config.host.ip);
     let ip_address = config.host.ip.clone();
@@ -103,7 +135,14 @@ async fn initialize(tx_grpc: Sender<HandleYamlRequest>, hostname: String) {
     .expect("nodeagent address parsing error");
     println!("NodeAgent listening on {}", addr);
 
-    let _ = Server::builder()
+    let mut builder = match common::transport::server(Component::NodeAgent) {
+        Ok(builder) => builder,
+        Err(e) => {
+            eprintln!("Failed to configure NodeAgent gRPC server: {}", e);
+            return;
+        }
+    };
+    let _ = builder
         .add_service(NodeAgentConnectionServer::new(server))
         .serve(addr)
         .await;
@@ -125,6 +164,7 @@ async fn main() {
     .trim()
     .to_string();
     println!("Starting NodeAgent on host: {}", hostname);
+    common::transport::set_local_component(Component::NodeAgent);
 
     let (tx_grpc, rx_grpc) = channel::<HandleYamlRequest>(100);
     let mgr = launch_manager(rx_grpc, hostname.clone());
@@ -4,7 +4,8 @@
 //! a gRPC sender for communicating with the monitoring server or other services.
 //! It is designed to be thread-safe and run in an async context.
 use crate::grpc::sender::NodeAgentSender;
-use common::monitoringserver::{ContainerInfo, ContainerList};
+use crate::resource::cache::{ContainerCache, ContainerDelta};
+use common::monitoringserver::ContainerList;
 use common::nodeagent::HandleYamlRequest;
 use common::Result;
 use std::sync::Arc;
@@ -18,6 +19,8 @@ pub struct NodeAgentManager {
     rx_grpc: Arc<Mutex<mpsc::Receiver<HandleYamlRequest>>>,
     /// gRPC sender for monitoring server
     sender: Arc<Mutex<NodeAgentSender>>,
+    /// Last known containers of this node
+    containers: Arc<Mutex<ContainerCache>>,
     // Add other shared state as needed
     hostname: String,
 }
@@ -31,6 +34,7 @@ impl NodeAgentManager {
         Self {
             rx_grpc: Arc::new(Mutex::new(rx)),
             sender: Arc::new(Mutex::new(NodeAgentSender::default())),
+            containers: Arc::new(Mutex::new(ContainerCache::default())),
             hostname,
         }
     }
@@ -63,59 +67,119 @@ impl NodeAgentManager {
         Ok(())
     }
 
-    /// Background task: Periodically gathers container info using inspect().
+    /// Background task: Keeps the container cache in sync with Podman events.
     ///
-    /// This runs in an infinite loop and logs or processes container info as needed.
-    async fn gather_container_info_loop(&self) {
-        use crate::resource::container::inspect;
-        use tokio::time::{sleep, Duration};
-
-        // This is the previous container list for comparison
-        let mut previous_container_list = Vec::new();
+    /// The full container list is inspected once, then only the containers named
+    /// in lifecycle events are re-inspected. When the events stream is lost the
+    /// list is inspected again after the container poll interval, which is also
+    /// the polling rate when Podman events are not available at all.
+    async fn watch_container_events_loop(&self) {
+        use crate::resource::container::{inspect, inspect_container};
+        use crate::resource::events::{watch, ContainerEvent};
+        use tokio::time::sleep;
 
         loop {
-            let container_list = inspect(self.hostname.clone()).await.unwrap_or_default();
-            let node = self.hostname.clone();
-
-            // Send the container info to the monitoring server
-            {
-                let mut sender = self.sender.lock().await;
-                if let Err(e) = sender
-                    .send_container_list(ContainerList {
-                        node_name: node.clone(),
-                        containers: container_list.clone(),
-                    })
-                    .await
-                {
-                    eprintln!("[NodeAgent] Error sending container info: {}", e);
+            // Subscribe before the full inspection so that no change is missed
+            let (tx, mut rx) = mpsc::channel(64);
+            let watcher = tokio::spawn(watch(tx));
+
+            match inspect(self.hostname.clone()).await {
+                Ok(list) => {
+                    let delta = self.containers.lock().await.replace_all(list);
+                    self.publish_containers(delta).await;
                 }
+                Err(e) => eprintln!("[NodeAgent] Error inspecting containers: {}", e),
+            }
+
+            while let Some(event) = rx.recv().await {
+                let delta = match event {
+                    ContainerEvent::Changed(id) => {
+                        match inspect_container(&id, &self.hostname).await {
+                            Ok(info) => self.containers.lock().await.upsert(info),
+                            // Gone before it could be inspected
+                            Err(_) => self.containers.lock().await.remove(&id),
+                        }
+                    }
+                    ContainerEvent::Removed(id) => self.containers.lock().await.remove(&id),
+                };
+                self.publish_containers(delta).await;
             }
 
-            // Check if the container list is changed from the previous one except for ContainerList.stats
-            // (which is not included in the comparison)
-            if !containers_equal_except_stats(&previous_container_list, &container_list) {
-                // println!(
-                //     "Container list changed for node: {}. Previous: {:?}, Current: {:?}",
-                //     node, previous_container_list, container_list
-                // );
+            if let Ok(Err(e)) = watcher.await {
+                if crate::config::log_enabled(crate::config::LogLevel::Debug) {
+                    println!("[NodeAgent] Podman events not available: {}", e);
+                }
+            }
+            sleep(crate::config::get().container_poll_interval).await;
+        }
+    }
 
-                // Save the previous container list for comparison
-                previous_container_list = container_list.clone();
+    /// Background task: Periodically collects stats of the known containers.
+    ///
+    /// Only containers whose stats changed are sent to the monitoring server.
+    async fn gather_container_stats_loop(&self) {
+        use crate::resource::container::{get_stats, stats_to_map};
+        use tokio::time::sleep;
 
-                // Send the changed container list to the state manager
-                let mut sender = self.sender.lock().await;
-                if let Err(e) = sender
-                    .send_changed_container_list(ContainerList {
-                        node_name: node.clone(),
-                        containers: container_list,
-                    })
+        loop {
+            sleep(crate::config::get().container_stats_interval).await;
+
+            let ids = self.containers.lock().await.ids();
+            let mut changed = Vec::new();
+            for id in ids {
+                let Ok(stats) = get_stats(&id).await else {
+                    continue;
+                };
+                if let Some(info) = self
+                    .containers
+                    .lock()
                     .await
+                    .update_stats(&id, stats_to_map(&stats))
                 {
-                    eprintln!("[NodeAgent] Error sending changed container list: {}", e);
+                    changed.push(info);
                 }
             }
 
-            sleep(Duration::from_secs(1)).await;
+            self.publish_containers(ContainerDelta {
+                changed,
+                ..Default::default()
+            })
+            .await;
+        }
+    }
+
+    /// Sends a container delta to the monitoring server, and the full container
+    /// list to the state manager when the lifecycle of a container changed.
+    async fn publish_containers(&self, delta: ContainerDelta) {
+        if delta.is_empty() {
+            return;
+        }
+        let node = self.hostname.clone();
+        let mut sender = self.sender.lock().await;
+
+        if let Err(e) = sender
+            .send_container_list(ContainerList {
+                node_name: node.clone(),
+                containers: delta.changed,
+                removed_ids: delta.removed,
+            })
+            .await
+        {
+            eprintln!("[NodeAgent] Error sending container info: {}", e);
+        }
+
+        if delta.lifecycle_changed {
+            let containers = self.containers.lock().await.list();
+            if let Err(e) = sender
+                .send_changed_container_list(ContainerList {
+                    node_name: node,
+                    containers,
+                    removed_ids: vec![],
+                })
+                .await
+            {
+                eprintln!("[NodeAgent] Error sending changed container list: {}", e);
+            }
         }
     }
 
@@ -125,7 +189,7 @@ impl NodeAgentManager {
     async fn gather_node_info_loop(&self) {
         use crate::resource::nodeinfo::extract_node_info_delta;
         use common::monitoringserver::NodeInfo;
-        use tokio::time::{sleep, Duration};
+        use tokio::time::sleep;
 
         loop {
             let node_info_data = extract_node_info_delta();
@@ -156,29 +220,32 @@ impl NodeAgentManager {
                 }
             }
 
-            println!(
-                "[NodeInfo] CPU: {:.2}%, CPU Count: {}, GPU Count: {}, Mem: {}/{} KB ({:.2}%), Net RX: {} B, Net TX: {} B, Disk Read: {} B, Disk Write: {} B, OS: {}, Arch: {}, IP: {}",
-                node_info.cpu_usage,
-                node_info.cpu_count,
-                node_info.gpu_count,
-                node_info.used_memory,
-                node_info.total_memory,
-                node_info.mem_usage,
-                node_info.rx_bytes,
-                node_info.tx_bytes,
-                node_info.read_bytes,
-                node_info.write_bytes,
-                node_info.os,
-                node_info.arch,
-                node_info.ip
-            );
-            sleep(Duration::from_secs(1)).await;
+            if crate::config::log_enabled(crate::config::LogLevel::Info) {
+                println!(
+                    "[NodeInfo] CPU: {:.2}%, CPU Count: {}, GPU Count: {}, Mem: {}/{} KB ({:.2}%), Net RX: {} B, Net TX: {} B, Disk Read: {} B, Disk Write: {} B, OS: {}, Arch: {}, IP: {}",
+                    node_info.cpu_usage,
+                    node_info.cpu_count,
+                    node_info.gpu_count,
+                    node_info.used_memory,
+                    node_info.total_memory,
+                    node_info.mem_usage,
+                    node_info.rx_bytes,
+                    node_info.tx_bytes,
+                    node_info.read_bytes,
+                    node_info.write_bytes,
+                    node_info.os,
+                    node_info.arch,
+                    node_info.ip
+                );
+            }
+            sleep(crate::config::get().node_info_interval).await;
         }
     }
 
     /// Runs the NodeAgentManager event loop.
     ///
-    /// Spawns the gRPC processing task and the container info gatherer, and waits for them to finish.
+    /// Spawns the gRPC processing task, the container watcher and the stats and node info
+    /// gatherers, and waits for them to finish.
     pub async fn run(self) -> Result<()> {
         let arc_self = Arc::new(self);
         let grpc_manager = Arc::clone(&arc_self);
@@ -188,8 +255,12 @@ impl NodeAgentManager {
             }
         });
         let container_manager = Arc::clone(&arc_self);
-        let container_gatherer = tokio::spawn(async move {
-            container_manager.gather_container_info_loop().await;
+        let container_watcher = tokio::spawn(async move {
+            container_manager.watch_container_events_loop().await;
+        });
+        let stats_manager = Arc::clone(&arc_self);
+        let stats_gatherer = tokio::spawn(async move {
+            stats_manager.gather_container_stats_loop().await;
         });
 
         // Spawn a background task to periodically extract and print system info
@@ -197,27 +268,17 @@ impl NodeAgentManager {
         let nodeinfo_task = tokio::spawn(async move {
             nodeinfo_manager.gather_node_info_loop().await;
         });
-        let _ = tokio::try_join!(grpc_processor, container_gatherer, nodeinfo_task);
+        let _ = tokio::try_join!(
+            grpc_processor,
+            container_watcher,
+            stats_gatherer,
+            nodeinfo_task
+        );
         println!("NodeAgentManager stopped");
         Ok(())
     }
 }
 
-fn containers_equal_except_stats<'a>(a: &'a [ContainerInfo], b: &'a [ContainerInfo]) -> bool {
-    if a.len() != b.len() {
-        return false;
-    }
-    a.iter().zip(b.iter()).all(|(c1, c2)| {
-        c1.id == c2.id
-            && c1.names == c2.names
-            && c1.image == c2.image
-            && c1.state == c2.state
-            && c1.config == c2.config
-            && c1.annotation == c2.annotation
-        // do NOT compare c1.stats/c2.stats
-    })
-}
-
 //unit test cases
 #[cfg(test)]
 mod tests {
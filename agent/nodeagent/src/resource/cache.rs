@@ -0,0 +1,213 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Last known state of the containers on this node
+//!
+//! The cache is updated from Podman events and periodic stats collection and
+//! reports what changed, so that only deltas are sent to the monitoring
+//! server.
+
+use common::monitoringserver::ContainerInfo;
+use std::collections::HashMap;
+
+/// Changes resulting from one cache update
+#[derive(Debug, Default, PartialEq)]
+pub struct ContainerDelta {
+    /// Containers that were added or whose information changed
+    pub changed: Vec<ContainerInfo>,
+    /// IDs of containers that no longer exist
+    pub removed: Vec<String>,
+    /// Whether anything other than stats changed
+    pub lifecycle_changed: bool,
+}
+
+impl ContainerDelta {
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Containers of this node keyed by container ID
+#[derive(Debug, Default)]
+pub struct ContainerCache {
+    containers: HashMap<String, ContainerInfo>,
+}
+
+impl ContainerCache {
+    /// Replace the whole cache with a freshly inspected container list
+    pub fn replace_all(&mut self, list: Vec<ContainerInfo>) -> ContainerDelta {
+        let mut delta = ContainerDelta::default();
+        let ids: Vec<String> = list.iter().map(|c| c.id.clone()).collect();
+
+        let mut removed: Vec<String> = self
+            .containers
+            .keys()
+            .filter(|id| !ids.contains(id))
+            .cloned()
+            .collect();
+        removed.sort();
+        for id in removed {
+            let mut removal = self.remove(&id);
+            delta.removed.append(&mut removal.removed);
+            delta.lifecycle_changed = true;
+        }
+
+        for info in list {
+            let mut update = self.upsert(info);
+            delta.changed.append(&mut update.changed);
+            delta.lifecycle_changed |= update.lifecycle_changed;
+        }
+
+        delta
+    }
+
+    /// Add or update a single container
+    pub fn upsert(&mut self, info: ContainerInfo) -> ContainerDelta {
+        let mut delta = ContainerDelta::default();
+
+        match self.containers.get(&info.id) {
+            Some(previous) if *previous == info => {}
+            Some(previous) => {
+                delta.lifecycle_changed = !same_except_stats(previous, &info);
+                delta.changed.push(info.clone());
+            }
+            None => {
+                delta.lifecycle_changed = true;
+                delta.changed.push(info.clone());
+            }
+        }
+
+        self.containers.insert(info.id.clone(), info);
+        delta
+    }
+
+    /// Drop a container from the cache
+    pub fn remove(&mut self, id: &str) -> ContainerDelta {
+        let mut delta = ContainerDelta::default();
+        if self.containers.remove(id).is_some() {
+            delta.removed.push(id.to_string());
+            delta.lifecycle_changed = true;
+        }
+        delta
+    }
+
+    /// Store new stats of a container
+    ///
+    /// Returns the updated container if its stats changed.
+    pub fn update_stats(
+        &mut self,
+        id: &str,
+        stats: HashMap<String, String>,
+    ) -> Option<ContainerInfo> {
+        let container = self.containers.get_mut(id)?;
+        if container.stats == stats {
+            return None;
+        }
+        container.stats = stats;
+        Some(container.clone())
+    }
+
+    /// IDs of all cached containers
+    pub fn ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.containers.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// All cached containers ordered by ID
+    pub fn list(&self) -> Vec<ContainerInfo> {
+        let mut list: Vec<ContainerInfo> = self.containers.values().cloned().collect();
+        list.sort_by(|a, b| a.id.cmp(&b.id));
+        list
+    }
+}
+
+fn same_except_stats(a: &ContainerInfo, b: &ContainerInfo) -> bool {
+    a.id == b.id
+        && a.names == b.names
+        && a.image == b.image
+        && a.state == b.state
+        && a.config == b.config
+        && a.annotation == b.annotation
+    // do NOT compare a.stats/b.stats
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_container(id: &str, status: &str, memory: &str) -> ContainerInfo {
+        let mut state = HashMap::new();
+        state.insert("Status".to_string(), status.to_string());
+        let mut stats = HashMap::new();
+        stats.insert("MemoryUsage".to_string(), memory.to_string());
+
+        ContainerInfo {
+            id: id.to_string(),
+            names: vec![id.to_string()],
+            image: "helloworld".to_string(),
+            state,
+            stats,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_replace_all_reports_added_and_removed() {
+        let mut cache = ContainerCache::default();
+        let delta = cache.replace_all(vec![
+            create_container("a", "running", "1"),
+            create_container("b", "running", "1"),
+        ]);
+        assert_eq!(delta.changed.len(), 2);
+        assert!(delta.lifecycle_changed);
+
+        let delta = cache.replace_all(vec![create_container("a", "running", "1")]);
+        assert!(delta.changed.is_empty());
+        assert_eq!(delta.removed, vec!["b".to_string()]);
+        assert!(delta.lifecycle_changed);
+        assert_eq!(cache.ids(), vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn test_upsert_unchanged_container_is_empty_delta() {
+        let mut cache = ContainerCache::default();
+        cache.upsert(create_container("a", "running", "1"));
+
+        let delta = cache.upsert(create_container("a", "running", "1"));
+        assert!(delta.is_empty());
+        assert!(!delta.lifecycle_changed);
+
+        let delta = cache.upsert(create_container("a", "exited", "1"));
+        assert_eq!(delta.changed.len(), 1);
+        assert!(delta.lifecycle_changed);
+    }
+
+    #[test]
+    fn test_stats_change_is_not_lifecycle_change() {
+        let mut cache = ContainerCache::default();
+        cache.upsert(create_container("a", "running", "1"));
+
+        let delta = cache.upsert(create_container("a", "running", "2"));
+        assert_eq!(delta.changed.len(), 1);
+        assert!(!delta.lifecycle_changed);
+
+        let mut stats = HashMap::new();
+        stats.insert("MemoryUsage".to_string(), "2".to_string());
+        assert!(cache.update_stats("a", stats.clone()).is_none());
+
+        stats.insert("MemoryUsage".to_string(), "3".to_string());
+        let updated = cache.update_stats("a", stats).unwrap();
+        assert_eq!(updated.stats.get("MemoryUsage").unwrap(), "3");
+        assert!(cache.update_stats("missing", HashMap::new()).is_none());
+    }
+
+    #[test]
+    fn test_remove_unknown_container() {
+        let mut cache = ContainerCache::default();
+        assert!(cache.remove("a").is_empty());
+    }
+}
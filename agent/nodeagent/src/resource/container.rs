@@ -7,110 +7,10 @@ pub type Result<T> = core::result::Result<T, Box<dyn std::error::Error + Send +
 
 pub async fn inspect(hostname: String) -> std::result::Result<Vec<ContainerInfo>, ContainerError> {
     let list = get_list().await?;
-    let infos: Vec<ContainerInfo> = try_join_all(list.iter().map(|container| {
-        let id = container.Id.clone();
-        let host_name = hostname.clone();
-        async move {
-            let inspect = get_inspect(&id).await?;
-            let stats = get_stats(&id).await?;
-
-            let mut state_map = HashMap::new();
-            state_map.insert("Status".to_string(), inspect.State.Status);
-            state_map.insert("Running".to_string(), inspect.State.Running.to_string());
-            state_map.insert("Paused".to_string(), inspect.State.Paused.to_string());
-            state_map.insert(
-                "Restarting".to_string(),
-                inspect.State.Restarting.to_string(),
-            );
-            state_map.insert("OOMKilled".to_string(), inspect.State.OOMKilled.to_string());
-            state_map.insert("Dead".to_string(), inspect.State.Dead.to_string());
-            state_map.insert("Pid".to_string(), inspect.State.Pid.to_string());
-            state_map.insert("ExitCode".to_string(), inspect.State.ExitCode.to_string());
-            state_map.insert("Error".to_string(), inspect.State.Error);
-            state_map.insert("StartedAt".to_string(), inspect.State.StartedAt);
-            state_map.insert("FinishedAt".to_string(), inspect.State.FinishedAt);
-
-            let mut config_map = HashMap::new();
-            config_map.insert("Hostname".to_string(), host_name);
-            config_map.insert("Domainname".to_string(), inspect.Config.Domainname);
-            config_map.insert("User".to_string(), inspect.Config.User);
-            config_map.insert(
-                "AttachStdin".to_string(),
-                inspect.Config.AttachStdin.to_string(),
-            );
-            config_map.insert(
-                "AttachStdout".to_string(),
-                inspect.Config.AttachStdout.to_string(),
-            );
-            config_map.insert(
-                "AttachStderr".to_string(),
-                inspect.Config.AttachStderr.to_string(),
-            );
-            config_map.insert("Tty".to_string(), inspect.Config.Tty.to_string());
-            config_map.insert(
-                "OpenStdin".to_string(),
-                inspect.Config.OpenStdin.to_string(),
-            );
-            config_map.insert(
-                "StdinOnce".to_string(),
-                inspect.Config.StdinOnce.to_string(),
-            );
-            config_map.insert("Image".to_string(), inspect.Config.Image.clone());
-            config_map.insert("WorkingDir".to_string(), inspect.Config.WorkingDir);
-
-            let annotation_map = if let Some(ann_map) = inspect.Config.Annotations {
-                ann_map.clone()
-            } else {
-                HashMap::new()
-            };
-
-            let mut stats_map = HashMap::new();
-            stats_map.insert(
-                "CpuTotalUsage".to_string(),
-                stats.cpu_stats.cpu_usage.total_usage.to_string(),
-            );
-            stats_map.insert(
-                "CpuUsageInKernelMode".to_string(),
-                stats.cpu_stats.cpu_usage.usage_in_kernelmode.to_string(),
-            );
-            stats_map.insert(
-                "CpuUsageInUserMode".to_string(),
-                stats.cpu_stats.cpu_usage.usage_in_usermode.to_string(),
-            );
-            stats_map.insert(
-                "MemoryUsage".to_string(),
-                stats.memory_stats.usage.to_string(),
-            );
-            stats_map.insert(
-                "MemoryLimit".to_string(),
-                stats.memory_stats.limit.to_string(),
-            );
-
-            stats_map.insert(
-                "Networks".to_string(),
-                stats
-                    .networks
-                    .as_ref()
-                    .map(|nets| {
-                        nets.iter()
-                            .map(|(name, net)| format!("{}: {{{}}}", name, net))
-                            .collect::<Vec<_>>()
-                            .join(", ")
-                    })
-                    .unwrap_or_else(|| "None".to_string()),
-            );
-
-            Ok::<ContainerInfo, ContainerError>(ContainerInfo {
-                id: inspect.Id,
-                names: vec![inspect.Name],
-                image: inspect.Config.Image.clone(),
-                state: state_map,
-                config: config_map,
-                annotation: annotation_map,
-                stats: stats_map,
-            })
-        }
-    }))
+    let infos: Vec<ContainerInfo> = try_join_all(
+        list.iter()
+            .map(|container| inspect_container(&container.Id, &hostname)),
+    )
     .await
     .map_err(|e| ContainerError::PodmanApi(Box::new(e)))?
     .into_iter()
@@ -119,6 +19,118 @@ pub async fn inspect(hostname: String) -> std::result::Result<Vec<ContainerInfo>
     Ok(infos)
 }
 
+/// Inspect a single container and collect its state, config and stats
+pub async fn inspect_container(
+    id: &str,
+    hostname: &str,
+) -> std::result::Result<ContainerInfo, ContainerError> {
+    let inspect = get_inspect(id).await?;
+    let stats = get_stats(id).await?;
+
+    let mut state_map = HashMap::new();
+    state_map.insert("Status".to_string(), inspect.State.Status);
+    state_map.insert("Running".to_string(), inspect.State.Running.to_string());
+    state_map.insert("Paused".to_string(), inspect.State.Paused.to_string());
+    state_map.insert(
+        "Restarting".to_string(),
+        inspect.State.Restarting.to_string(),
+    );
+    state_map.insert("OOMKilled".to_string(), inspect.State.OOMKilled.to_string());
+    state_map.insert("Dead".to_string(), inspect.State.Dead.to_string());
+    state_map.insert("Pid".to_string(), inspect.State.Pid.to_string());
+    state_map.insert("ExitCode".to_string(), inspect.State.ExitCode.to_string());
+    state_map.insert("Error".to_string(), inspect.State.Error);
+    state_map.insert("StartedAt".to_string(), inspect.State.StartedAt);
+    state_map.insert("FinishedAt".to_string(), inspect.State.FinishedAt);
+
+    let mut config_map = HashMap::new();
+    config_map.insert("Hostname".to_string(), hostname.to_string());
+    config_map.insert("Domainname".to_string(), inspect.Config.Domainname);
+    config_map.insert("User".to_string(), inspect.Config.User);
+    config_map.insert(
+        "AttachStdin".to_string(),
+        inspect.Config.AttachStdin.to_string(),
+    );
+    config_map.insert(
+        "AttachStdout".to_string(),
+        inspect.Config.AttachStdout.to_string(),
+    );
+    config_map.insert(
+        "AttachStderr".to_string(),
+        inspect.Config.AttachStderr.to_string(),
+    );
+    config_map.insert("Tty".to_string(), inspect.Config.Tty.to_string());
+    config_map.insert(
+        "OpenStdin".to_string(),
+        inspect.Config.OpenStdin.to_string(),
+    );
+    config_map.insert(
+        "StdinOnce".to_string(),
+        inspect.Config.StdinOnce.to_string(),
+    );
+    config_map.insert("Image".to_string(), inspect.Config.Image.clone());
+    config_map.insert("WorkingDir".to_string(), inspect.Config.WorkingDir);
+
+    let annotation_map = if let Some(ann_map) = inspect.Config.Annotations {
+        ann_map.clone()
+    } else {
+        HashMap::new()
+    };
+
+    let stats_map = stats_to_map(&stats);
+
+    Ok(ContainerInfo {
+        id: inspect.Id,
+        names: vec![inspect.Name],
+        image: inspect.Config.Image.clone(),
+        state: state_map,
+        config: config_map,
+        annotation: annotation_map,
+        stats: stats_map,
+    })
+}
+
+/// Convert Podman container stats into the map sent to monitoring
+pub fn stats_to_map(stats: &ContainerStats) -> HashMap<String, String> {
+    let mut stats_map = HashMap::new();
+    stats_map.insert(
+        "CpuTotalUsage".to_string(),
+        stats.cpu_stats.cpu_usage.total_usage.to_string(),
+    );
+    stats_map.insert(
+        "CpuUsageInKernelMode".to_string(),
+        stats.cpu_stats.cpu_usage.usage_in_kernelmode.to_string(),
+    );
+    stats_map.insert(
+        "CpuUsageInUserMode".to_string(),
+        stats.cpu_stats.cpu_usage.usage_in_usermode.to_string(),
+    );
+    stats_map.insert(
+        "MemoryUsage".to_string(),
+        stats.memory_stats.usage.to_string(),
+    );
+    stats_map.insert(
+        "MemoryLimit".to_string(),
+        stats.memory_stats.limit.to_string(),
+    );
+
+    stats_map.insert(
+        "Networks".to_string(),
+        stats
+            .networks
+            .as_ref()
+            .map(|nets| {
+                nets.iter()
+                    .map(|(name, net)| format!("{}: {{{}}}", name, net))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .unwrap_or_else(|| "None".to_string()),
+    );
+
+    stats_map
+}
+
 pub async fn get_list() -> Result<Vec<Container>> {
     let body = get("/v1.0.0/libpod/containers/json").await?;
 
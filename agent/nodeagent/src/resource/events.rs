@@ -0,0 +1,146 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Container lifecycle events from the Podman events stream
+//!
+//! Podman writes one JSON object per line on `/events` for as long as the
+//! connection stays open. Only container events are requested, and they are
+//! reduced to the container that changed so that the caller re-inspects just
+//! that container instead of the whole list.
+
+use super::{get_stream, ContainerError};
+use hyper::body::HttpBody;
+use serde::Deserialize;
+use tokio::sync::mpsc;
+
+/// `{"type":["container"]}`, url-encoded
+const EVENTS_PATH: &str =
+    "/v1.0.0/libpod/events?stream=true&filters=%7B%22type%22%3A%5B%22container%22%5D%7D";
+
+/// Lifecycle change of a single container
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContainerEvent {
+    /// The container was created or its state changed, re-inspect it
+    Changed(String),
+    /// The container no longer exists
+    Removed(String),
+}
+
+#[derive(Deserialize, Debug)]
+struct RawEvent {
+    #[serde(rename = "Type")]
+    kind: Option<String>,
+    #[serde(rename = "Action")]
+    action: Option<String>,
+    /// Older libpod events carry the action as `Status`
+    #[serde(rename = "Status", alias = "status")]
+    status: Option<String>,
+    #[serde(rename = "Actor")]
+    actor: Option<RawActor>,
+    #[serde(rename = "ID", alias = "id")]
+    id: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct RawActor {
+    #[serde(rename = "ID")]
+    id: Option<String>,
+}
+
+/// Parse one line of the events stream
+///
+/// Returns `None` for lines that are not container events and for actions
+/// that do not change the container itself (exec, attach, ...).
+pub fn parse_event(line: &str) -> Option<ContainerEvent> {
+    let event: RawEvent = serde_json::from_str(line.trim()).ok()?;
+    if event.kind.as_deref() != Some("container") {
+        return None;
+    }
+
+    let id = event
+        .actor
+        .and_then(|actor| actor.id)
+        .or(event.id)
+        .filter(|id| !id.is_empty())?;
+    let action = event.action.or(event.status)?;
+
+    match action.as_str() {
+        "remove" => Some(ContainerEvent::Removed(id)),
+        a if a.starts_with("exec") || a.starts_with("attach") || a == "health_status" => None,
+        _ => Some(ContainerEvent::Changed(id)),
+    }
+}
+
+/// Forward container events from Podman until the stream ends
+///
+/// ### Parameters
+/// * `tx` - channel receiving the parsed events
+/// ### Returns
+/// `Ok(())` when Podman or the receiver closed the stream, an error when the
+/// socket cannot be read.
+pub async fn watch(tx: mpsc::Sender<ContainerEvent>) -> Result<(), ContainerError> {
+    let mut body = get_stream(EVENTS_PATH)
+        .await
+        .map_err(|e| ContainerError::PodmanApi(Box::new(e)))?;
+    let mut pending = Vec::new();
+
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| ContainerError::PodmanApi(Box::new(e)))?;
+        pending.extend_from_slice(&chunk);
+
+        while let Some(pos) = pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=pos).collect();
+            let Some(event) = parse_event(&String::from_utf8_lossy(&line)) else {
+                continue;
+            };
+            if tx.send(event).await.is_err() {
+                return Ok(());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_docker_style_event() {
+        let line = r#"{"status":"start","id":"abc","Type":"container","Action":"start","Actor":{"ID":"abc","Attributes":{"name":"helloworld"}}}"#;
+        assert_eq!(
+            parse_event(line),
+            Some(ContainerEvent::Changed("abc".to_string()))
+        );
+
+        let line = r#"{"Type":"container","Action":"remove","Actor":{"ID":"abc"}}"#;
+        assert_eq!(
+            parse_event(line),
+            Some(ContainerEvent::Removed("abc".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_libpod_style_event() {
+        let line = r#"{"ID":"def","Image":"helloworld","Name":"hello","Status":"died","Type":"container"}"#;
+        assert_eq!(
+            parse_event(line),
+            Some(ContainerEvent::Changed("def".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_ignored_events() {
+        let exec = r#"{"Type":"container","Action":"exec_died","Actor":{"ID":"abc"}}"#;
+        assert_eq!(parse_event(exec), None);
+
+        let image = r#"{"Type":"image","Action":"pull","Actor":{"ID":"img"}}"#;
+        assert_eq!(parse_event(image), None);
+
+        assert_eq!(parse_event("not json"), None);
+    }
+}
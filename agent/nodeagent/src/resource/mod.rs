@@ -1,4 +1,6 @@
+pub mod cache;
 pub mod container;
+pub mod events;
 pub mod nodeinfo;
 
 use hyper::{Body, Client, Uri};
@@ -8,6 +10,13 @@ use std::collections::HashMap;
 use thiserror::Error;
 
 async fn get(path: &str) -> Result<hyper::body::Bytes, hyper::Error> {
+    let res = get_stream(path).await?;
+    hyper::body::to_bytes(res).await
+}
+
+/// Send a GET request to Podman and return the body without reading it,
+/// for endpoints that keep streaming such as `/events`
+async fn get_stream(path: &str) -> Result<Body, hyper::Error> {
     let connector = UnixConnector;
     let client = Client::builder().build::<_, Body>(connector);
 
@@ -22,7 +31,7 @@ async fn get(path: &str) -> Result<hyper::body::Bytes, hyper::Error> {
     let uri: Uri = UnixUri::new(socket, path).into();
 
     let res = client.get(uri).await?;
-    hyper::body::to_bytes(res).await
+    Ok(res.into_body())
 }
 
 /// Node information matching the requested DataCache structure.
@@ -3,7 +3,10 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 
-use etcd_client::{Client, DeleteOptions, Error, GetOptions, SortOrder, SortTarget};
+use etcd_client::{
+    Client, DeleteOptions, Error, GetOptions, SortOrder, SortTarget, WatchOptions, WatchStream,
+    Watcher,
+};
 
 pub fn open_server() -> String {
     let config = crate::setting::get_config();
@@ -129,6 +132,16 @@ pub async fn get_all_with_prefix(key: &str) -> Result<Vec<KV>, Error> {
         .collect())
 }
 
+/// Watch every key under a prefix
+///
+/// The `Watcher` must be kept as long as events are read from the stream.
+pub async fn watch_prefix(key: &str) -> Result<(Watcher, WatchStream), Error> {
+    let mut client = get_client().await?;
+    client
+        .watch(key, Some(WatchOptions::new().with_prefix()))
+        .await
+}
+
 pub async fn delete(key: &str) -> Result<(), Error> {
     let mut client = get_client().await?;
     // Validate key length
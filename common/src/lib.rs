@@ -8,6 +8,7 @@ pub mod error;
 pub mod etcd;
 pub mod setting;
 pub mod spec;
+pub mod transport;
 
 fn open_server(port: u16) -> String {
     format!("{}:{}", crate::setting::get_config().host.ip, port)
@@ -29,7 +30,16 @@ fn open_guest_server(port: u16) -> String {
 }
 
 fn connect_server(port: u16) -> String {
-    format!("http://{}:{}", crate::setting::get_config().host.ip, port)
+    format!(
+        "{}://{}:{}",
+        crate::transport::scheme(),
+        crate::setting::get_config().host.ip,
+        port
+    )
+}
+
+fn connect_node_server(ip: &str, port: u16) -> String {
+    format!("{}://{ip}:{port}", crate::transport::scheme())
 }
 
 fn connect_guest_server(port: u16) -> String {
@@ -40,7 +50,7 @@ fn connect_guest_server(port: u16) -> String {
         .map(|guest: &setting::GuestSettings| guest.ip.as_str())
         .unwrap();
 
-    format!("http://{guest_ip}:{port}")
+    format!("{}://{guest_ip}:{port}", crate::transport::scheme())
 }
 
 pub mod actioncontroller {
@@ -113,6 +123,10 @@ pub mod nodeagent {
     pub fn connect_guest_server() -> String {
         super::connect_guest_server(47004)
     }
+
+    pub fn connect_node_server(ip: &str) -> String {
+        super::connect_node_server(ip, 47004)
+    }
 }
 
 pub mod policymanager {
@@ -1,4 +1,5 @@
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::sync::OnceLock;
 static SETTINGS: OnceLock<Settings> = OnceLock::new();
 
@@ -8,6 +9,8 @@ pub struct Settings {
     pub piccolo_cloud: String,
     pub host: HostSettings,
     pub guest: Option<Vec<GuestSettings>>,
+    #[serde(default)]
+    pub tls: Option<TlsSettings>,
 }
 
 #[derive(Deserialize)]
@@ -24,6 +27,21 @@ pub struct GuestSettings {
     pub r#type: String,
 }
 
+/// Mutual TLS configuration for inter-component gRPC channels
+#[derive(Deserialize, Clone)]
+pub struct TlsSettings {
+    /// PEM file of the CA that issued every component certificate
+    pub ca: String,
+    /// Certificate and key per component role (`apiserver`, `nodeagent`, ...)
+    pub components: HashMap<String, ComponentTlsSettings>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct ComponentTlsSettings {
+    pub cert: String,
+    pub key: String,
+}
+
 fn parse_settings_yaml() -> Settings {
     let default_settings: Settings = Settings {
         yaml_storage: String::from("/etc/piccolo/yaml"),
@@ -39,6 +57,7 @@ fn parse_settings_yaml() -> Settings {
         //     r#type: String::from("bluechi"),
         // }]),
         guest: None,
+        tls: None,
     };
 
     let settings = config::Config::builder()
@@ -73,6 +92,7 @@ mod tests {
         assert_eq!(settings.host.ip, "0.0.0.0");
         assert_eq!(settings.host.r#type, "bluechi");
         assert!(settings.guest.is_none());
+        assert!(settings.tls.is_none());
     }
 
     // Test guest settings when provided
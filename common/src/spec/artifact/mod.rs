@@ -5,6 +5,9 @@ mod package;
 mod scenario;
 mod volume;
 
+pub use package::{Placement, ResourceRequests};
+pub use scenario::{Condition, TopicQos, Window};
+
 use super::MetaData;
 use serde::{Deserialize, Serialize};
 
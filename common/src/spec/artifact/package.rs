@@ -1,5 +1,6 @@
 use super::Artifact;
 use super::Package;
+use std::collections::HashMap;
 
 impl Artifact for Package {
     fn get_name(&self) -> String {
@@ -27,8 +28,17 @@ struct Pattern {
 #[derive(Debug, serde::Deserialize, PartialEq)]
 pub struct ModelInfo {
     name: String,
+    /// Target node, may be left empty when `placement` is given
+    #[serde(default)]
     node: String,
     resources: Resource,
+    /// Nodes the model may be moved to when `node` is drained
+    alternates: Option<Vec<String>>,
+    /// Constraints used by apiserver to choose `node`
+    placement: Option<Placement>,
+    /// Whether the package cannot run without this model
+    #[serde(default)]
+    critical: Option<bool>,
 }
 
 impl ModelInfo {
@@ -43,6 +53,52 @@ impl ModelInfo {
     pub fn get_resources(&self) -> Resource {
         self.resources.clone()
     }
+
+    pub fn get_alternates(&self) -> Vec<String> {
+        self.alternates.clone().unwrap_or_default()
+    }
+
+    pub fn get_placement(&self) -> Option<Placement> {
+        self.placement.clone()
+    }
+
+    pub fn is_critical(&self) -> bool {
+        self.critical.unwrap_or(false)
+    }
+}
+
+#[derive(Clone, Debug, Default, serde::Deserialize, PartialEq)]
+pub struct Placement {
+    #[serde(rename = "nodeSelector")]
+    node_selector: Option<HashMap<String, String>>,
+    requests: Option<ResourceRequests>,
+}
+
+impl Placement {
+    pub fn get_node_selector(&self) -> HashMap<String, String> {
+        self.node_selector.clone().unwrap_or_default()
+    }
+
+    pub fn get_requests(&self) -> ResourceRequests {
+        self.requests.clone().unwrap_or_default()
+    }
+}
+
+/// Resources a model needs on its node, cpu in cores and memory in MB
+#[derive(Clone, Debug, Default, serde::Deserialize, PartialEq)]
+pub struct ResourceRequests {
+    cpu: Option<f64>,
+    memory: Option<i64>,
+}
+
+impl ResourceRequests {
+    pub fn get_cpu(&self) -> f64 {
+        self.cpu.unwrap_or_default()
+    }
+
+    pub fn get_memory(&self) -> i64 {
+        self.memory.unwrap_or_default()
+    }
 }
 
 #[derive(Clone, Debug, serde::Deserialize, PartialEq)]
@@ -110,6 +166,9 @@ mod tests {
                             volume: Some("vol1".to_string()),
                             network: Some("net1".to_string()),
                         },
+                        alternates: None,
+                        placement: None,
+                        critical: None,
                     },
                     ModelInfo {
                         name: "model2".to_string(),
@@ -118,6 +177,9 @@ mod tests {
                             volume: Some("vol2".to_string()),
                             network: None,
                         },
+                        alternates: None,
+                        placement: None,
+                        critical: None,
                     },
                 ],
             },
@@ -160,6 +222,9 @@ mod tests {
                 volume: Some("test-vol".to_string()),
                 network: Some("test-net".to_string()),
             },
+            alternates: Some(vec!["alt-node".to_string()]),
+            placement: None,
+            critical: Some(true),
         };
 
         assert_eq!(model.get_name(), "test-model");
@@ -168,6 +233,53 @@ mod tests {
         let resources = model.get_resources();
         assert_eq!(resources.get_volume(), Some("test-vol".to_string()));
         assert_eq!(resources.get_network(), Some("test-net".to_string()));
+        assert_eq!(model.get_alternates(), vec!["alt-node".to_string()]);
+        assert!(model.is_critical());
+    }
+
+    #[test]
+    fn test_model_info_alternates_optional() {
+        let yaml = r#"
+name: test-model
+node: test-node
+resources:
+  volume:
+  network:
+"#;
+        let model: ModelInfo = serde_yaml::from_str(yaml).unwrap();
+        assert!(model.get_alternates().is_empty());
+        assert!(model.get_placement().is_none());
+        assert!(!model.is_critical());
+    }
+
+    #[test]
+    fn test_model_info_placement() {
+        let yaml = r#"
+name: test-model
+resources:
+  volume:
+  network:
+placement:
+  nodeSelector:
+    variant: premium
+  requests:
+    cpu: 0.5
+    memory: 256
+"#;
+        let model: ModelInfo = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(model.get_node(), "");
+
+        let placement = model.get_placement().unwrap();
+        assert_eq!(
+            placement.get_node_selector().get("variant"),
+            Some(&"premium".to_string())
+        );
+        assert_eq!(placement.get_requests().get_cpu(), 0.5);
+        assert_eq!(placement.get_requests().get_memory(), 256);
+
+        let empty = Placement::default();
+        assert!(empty.get_node_selector().is_empty());
+        assert_eq!(empty.get_requests().get_memory(), 0);
     }
 
     #[test]
@@ -46,6 +46,48 @@ pub struct Condition {
     express: String,
     value: String,
     operands: Operand,
+    /// Milliseconds the condition must stay true before the action fires
+    #[serde(rename = "holdFor", default, skip_serializing_if = "Option::is_none")]
+    hold_for: Option<u64>,
+    /// Milliseconds after firing during which new transitions are ignored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cooldown: Option<u64>,
+    /// Fire at most once for the lifetime of the scenario
+    #[serde(rename = "oneShot", default, skip_serializing_if = "Option::is_none")]
+    one_shot: Option<bool>,
+    /// Evaluate an aggregate of recent samples instead of the latest one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    window: Option<Window>,
+    /// Cron expression (`minute hour day month weekday`) limiting the
+    /// condition to the minutes it matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    schedule: Option<String>,
+}
+
+/// Time window over which the operand of a condition is aggregated
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct Window {
+    /// Length of the window in milliseconds
+    duration: u64,
+    /// One of `avg`, `min`, `max`, `count`, `delta` or `rate`
+    aggregate: String,
+    /// For `count`, only samples equal to this value are counted
+    #[serde(rename = "countOf", default, skip_serializing_if = "Option::is_none")]
+    count_of: Option<String>,
+}
+
+impl Window {
+    pub fn get_duration(&self) -> u64 {
+        self.duration
+    }
+
+    pub fn get_aggregate(&self) -> String {
+        self.aggregate.clone()
+    }
+
+    pub fn get_count_of(&self) -> Option<String> {
+        self.count_of.clone()
+    }
 }
 
 impl Condition {
@@ -64,6 +106,99 @@ impl Condition {
     pub fn get_operand_name(&self) -> String {
         self.operands.name.clone()
     }
+
+    pub fn get_operand_type(&self) -> String {
+        self.operands.r#type.clone()
+    }
+
+    pub fn get_hold_for(&self) -> u64 {
+        self.hold_for.unwrap_or(0)
+    }
+
+    pub fn get_cooldown(&self) -> u64 {
+        self.cooldown.unwrap_or(0)
+    }
+
+    pub fn is_one_shot(&self) -> bool {
+        self.one_shot.unwrap_or(false)
+    }
+
+    pub fn get_window(&self) -> Option<Window> {
+        self.window.clone()
+    }
+
+    pub fn get_schedule(&self) -> Option<String> {
+        self.schedule.clone()
+    }
+
+    pub fn get_operand_idl(&self) -> Option<String> {
+        self.operands.idl.clone()
+    }
+
+    pub fn get_operand_qos(&self) -> Option<TopicQos> {
+        self.operands.qos.clone()
+    }
+}
+
+/// DDS QoS of the reader of a topic, unset policies keep their default
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct TopicQos {
+    /// `reliable` or `bestEffort`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reliability: Option<String>,
+    /// `volatile` or `transientLocal` to receive the last-known state on subscription
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    durability: Option<String>,
+    /// Samples kept per instance, 0 keeps all of them
+    #[serde(
+        rename = "historyDepth",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    history_depth: Option<u32>,
+    /// Milliseconds between two samples after which a deadline miss is reported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deadline: Option<u64>,
+    /// Milliseconds without a sign of life after which writers are considered lost
+    #[serde(
+        rename = "livelinessLease",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    liveliness_lease: Option<u64>,
+}
+
+impl TopicQos {
+    pub fn get_reliability(&self) -> Option<String> {
+        self.reliability.clone()
+    }
+
+    pub fn get_durability(&self) -> Option<String> {
+        self.durability.clone()
+    }
+
+    pub fn get_history_depth(&self) -> Option<u32> {
+        self.history_depth
+    }
+
+    pub fn get_deadline(&self) -> Option<u64> {
+        self.deadline
+    }
+
+    pub fn get_liveliness_lease(&self) -> Option<u64> {
+        self.liveliness_lease
+    }
+
+    /// Policies set in `other` replace the ones of `self`
+    pub fn merge(&self, other: &TopicQos) -> TopicQos {
+        TopicQos {
+            reliability: other.reliability.clone().or(self.reliability.clone()),
+            durability: other.durability.clone().or(self.durability.clone()),
+            history_depth: other.history_depth.or(self.history_depth),
+            deadline: other.deadline.or(self.deadline),
+            liveliness_lease: other.liveliness_lease.or(self.liveliness_lease),
+        }
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
@@ -71,6 +206,12 @@ struct Operand {
     r#type: String,
     name: String,
     value: String,
+    /// IDL of the topic type, for types filtergateway was not built with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    idl: Option<String>,
+    /// QoS of the DDS reader of the topic
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    qos: Option<TopicQos>,
 }
 
 //Unit Test Cases
@@ -96,7 +237,14 @@ mod tests {
                         r#type: "pod".to_string(),
                         name: "test-pod".to_string(),
                         value: "status".to_string(),
+                        idl: None,
+                        qos: None,
                     },
+                    hold_for: None,
+                    cooldown: None,
+                    one_shot: None,
+                    window: None,
+                    schedule: None,
                 }),
                 action: "start".to_string(),
                 target: "model-1".to_string(),
@@ -122,6 +270,7 @@ mod tests {
         assert_eq!(conditions.get_value(), "ready");
         assert_eq!(conditions.get_operand_name(), "test-pod");
         assert_eq!(conditions.get_operand_value(), "status");
+        assert_eq!(conditions.get_operand_type(), "pod");
     }
 
     #[test]
@@ -206,7 +355,14 @@ mod tests {
                     r#type: "metric".to_string(),
                     name: "cpu_usage".to_string(),
                     value: "value".to_string(),
+                    idl: None,
+                    qos: None,
                 },
+                hold_for: None,
+                cooldown: None,
+                one_shot: None,
+                window: None,
+                schedule: None,
             }),
             action: "scale".to_string(),
             target: "deployment".to_string(),
@@ -227,10 +383,146 @@ mod tests {
                 r#type: "metric".to_string(),
                 name: "memory_usage".to_string(),
                 value: "value".to_string(),
+                idl: None,
+                qos: None,
             },
+            hold_for: None,
+            cooldown: None,
+            one_shot: None,
+            window: None,
+            schedule: None,
         };
 
         let cloned = condition.clone();
         assert_eq!(condition, cloned);
     }
+
+    #[test]
+    fn test_condition_trigger_options() {
+        let yaml = r#"
+express: eq
+value: "P"
+operands:
+  type: DDS
+  name: gear
+  value: rt/piccolo/gear_state
+holdFor: 500
+cooldown: 10000
+oneShot: true
+"#;
+        let condition: Condition = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(condition.get_hold_for(), 500);
+        assert_eq!(condition.get_cooldown(), 10000);
+        assert!(condition.is_one_shot());
+
+        let condition = create_test_scenario().get_conditions().unwrap();
+        assert_eq!(condition.get_hold_for(), 0);
+        assert_eq!(condition.get_cooldown(), 0);
+        assert!(!condition.is_one_shot());
+    }
+
+    #[test]
+    fn test_condition_window() {
+        let yaml = r#"
+express: gt
+value: "5"
+operands:
+  type: DDS
+  name: temperature
+  value: rt/piccolo/battery
+window:
+  duration: 60000
+  aggregate: delta
+"#;
+        let condition: Condition = serde_yaml::from_str(yaml).unwrap();
+        let window = condition.get_window().unwrap();
+        assert_eq!(window.get_duration(), 60000);
+        assert_eq!(window.get_aggregate(), "delta");
+        assert!(window.get_count_of().is_none());
+
+        assert!(create_test_scenario()
+            .get_conditions()
+            .unwrap()
+            .get_window()
+            .is_none());
+    }
+
+    #[test]
+    fn test_condition_schedule() {
+        let yaml = r#"
+express: eq
+value: "P"
+operands:
+  type: DDS
+  name: gear
+  value: rt/piccolo/gear_state
+schedule: "0 2 * * *"
+"#;
+        let condition: Condition = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(condition.get_schedule().as_deref(), Some("0 2 * * *"));
+        assert!(create_test_scenario()
+            .get_conditions()
+            .unwrap()
+            .get_schedule()
+            .is_none());
+    }
+
+    #[test]
+    fn test_operand_idl() {
+        let yaml = r#"
+express: eq
+value: "on"
+operands:
+  type: DDS
+  name: state
+  value: HornState
+  idl: |
+    struct HornState { boolean state; };
+"#;
+        let condition: Condition = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            condition.get_operand_idl().as_deref(),
+            Some("struct HornState { boolean state; };\n")
+        );
+        assert!(create_test_scenario()
+            .get_conditions()
+            .unwrap()
+            .get_operand_idl()
+            .is_none());
+    }
+
+    #[test]
+    fn test_operand_qos() {
+        let yaml = r#"
+express: eq
+value: "P"
+operands:
+  type: DDS
+  name: gear
+  value: rt/piccolo/gear_state
+  qos:
+    reliability: reliable
+    durability: transientLocal
+    historyDepth: 1
+"#;
+        let condition: Condition = serde_yaml::from_str(yaml).unwrap();
+        let qos = condition.get_operand_qos().unwrap();
+        assert_eq!(qos.get_reliability().as_deref(), Some("reliable"));
+        assert_eq!(qos.get_durability().as_deref(), Some("transientLocal"));
+        assert_eq!(qos.get_history_depth(), Some(1));
+        assert!(qos.get_deadline().is_none());
+
+        let defaults: TopicQos =
+            serde_yaml::from_str("reliability: bestEffort\ndeadline: 100").unwrap();
+        let merged = defaults.merge(&qos);
+        assert_eq!(merged.get_reliability().as_deref(), Some("reliable"));
+        assert_eq!(merged.get_deadline(), Some(100));
+        assert!(merged.get_liveliness_lease().is_none());
+
+        assert!(create_test_scenario()
+            .get_conditions()
+            .unwrap()
+            .get_operand_qos()
+            .is_none());
+    }
 }
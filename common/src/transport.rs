@@ -0,0 +1,323 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! gRPC channel and server construction shared by all Pullpiri components
+//!
+//! Every tonic client and server is built through this module so that the
+//! transport security is decided in one place. When `tls` is absent from
+//! `settings.yaml` plaintext `http://` is used as before. When it is present,
+//! mutual TLS is applied to every channel:
+//!
+//! * servers present the certificate of their own component and require a
+//!   client certificate issued by the configured CA whose subject is one of
+//!   the components that call the server (see [`Component::callers`]),
+//! * clients present the certificate of their own component and only accept a
+//!   server whose certificate subject (DNS SAN) is the expected component
+//!   role, e.g. `apiserver` when connecting to the API server.
+
+use crate::setting::{get_config, TlsSettings};
+use rustls_pki_types::ServerName;
+use std::sync::OnceLock;
+use tonic::service::interceptor::InterceptorLayer;
+use tonic::service::Interceptor;
+use tonic::transport::{
+    Certificate, CertificateDer, Channel, ClientTlsConfig, Endpoint, Identity, Server,
+    ServerTlsConfig,
+};
+use tonic::{Request, Status};
+use tower_layer::{Identity as NoLayer, Stack};
+
+static LOCAL_COMPONENT: OnceLock<Component> = OnceLock::new();
+
+/// Error type of this module, `Send` so that channels can be opened from
+/// spawned tasks
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Pullpiri component roles that take part in gRPC communication
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Component {
+    ActionController,
+    ApiServer,
+    FilterGateway,
+    MonitoringServer,
+    NodeAgent,
+    PolicyManager,
+    StateManager,
+}
+
+impl Component {
+    /// Role name used as key in `tls.components` and as certificate subject
+    pub fn name(&self) -> &'static str {
+        match self {
+            Component::ActionController => "actioncontroller",
+            Component::ApiServer => "apiserver",
+            Component::FilterGateway => "filtergateway",
+            Component::MonitoringServer => "monitoringserver",
+            Component::NodeAgent => "nodeagent",
+            Component::PolicyManager => "policymanager",
+            Component::StateManager => "statemanager",
+        }
+    }
+
+    /// Components that open channels to the server of this component
+    ///
+    /// With TLS enabled the server rejects clients whose certificate is not
+    /// issued for one of these roles.
+    pub fn callers(&self) -> &'static [Component] {
+        match self {
+            Component::ActionController => &[
+                Component::ApiServer,
+                Component::FilterGateway,
+                Component::StateManager,
+            ],
+            Component::ApiServer => &[Component::ActionController, Component::NodeAgent],
+            Component::FilterGateway => &[Component::ApiServer],
+            Component::MonitoringServer => &[Component::NodeAgent],
+            Component::NodeAgent => &[Component::ActionController, Component::ApiServer],
+            Component::PolicyManager => &[Component::ActionController],
+            Component::StateManager => &[
+                Component::ActionController,
+                Component::ApiServer,
+                Component::FilterGateway,
+                Component::NodeAgent,
+            ],
+        }
+    }
+}
+
+/// Server builder returned by [`server`], checking the role of each client
+pub type ComponentServer = Server<Stack<InterceptorLayer<PeerCheck>, NoLayer>>;
+
+/// Interceptor rejecting requests from clients that may not call a server
+///
+/// The certificate chain is verified by the TLS handshake already; this only
+/// checks that the client certificate is issued for one of the allowed
+/// component roles. Without TLS every request passes.
+#[derive(Debug, Clone)]
+pub struct PeerCheck {
+    allowed: Option<&'static [Component]>,
+}
+
+impl PeerCheck {
+    /// Status to reject a request with, if its client may not call
+    fn rejection(&self, certs: Option<&[CertificateDer<'static>]>) -> Option<Status> {
+        let allowed = self.allowed?;
+        let Some(cert) = certs.and_then(|certs| certs.first()) else {
+            return Some(Status::unauthenticated("client certificate is required"));
+        };
+        let cert = match webpki::EndEntityCert::try_from(cert) {
+            Ok(cert) => cert,
+            Err(e) => {
+                return Some(Status::unauthenticated(format!(
+                    "invalid client certificate: {e}"
+                )))
+            }
+        };
+        let permitted = allowed.iter().any(|component| {
+            ServerName::try_from(component.name())
+                .is_ok_and(|name| cert.verify_is_valid_for_subject_name(&name).is_ok())
+        });
+        (!permitted).then(|| {
+            Status::permission_denied(
+                "client certificate is not issued for a component allowed to call this server",
+            )
+        })
+    }
+}
+
+impl Interceptor for PeerCheck {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let certs = request.peer_certs();
+        match self.rejection(certs.as_deref().map(|certs| certs.as_slice())) {
+            Some(status) => Err(status),
+            None => Ok(request),
+        }
+    }
+}
+
+/// Set the role of the running process
+///
+/// ### Parameters
+/// * `component: Component` - role of this binary
+/// ### Description
+/// Must be called once at startup before any channel is opened. The role
+/// selects the certificate this process presents as a client. Later calls
+/// are ignored.
+pub fn set_local_component(component: Component) {
+    let _ = LOCAL_COMPONENT.set(component);
+}
+
+/// Check whether mutual TLS is configured in settings
+pub fn is_tls_enabled() -> bool {
+    get_config().tls.is_some()
+}
+
+/// URI scheme matching the configured transport security
+pub fn scheme() -> &'static str {
+    if is_tls_enabled() {
+        "https"
+    } else {
+        "http"
+    }
+}
+
+/// Open a channel to a Pullpiri gRPC server
+///
+/// ### Parameters
+/// * `target: Component` - role expected on the server side
+/// * `addr: String` - server URL from `connect_server()` and friends
+/// ### Description
+/// With TLS enabled the server certificate must be issued by the configured
+/// CA for the DNS name of `target`, otherwise the handshake fails.
+pub async fn connect(target: Component, addr: String) -> Result<Channel, Error> {
+    let endpoint = Endpoint::from_shared(addr)?;
+
+    let endpoint = match &get_config().tls {
+        Some(tls) => {
+            let local = LOCAL_COMPONENT
+                .get()
+                .ok_or("local component is not set, cannot select a client certificate")?;
+            let config = ClientTlsConfig::new()
+                .ca_certificate(load_ca(tls)?)
+                .identity(load_identity(tls, *local)?)
+                .domain_name(target.name());
+            endpoint.tls_config(config)?
+        }
+        None => endpoint,
+    };
+
+    Ok(endpoint.connect().await?)
+}
+
+/// Create a server builder for a Pullpiri component
+///
+/// ### Parameters
+/// * `component: Component` - role of the server being started
+/// ### Description
+/// With TLS enabled the builder serves the certificate of `component` and
+/// rejects clients that do not present a certificate issued by the CA for
+/// one of the callers of `component`.
+pub fn server(component: Component) -> Result<ComponentServer, Error> {
+    let builder = Server::builder();
+
+    let (builder, allowed) = match &get_config().tls {
+        Some(tls) => {
+            let config = ServerTlsConfig::new()
+                .identity(load_identity(tls, component)?)
+                .client_ca_root(load_ca(tls)?);
+            (builder.tls_config(config)?, Some(component.callers()))
+        }
+        None => (builder, None),
+    };
+    Ok(builder.layer(tonic::service::interceptor(PeerCheck { allowed })))
+}
+
+fn load_ca(tls: &TlsSettings) -> Result<Certificate, Error> {
+    let pem = std::fs::read(&tls.ca)
+        .map_err(|e| format!("cannot read CA certificate '{}': {}", tls.ca, e))?;
+    Ok(Certificate::from_pem(pem))
+}
+
+fn load_identity(tls: &TlsSettings, component: Component) -> Result<Identity, Error> {
+    let files = tls.components.get(component.name()).ok_or_else(|| {
+        format!(
+            "no certificate configured for component '{}'",
+            component.name()
+        )
+    })?;
+    let cert = std::fs::read(&files.cert)
+        .map_err(|e| format!("cannot read certificate '{}': {}", files.cert, e))?;
+    let key = std::fs::read(&files.key)
+        .map_err(|e| format!("cannot read private key '{}': {}", files.key, e))?;
+    Ok(Identity::from_pem(cert, key))
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_component_names_are_unique() {
+        let all = [
+            Component::ActionController,
+            Component::ApiServer,
+            Component::FilterGateway,
+            Component::MonitoringServer,
+            Component::NodeAgent,
+            Component::PolicyManager,
+            Component::StateManager,
+        ];
+        let mut names: Vec<&str> = all.iter().map(|c| c.name()).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), all.len());
+    }
+
+    #[test]
+    fn test_plaintext_without_tls_settings() {
+        assert!(!is_tls_enabled());
+        assert_eq!(scheme(), "http");
+        assert!(server(Component::ApiServer).is_ok());
+    }
+
+    #[test]
+    fn test_load_identity_missing_component() {
+        let tls = TlsSettings {
+            ca: String::from("/nonexistent/ca.pem"),
+            components: std::collections::HashMap::new(),
+        };
+        let result = load_identity(&tls, Component::NodeAgent);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("nodeagent"));
+    }
+
+    #[test]
+    fn test_load_ca_missing_file() {
+        let tls = TlsSettings {
+            ca: String::from("/nonexistent/ca.pem"),
+            components: std::collections::HashMap::new(),
+        };
+        assert!(load_ca(&tls).is_err());
+    }
+
+    #[test]
+    fn test_peer_check() {
+        let plaintext = PeerCheck { allowed: None };
+        assert!(plaintext.rejection(None).is_none());
+
+        let check = PeerCheck {
+            allowed: Some(Component::MonitoringServer.callers()),
+        };
+        let missing = check.rejection(None).unwrap();
+        assert_eq!(missing.code(), tonic::Code::Unauthenticated);
+        let garbage = [CertificateDer::from(vec![0u8; 16])];
+        let invalid = check.rejection(Some(&garbage)).unwrap();
+        assert_eq!(invalid.code(), tonic::Code::Unauthenticated);
+    }
+
+    #[test]
+    fn test_callers_are_other_components() {
+        let all = [
+            Component::ActionController,
+            Component::ApiServer,
+            Component::FilterGateway,
+            Component::MonitoringServer,
+            Component::NodeAgent,
+            Component::PolicyManager,
+            Component::StateManager,
+        ];
+        for component in all {
+            assert!(!component.callers().is_empty());
+            assert!(!component.callers().contains(&component));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connect_invalid_address() {
+        let result = connect(Component::ApiServer, String::from("not a uri")).await;
+        assert!(result.is_err());
+    }
+}
@@ -3,7 +3,7 @@ pub mod sender;
 
 use std::sync::Arc;
 
-use tonic::transport::Server;
+use common::transport::Component;
 
 /// Initialize the gRPC communication system for ActionController
 ///
@@ -22,13 +22,18 @@ use tonic::transport::Server;
 /// - Client connection establishment fails
 pub async fn init(manager: crate::manager::ActionControllerManager) -> common::Result<()> {
     let arc_manager = Arc::new(manager);
+
+    let watcher = arc_manager.clone();
+    tokio::spawn(async move { watcher.node_inventory.watch_registry().await });
     let grpc_server = receiver::ActionControllerReceiver::new(arc_manager.clone());
 
     let addr = common::actioncontroller::open_server().parse()?;
+    let mut builder = common::transport::server(Component::ActionController)
+        .map_err(|e| format!("Failed to configure gRPC server: {}", e))?;
     println!("Starting gRPC server on {}", addr);
 
     tokio::spawn(async move {
-        if let Err(e) = Server::builder()
+        if let Err(e) = builder
             .add_service(grpc_server.into_service())
             .serve(addr)
             .await
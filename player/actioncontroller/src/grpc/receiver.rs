@@ -1,4 +1,5 @@
-use std::sync::Arc;
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
 use tonic::{Request, Response, Status};
 
 // Import the generated protobuf code
@@ -6,9 +7,9 @@ use common::actioncontroller::{
     action_controller_connection_server::{
         ActionControllerConnection, ActionControllerConnectionServer,
     },
-    CompleteNetworkSettingRequest, CompleteNetworkSettingResponse, NetworkStatus,
-    PodStatus as ActionStatus, ReconcileRequest, ReconcileResponse, TriggerActionRequest,
-    TriggerActionResponse,
+    CompleteNetworkSettingRequest, CompleteNetworkSettingResponse, DrainNodeRequest,
+    DrainNodeResponse, NetworkStatus, PodStatus as ActionStatus, ReconcileRequest,
+    ReconcileResponse, TriggerActionRequest, TriggerActionResponse,
 };
 
 /// Receiver for handling incoming gRPC requests for ActionController
@@ -17,9 +18,93 @@ use common::actioncontroller::{
 /// the protobuf specification. Handles incoming requests from:
 /// - FilterGateway (trigger_action)
 /// - StateManager (reconcile)
+/// - ApiServer (drain_node)
 pub struct ActionControllerReceiver {
     /// Reference to the ActionController manager
     manager: Arc<crate::manager::ActionControllerManager>,
+    /// Idempotency keys of the triggers already handled or in progress
+    triggers: Mutex<TriggerKeys>,
+}
+
+/// Number of completed trigger keys remembered to detect redelivery
+const TRIGGER_KEY_CAPACITY: usize = 1024;
+
+/// Idempotency keys of recent `TriggerActionRequest`s
+///
+/// FilterGateway retries a trigger with the same key until it gets an
+/// answer, so a trigger whose response was lost is received again.
+#[derive(Debug, Default)]
+struct TriggerKeys {
+    in_progress: HashSet<String>,
+    completed: HashSet<String>,
+    /// Completed keys, oldest first
+    order: VecDeque<String>,
+}
+
+/// What to do with a received trigger
+#[derive(Debug, PartialEq)]
+enum TriggerCheck {
+    /// First delivery, run the action
+    Run,
+    /// The action already ran for this key
+    Completed,
+    /// The action is still running for this key
+    InProgress,
+}
+
+impl TriggerKeys {
+    fn begin(&mut self, key: &str) -> TriggerCheck {
+        if self.completed.contains(key) {
+            TriggerCheck::Completed
+        } else if !self.in_progress.insert(key.to_string()) {
+            TriggerCheck::InProgress
+        } else {
+            TriggerCheck::Run
+        }
+    }
+
+    /// Forget the running trigger, and remember it if the action succeeded
+    /// so that a failed trigger can be retried
+    fn finish(&mut self, key: &str, succeeded: bool) {
+        self.in_progress.remove(key);
+        if !succeeded || !self.completed.insert(key.to_string()) {
+            return;
+        }
+        self.order.push_back(key.to_string());
+        if self.order.len() > TRIGGER_KEY_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.completed.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// Trigger key held while its action runs
+///
+/// Dropping the guard releases the key, also when tonic drops the request
+/// future because the caller gave up, so that a redelivery can run it again.
+struct TriggerGuard<'a> {
+    keys: &'a Mutex<TriggerKeys>,
+    key: String,
+    succeeded: bool,
+}
+
+impl<'a> TriggerGuard<'a> {
+    fn new(keys: &'a Mutex<TriggerKeys>, key: String) -> Self {
+        Self {
+            keys,
+            key,
+            succeeded: false,
+        }
+    }
+}
+
+impl Drop for TriggerGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut keys) = self.keys.lock() {
+            keys.finish(&self.key, self.succeeded);
+        }
+    }
 }
 
 impl ActionControllerReceiver {
@@ -33,7 +118,10 @@ impl ActionControllerReceiver {
     ///
     /// A new ActionControllerReceiver instance
     pub fn new(manager: Arc<crate::manager::ActionControllerManager>) -> Self {
-        Self { manager }
+        Self {
+            manager,
+            triggers: Mutex::new(TriggerKeys::default()),
+        }
     }
 
     /// Get a gRPC server for this receiver
@@ -67,10 +155,41 @@ impl ActionControllerConnection for ActionControllerReceiver {
 
         println!("trigger_action in grpc receiver");
 
-        let scenario_name = request.into_inner().scenario_name;
+        let req = request.into_inner();
+        let scenario_name = req.scenario_name;
+        let key = req.idempotency_key;
         println!("trigger_action scenario: {}", scenario_name);
 
-        let result = match self.manager.trigger_manager_action(&scenario_name).await {
+        let mut guard = None;
+        if !key.is_empty() {
+            let check = match self.triggers.lock() {
+                Ok(mut triggers) => triggers.begin(&key),
+                Err(_) => TriggerCheck::Run,
+            };
+            match check {
+                TriggerCheck::Run => guard = Some(TriggerGuard::new(&self.triggers, key)),
+                TriggerCheck::Completed => {
+                    println!("trigger_action {} already handled, skipping", key);
+                    return Ok(Response::new(TriggerActionResponse {
+                        status: 0,
+                        desc: "Action already triggered".to_string(),
+                    }));
+                }
+                TriggerCheck::InProgress => {
+                    return Err(Status::aborted(format!(
+                        "Trigger {} is still in progress",
+                        key
+                    )));
+                }
+            }
+        }
+
+        let outcome = self.manager.trigger_manager_action(&scenario_name).await;
+        if let Some(mut guard) = guard {
+            guard.succeeded = outcome.is_ok();
+        }
+
+        let result = match outcome {
             Ok(_) => Ok(Response::new(TriggerActionResponse {
                 status: 0,
                 desc: "Action triggered successfully".to_string(),
@@ -159,6 +278,49 @@ impl ActionControllerConnection for ActionControllerReceiver {
         let response = CompleteNetworkSettingResponse { acknowledged: true };
         Ok(Response::new(response))
     }
+
+    /// Handle drain requests from ApiServer
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - gRPC request containing the node to drain and the nodes
+    ///   that must not receive moved models
+    ///
+    /// # Returns
+    ///
+    /// * `Response<DrainNodeResponse>` - stopped models and moved models
+    /// * `Status` - gRPC status error if the request fails
+    async fn drain_node(
+        &self,
+        request: Request<DrainNodeRequest>,
+    ) -> Result<Response<DrainNodeResponse>, Status> {
+        let req = request.into_inner();
+        println!("drain_node in grpc receiver: {}", req.node_name);
+
+        match self
+            .manager
+            .drain_node(&req.node_name, &req.unavailable_nodes)
+            .await
+        {
+            Ok((stopped_models, moved_models)) => Ok(Response::new(DrainNodeResponse {
+                status: 0,
+                desc: format!("Node '{}' drained successfully", req.node_name),
+                stopped_models,
+                moved_models,
+            })),
+            Err(e) => {
+                let err_msg = e.to_string();
+                if err_msg.contains("not found") || err_msg.contains("cannot be empty") {
+                    Err(Status::invalid_argument(err_msg))
+                } else {
+                    Err(Status::internal(format!(
+                        "Failed to drain node: {}",
+                        err_msg
+                    )))
+                }
+            }
+        }
+    }
 }
 
 fn i32_to_status(value: i32) -> ActionStatus {
@@ -181,6 +343,7 @@ mod tests {
     use crate::manager::ActionControllerManager;
     use common::actioncontroller::{ReconcileRequest, TriggerActionRequest};
     use std::sync::Arc;
+    use std::time::Duration;
     use tonic::Request;
 
     // #[tokio::test]
@@ -260,6 +423,7 @@ mod tests {
 
         let request = Request::new(TriggerActionRequest {
             scenario_name: "invalid_scenario".to_string(),
+            ..Default::default()
         });
 
         let response = receiver.trigger_action(request).await.unwrap_err();
@@ -328,6 +492,7 @@ mod tests {
 
         let request = Request::new(TriggerActionRequest {
             scenario_name: "antipinch-enable".to_string(),
+            ..Default::default()
         });
 
         let response = receiver.trigger_action(request).await.unwrap();
@@ -352,6 +517,71 @@ mod tests {
         assert!(response.message().contains("Failed to reconcile"));
     }
 
+    #[test]
+    fn test_trigger_keys_detect_redelivery() {
+        let mut keys = TriggerKeys::default();
+        assert_eq!(keys.begin("a"), TriggerCheck::Run);
+        assert_eq!(keys.begin("a"), TriggerCheck::InProgress);
+
+        // A failed trigger may be retried
+        keys.finish("a", false);
+        assert_eq!(keys.begin("a"), TriggerCheck::Run);
+        keys.finish("a", true);
+        assert_eq!(keys.begin("a"), TriggerCheck::Completed);
+
+        for i in 0..TRIGGER_KEY_CAPACITY {
+            let key = i.to_string();
+            keys.begin(&key);
+            keys.finish(&key, true);
+        }
+        assert_eq!(keys.begin("a"), TriggerCheck::Run);
+        assert_eq!(keys.completed.len(), TRIGGER_KEY_CAPACITY);
+    }
+
+    #[tokio::test]
+    async fn test_trigger_action_failure_with_idempotency_key() {
+        let manager = Arc::new(ActionControllerManager::new());
+        let receiver = ActionControllerReceiver::new(manager.clone());
+
+        // A failed trigger is run again when it is redelivered
+        for _ in 0..2 {
+            let request = Request::new(TriggerActionRequest {
+                scenario_name: "invalid_scenario".to_string(),
+                idempotency_key: "invalid_scenario-1".to_string(),
+            });
+            let response = receiver.trigger_action(request).await.unwrap_err();
+            assert!(response.message().contains("not found"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trigger_action_dropped_releases_key() {
+        let manager = Arc::new(ActionControllerManager::new());
+        let receiver = ActionControllerReceiver::new(manager.clone());
+        let request = || {
+            Request::new(TriggerActionRequest {
+                scenario_name: "invalid_scenario".to_string(),
+                idempotency_key: "invalid_scenario-2".to_string(),
+            })
+        };
+
+        // Drop the call mid-way, as tonic does when the caller times out
+        let mut call = Box::pin(receiver.trigger_action(request()));
+        if tokio::time::timeout(Duration::ZERO, &mut call)
+            .await
+            .is_err()
+        {
+            let keys = receiver.triggers.lock().unwrap();
+            assert!(keys.in_progress.contains("invalid_scenario-2"));
+        }
+        drop(call);
+        assert!(receiver.triggers.lock().unwrap().in_progress.is_empty());
+
+        // The redelivered trigger runs instead of being reported in progress
+        let response = receiver.trigger_action(request()).await.unwrap_err();
+        assert!(response.message().contains("not found"));
+    }
+
     #[test]
     fn test_i32_to_status_all_variants() {
         assert_eq!(i32_to_status(0), ActionStatus::None);
@@ -364,6 +594,20 @@ mod tests {
         assert_eq!(i32_to_status(-1), ActionStatus::Unknown);
     }
 
+    #[tokio::test]
+    async fn test_drain_node_unknown_node() {
+        let manager = Arc::new(ActionControllerManager::new());
+        let receiver = ActionControllerReceiver::new(manager.clone());
+
+        let request = Request::new(DrainNodeRequest {
+            node_name: "unknown-node".to_string(),
+            unavailable_nodes: vec![],
+        });
+
+        let response = receiver.drain_node(request).await.unwrap_err();
+        assert_eq!(response.code(), tonic::Code::InvalidArgument);
+    }
+
     #[test]
     fn test_receiver_new_and_into_service() {
         let manager = Arc::new(ActionControllerManager::new());
@@ -0,0 +1,47 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use common::apiserver::{
+    api_server_connection_client::ApiServerConnectionClient, GetNodesRequest, NodeInfo,
+};
+use common::transport::Component;
+use common::Result;
+use tonic::Request;
+
+/// Get all nodes registered in the cluster
+///
+/// Makes a gRPC request to ApiServer, which keeps the node registry.
+///
+/// # Returns
+///
+/// * `Ok(Vec<NodeInfo>)` - all registered nodes
+/// * `Err(...)` if the request fails
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The connection to ApiServer cannot be established
+/// - ApiServer reports that the registry could not be read
+pub async fn get_nodes() -> Result<Vec<NodeInfo>> {
+    let addr = common::apiserver::connect_grpc_server();
+    let channel = common::transport::connect(Component::ApiServer, addr)
+        .await
+        .map_err(|e| format!("Failed to connect to ApiServer: {}", e))?;
+    let mut client = ApiServerConnectionClient::new(channel);
+
+    let response = client
+        .get_nodes(Request::new(GetNodesRequest {
+            filter: None,
+            status_filter: None,
+        }))
+        .await?
+        .into_inner();
+
+    if response.success {
+        Ok(response.nodes)
+    } else {
+        Err(format!("Failed to get nodes: {}", response.message).into())
+    }
+}
@@ -5,6 +5,8 @@
 
 //! Running gRPC message sending
 
+pub mod apiserver;
+pub mod nodeagent;
 pub mod pharos;
 pub mod policymanager;
 pub mod statemanager;
@@ -0,0 +1,62 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use common::nodeagent::{
+    node_agent_connection_client::NodeAgentConnectionClient, HandleWorkloadRequest, WorkloadCommand,
+};
+use common::transport::Component;
+use common::Result;
+use tonic::Request;
+
+/// Start or stop the unit of a model on a NodeAgent node
+///
+/// # Arguments
+///
+/// * `addr` - URL of the NodeAgent of the node
+/// * `command` - Start or stop
+/// * `unit` - Unit name of the model, e.g. `version-display.service`
+///
+/// # Returns
+///
+/// * `Ok(())` if NodeAgent started or stopped the unit
+/// * `Err(...)` if NodeAgent cannot be reached or reports a failure
+pub async fn handle_workload(addr: String, command: WorkloadCommand, unit: &str) -> Result<()> {
+    let channel = common::transport::connect(Component::NodeAgent, addr)
+        .await
+        .map_err(|e| format!("Failed to connect to NodeAgent: {}", e))?;
+    let mut client = NodeAgentConnectionClient::new(channel);
+
+    let request = Request::new(HandleWorkloadRequest {
+        command: command.into(),
+        unit: unit.to_string(),
+    });
+    let response = client.handle_workload(request).await?.into_inner();
+    if response.status {
+        Ok(())
+    } else {
+        Err(format!(
+            "NodeAgent failed to {:?} '{}': {}",
+            command, unit, response.desc
+        )
+        .into())
+    }
+}
+
+//UNIT TEST
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_handle_workload_unreachable_node() {
+        let result = handle_workload(
+            "http://127.0.0.1:1".to_string(),
+            WorkloadCommand::Start,
+            "version-display.service",
+        )
+        .await;
+        assert!(result.is_err());
+    }
+}
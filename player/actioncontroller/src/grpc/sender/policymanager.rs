@@ -4,6 +4,7 @@ use common::nodeagent::{
 use common::policymanager::{
     policy_manager_connection_client::PolicyManagerConnectionClient, CheckPolicyRequest,
 };
+use common::transport::Component;
 use common::Result;
 use tonic::Request;
 
@@ -34,9 +35,10 @@ pub async fn check_policy(scenario_name: String) -> Result<()> {
     }
 
     let addr = common::policymanager::connect_server();
-    let mut client = PolicyManagerConnectionClient::connect(addr)
+    let channel = common::transport::connect(Component::PolicyManager, addr)
         .await
         .map_err(|e| format!("Failed to connect to PolicyManager: {}", e))?;
+    let mut client = PolicyManagerConnectionClient::new(channel);
 
     let request = tonic::Request::new(CheckPolicyRequest {
         scenario_name: scenario_name.clone(),
@@ -75,9 +77,9 @@ pub async fn check_policy(scenario_name: String) -> Result<()> {
 ///
 /// # Arguments
 ///
+/// * `addr` - URL of the NodeAgent of the target node, resolved through the
+///   node inventory by `ActionControllerManager::nodeagent_address`
 /// * `workload_name` - The name of the workload to handle
-/// * `action` - The action to perform (numeric code)
-/// * `description` - Additional information about the action
 ///
 /// # Returns
 ///
@@ -90,15 +92,15 @@ pub async fn check_policy(scenario_name: String) -> Result<()> {
 /// - The connection to NodeAgent is not established
 /// - The gRPC request fails
 /// - The workload handling operation fails
-pub async fn handle_yaml(workload_name: String) -> Result<bool> {
+pub async fn handle_yaml(addr: String, workload_name: String) -> Result<bool> {
     if workload_name.trim().is_empty() {
         return Err("Invalid input: workload name and description cannot be empty".into());
     }
 
-    let addr = common::nodeagent::connect_server();
-    let mut client = NodeAgentConnectionClient::connect(addr)
-        .await //.unwrap();
+    let channel = common::transport::connect(Component::NodeAgent, addr)
+        .await
         .map_err(|e| format!("Failed to connect to NodeAgent: {}", e))?;
+    let mut client = NodeAgentConnectionClient::new(channel);
 
     let request = Request::new(HandleYamlRequest {
         yaml: workload_name,
@@ -147,7 +149,7 @@ mod tests {
         let action = 1;
         let description = "example description".to_string();
 
-        let result = handle_yaml(workload_name).await;
+        let result = handle_yaml(common::nodeagent::connect_server(), workload_name).await;
         if let Err(ref e) = result {
             println!("Error in test_handle_workload_success: {:?}", e);
         } else {
@@ -163,7 +165,7 @@ mod tests {
         let action = -999; // Invalid action code
         let description = "".to_string(); // Empty description
 
-        let result = handle_yaml(workload_name).await;
+        let result = handle_yaml(common::nodeagent::connect_server(), workload_name).await;
 
         assert!(result.is_err());
     }
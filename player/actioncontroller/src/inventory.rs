@@ -0,0 +1,220 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Node inventory backed by the ApiServer node registry
+//!
+//! Nodes registered at runtime are looked up in the cluster registry kept by
+//! ApiServer. The answer is cached and refreshed whenever the registry
+//! changes in etcd, so that newly registered nodes become deployable targets
+//! without restarting ActionController. As a fallback when the watch is not
+//! running, the cache is also refreshed periodically, and right away (rate
+//! limited) when a node is not in the cache.
+
+use common::apiserver::NodeInfo;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// Metadata key in which a node reports its runtime type at registration
+pub const RUNTIME_METADATA_KEY: &str = "runtime";
+
+/// How long cached registry data is used before it is fetched again
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Minimum delay between two fetches triggered by unknown nodes
+const MISS_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// etcd prefix under which ApiServer stores registered nodes
+const REGISTRY_PREFIX: &str = "cluster/nodes/";
+
+/// Delay before watching the registry again after the watch ended
+const WATCH_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Runtime information of a registered node
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeEntry {
+    /// Runtime managing workloads on the node, `bluechi` or `nodeagent`
+    pub node_type: String,
+    /// IP address the node registered with
+    pub address: String,
+}
+
+/// Cache of the cluster node registry
+pub struct NodeInventory {
+    /// Entries keyed by lowercase hostname and node ID
+    cache: RwLock<HashMap<String, NodeEntry>>,
+    /// Time of the last fetch attempt, successful or not
+    refreshed_at: RwLock<Option<Instant>>,
+}
+
+impl Default for NodeInventory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NodeInventory {
+    /// Create an empty inventory, filled on first lookup
+    pub fn new() -> Self {
+        Self {
+            cache: RwLock::new(HashMap::new()),
+            refreshed_at: RwLock::new(None),
+        }
+    }
+
+    /// Look up a node by hostname or node ID
+    ///
+    /// # Arguments
+    ///
+    /// * `node_name` - Hostname or node ID, compared case-insensitively
+    ///
+    /// # Returns
+    ///
+    /// * `Some(NodeEntry)` if the node is registered
+    /// * `None` if it is unknown or the registry cannot be reached
+    pub async fn get(&self, node_name: &str) -> Option<NodeEntry> {
+        let key = node_name.to_ascii_lowercase();
+        let cached = self.cache.read().ok()?.get(&key).cloned();
+
+        let since_refresh = match *self.refreshed_at.read().ok()? {
+            Some(at) => at.elapsed(),
+            None => Duration::MAX,
+        };
+        let needs_refresh = match cached {
+            Some(_) => since_refresh >= REFRESH_INTERVAL,
+            None => since_refresh >= MISS_REFRESH_INTERVAL,
+        };
+        if !needs_refresh {
+            return cached;
+        }
+
+        self.refresh().await;
+        self.cache.read().ok()?.get(&key).cloned()
+    }
+
+    /// Fetch all nodes from the registry and replace the cache
+    ///
+    /// The previous cache is kept when ApiServer cannot be reached.
+    pub async fn refresh(&self) {
+        if let Ok(mut refreshed_at) = self.refreshed_at.write() {
+            *refreshed_at = Some(Instant::now());
+        }
+
+        let nodes = match crate::grpc::sender::apiserver::get_nodes().await {
+            Ok(nodes) => nodes,
+            Err(e) => {
+                println!("Node registry is not available: {}", e);
+                return;
+            }
+        };
+
+        self.update(&nodes);
+    }
+
+    /// Refresh the cache whenever the registry changes
+    ///
+    /// Watches the etcd prefix in which ApiServer stores registered nodes and
+    /// fetches the registry again after every change. Runs until the task is
+    /// dropped, watching again when the watch ends or cannot be set up.
+    pub async fn watch_registry(&self) {
+        loop {
+            match common::etcd::watch_prefix(REGISTRY_PREFIX).await {
+                Ok((_watcher, mut stream)) => {
+                    // Changes made before the watch started are not reported
+                    self.refresh().await;
+                    while let Ok(Some(response)) = stream.message().await {
+                        if !response.events().is_empty() {
+                            self.refresh().await;
+                        }
+                    }
+                    println!("Node registry watch ended");
+                }
+                Err(e) => println!("Cannot watch node registry: {}", e),
+            }
+            tokio::time::sleep(WATCH_RETRY_INTERVAL).await;
+        }
+    }
+
+    /// Replace the cache with entries built from registered nodes
+    fn update(&self, nodes: &[NodeInfo]) {
+        let mut entries = HashMap::new();
+        for node in nodes {
+            let entry = NodeEntry {
+                node_type: node
+                    .metadata
+                    .get(RUNTIME_METADATA_KEY)
+                    .cloned()
+                    .unwrap_or_else(|| "nodeagent".to_string()),
+                address: node.ip_address.clone(),
+            };
+            entries.insert(node.hostname.to_ascii_lowercase(), entry.clone());
+            entries.insert(node.node_id.to_ascii_lowercase(), entry);
+        }
+
+        if let Ok(mut cache) = self.cache.write() {
+            if *cache != entries {
+                println!("Node inventory updated: {} nodes", nodes.len());
+                *cache = entries;
+            }
+        }
+    }
+}
+
+//UNIT TEST
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_node(hostname: &str, runtime: Option<&str>) -> NodeInfo {
+        let mut metadata = HashMap::new();
+        if let Some(runtime) = runtime {
+            metadata.insert(RUNTIME_METADATA_KEY.to_string(), runtime.to_string());
+        }
+        NodeInfo {
+            node_id: format!("{}-192.168.0.2", hostname),
+            hostname: hostname.to_string(),
+            ip_address: "192.168.0.2".to_string(),
+            metadata,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_cached_entries() {
+        let inventory = NodeInventory::new();
+        inventory.update(&[
+            create_test_node("ZONE", Some("nodeagent")),
+            create_test_node("HPC2", Some("bluechi")),
+            create_test_node("cloud", None),
+        ]);
+        // Pretend the registry was just read so that lookups use the cache
+        *inventory.refreshed_at.write().unwrap() = Some(Instant::now());
+
+        let entry = inventory.get("zone").await.unwrap();
+        assert_eq!(entry.node_type, "nodeagent");
+        assert_eq!(entry.address, "192.168.0.2");
+
+        let entry = inventory.get("HPC2-192.168.0.2").await.unwrap();
+        assert_eq!(entry.node_type, "bluechi");
+
+        // Nodes registered without runtime metadata run NodeAgent
+        let entry = inventory.get("cloud").await.unwrap();
+        assert_eq!(entry.node_type, "nodeagent");
+
+        // Misses within the retry interval do not contact ApiServer
+        assert!(inventory.get("unknown").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_update_replaces_removed_nodes() {
+        let inventory = NodeInventory::new();
+        inventory.update(&[create_test_node("ZONE", Some("nodeagent"))]);
+        inventory.update(&[create_test_node("HPC2", Some("bluechi"))]);
+        *inventory.refreshed_at.write().unwrap() = Some(Instant::now());
+
+        assert!(inventory.get("ZONE").await.is_none());
+        assert!(inventory.get("HPC2").await.is_some());
+    }
+}
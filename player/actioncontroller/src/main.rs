@@ -1,6 +1,7 @@
 use std::error::Error;
 
 mod grpc;
+mod inventory;
 mod manager;
 mod runtime;
 
@@ -41,6 +42,7 @@ async fn initialize(skip_grpc: bool) -> Result<(), Box<dyn Error>> {
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     println!("Starting ActionController...");
+    common::transport::set_local_component(common::transport::Component::ActionController);
 
     // Initialize the controller
     initialize(false).await?;
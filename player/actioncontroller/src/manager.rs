@@ -1,9 +1,14 @@
 use std::{thread, time::Duration};
 
-use crate::{grpc::sender::pharos::request_network_pod, runtime::bluechi};
+use crate::{
+    grpc::sender::{nodeagent, pharos::request_network_pod},
+    inventory::NodeInventory,
+    runtime::bluechi,
+};
 use common::{
-    actioncontroller::PodStatus as Status,
-    spec::artifact::{Network, Node, Package, Scenario},
+    actioncontroller::{MovedModel, PodStatus as Status},
+    nodeagent::WorkloadCommand,
+    spec::artifact::{Artifact, Network, Node, Package, Scenario},
     Result,
 };
 
@@ -19,6 +24,8 @@ pub struct ActionControllerManager {
     pub bluechi_nodes: Vec<String>,
     /// List of nodes managed by NodeAgent
     pub nodeagent_nodes: Vec<String>,
+    /// Nodes registered in the cluster at runtime
+    pub node_inventory: NodeInventory,
     // Add other fields as needed
 }
 
@@ -55,6 +62,7 @@ impl ActionControllerManager {
         Self {
             bluechi_nodes,
             nodeagent_nodes,
+            node_inventory: NodeInventory::new(),
         }
     }
 
@@ -127,27 +135,21 @@ impl ActionControllerManager {
         for mi in package.get_models() {
             let model_name = format!("{}.service", mi.get_name());
             let model_node = mi.get_node();
-            let node_type = if self.bluechi_nodes.contains(&model_node) {
-                println!("Node {} is bluechi", model_node);
-                "bluechi"
-            } else if self.nodeagent_nodes.contains(&model_node) {
-                println!("Node {} is nodeagent", model_node);
-                "nodeagent"
-            } else {
+            let Some(node_type) = self.get_node_type(&model_node).await else {
                 // Log warning for unknown node types and skip processing
                 println!(
-                    "Warning: Node '{}' is not explicitly configured. Skipping deployment.",
+                    "Warning: Node '{}' is neither configured nor registered. Skipping deployment.",
                     model_node
                 );
                 continue;
             };
+            println!("Node {} is {}", model_node, node_type);
             println!(
                 "Processing model '{}' on node '{}' with action '{}'",
                 model_name, model_node, action
             );
             match action.as_str() {
                 "launch" => {
-                    self.reload_all_node(&model_name, &model_node).await?;
                     self.start_workload(&model_name, &model_node, &node_type)
                         .await
                         .map_err(|e| format!("Failed to start workload '{}': {}", model_name, e))?;
@@ -166,18 +168,15 @@ impl ActionControllerManager {
                     }
                 }
                 "terminate" => {
-                    self.reload_all_node(&model_name, &model_node).await?;
                     self.stop_workload(&model_name, &model_node, &node_type)
                         .await
                         .map_err(|e| format!("Failed to stop workload '{}': {}", model_name, e))?;
                 }
                 "update" | "rollback" => {
-                    self.reload_all_node(&model_name, &model_node).await?;
                     self.stop_workload(&model_name, &model_node, &node_type)
                         .await
                         .map_err(|e| format!("Failed to stop workload '{}': {}", model_name, e))?;
 
-                    self.reload_all_node(&model_name, &model_node).await?;
                     self.start_workload(&model_name, &model_node, &node_type)
                         .await
                         .map_err(|e| format!("Failed to start workload '{}': {}", model_name, e))?;
@@ -250,14 +249,10 @@ impl ActionControllerManager {
         for mi in package.get_models() {
             let model_name = format!("{}.service", mi.get_name());
             let model_node = mi.get_node();
-            let node_type = if self.bluechi_nodes.contains(&model_node) {
-                "bluechi"
-            } else if self.nodeagent_nodes.contains(&model_node) {
-                "nodeagent"
-            } else {
+            let Some(node_type) = self.get_node_type(&model_node).await else {
                 // Log warning for unknown node types and skip processing
                 println!(
-                    "Warning: Node '{}' is not explicitly configured. Skipping deployment.",
+                    "Warning: Node '{}' is neither configured nor registered. Skipping deployment.",
                     model_node
                 );
                 continue;
@@ -363,6 +358,9 @@ impl ActionControllerManager {
 
     /// Starts a paused or stopped workload for the specified scenario
     ///
+    /// Bluechi nodes are reloaded before the unit is started, NodeAgent nodes
+    /// are asked over gRPC to start the unit.
+    ///
     /// # Arguments
     ///
     /// * `scenario_name` - Name of the scenario
@@ -387,14 +385,15 @@ impl ActionControllerManager {
     ) -> Result<()> {
         match node_type {
             "bluechi" => {
+                self.reload_all_node(model_name, node_name).await?;
                 let cmd = bluechi::BluechiCmd {
                     command: bluechi::Command::UnitStart,
                 };
                 bluechi::handle_bluechi_cmd(&model_name, &node_name, cmd).await?;
             }
             "nodeagent" => {
-                // let runtime = crate::runtime::nodeagent::NodeAgentRuntime::new();
-                // runtime.start_workload(model_name).await?;
+                let addr = self.nodeagent_address(node_name).await?;
+                nodeagent::handle_workload(addr, WorkloadCommand::Start, model_name).await?;
             }
             _ => {
                 return Err(format!(
@@ -409,6 +408,9 @@ impl ActionControllerManager {
 
     /// Stops an active workload for the specified scenario
     ///
+    /// Bluechi nodes are reloaded before the unit is stopped, NodeAgent nodes
+    /// are asked over gRPC to stop the unit.
+    ///
     /// # Arguments
     ///
     /// * `scenario_name` - Name of the scenario
@@ -433,14 +435,15 @@ impl ActionControllerManager {
     ) -> Result<()> {
         match node_type {
             "bluechi" => {
+                self.reload_all_node(model_name, node_name).await?;
                 let cmd = bluechi::BluechiCmd {
                     command: bluechi::Command::UnitStop,
                 };
                 bluechi::handle_bluechi_cmd(&model_name, &node_name, cmd).await?;
             }
             "nodeagent" => {
-                // let runtime = crate::runtime::nodeagent::NodeAgentRuntime::new();
-                // runtime.start_workload(model_name).await?;
+                let addr = self.nodeagent_address(node_name).await?;
+                nodeagent::handle_workload(addr, WorkloadCommand::Stop, model_name).await?;
             }
             _ => {
                 return Err(format!(
@@ -453,6 +456,165 @@ impl ActionControllerManager {
         Ok(())
     }
 
+    /// Stops every model placed on a node and moves it when possible
+    ///
+    /// Scans all packages stored in ETCD for models assigned to `node_name`.
+    /// Each of them is stopped, and when the model lists `alternates`, it is
+    /// started on the first alternate node that is managed by this
+    /// actioncontroller and not listed in `unavailable_nodes`.
+    ///
+    /// # Arguments
+    ///
+    /// * `node_name` - Name of the node to drain
+    /// * `unavailable_nodes` - Nodes that must not receive moved models
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((stopped, moved))` - names of stopped models and the models that
+    ///   were started on an alternate node
+    /// * `Err(...)` if the node is unknown or a runtime operation fails
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The node name is empty or not managed by this actioncontroller
+    /// - Packages cannot be read from ETCD
+    /// - Stopping or starting a workload fails
+    pub async fn drain_node(
+        &self,
+        node_name: &str,
+        unavailable_nodes: &[String],
+    ) -> Result<(Vec<String>, Vec<MovedModel>)> {
+        if node_name.trim().is_empty() {
+            return Err("Node name cannot be empty".into());
+        }
+        let node_type = self
+            .get_node_type(node_name)
+            .await
+            .ok_or_else(|| format!("Node '{}' not found in managed nodes", node_name))?;
+
+        let packages = common::etcd::get_all_with_prefix("Package/")
+            .await
+            .map_err(|e| format!("Failed to read packages: {}", e))?;
+
+        let mut stopped = Vec::new();
+        let mut moved = Vec::new();
+        for kv in packages {
+            let package: Package = match serde_yaml::from_str(&kv.value) {
+                Ok(package) => package,
+                Err(e) => {
+                    println!("Warning: skipping package '{}': {}", kv.key, e);
+                    continue;
+                }
+            };
+
+            for mi in package.get_models() {
+                let model_node = mi.get_node();
+                if !model_node.eq_ignore_ascii_case(node_name) {
+                    continue;
+                }
+                let model_name = format!("{}.service", mi.get_name());
+
+                self.stop_workload(&model_name, &model_node, node_type)
+                    .await
+                    .map_err(|e| format!("Failed to stop workload '{}': {}", model_name, e))?;
+                stopped.push(mi.get_name());
+
+                let mut target = None;
+                for alt in mi.get_alternates() {
+                    if alt.eq_ignore_ascii_case(node_name)
+                        || unavailable_nodes
+                            .iter()
+                            .any(|n| n.eq_ignore_ascii_case(&alt))
+                    {
+                        continue;
+                    }
+                    if let Some(alt_type) = self.get_node_type(&alt).await {
+                        target = Some((alt, alt_type));
+                        break;
+                    }
+                }
+                let Some((target, target_type)) = target else {
+                    println!("Model '{}' stopped, no alternate node", model_name);
+                    continue;
+                };
+
+                self.start_workload(&model_name, &target, target_type)
+                    .await
+                    .map_err(|e| format!("Failed to start workload '{}': {}", model_name, e))?;
+                println!("Model '{}' moved to node '{}'", model_name, target);
+
+                moved.push(MovedModel {
+                    package_name: package.get_name(),
+                    model_name: mi.get_name(),
+                    node_name: target,
+                });
+            }
+        }
+
+        Ok((stopped, moved))
+    }
+
+    /// Returns the runtime type of a node, `None` if it is unknown
+    ///
+    /// Nodes registered in the cluster are resolved through the node
+    /// inventory first, nodes configured in settings are the fallback when
+    /// the registry does not know the node or cannot be reached. Node names
+    /// are compared case-insensitively, like host names.
+    async fn get_node_type(&self, node_name: &str) -> Option<&'static str> {
+        if let Some(entry) = self.node_inventory.get(node_name).await {
+            match entry.node_type.as_str() {
+                "bluechi" => return Some("bluechi"),
+                "nodeagent" => return Some("nodeagent"),
+                other => println!(
+                    "Warning: Node '{}' ({}) has unsupported runtime '{}'",
+                    node_name, entry.address, other
+                ),
+            }
+        }
+
+        if self
+            .bluechi_nodes
+            .iter()
+            .any(|n| n.eq_ignore_ascii_case(node_name))
+        {
+            Some("bluechi")
+        } else if self
+            .nodeagent_nodes
+            .iter()
+            .any(|n| n.eq_ignore_ascii_case(node_name))
+        {
+            Some("nodeagent")
+        } else {
+            None
+        }
+    }
+
+    /// Returns the URL of the NodeAgent running on a node
+    ///
+    /// The address a node registered with is preferred, nodes configured in
+    /// settings fall back to their configured IP.
+    async fn nodeagent_address(&self, node_name: &str) -> Result<String> {
+        if let Some(entry) = self.node_inventory.get(node_name).await {
+            if !entry.address.is_empty() {
+                return Ok(common::nodeagent::connect_node_server(&entry.address));
+            }
+        }
+
+        let config = common::setting::get_config();
+        if config.host.name.eq_ignore_ascii_case(node_name) {
+            return Ok(common::nodeagent::connect_node_server(&config.host.ip));
+        }
+        config
+            .guest
+            .iter()
+            .flatten()
+            .find(|guest| guest.name.eq_ignore_ascii_case(node_name))
+            .map(|guest| common::nodeagent::connect_node_server(&guest.ip))
+            .ok_or_else(|| format!("No address known for NodeAgent node '{}'", node_name).into())
+    }
+
+    /// Reloads all Bluechi nodes so that new unit files are picked up
     pub async fn reload_all_node(&self, model_name: &str, model_node: &str) -> Result<()> {
         let cmd = bluechi::BluechiCmd {
             command: bluechi::Command::ControllerReloadAllNodes,
@@ -477,6 +639,7 @@ mod tests {
         let manager = ActionControllerManager {
             bluechi_nodes: vec!["HPC".to_string()],
             nodeagent_nodes: vec![],
+            node_inventory: NodeInventory::new(),
         };
         let result = manager
             .reconcile_do("antipinch-enable".into(), Status::Running, Status::Running)
@@ -529,6 +692,7 @@ spec:
         let manager = ActionControllerManager {
             bluechi_nodes: vec!["HPC".to_string()],
             nodeagent_nodes: vec![],
+            node_inventory: NodeInventory::new(),
         };
 
         let result = manager.trigger_manager_action("antipinch-enable").await;
@@ -556,6 +720,7 @@ spec:
         let manager: ActionControllerManager = ActionControllerManager {
             bluechi_nodes: vec!["HPC".to_string()],
             nodeagent_nodes: vec![],
+            node_inventory: NodeInventory::new(),
         };
 
         let result = manager.trigger_manager_action("invalid_scenario").await;
@@ -568,6 +733,7 @@ spec:
         let manager = ActionControllerManager {
             bluechi_nodes: vec!["HPC".to_string()],
             nodeagent_nodes: vec![],
+            node_inventory: NodeInventory::new(),
         };
 
         let result = manager
@@ -582,6 +748,7 @@ spec:
         let manager = ActionControllerManager {
             bluechi_nodes: vec!["HPC".to_string()],
             nodeagent_nodes: vec![],
+            node_inventory: NodeInventory::new(),
         };
 
         let result: std::result::Result<(), Box<dyn Error>> = manager
@@ -596,6 +763,7 @@ spec:
         let manager: ActionControllerManager = ActionControllerManager {
             bluechi_nodes: vec!["HPC".to_string()],
             nodeagent_nodes: vec![],
+            node_inventory: NodeInventory::new(),
         };
 
         let result = manager
@@ -618,6 +786,7 @@ spec:
         let manager = ActionControllerManager {
             bluechi_nodes: vec![],
             nodeagent_nodes: vec![],
+            node_inventory: NodeInventory::new(),
         };
 
         assert!(manager.create_workload("test".into()).await.is_ok());
@@ -632,6 +801,7 @@ spec:
         let manager = ActionControllerManager {
             bluechi_nodes: vec!["HPC".to_string()],
             nodeagent_nodes: vec!["ZONE".to_string()],
+            node_inventory: NodeInventory::new(),
         };
 
         // Test that nodes are properly categorized
@@ -642,4 +812,34 @@ spec:
         // The logic now skips unknown nodes instead of processing them
         // This test validates that the manager is set up correctly
     }
+
+    #[tokio::test]
+    async fn test_get_node_type() {
+        let manager = ActionControllerManager {
+            bluechi_nodes: vec!["HPC".to_string()],
+            nodeagent_nodes: vec!["ZONE".to_string()],
+            node_inventory: NodeInventory::new(),
+        };
+
+        assert_eq!(manager.get_node_type("HPC").await, Some("bluechi"));
+        assert_eq!(manager.get_node_type("ZONE").await, Some("nodeagent"));
+        assert_eq!(manager.get_node_type("hpc").await, Some("bluechi"));
+        assert_eq!(manager.get_node_type("cloud").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_drain_node_unknown_node() {
+        let manager = ActionControllerManager {
+            bluechi_nodes: vec!["HPC".to_string()],
+            nodeagent_nodes: vec![],
+            node_inventory: NodeInventory::new(),
+        };
+
+        let result = manager.drain_node("cloud", &[]).await;
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("not found"));
+
+        let result = manager.drain_node("", &[]).await;
+        assert!(result.is_err());
+    }
 }
@@ -1,45 +1,176 @@
 // Code generation module
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
-use crate::build_scripts::idl::{collect_idl_files, IdlParser};
-use crate::build_scripts::types::idl_to_rust_type;
+use crate::build_scripts::grammar::{EnumDesc, StructDesc, TypeDesc};
+use crate::build_scripts::idl::{IdlFile, IdlParser};
+use crate::build_scripts::types::{field_name, rust_type};
 
-/// Function to generate struct file
+/// Generated items of one IDL module
+#[derive(Default)]
+struct ModuleTree {
+    imports: BTreeSet<String>,
+    items: Vec<String>,
+    children: BTreeMap<String, ModuleTree>,
+}
+
+impl ModuleTree {
+    fn render(&self, depth: usize, out: &mut String) {
+        let indent = "    ".repeat(depth);
+        out.push_str(&format!(
+            "{}use serde::{{Deserialize, Serialize}};\n",
+            indent
+        ));
+        out.push_str(&format!(
+            "{}use dust_dds::topic_definition::type_support::{{DdsType, DdsSerialize, DdsDeserialize}};\n",
+            indent
+        ));
+        for import in &self.imports {
+            out.push_str(&format!("{}{}\n", indent, import));
+        }
+        for item in &self.items {
+            out.push('\n');
+            for line in item.lines() {
+                out.push_str(&format!("{}{}\n", indent, line));
+            }
+        }
+        for (name, child) in &self.children {
+            out.push_str(&format!("\n{}pub mod {} {{\n", indent, name));
+            child.render(depth + 1, out);
+            out.push_str(&format!("{}}}\n", indent));
+        }
+    }
+}
+
+fn render_struct(
+    desc: &StructDesc,
+    scope: &[&str],
+    imports: &mut BTreeSet<String>,
+) -> Result<String, String> {
+    let name = desc.name.rsplit("::").next().unwrap_or(&desc.name);
+    let mut item = String::new();
+    item.push_str("#[derive(Debug, Clone, Serialize, Deserialize, DdsType, Default)]\n");
+    item.push_str(&format!("pub struct {} {{\n", name));
+    for (member, member_type) in &desc.members {
+        let member_type = rust_type(member_type, scope, imports)
+            .map_err(|e| format!("{}.{}: {}", desc.name, member, e))?;
+        if desc.keys.contains(member) {
+            item.push_str("    #[dust_dds(key)]\n");
+        }
+        item.push_str(&format!(
+            "    pub {}: {},\n",
+            field_name(member),
+            member_type
+        ));
+    }
+    item.push_str("}\n");
+    Ok(item)
+}
+
+fn render_enum(desc: &EnumDesc) -> String {
+    let name = desc.name.rsplit("::").next().unwrap_or(&desc.name);
+    let mut item = String::new();
+    item.push_str(
+        "#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, DdsType, Default)]\n",
+    );
+    item.push_str(&format!("pub enum {} {{\n", name));
+    for (i, enumerator) in desc.enumerators.iter().enumerate() {
+        if i == 0 {
+            item.push_str("    #[default]\n");
+        }
+        item.push_str(&format!("    {},\n", enumerator));
+    }
+    item.push_str("}\n");
+    item
+}
+
+/// Generate the Rust source of an IDL file
+///
+/// IDL modules become nested Rust modules, `@key` members get
+/// `#[dust_dds(key)]` and typedefs become type aliases.
+pub fn generate_module_source(file: &IdlFile) -> Result<String, String> {
+    let mut root = ModuleTree::default();
+
+    for (qualified, desc) in &file.types {
+        let parts: Vec<&str> = qualified.split("::").collect();
+        let Some((name, scope)) = parts.split_last() else {
+            continue;
+        };
+        let node = scope.iter().fold(&mut root, |node, module| {
+            node.children.entry(module.to_string()).or_default()
+        });
+
+        let item = match desc {
+            TypeDesc::Struct(s) if s.name == *qualified => {
+                render_struct(s, scope, &mut node.imports)?
+            }
+            TypeDesc::Enum(e) if e.name == *qualified => render_enum(e),
+            alias => format!(
+                "pub type {} = {};\n",
+                name,
+                rust_type(alias, scope, &mut node.imports)
+                    .map_err(|e| format!("{}: {}", qualified, e))?
+            ),
+        };
+        node.items.push(item);
+    }
+
+    let mut out = String::new();
+    root.render(0, &mut out);
+    Ok(out)
+}
+
+/// Function to generate the Rust file of an IDL file
 pub fn generate_struct_file(
     out_dir: &str,
-    file_name: &str,
-    struct_name: &str,
-    fields: &HashMap<String, String>,
+    file: &IdlFile,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let output_path = Path::new(out_dir).join(format!("{}.rs", file_name));
-    let mut file = fs::File::create(output_path)?;
-
-    // Write struct header with updated derive attributes
-    writeln!(file, "use serde::{{Deserialize, Serialize}};")?;
-    writeln!(
-        file,
-        "use dust_dds::topic_definition::type_support::{{DdsType, DdsSerialize, DdsDeserialize}};"
-    )?;
-    writeln!(file, "")?;
-    writeln!(
-        file,
-        "#[derive(Debug, Clone, Serialize, Deserialize, DdsType, Default)]"
-    )?;
-    writeln!(file, "pub struct {} {{", struct_name)?;
+    let source = generate_module_source(file)?;
+    let output_path = Path::new(out_dir).join(format!("{}.rs", file.module));
+    fs::write(output_path, source)?;
+    Ok(())
+}
 
-    // Write fields
-    for (name, field_type) in fields {
-        let rust_type = idl_to_rust_type(field_type);
-        writeln!(file, "    pub {}: {},", name, rust_type)?;
+/// Topic type names of the generated structs and their Rust paths
+///
+/// Every struct is registered under its module-qualified name, and also
+/// under its bare name when no other struct has the same one.
+pub fn registry_entries(files: &[IdlFile]) -> Vec<(String, String)> {
+    let mut bare_names: HashMap<String, usize> = HashMap::new();
+    for file in files {
+        for s in file.structs() {
+            let bare = s.name.rsplit("::").next().unwrap_or(&s.name).to_string();
+            *bare_names.entry(bare).or_default() += 1;
+        }
     }
 
-    // Close struct (removed manual impl of DdsType)
-    writeln!(file, "}}")?;
+    let mut entries = Vec::new();
+    for file in files {
+        for s in file.structs() {
+            let rust_path = format!("{}::{}", file.module, s.name);
+            entries.push((s.name.clone(), rust_path.clone()));
 
-    Ok(())
+            let bare = s.name.rsplit("::").next().unwrap_or(&s.name);
+            if bare != s.name && bare_names.get(bare) == Some(&1) {
+                entries.push((bare.to_string(), rust_path));
+            }
+        }
+    }
+    entries
+}
+
+/// Parse the IDL files a Rust module is generated for
+///
+/// Files that cannot be parsed or generated are skipped, as in
+/// `generate_dds_module`, so that no registry entry refers to a missing type.
+fn parse_idl_files(idl_files: &[PathBuf]) -> Vec<IdlFile> {
+    idl_files
+        .iter()
+        .filter_map(|path| IdlParser::parse_idl_file(path).ok())
+        .filter(|file| generate_module_source(file).is_ok())
+        .collect()
 }
 
 /// 타입 레지스트리 생성 함수
@@ -75,31 +206,21 @@ pub fn generate_type_registry(
     )?;
     writeln!(registry_file, "    match type_name {{")?;
 
-    // 각 IDL 파일에 대한 매핑 생성
-    for idl_file in idl_files {
-        if let Some(file_stem) = idl_file.file_stem() {
-            let module_name = file_stem.to_string_lossy();
-
-            // IDL 파일 파싱
-            if let Ok(dds_data) = IdlParser::parse_idl_file(idl_file) {
-                let struct_name = &dds_data.name;
-
-                // 타입 매핑 추가
-                writeln!(registry_file, "        \"{}\" => {{", struct_name)?;
-                writeln!(
-                    registry_file,
-                    "            let listener = Box::new(GenericTopicListener::<{}::{}>::new(",
-                    module_name, struct_name
-                )?;
-                writeln!(registry_file, "                topic_name,")?;
-                writeln!(registry_file, "                type_name.to_string(),")?;
-                writeln!(registry_file, "                tx,")?;
-                writeln!(registry_file, "                domain_id,")?;
-                writeln!(registry_file, "            ));")?;
-                writeln!(registry_file, "            Some(listener)")?;
-                writeln!(registry_file, "        }},")?;
-            }
-        }
+    // 각 구조체에 대한 매핑 생성
+    for (type_name, rust_path) in registry_entries(&parse_idl_files(idl_files)) {
+        writeln!(registry_file, "        \"{}\" => {{", type_name)?;
+        writeln!(
+            registry_file,
+            "            let listener = Box::new(GenericTopicListener::<{}>::new(",
+            rust_path
+        )?;
+        writeln!(registry_file, "                topic_name,")?;
+        writeln!(registry_file, "                type_name.to_string(),")?;
+        writeln!(registry_file, "                tx,")?;
+        writeln!(registry_file, "                domain_id,")?;
+        writeln!(registry_file, "            ));")?;
+        writeln!(registry_file, "            Some(listener)")?;
+        writeln!(registry_file, "        }},")?;
     }
 
     // 기본 매핑 종료
@@ -167,13 +288,14 @@ pub fn generate_dds_module(
         let file_stem = idl_file.file_stem().unwrap().to_string_lossy();
 
         // IDL 파일 파싱
-        let dds_data = match IdlParser::parse_idl_file(idl_file) {
-            Ok(data) => {
+        let idl = match IdlParser::parse_idl_file(idl_file) {
+            Ok(idl) => {
                 println!(
-                    "Successfully parsed IDL file: {} (struct: {})",
-                    file_stem, data.name
+                    "Successfully parsed IDL file: {} (types: {:?})",
+                    file_stem,
+                    idl.types.keys().collect::<Vec<_>>()
                 );
-                data
+                idl
             }
             Err(e) => {
                 println!("Error parsing IDL file {}: {:?}", file_stem, e);
@@ -181,18 +303,21 @@ pub fn generate_dds_module(
             }
         };
 
-        if dds_data.fields.is_empty() {
-            println!("Warning: No fields found in struct {}", dds_data.name);
+        if idl.structs().is_empty() {
+            println!("Warning: No struct found in {}", file_stem);
         }
 
-        // 구조체 파일 생성
-        if let Err(e) = generate_struct_file(out_dir, &file_stem, &dds_data.name, &dds_data.fields)
-        {
+        // 모듈 파일 생성
+        if let Err(e) = generate_struct_file(out_dir, &idl) {
             println!("Error generating struct file for {}: {:?}", file_stem, e);
             continue;
         }
 
         // 모듈에 추가
+        writeln!(
+            modules_file,
+            "#[allow(non_camel_case_types, non_snake_case)]"
+        )?;
         writeln!(modules_file, "pub mod {} {{", file_stem)?;
         writeln!(modules_file, "    include!(\"{}.rs\");", file_stem)?;
         writeln!(modules_file, "}}")?;
@@ -241,46 +366,39 @@ pub fn generate_type_metadata_registry(
     writeln!(registry_file, "    let mut metadata = HashMap::new();")?;
     writeln!(registry_file, "    let mut fields;")?;
 
-    // 각 타입에 대한 메타데이터 추가
-    for idl_file in idl_files {
-        if let Some(file_stem) = idl_file.file_stem() {
-            let module_name = file_stem.to_string_lossy();
-
-            // IDL 파일 파싱
-            if let Ok(dds_data) = IdlParser::parse_idl_file(idl_file) {
-                let struct_name = &dds_data.name;
-
-                writeln!(registry_file, "    fields = HashMap::new();")?;
+    // 각 구조체에 대한 메타데이터 추가
+    for idl in parse_idl_files(idl_files) {
+        for s in idl.structs() {
+            let scope: Vec<&str> = s.name.split("::").collect();
+            let scope = &scope[..scope.len() - 1];
 
-                // 필드 정보 추가
-                for (field_name, field_type) in &dds_data.fields {
-                    let rust_type = idl_to_rust_type(field_type);
-                    writeln!(
-                        registry_file,
-                        "    fields.insert(\"{}\".to_string(), \"{}\".to_string());",
-                        field_name, rust_type
-                    )?;
-                }
+            writeln!(registry_file, "    fields = HashMap::new();")?;
 
-                // 메타데이터 객체 추가
+            // 필드 정보 추가
+            for (field_name, field_type) in &s.members {
+                let rust_type =
+                    rust_type(field_type, scope, &mut BTreeSet::new()).unwrap_or_default();
                 writeln!(
                     registry_file,
-                    "    metadata.insert(\"{}\".to_string(), TypeMetadata {{",
-                    struct_name
+                    "    fields.insert(\"{}\".to_string(), \"{}\".to_string());",
+                    field_name, rust_type
                 )?;
-                writeln!(
-                    registry_file,
-                    "        name: \"{}\".to_string(),",
-                    struct_name
-                )?;
-                writeln!(
-                    registry_file,
-                    "        module: \"{}\".to_string(),",
-                    module_name
-                )?;
-                writeln!(registry_file, "        fields,")?;
-                writeln!(registry_file, "    }});")?;
             }
+
+            // 메타데이터 객체 추가
+            writeln!(
+                registry_file,
+                "    metadata.insert(\"{}\".to_string(), TypeMetadata {{",
+                s.name
+            )?;
+            writeln!(registry_file, "        name: \"{}\".to_string(),", s.name)?;
+            writeln!(
+                registry_file,
+                "        module: \"{}\".to_string(),",
+                idl.module
+            )?;
+            writeln!(registry_file, "        fields,")?;
+            writeln!(registry_file, "    }});")?;
         }
     }
 
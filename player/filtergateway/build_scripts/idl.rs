@@ -1,74 +1,46 @@
 // Module for IDL parsing
-use crate::build_scripts::types::DdsData;
+use crate::build_scripts::grammar::{self, StructDesc, TypeDesc};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// Types declared in one IDL file
+pub struct IdlFile {
+    /// Rust module of the file, named after the file
+    pub module: String,
+    /// Structs, enums and typedefs by module-qualified name (e.g. `vehicle::Door`)
+    pub types: BTreeMap<String, TypeDesc>,
+}
+
+impl IdlFile {
+    /// Structs declared in the file, without typedefs of structs
+    pub fn structs(&self) -> Vec<&StructDesc> {
+        self.types
+            .iter()
+            .filter_map(|(name, desc)| match desc {
+                TypeDesc::Struct(s) if s.name == *name => Some(s),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
 /// IDL parser implementation
 pub struct IdlParser;
 
 impl IdlParser {
     /// Parse IDL file
-    pub fn parse_idl_file(file_path: &Path) -> Result<DdsData, Box<dyn std::error::Error>> {
+    pub fn parse_idl_file(file_path: &Path) -> Result<IdlFile, Box<dyn std::error::Error>> {
         let content = fs::read_to_string(file_path)?;
-        let mut lines = content.lines();
-
-        // Struct name extraction
-        let mut struct_name = String::new();
-        let mut fields = std::collections::HashMap::new();
-
-        // Find struct definition
-        while let Some(line) = lines.next() {
-            let line = line.trim();
-
-            // Look for struct definition
-            if let Some(pos) = line.find("struct") {
-                let remaining = &line[pos + "struct".len()..].trim();
-                if let Some(end_pos) = remaining.find('{') {
-                    struct_name = remaining[..end_pos].trim().to_string();
-                    break;
-                } else {
-                    struct_name = remaining.to_string();
-                    break;
-                }
-            }
-        }
-
-        // Find fields
-        let mut inside_struct = false;
-        for line in content.lines() {
-            let line = line.trim();
-
-            // Skip empty lines and comments
-            if line.is_empty() || line.starts_with("//") {
-                continue;
-            }
-
-            if !inside_struct && line.contains('{') {
-                inside_struct = true;
-                continue;
-            }
-
-            if inside_struct {
-                if line.contains('}') {
-                    break;
-                }
-
-                // Parse field
-                let line = line.trim_end_matches(';');
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 2 {
-                    let field_type = parts[0].to_string();
-                    let field_name = parts[1].to_string();
-                    fields.insert(field_name, field_type);
-                }
-            }
-        }
-
-        Ok(DdsData {
-            name: struct_name,
-            value: "{}".to_string(), // Default empty JSON
-            fields,
-        })
+        let module = file_path
+            .file_stem()
+            .ok_or("IDL file has no name")?
+            .to_string_lossy()
+            .to_string();
+        let types =
+            grammar::parse(&content).map_err(|e| format!("{}: {}", file_path.display(), e))?;
+
+        Ok(IdlFile { module, types })
     }
 }
 
@@ -93,6 +65,9 @@ pub fn collect_idl_files(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error:
         }
     }
 
+    // 생성 코드가 빌드마다 같도록 정렬
+    idl_files.sort();
+
     if idl_files.is_empty() {
         println!("No IDL files found in directory: {:?}", dir);
     } else {
@@ -137,11 +112,7 @@ pub fn get_idl_files(dir: &Path) -> Result<Vec<(String, String)>, Box<dyn std::e
         }
     }
 
+    result.sort();
     println!("*** get_idl_files found {} IDL files ***", result.len());
     Ok(result)
 }
-
-/// IDL 파일을 로드하여 DdsData 구조체로 반환
-pub fn load_idl_file(path: &Path) -> Result<DdsData, Box<dyn std::error::Error>> {
-    IdlParser::parse_idl_file(path)
-}
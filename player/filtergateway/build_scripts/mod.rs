@@ -5,3 +5,8 @@ pub mod generator;
 pub mod idl;
 pub mod settings;
 pub mod types;
+
+// IDL grammar shared with the runtime type loader of filtergateway
+#[allow(dead_code)]
+#[path = "../src/vehicle/dds/dynamic/idl.rs"]
+pub mod grammar;
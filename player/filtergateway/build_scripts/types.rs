@@ -1,30 +1,82 @@
 // Module for DDS type definitions
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use crate::build_scripts::grammar::TypeDesc;
+use std::collections::BTreeSet;
 
-/// Definition of DDS data structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DdsData {
-    pub name: String,
-    pub value: String,
-    pub fields: HashMap<String, String>,
+/// Longest array serde and `Default` are implemented for
+pub const MAX_ARRAY_LEN: usize = 32;
+
+/// Rust keywords that need `r#` to be used as field names
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else", "enum",
+    "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+    "mut", "pub", "ref", "return", "static", "struct", "trait", "true", "type", "unsafe", "use",
+    "where", "while", "yield",
+];
+
+/// Convert IDL member name to Rust field name
+pub fn field_name(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("r#{}", name)
+    } else {
+        name.to_string()
+    }
 }
 
 /// Convert IDL type to Rust type
-pub fn idl_to_rust_type(idl_type: &str) -> &str {
-    match idl_type {
-        "boolean" => "bool",
-        "short" | "int16_t" => "i16",
-        "unsigned short" | "uint16_t" => "u16",
-        "long" | "int32_t" => "i32",
-        "unsigned long" | "uint32_t" => "u32",
-        "long long" | "int64_t" => "i64",
-        "unsigned long long" | "uint64_t" => "u64",
-        "float" => "f32",
-        "double" => "f64",
-        "string" | "std::string" => "String",
-        "octet" | "byte" => "u8",
-        "char" => "char",
-        _ => "String", // Default to String for complex types
+///
+/// `scope` is the IDL module the type is used in. The DdsType derive only
+/// accepts plain identifiers for nested types, so a struct or enum of
+/// another module is imported under an alias such as `vehicle_Point`, and
+/// the `use` line is added to `imports`.
+pub fn rust_type(
+    desc: &TypeDesc,
+    scope: &[&str],
+    imports: &mut BTreeSet<String>,
+) -> Result<String, String> {
+    let rust_type = match desc {
+        TypeDesc::Bool => "bool".to_string(),
+        TypeDesc::Char => "char".to_string(),
+        TypeDesc::Octet | TypeDesc::UInt8 => "u8".to_string(),
+        TypeDesc::Int8 => "i8".to_string(),
+        TypeDesc::Int16 => "i16".to_string(),
+        TypeDesc::UInt16 => "u16".to_string(),
+        TypeDesc::Int32 => "i32".to_string(),
+        TypeDesc::UInt32 => "u32".to_string(),
+        TypeDesc::Int64 => "i64".to_string(),
+        TypeDesc::UInt64 => "u64".to_string(),
+        TypeDesc::Float32 => "f32".to_string(),
+        TypeDesc::Float64 => "f64".to_string(),
+        TypeDesc::String => "String".to_string(),
+        TypeDesc::Sequence(element) => format!("Vec<{}>", rust_type(element, scope, imports)?),
+        TypeDesc::Array(element, length) => {
+            if *length > MAX_ARRAY_LEN {
+                return Err(format!(
+                    "arrays longer than {} elements are not supported",
+                    MAX_ARRAY_LEN
+                ));
+            }
+            format!("[{}; {}]", rust_type(element, scope, imports)?, length)
+        }
+        TypeDesc::Enum(desc) => named_type(&desc.name, scope, imports),
+        TypeDesc::Struct(desc) => named_type(&desc.name, scope, imports),
+    };
+    Ok(rust_type)
+}
+
+fn named_type(qualified: &str, scope: &[&str], imports: &mut BTreeSet<String>) -> String {
+    let parts: Vec<&str> = qualified.split("::").collect();
+    let (name, module) = parts.split_last().unwrap_or((&qualified, &[]));
+    if module == scope {
+        return name.to_string();
     }
+
+    // Path from the module of the IDL file, which is the root of the IDL scopes
+    let root = if scope.is_empty() {
+        "self::".to_string()
+    } else {
+        "super::".repeat(scope.len())
+    };
+    let alias = parts.join("_");
+    imports.insert(format!("use {}{} as {};", root, qualified, alias));
+    alias
 }
@@ -0,0 +1,66 @@
+// SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Scenario condition simulator
+//!
+//! Evaluates the condition of a Scenario against a script of signal values
+//! and prints when its action would have been triggered.
+
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use filtergateway::filter::simulator::{self, TriggerCause};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Scenario simulator command line arguments
+#[derive(Parser, Debug)]
+#[command(name = "scenario-sim")]
+#[command(about = "Evaluate a Scenario condition against scripted signal values")]
+struct Args {
+    /// Scenario YAML file
+    scenario: PathBuf,
+
+    /// Signal script, CSV if the file name ends in `.csv` and YAML otherwise
+    script: PathBuf,
+
+    /// Expected trigger times in milliseconds (comma separated), checked
+    /// after the report
+    #[arg(long, value_delimiter = ',')]
+    expect: Option<Vec<u64>>,
+}
+
+fn main() -> Result<ExitCode> {
+    let args = Args::parse();
+
+    let source = std::fs::read_to_string(&args.scenario)?;
+    let scenario =
+        simulator::parse_scenario(&source).map_err(|e| anyhow!("{:?}: {}", args.scenario, e))?;
+    let name = common::spec::artifact::Artifact::get_name(&scenario);
+    let script = simulator::load_script(&args.script).map_err(|e| anyhow!(e))?;
+    let report = simulator::simulate(scenario, &script).map_err(|e| anyhow!("{}", e))?;
+
+    println!("scenario '{}': {} trigger(s)", name, report.triggers.len());
+    for trigger in &report.triggers {
+        let cause = match trigger.cause {
+            TriggerCause::Launch => "launch, no condition".to_string(),
+            TriggerCause::Sample(index) => format!("sample {}", index),
+            TriggerCause::HoldTime => "end of holdFor".to_string(),
+        };
+        println!("{:>10} ms  {}", trigger.at, cause);
+    }
+    for (index, error) in &report.errors {
+        eprintln!("sample {}: {}", index, error);
+    }
+
+    if let Some(expected) = args.expect {
+        let actual = report.trigger_times();
+        if actual != expected {
+            eprintln!(
+                "expected triggers at {:?} ms, got {:?} ms",
+                expected, actual
+            );
+            return Ok(ExitCode::FAILURE);
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,242 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Active filters indexed by topic
+//!
+//! A sample is only offered to the filters whose condition reads its topic.
+//! Each filter has its own lock, so the index is only locked to look the
+//! filters up and never while a condition is evaluated or an action is sent.
+
+use super::Filter;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// Filter shared between the index and the dispatch loop
+pub type SharedFilter = Arc<Mutex<Filter>>;
+
+/// Index shared between the FilterGatewayManager and the gRPC receiver
+pub type SharedFilterIndex = Arc<tokio::sync::Mutex<FilterIndex>>;
+
+/// Evaluation latency of one scenario filter
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EvaluationStats {
+    /// Number of evaluated samples
+    pub count: u64,
+    pub total: Duration,
+    pub max: Duration,
+    pub last: Duration,
+}
+
+impl EvaluationStats {
+    pub fn record(&mut self, elapsed: Duration) {
+        self.count += 1;
+        self.total += elapsed;
+        self.max = self.max.max(elapsed);
+        self.last = elapsed;
+    }
+
+    pub fn average(&self) -> Duration {
+        match u32::try_from(self.count) {
+            Ok(0) => Duration::ZERO,
+            Ok(count) => self.total / count,
+            Err(_) => Duration::from_secs_f64(self.total.as_secs_f64() / self.count as f64),
+        }
+    }
+}
+
+/// Current condition state of one scenario filter
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FilterState {
+    pub scenario_name: String,
+    /// Topic or signal read by the condition
+    pub topic: String,
+    /// `false` while the filter is paused
+    pub active: bool,
+    /// Receive time of the last evaluated sample
+    pub last_sample: Option<SystemTime>,
+    /// Condition field value of the last evaluated sample
+    pub last_value: Option<String>,
+    /// Whether the condition matched the last evaluated sample
+    pub last_result: Option<bool>,
+    /// Number of times the scenario action was triggered
+    pub trigger_count: u64,
+}
+
+/// Active filters by scenario name and by topic
+#[derive(Default)]
+pub struct FilterIndex {
+    filters: BTreeMap<String, SharedFilter>,
+    /// Topic → names of the scenarios reading it
+    topics: HashMap<String, Vec<String>>,
+}
+
+impl FilterIndex {
+    /// Add the filter of a scenario
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Filter to add
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `false` if a filter for the same scenario already exists,
+    ///   in which case the index is unchanged
+    pub fn insert(&mut self, filter: Filter) -> bool {
+        let name = filter.scenario_name.clone();
+        if self.filters.contains_key(&name) {
+            return false;
+        }
+        for topic in filter.topics() {
+            self.topics.entry(topic).or_default().push(name.clone());
+        }
+        self.filters.insert(name, Arc::new(Mutex::new(filter)));
+        true
+    }
+
+    /// Remove the filter of a scenario
+    pub fn remove(&mut self, scenario_name: &str) -> Option<SharedFilter> {
+        let filter = self.filters.remove(scenario_name)?;
+        self.topics.retain(|_, names| {
+            names.retain(|name| name != scenario_name);
+            !names.is_empty()
+        });
+        Some(filter)
+    }
+
+    pub fn contains(&self, scenario_name: &str) -> bool {
+        self.filters.contains_key(scenario_name)
+    }
+
+    pub fn get(&self, scenario_name: &str) -> Option<SharedFilter> {
+        self.filters.get(scenario_name).cloned()
+    }
+
+    /// Filters whose condition reads the topic
+    pub fn for_topic(&self, topic: &str) -> Vec<SharedFilter> {
+        self.topics
+            .get(topic)
+            .map(|names| {
+                names
+                    .iter()
+                    .filter_map(|name| self.filters.get(name).cloned())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Names of all scenarios with a filter, in order
+    pub fn names(&self) -> Vec<String> {
+        self.filters.keys().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.filters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    /// Evaluation latency of every filter, by scenario name
+    pub fn stats(&self) -> Vec<(String, EvaluationStats)> {
+        self.filters
+            .iter()
+            .filter_map(|(name, filter)| {
+                let stats = filter.lock().ok()?.stats();
+                Some((name.clone(), stats))
+            })
+            .collect()
+    }
+
+    /// Condition state of every filter, by scenario name
+    pub fn states(&self) -> Vec<FilterState> {
+        self.filters
+            .values()
+            .filter_map(|filter| Some(filter.lock().ok()?.state()))
+            .collect()
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc::sender::actioncontroller::FilterGatewaySender;
+    use common::spec::artifact::Scenario;
+
+    fn create_filter(name: &str, topic: &str) -> Filter {
+        let yaml = format!(
+            r#"
+apiVersion: v1
+kind: Scenario
+metadata:
+  name: {name}
+spec:
+  condition:
+    express: eq
+    value: "on"
+    operands:
+      type: DDS
+      name: status
+      value: {topic}
+  action: update
+  target: {name}
+"#
+        );
+        let scenario: Scenario = serde_yaml::from_str(&yaml).unwrap();
+        Filter::new(name.to_string(), scenario, true, FilterGatewaySender::new()).unwrap()
+    }
+
+    #[test]
+    fn test_filters_are_indexed_by_topic() {
+        let mut index = FilterIndex::default();
+        assert!(index.insert(create_filter("antipinch", "DoorState")));
+        assert!(index.insert(create_filter("welcome-light", "DoorState")));
+        assert!(index.insert(create_filter("parking", "GearState")));
+        assert!(!index.insert(create_filter("parking", "GearState")));
+
+        assert_eq!(index.for_topic("DoorState").len(), 2);
+        assert_eq!(index.for_topic("GearState").len(), 1);
+        assert!(index.for_topic("Speed").is_empty());
+
+        assert!(index.remove("antipinch").is_some());
+        assert!(index.remove("antipinch").is_none());
+        assert_eq!(index.for_topic("DoorState").len(), 1);
+        assert_eq!(
+            index.names(),
+            vec!["parking".to_string(), "welcome-light".to_string()]
+        );
+
+        index.remove("welcome-light");
+        assert!(index.for_topic("DoorState").is_empty());
+        assert_eq!(index.len(), 1);
+    }
+
+    #[test]
+    fn test_evaluation_stats() {
+        let mut stats = EvaluationStats::default();
+        assert_eq!(stats.average(), Duration::ZERO);
+
+        stats.record(Duration::from_micros(10));
+        stats.record(Duration::from_micros(30));
+        assert_eq!(stats.count, 2);
+        assert_eq!(stats.average(), Duration::from_micros(20));
+        assert_eq!(stats.max, Duration::from_micros(30));
+        assert_eq!(stats.last, Duration::from_micros(30));
+
+        let mut index = FilterIndex::default();
+        index.insert(create_filter("antipinch", "DoorState"));
+        index
+            .get("antipinch")
+            .unwrap()
+            .lock()
+            .unwrap()
+            .record_evaluation(Duration::from_micros(5));
+        let all = index.stats();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].1.count, 1);
+    }
+}
@@ -1,10 +1,29 @@
+pub mod index;
+pub mod operator;
+pub mod path;
+pub mod simulator;
+pub mod trigger;
+pub mod window;
+
 use crate::grpc::sender::actioncontroller::FilterGatewaySender;
+use crate::grpc::sender::statemanager::ScenarioStateReporter;
 use crate::vehicle::dds::DdsData;
-use common::spec::artifact::Scenario;
+use crate::vehicle::source::timer::{CronSchedule, TimerSpec};
+use crate::vehicle::source::SourceKind;
+use common::spec::artifact::Condition;
+use common::spec::artifact::{Artifact, Scenario};
+use common::statemanager::ScenarioState;
 use common::Result;
+use index::{EvaluationStats, FilterState};
+use operator::ConditionCheck;
+use path::FieldPath;
 // use dust_dds::infrastructure::wait_set::Condition;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Instant, SystemTime};
 use tokio::sync::{mpsc, Mutex};
+use trigger::{Decision, TriggerOptions, TriggerState};
+use window::{SignalHistory, WindowSpec};
 
 /// Filter for evaluating scenario conditions
 pub struct Filter {
@@ -16,6 +35,34 @@ pub struct Filter {
     is_active: bool,
     /// gRPC sender for action controller
     sender: FilterGatewaySender,
+    /// Compiled operator of the scenario condition
+    check: Option<ConditionCheck>,
+    /// Parsed operand name of the scenario condition
+    field_path: Option<FieldPath>,
+    /// Aggregate window of the scenario condition, if any
+    window: Option<WindowSpec>,
+    /// Recent samples read by `window`
+    history: Arc<std::sync::Mutex<SignalHistory>>,
+    /// Whether this filter records samples into `history` itself, which is
+    /// the case until a history shared with other filters is attached
+    owns_history: bool,
+    /// Evaluation latency of the scenario condition
+    stats: EvaluationStats,
+    /// Edge and debounce state of the scenario condition
+    trigger: Arc<std::sync::Mutex<TriggerState>>,
+    /// Receive time, condition field value and result of the last evaluation
+    last_sample: Option<(SystemTime, String, bool)>,
+    /// Number of times the action was triggered, including by hold timers
+    trigger_count: Arc<AtomicU64>,
+    /// Condition progress of the scenario, reported to the StateManager
+    progress: ScenarioProgress,
+    /// Cron expression limiting the condition, also the name of its samples
+    schedule: Option<String>,
+    /// Whether the last sample of `schedule` was due
+    schedule_due: bool,
+    /// Result of the condition for the last sample of its operand, before
+    /// `schedule` is applied
+    signal_result: Option<bool>,
 }
 
 impl Filter {
@@ -30,25 +77,144 @@ impl Filter {
     ///
     /// # Returns
     ///
-    /// A new Filter instance
+    /// * `Result<Filter>` - A new Filter instance, or an error if the
+    ///   scenario condition cannot be compiled
     pub fn new(
         scenario_name: String,
         scenario: Scenario,
         is_active: bool,
         sender: FilterGatewaySender,
-    ) -> Self {
-        Self {
+    ) -> Result<Self> {
+        let condition = scenario.get_conditions();
+        let options = condition
+            .as_ref()
+            .map(TriggerOptions::from)
+            .unwrap_or_default();
+        let compiled = match condition.as_ref().map(compile_condition).transpose() {
+            Ok(compiled) => compiled,
+            Err(e) => return Err(format!("scenario '{}': {}", scenario_name, e).into()),
+        };
+        let (check, field_path, window) = match compiled {
+            Some(c) => (Some(c.check), Some(c.field_path), c.window),
+            None => (None, None, None),
+        };
+
+        let filter = Self {
             scenario_name,
             scenario,
             is_active,
             sender,
+            check,
+            field_path,
+            window,
+            history: Arc::new(std::sync::Mutex::new(SignalHistory::default())),
+            owns_history: true,
+            stats: EvaluationStats::default(),
+            trigger: Arc::new(std::sync::Mutex::new(TriggerState::new(options))),
+            last_sample: None,
+            trigger_count: Arc::new(AtomicU64::new(0)),
+            progress: ScenarioProgress::default(),
+            schedule: condition.as_ref().and_then(|c| c.get_schedule()),
+            schedule_due: false,
+            signal_result: None,
+        };
+        filter.register_window();
+        Ok(filter)
+    }
+
+    /// Read windowed samples from a history shared by all filters
+    ///
+    /// The owner of the shared history records every received sample before
+    /// passing it to the filters.
+    ///
+    /// # Arguments
+    ///
+    /// * `history` - History recorded by the FilterGatewayManager
+    ///
+    /// # Returns
+    ///
+    /// The filter using the shared history
+    pub fn with_shared_history(mut self, history: Arc<std::sync::Mutex<SignalHistory>>) -> Self {
+        self.history = history;
+        self.owns_history = false;
+        self.register_window();
+        self
+    }
+
+    /// Report to the StateManager when the condition is met or re-armed
+    ///
+    /// # Arguments
+    ///
+    /// * `reporter` - Reporter shared by the filters of the FilterGatewayManager
+    ///
+    /// # Returns
+    ///
+    /// The filter reporting its condition progress
+    pub fn with_state_reporter(mut self, reporter: ScenarioStateReporter) -> Self {
+        self.progress.reporter = Some(reporter);
+        self
+    }
+
+    /// Size the history buffer of the condition field for this filter's window
+    fn register_window(&self) {
+        let (Some(window), Some(path), Some(condition)) = (
+            self.window.as_ref(),
+            self.field_path.as_ref(),
+            self.scenario.get_conditions(),
+        ) else {
+            return;
+        };
+        if let Ok(mut history) = self.history.lock() {
+            history.register(
+                &self.scenario_name,
+                &condition.get_operand_value(),
+                path,
+                window.duration,
+            );
         }
     }
 
+    /// Check that the condition of a scenario can be evaluated
+    ///
+    /// Used when a scenario is registered, so that an invalid operator,
+    /// target value, field path or shipped IDL is reported to the caller.
+    ///
+    /// # Arguments
+    ///
+    /// * `scenario` - Full scenario definition
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - Success or the reason the condition is invalid
+    pub fn validate(scenario: &Scenario) -> Result<()> {
+        if let Some(condition) = scenario.get_conditions() {
+            compile_condition(&condition)
+                .map_err(|e| format!("scenario '{}': {}", scenario.get_name(), e))?;
+            if let Some(source) = condition.get_operand_idl() {
+                crate::vehicle::dds::dynamic::idl::parse(&source).map_err(|e| {
+                    format!("scenario '{}': invalid IDL: {}", scenario.get_name(), e)
+                })?;
+            }
+            if let Some(qos) = condition.get_operand_qos() {
+                crate::vehicle::dds::qos::reader_qos(&qos).map_err(|e| {
+                    format!("scenario '{}': invalid QoS: {}", scenario.get_name(), e)
+                })?;
+            }
+            let kind = SourceKind::from_operand_type(&condition.get_operand_type());
+            if matches!(kind, SourceKind::Timer | SourceKind::Schedule) {
+                TimerSpec::parse(kind, &condition.get_operand_value())
+                    .map_err(|e| format!("scenario '{}': {}", scenario.get_name(), e))?;
+            }
+        }
+        Ok(())
+    }
+
     /// Check if scenario conditions are met
     ///
     /// Evaluates if the received vehicle data meets the scenario conditions.
-    /// If conditions are met, triggers an action through ActionController.
+    /// An action is triggered through ActionController only when the condition
+    /// becomes true, after the optional `holdFor` time, outside the cooldown
+    /// and at most once for `oneShot` scenarios.
     ///
     /// # Arguments
     ///
@@ -58,92 +224,254 @@ impl Filter {
     ///
     /// * `Result<()>` - Success or error result
     pub async fn meet_scenario_condition(&mut self, data: &DdsData) -> Result<()> {
-        use std::time::Instant;
-        let start = Instant::now();
+        if !self.evaluate(data)? {
+            return Err("cannot meet condition".into());
+        }
+
+        self.action().fire().await
+    }
+
+    /// Evaluate the scenario condition for a sample without triggering the action
+    ///
+    /// A pending `holdFor` check is scheduled if needed, and fires the action
+    /// by itself when the hold time ends.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Vehicle message data
+    ///
+    /// # Returns
+    ///
+    /// * `Result<bool>` - Whether the action must be triggered now
+    pub fn evaluate(&mut self, data: &DdsData) -> Result<bool> {
+        match self.evaluate_at(data, Instant::now())? {
+            Decision::Fire => Ok(true),
+            Decision::Wait { after, epoch } => {
+                self.schedule_hold_check(after, epoch);
+                Ok(false)
+            }
+            Decision::Idle => Ok(false),
+        }
+    }
 
-        let condition = self.scenario.get_conditions().unwrap();
+    /// Evaluate the scenario condition for a sample received at a given time
+    ///
+    /// Unlike `evaluate`, no hold check is scheduled: on `Decision::Wait` the
+    /// caller must call `hold_elapsed` when the hold time ends.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Vehicle message data
+    /// * `now` - Time the sample was received
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Decision>` - Whether to fire, wait for the hold time, or do nothing
+    pub fn evaluate_at(&mut self, data: &DdsData, now: Instant) -> Result<Decision> {
+        let condition = self
+            .scenario
+            .get_conditions()
+            .ok_or("no condition in scenario")?;
         let topic = condition.get_operand_value();
         let value_name = condition.get_operand_name();
-        let target_value = condition.get_value();
-        let express = condition.get_express();
-
-        print!(
-        "Checking condition for scenario: {}\nTopic: {}\nTarget Value: {}\nValue Name: {}\nExpression: {}\n",
-        self.scenario_name, topic, target_value, value_name, express
-    );
 
+        if self.schedule.as_ref() == Some(&data.name) {
+            self.schedule_due = data.fields.get("due").is_some_and(|due| due == "true");
+            if !data.name.eq(&topic) {
+                let check = self.signal_result.unwrap_or(false) && self.schedule_due;
+                if let Some((_, _, result)) = self.last_sample.as_mut() {
+                    *result = check;
+                }
+                return self.update_trigger(check, now);
+            }
+        }
         if !data.name.eq(&topic) {
-            let elapsed = start.elapsed();
-            println!("meet_scenario_condition: elapsed = {:?}", elapsed);
             return Err("data topic does not match".into());
         }
 
-        let field_value = match data.fields.get(&value_name) {
-            Some(v) => v,
-            None => {
-                let elapsed = start.elapsed();
-                println!("meet_scenario_condition: elapsed = {:?}", elapsed);
-                return Err(format!("field '{}' not found in data.fields", value_name).into());
-            }
+        let field_value = match self.window.as_ref() {
+            Some(window) => Some(self.window_value(window, data, now)),
+            None => self
+                .field_path
+                .as_ref()
+                .and_then(|path| path.lookup(data))
+                .map(Ok),
+        };
+        let field_value = match field_value {
+            Some(Ok(v)) => v,
+            Some(Err(e)) => return Err(e.into()),
+            None => return Err(format!("field '{}' not found in data.fields", value_name).into()),
         };
 
-        let check: bool = match express.as_str() {
-            "eq" => target_value.to_lowercase() == field_value.to_lowercase(),
-            "lt" => {
-                let target_v = target_value
-                    .parse::<f32>()
-                    .map_err(|_| "target_value parse error")?;
-                let current_v = field_value
-                    .parse::<f32>()
-                    .map_err(|_| "field_value parse error")?;
-                current_v < target_v
-            }
-            "le" => {
-                let target_v = target_value
-                    .parse::<f32>()
-                    .map_err(|_| "target_value parse error")?;
-                let current_v = field_value
-                    .parse::<f32>()
-                    .map_err(|_| "field_value parse error")?;
-                current_v <= target_v
-            }
-            "ge" => {
-                let target_v = target_value
-                    .parse::<f32>()
-                    .map_err(|_| "target_value parse error")?;
-                let current_v = field_value
-                    .parse::<f32>()
-                    .map_err(|_| "field_value parse error")?;
-                current_v >= target_v
-            }
-            "gt" => {
-                let target_v = target_value
-                    .parse::<f32>()
-                    .map_err(|_| "target_value parse error")?;
-                let current_v = field_value
-                    .parse::<f32>()
-                    .map_err(|_| "field_value parse error")?;
-                current_v > target_v
+        let check = match self.check.as_mut() {
+            Some(check) => check.evaluate(&field_value)?,
+            None => return Err("no condition in scenario".into()),
+        };
+
+        self.signal_result = Some(check);
+        let check = check && (self.schedule.is_none() || self.schedule_due);
+        self.last_sample = Some((SystemTime::now(), field_value, check));
+        self.update_trigger(check, now)
+    }
+
+    /// Feed the result of the condition to the edge and debounce state
+    ///
+    /// A condition that becomes false after firing re-arms the scenario,
+    /// which waits for the condition again.
+    fn update_trigger(&self, check: bool, now: Instant) -> Result<Decision> {
+        if !check {
+            self.progress
+                .transition(&self.scenario_name, ScenarioState::Waiting);
+        }
+        let decision = match self.trigger.lock() {
+            Ok(mut state) => state.update(check, now),
+            Err(_) => return Err("trigger state is poisoned".into()),
+        };
+        if decision == Decision::Fire {
+            self.trigger_count.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(decision)
+    }
+
+    /// Handle the end of a hold time returned by `evaluate_at`
+    ///
+    /// # Arguments
+    ///
+    /// * `epoch` - Epoch of the `Decision::Wait`
+    /// * `now` - End of the hold time
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the condition held and the action must fire now
+    pub fn hold_elapsed(&self, epoch: u64, now: Instant) -> bool {
+        let fire = match self.trigger.lock() {
+            Ok(mut state) => state.on_timer(epoch, now),
+            Err(_) => false,
+        };
+        if fire {
+            self.trigger_count.fetch_add(1, Ordering::Relaxed);
+        }
+        fire
+    }
+
+    /// Trigger the scenario action without waiting for ActionController
+    ///
+    /// Used by the dispatch loop so that one slow action does not delay the
+    /// evaluation of the following samples.
+    pub fn spawn_action(&self) {
+        let action = self.action();
+        tokio::spawn(async move {
+            if let Err(e) = action.fire().await {
+                println!("Error triggering action: {:?}", e);
             }
-            _ => {
-                let elapsed = start.elapsed();
-                println!("meet_scenario_condition: elapsed = {:?}", elapsed);
-                return Err("wrong expression in condition".into());
+        });
+    }
+
+    /// Action of the scenario, to fire outside of the filter lock
+    fn action(&self) -> ScenarioAction {
+        ScenarioAction {
+            scenario_name: self.scenario_name.clone(),
+            sender: self.sender.clone(),
+            progress: self.progress.clone(),
+            trigger: Arc::clone(&self.trigger),
+        }
+    }
+
+    /// Topic read by the scenario condition
+    pub fn topic(&self) -> Option<String> {
+        self.scenario
+            .get_conditions()
+            .map(|condition| condition.get_operand_value())
+    }
+
+    /// Topics read by the filter: the condition topic and its `schedule`
+    pub fn topics(&self) -> Vec<String> {
+        let mut topics: Vec<String> = self.topic().into_iter().collect();
+        if let Some(schedule) = self.schedule.as_ref() {
+            if !topics.contains(schedule) {
+                topics.push(schedule.clone());
             }
+        }
+        topics
+    }
+
+    /// Record the duration of one evaluation
+    pub fn record_evaluation(&mut self, elapsed: std::time::Duration) {
+        self.stats.record(elapsed);
+    }
+
+    /// Evaluation latency of this filter
+    pub fn stats(&self) -> EvaluationStats {
+        self.stats
+    }
+
+    /// Scenario state last reported for this filter
+    pub fn scenario_state(&self) -> ScenarioState {
+        self.progress.current()
+    }
+
+    /// Current condition state of this filter
+    pub fn state(&self) -> FilterState {
+        let (last_sample, last_value, last_result) = match &self.last_sample {
+            Some((time, value, result)) => (Some(*time), Some(value.clone()), Some(*result)),
+            None => (None, None, None),
         };
+        FilterState {
+            scenario_name: self.scenario_name.clone(),
+            topic: self.topic().unwrap_or_default(),
+            active: self.is_active,
+            last_sample,
+            last_value,
+            last_result,
+            trigger_count: self.trigger_count.load(Ordering::Relaxed),
+        }
+    }
 
-        let elapsed = start.elapsed();
-        println!("meet_scenario_condition: elapsed = {:?}", elapsed);
-
-        if check {
-            println!("Condition met for scenario: {}", self.scenario_name);
-            self.sender
-                .trigger_action(self.scenario_name.clone())
-                .await?;
-            Ok(())
-        } else {
-            Err("cannot meet condition".into())
+    /// Aggregate of the condition field over the window, including `data`
+    fn window_value(
+        &self,
+        window: &WindowSpec,
+        data: &DdsData,
+        now: Instant,
+    ) -> std::result::Result<String, String> {
+        let path = self
+            .field_path
+            .as_ref()
+            .ok_or_else(|| "no condition in scenario".to_string())?;
+        let mut history = self
+            .history
+            .lock()
+            .map_err(|_| "signal history is poisoned".to_string())?;
+        if self.owns_history {
+            history.record(data, now);
         }
+        let samples = history.window(&data.name, path, window.duration, now);
+        window.compute(&samples)
+    }
+
+    /// Fire the action once the hold time ends if the condition stayed true
+    ///
+    /// Samples may stop arriving while the condition holds, e.g. for topics
+    /// that are only published on change, so the end of `holdFor` is checked
+    /// by a timer as well.
+    fn schedule_hold_check(&self, after: std::time::Duration, epoch: u64) {
+        let trigger_count = Arc::clone(&self.trigger_count);
+        let action = self.action();
+
+        tokio::spawn(async move {
+            tokio::time::sleep(after).await;
+            let fire = match action.trigger.lock() {
+                Ok(mut state) => state.on_timer(epoch, Instant::now()),
+                Err(_) => false,
+            };
+            if fire {
+                trigger_count.fetch_add(1, Ordering::Relaxed);
+                println!("Condition held for scenario: {}", action.scenario_name);
+                if let Err(e) = action.fire().await {
+                    println!("Error triggering action: {:?}", e);
+                }
+            }
+        });
     }
 
     /// Pause the filter processing
@@ -158,8 +486,7 @@ impl Filter {
     ///
     /// * `Result<()>` - Success or error result
     pub async fn pause_scenario_filter(&mut self) -> Result<()> {
-        // TODO: Implementation
-        self.is_active = false;
+        self.set_active(false);
         Ok(())
     }
 
@@ -175,11 +502,40 @@ impl Filter {
     ///
     /// * `Result<()>` - Success or error result
     pub async fn resume_scenario_filter(&mut self) -> Result<()> {
-        // TODO: Implementation
-        self.is_active = true;
+        self.set_active(true);
         Ok(())
     }
 
+    /// Enable or disable condition evaluation
+    ///
+    /// A pending `holdFor` check is cancelled when the filter is paused, so
+    /// that no action fires while it is disabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `active` - `false` to pause the filter, `true` to resume it
+    pub fn set_active(&mut self, active: bool) {
+        if !active {
+            if let Ok(mut state) = self.trigger.lock() {
+                state.reset();
+            }
+        }
+        self.is_active = active;
+    }
+
+    /// Stop the filter of a withdrawn scenario
+    ///
+    /// A pending `holdFor` check is cancelled so that no action fires after
+    /// the withdrawal, and triggers still being delivered no longer report.
+    ///
+    /// # Returns
+    ///
+    /// * `ScenarioState` - State the scenario leaves for `Idle`
+    pub fn withdraw(&mut self) -> ScenarioState {
+        self.set_active(false);
+        self.progress.finish()
+    }
+
     /// Check if filter is active
     ///
     /// # Returns
@@ -213,14 +569,8 @@ impl Filter {
         );
 
         // Check if topic matches filter condition
-        let condition = match self.scenario.get_conditions() {
-            Some(c) => c,
-            None => return Ok(()), // No conditions case (already handled)
-        };
-
-        let topic = condition.get_operand_value();
-        if !data.name.eq(&topic) {
-            return Ok(()); // Ignore unrelated topics
+        if !self.topics().contains(&data.name) {
+            return Ok(()); // Ignore unrelated topics, or no conditions case
         }
 
         // Perform condition check
@@ -241,6 +591,112 @@ impl Filter {
         Ok(())
     }
 }
+
+/// Action of a scenario whose condition fired
+struct ScenarioAction {
+    scenario_name: String,
+    sender: FilterGatewaySender,
+    progress: ScenarioProgress,
+    trigger: Arc<std::sync::Mutex<TriggerState>>,
+}
+
+impl ScenarioAction {
+    /// Trigger the action and report `condition_met` once ActionController
+    /// has accepted it
+    ///
+    /// A trigger that is not delivered leaves the scenario `Waiting`.
+    async fn fire(mut self) -> Result<()> {
+        println!("Condition met for scenario: {}", self.scenario_name);
+        self.sender
+            .trigger_action(self.scenario_name.clone())
+            .await?;
+        self.progress
+            .transition(&self.scenario_name, ScenarioState::Allowed);
+        // The condition may have become false during the delivery
+        if !self.trigger.lock().is_ok_and(|state| state.is_true()) {
+            self.progress
+                .transition(&self.scenario_name, ScenarioState::Waiting);
+        }
+        Ok(())
+    }
+}
+
+/// Scenario state of a filter, shared with its hold timers
+///
+/// A filter is launched `Waiting`, goes to `Allowed` when the action of the
+/// condition is delivered and back to `Waiting` when the condition becomes
+/// false again. Each change is reported from the tracked state, if reporting
+/// is enabled. Once withdrawn, the scenario is `Idle` and nothing more is
+/// reported.
+#[derive(Clone)]
+struct ScenarioProgress {
+    reporter: Option<ScenarioStateReporter>,
+    state: Arc<std::sync::Mutex<ScenarioState>>,
+}
+
+impl Default for ScenarioProgress {
+    fn default() -> Self {
+        Self {
+            reporter: None,
+            state: Arc::new(std::sync::Mutex::new(ScenarioState::Waiting)),
+        }
+    }
+}
+
+impl ScenarioProgress {
+    fn current(&self) -> ScenarioState {
+        self.state
+            .lock()
+            .map_or(ScenarioState::Waiting, |state| *state)
+    }
+
+    /// Move to `target`, reporting the transition if the state changes
+    fn transition(&self, scenario_name: &str, target: ScenarioState) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        if *state == target || *state == ScenarioState::Idle {
+            return;
+        }
+        if let Some(reporter) = self.reporter.as_ref() {
+            reporter.report(scenario_name, *state, target);
+        }
+        *state = target;
+    }
+
+    /// Move to `Idle` without reporting, returning the state left
+    fn finish(&self) -> ScenarioState {
+        self.state
+            .lock()
+            .map_or(ScenarioState::Waiting, |mut state| {
+                std::mem::replace(&mut *state, ScenarioState::Idle)
+            })
+    }
+}
+
+/// Parts of a condition compiled when the scenario is registered
+struct CompiledCondition {
+    check: ConditionCheck,
+    field_path: FieldPath,
+    window: Option<WindowSpec>,
+}
+
+/// Compile the operator, operand field path and window of a condition
+fn compile_condition(condition: &Condition) -> std::result::Result<CompiledCondition, String> {
+    if let Some(schedule) = condition.get_schedule() {
+        CronSchedule::parse(&schedule)?;
+    }
+    Ok(CompiledCondition {
+        check: ConditionCheck::from_condition(condition)?,
+        field_path: FieldPath::parse(&condition.get_operand_name())?,
+        window: condition
+            .get_window()
+            .as_ref()
+            .map(WindowSpec::from_window)
+            .transpose()?,
+    })
+}
+
 //Unit Test Cases
 #[cfg(test)]
 mod tests {
@@ -646,4 +1102,92 @@ mod tests {
 
         assert!(result.is_ok());
     }
+
+    // Helper to create a real filter of a GearState condition
+    fn parking_filter(hold_for: u64) -> super::Filter {
+        use crate::grpc::sender::actioncontroller::FilterGatewaySender;
+
+        let scenario: common::spec::artifact::Scenario = serde_yaml::from_str(&format!(
+            r#"
+apiVersion: v1
+kind: Scenario
+metadata:
+  name: parking
+spec:
+  condition:
+    express: eq
+    value: "on"
+    holdFor: {hold_for}
+    operands:
+      type: DDS
+      name: status
+      value: GearState
+  action: update
+  target: parking
+"#
+        ))
+        .unwrap();
+        super::Filter::new("parking".into(), scenario, true, FilterGatewaySender::new()).unwrap()
+    }
+
+    // Helper to create a GearState sample
+    fn gear_sample(status: &str) -> crate::vehicle::dds::DdsData {
+        crate::vehicle::dds::DdsData::from_json(
+            "GearState",
+            format!(r#"{{"status":"{}"}}"#, status),
+        )
+    }
+
+    // Test: The scenario state follows the condition and is re-armed
+    #[tokio::test]
+    async fn test_scenario_state_is_rearmed() {
+        use common::statemanager::ScenarioState;
+
+        let mut filter = parking_filter(0);
+        assert_eq!(filter.scenario_state(), ScenarioState::Waiting);
+
+        // Firing moves the scenario to allowed, as done by the dispatch loop
+        assert!(filter.evaluate(&gear_sample("on")).unwrap());
+        filter
+            .progress
+            .transition("parking", ScenarioState::Allowed);
+        assert!(!filter.evaluate(&gear_sample("on")).unwrap());
+        assert_eq!(filter.scenario_state(), ScenarioState::Allowed);
+
+        // The condition becoming false waits for the next rising edge
+        assert!(!filter.evaluate(&gear_sample("off")).unwrap());
+        assert_eq!(filter.scenario_state(), ScenarioState::Waiting);
+    }
+
+    // Test: An action that is not delivered leaves the scenario waiting
+    #[tokio::test]
+    async fn test_undelivered_action_is_not_reported() {
+        use crate::grpc::sender::actioncontroller::FilterGatewaySender;
+        use common::statemanager::ScenarioState;
+
+        let mut filter = parking_filter(0);
+        let closed = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        filter.sender = FilterGatewaySender::with_address(format!("http://{}", closed));
+
+        assert!(filter.evaluate(&gear_sample("on")).unwrap());
+        assert!(filter.action().fire().await.is_err());
+        assert_eq!(filter.scenario_state(), ScenarioState::Waiting);
+    }
+
+    // Test: A scenario withdrawn during holdFor does not fire afterwards
+    #[tokio::test]
+    async fn test_withdraw_during_hold_for() {
+        use common::statemanager::ScenarioState;
+
+        let mut filter = parking_filter(50);
+        assert!(!filter.evaluate(&gear_sample("on")).unwrap());
+        assert_eq!(filter.withdraw(), ScenarioState::Waiting);
+
+        tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+        assert_eq!(filter.state().trigger_count, 0);
+        assert_eq!(filter.scenario_state(), ScenarioState::Idle);
+    }
 }
@@ -0,0 +1,357 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Condition operators of scenarios
+//!
+//! The `express` and `value` of a condition are compiled once, when the
+//! scenario is registered, so that a typo in an operator, a number or a regex
+//! is reported to the user instead of failing on every received sample.
+//! Only the field value of the sample is parsed at evaluation time.
+
+use common::spec::artifact::Condition;
+use regex::Regex;
+use std::cmp::Ordering;
+
+/// Number read from a condition or a sample
+///
+/// Integers are kept exact so that large `u64` counters compare correctly,
+/// and are only converted to floating point when compared with a fraction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Number {
+    Int(i128),
+    Float(f64),
+}
+
+impl Number {
+    pub fn parse(s: &str) -> Option<Number> {
+        let s = s.trim();
+        if let Ok(i) = s.parse::<i128>() {
+            return Some(Number::Int(i));
+        }
+        match s.parse::<f64>() {
+            Ok(f) if f.is_finite() => Some(Number::Float(f)),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(self) -> f64 {
+        match self {
+            Number::Int(i) => i as f64,
+            Number::Float(f) => f,
+        }
+    }
+
+    pub fn compare(self, other: Number) -> Option<Ordering> {
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) => Some(a.cmp(&b)),
+            (a, b) => a.as_f64().partial_cmp(&b.as_f64()),
+        }
+    }
+
+    /// `self - other`, exact for integers
+    pub fn minus(self, other: Number) -> Number {
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) if a.checked_sub(b).is_some() => Number::Int(a - b),
+            (a, b) => Number::Float(a.as_f64() - b.as_f64()),
+        }
+    }
+
+    /// Absolute difference between two numbers
+    fn distance(self, other: Number) -> Number {
+        match self.minus(other) {
+            Number::Int(i) => i
+                .checked_abs()
+                .map_or(Number::Float((i as f64).abs()), Number::Int),
+            Number::Float(f) => Number::Float(f.abs()),
+        }
+    }
+}
+
+impl std::fmt::Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Number::Int(i) => write!(f, "{}", i),
+            Number::Float(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+/// Compiled `express` of a condition
+#[derive(Debug, Clone)]
+pub enum Operator {
+    /// `eq`, case-insensitive string comparison
+    Eq(String),
+    /// `ne`, case-insensitive string comparison
+    Ne(String),
+    /// `lt`, `le`, `ge` and `gt`, satisfied if the ordering of the field value
+    /// against the target is one of the listed orderings
+    Compare(Vec<Ordering>, Number),
+    /// `in`, comma separated list of values, case-insensitive
+    In(Vec<String>),
+    /// `notIn`, comma separated list of values, case-insensitive
+    NotIn(Vec<String>),
+    /// `range`, `min..max` with both bounds included
+    Range(Number, Number),
+    /// `regex`, satisfied if the pattern matches the field value
+    Matches(Regex),
+    /// `bool`, field value is `true`/`false` or `1`/`0`
+    Bool(bool),
+    /// `changed`, satisfied if the field value differs from the previous sample
+    Changed,
+    /// `deltaExceeds`, satisfied if the field value moved by more than the
+    /// target since the previous sample
+    DeltaExceeds(Number),
+}
+
+impl Operator {
+    /// Compile an operator from the `express` and `value` of a condition
+    ///
+    /// # Arguments
+    ///
+    /// * `express` - Operator name, case-insensitive
+    /// * `value` - Target value of the condition
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Operator, String>` - Compiled operator or the reason it is invalid
+    pub fn compile(express: &str, value: &str) -> Result<Operator, String> {
+        let number = || {
+            Number::parse(value)
+                .ok_or_else(|| format!("'{}' requires a number, got '{}'", express, value))
+        };
+
+        let operator = match express.to_ascii_lowercase().as_str() {
+            "eq" | "equal" => Operator::Eq(value.to_lowercase()),
+            "ne" | "notequal" => Operator::Ne(value.to_lowercase()),
+            "lt" | "lessthan" => Operator::Compare(vec![Ordering::Less], number()?),
+            "le" | "lessorequal" => {
+                Operator::Compare(vec![Ordering::Less, Ordering::Equal], number()?)
+            }
+            "ge" | "greaterorequal" => {
+                Operator::Compare(vec![Ordering::Greater, Ordering::Equal], number()?)
+            }
+            "gt" | "greaterthan" => Operator::Compare(vec![Ordering::Greater], number()?),
+            "in" => Operator::In(parse_list(value)?),
+            "notin" => Operator::NotIn(parse_list(value)?),
+            "range" => {
+                let (min, max) = parse_range(value)?;
+                Operator::Range(min, max)
+            }
+            "regex" => Operator::Matches(
+                Regex::new(value).map_err(|e| format!("invalid regex '{}': {}", value, e))?,
+            ),
+            "bool" => Operator::Bool(
+                parse_bool(value)
+                    .ok_or_else(|| format!("'bool' requires true or false, got '{}'", value))?,
+            ),
+            "changed" => Operator::Changed,
+            "deltaexceeds" => match number()? {
+                n if n.compare(Number::Int(0)) == Some(Ordering::Less) => {
+                    return Err(format!(
+                        "'deltaExceeds' requires a positive number, got '{}'",
+                        value
+                    ))
+                }
+                n => Operator::DeltaExceeds(n),
+            },
+            _ => return Err(format!("unknown expression '{}' in condition", express)),
+        };
+        Ok(operator)
+    }
+}
+
+/// Operator of a scenario condition together with the previous sample,
+/// needed by `changed` and `deltaExceeds`
+#[derive(Debug, Clone)]
+pub struct ConditionCheck {
+    operator: Operator,
+    previous: Option<String>,
+}
+
+impl ConditionCheck {
+    pub fn new(operator: Operator) -> Self {
+        Self {
+            operator,
+            previous: None,
+        }
+    }
+
+    /// Compile the operator of a scenario condition
+    pub fn from_condition(condition: &Condition) -> Result<Self, String> {
+        Operator::compile(&condition.get_express(), &condition.get_value()).map(Self::new)
+    }
+
+    /// Evaluate the condition for the field value of a new sample
+    ///
+    /// # Arguments
+    ///
+    /// * `field_value` - Value of the condition field in the sample
+    ///
+    /// # Returns
+    ///
+    /// * `Result<bool, String>` - Whether the condition is satisfied, or an
+    ///   error if the field value does not have the type the operator expects
+    pub fn evaluate(&mut self, field_value: &str) -> Result<bool, String> {
+        let previous = self.previous.replace(field_value.to_string());
+
+        match &self.operator {
+            Operator::Eq(target) => Ok(field_value.to_lowercase() == *target),
+            Operator::Ne(target) => Ok(field_value.to_lowercase() != *target),
+            Operator::Compare(orderings, target) => {
+                let ordering = parse_number(field_value)?.compare(*target);
+                Ok(ordering.is_some_and(|o| orderings.contains(&o)))
+            }
+            Operator::In(set) => Ok(set.contains(&field_value.trim().to_lowercase())),
+            Operator::NotIn(set) => Ok(!set.contains(&field_value.trim().to_lowercase())),
+            Operator::Range(min, max) => {
+                let current = parse_number(field_value)?;
+                Ok(current.compare(*min).is_some_and(|o| o != Ordering::Less)
+                    && current
+                        .compare(*max)
+                        .is_some_and(|o| o != Ordering::Greater))
+            }
+            Operator::Matches(regex) => Ok(regex.is_match(field_value)),
+            Operator::Bool(target) => parse_bool(field_value)
+                .map(|b| b == *target)
+                .ok_or_else(|| format!("field value '{}' is not a boolean", field_value)),
+            Operator::Changed => Ok(previous.is_some_and(|p| p != field_value)),
+            Operator::DeltaExceeds(threshold) => {
+                let current = parse_number(field_value)?;
+                let Some(previous) = previous.as_deref().and_then(Number::parse) else {
+                    return Ok(false);
+                };
+                Ok(current.distance(previous).compare(*threshold) == Some(Ordering::Greater))
+            }
+        }
+    }
+}
+
+fn parse_number(field_value: &str) -> Result<Number, String> {
+    Number::parse(field_value)
+        .ok_or_else(|| format!("field value '{}' is not a number", field_value))
+}
+
+fn parse_bool(s: &str) -> Option<bool> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "true" | "1" => Some(true),
+        "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_list(value: &str) -> Result<Vec<String>, String> {
+    let list: Vec<String> = value
+        .split(',')
+        .map(|v| v.trim().to_lowercase())
+        .filter(|v| !v.is_empty())
+        .collect();
+    if list.is_empty() {
+        return Err("'in' and 'notIn' require a comma separated list of values".to_string());
+    }
+    Ok(list)
+}
+
+fn parse_range(value: &str) -> Result<(Number, Number), String> {
+    let invalid = || format!("'range' requires 'min..max', got '{}'", value);
+    let (min, max) = value.split_once("..").ok_or_else(invalid)?;
+    let min = Number::parse(min).ok_or_else(invalid)?;
+    let max = Number::parse(max).ok_or_else(invalid)?;
+    if min.compare(max) == Some(Ordering::Greater) {
+        return Err(format!(
+            "'range' minimum is greater than maximum in '{}'",
+            value
+        ));
+    }
+    Ok((min, max))
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(express: &str, value: &str) -> ConditionCheck {
+        ConditionCheck::new(Operator::compile(express, value).unwrap())
+    }
+
+    #[test]
+    fn test_string_operators() {
+        let mut eq = check("eq", "Parking");
+        assert!(eq.evaluate("parking").unwrap());
+        assert!(!eq.evaluate("drive").unwrap());
+
+        let mut equal = check("Equal", "parking");
+        assert!(equal.evaluate("PARKING").unwrap());
+
+        let mut ne = check("ne", "parking");
+        assert!(ne.evaluate("drive").unwrap());
+
+        let mut set = check("in", "drive, reverse");
+        assert!(set.evaluate("Reverse").unwrap());
+        assert!(!set.evaluate("parking").unwrap());
+
+        let mut not_in = check("notIn", "drive,reverse");
+        assert!(not_in.evaluate("parking").unwrap());
+
+        let mut regex = check("regex", "^err(or)?_[0-9]+$");
+        assert!(regex.evaluate("error_42").unwrap());
+        assert!(!regex.evaluate("warning_42").unwrap());
+    }
+
+    #[test]
+    fn test_numeric_operators() {
+        let mut lt = check("lt", "10.5");
+        assert!(lt.evaluate("10").unwrap());
+        assert!(!lt.evaluate("10.5").unwrap());
+        assert!(lt.evaluate("speed").is_err());
+
+        let mut range = check("range", "-5..5");
+        assert!(range.evaluate("-5").unwrap());
+        assert!(range.evaluate("5").unwrap());
+        assert!(!range.evaluate("5.01").unwrap());
+
+        let mut flag = check("bool", "true");
+        assert!(flag.evaluate("1").unwrap());
+        assert!(!flag.evaluate("False").unwrap());
+        assert!(flag.evaluate("on").is_err());
+    }
+
+    #[test]
+    fn test_integer_comparison_is_exact() {
+        // Both values round to the same f32 and f64
+        let mut gt = check("gt", "18446744073709551614");
+        assert!(gt.evaluate("18446744073709551615").unwrap());
+        assert!(!gt.evaluate("18446744073709551614").unwrap());
+
+        let mut ge = check("ge", "9007199254740993");
+        assert!(!ge.evaluate("9007199254740992").unwrap());
+    }
+
+    #[test]
+    fn test_stateful_operators() {
+        let mut changed = check("changed", "");
+        assert!(!changed.evaluate("parking").unwrap());
+        assert!(!changed.evaluate("parking").unwrap());
+        assert!(changed.evaluate("drive").unwrap());
+
+        let mut delta = check("deltaExceeds", "10");
+        assert!(!delta.evaluate("100").unwrap());
+        assert!(!delta.evaluate("110").unwrap());
+        assert!(delta.evaluate("95").unwrap());
+        assert!(delta.evaluate("105.5").unwrap());
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        assert!(Operator::compile("between", "1").is_err());
+        assert!(Operator::compile("gt", "fast").is_err());
+        assert!(Operator::compile("range", "10").is_err());
+        assert!(Operator::compile("range", "10..1").is_err());
+        assert!(Operator::compile("regex", "(unclosed").is_err());
+        assert!(Operator::compile("bool", "yes").is_err());
+        assert!(Operator::compile("in", " , ").is_err());
+        assert!(Operator::compile("deltaExceeds", "-1").is_err());
+    }
+}
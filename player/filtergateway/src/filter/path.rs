@@ -0,0 +1,193 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Field paths of condition operands
+//!
+//! `operands.name` may point into nested IDL structs and sequences, e.g.
+//! `obstacle.distance` or `lamps[2].state`. The path is parsed when the
+//! scenario is registered and resolved against the JSON value of each sample.
+
+use crate::vehicle::dds::DdsData;
+use serde_json::Value;
+
+/// One step of a field path
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    /// Member of a struct
+    Key(String),
+    /// Element of a sequence or array
+    Index(usize),
+}
+
+/// Parsed `operands.name` of a condition
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldPath {
+    name: String,
+    segments: Vec<Segment>,
+}
+
+impl FieldPath {
+    /// Parse a path such as `obstacle.distance` or `lamps[2].state`
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Operand name of the condition
+    ///
+    /// # Returns
+    ///
+    /// * `Result<FieldPath, String>` - Parsed path or the reason it is invalid
+    pub fn parse(name: &str) -> Result<FieldPath, String> {
+        let invalid = |reason: &str| format!("invalid field path '{}': {}", name, reason);
+        let mut segments = Vec::new();
+
+        for part in name.split('.') {
+            let (key, mut rest) = match part.find('[') {
+                Some(pos) => part.split_at(pos),
+                None => (part, ""),
+            };
+            if key.is_empty() && (segments.is_empty() || rest.is_empty()) {
+                return Err(invalid("empty member name"));
+            }
+            if !key.is_empty() {
+                segments.push(Segment::Key(key.to_string()));
+            }
+
+            while !rest.is_empty() {
+                let Some(inner) = rest.strip_prefix('[') else {
+                    return Err(invalid("unexpected text after index"));
+                };
+                let Some(end) = inner.find(']') else {
+                    return Err(invalid("missing ']'"));
+                };
+                let index = inner[..end]
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| invalid("index must be a non-negative integer"))?;
+                segments.push(Segment::Index(index));
+                rest = &inner[end + 1..];
+            }
+        }
+
+        Ok(FieldPath {
+            name: name.to_string(),
+            segments,
+        })
+    }
+
+    /// Find the value the path points to
+    pub fn resolve<'a>(&self, value: &'a Value) -> Option<&'a Value> {
+        self.segments
+            .iter()
+            .try_fold(value, |current, segment| match segment {
+                Segment::Key(key) => current.get(key.as_str()),
+                Segment::Index(index) => current.get(*index),
+            })
+    }
+
+    /// Read the field of a sample as text
+    ///
+    /// A flattened field with exactly this name is used first, otherwise the
+    /// path is resolved against the JSON value of the sample. Strings are
+    /// returned without quotes, other values as JSON.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Received DDS data
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - Field value, or `None` if the sample has no such field
+    pub fn lookup(&self, data: &DdsData) -> Option<String> {
+        if let Some(field) = data.fields.get(&self.name) {
+            return Some(field.clone());
+        }
+        let value = serde_json::from_str::<Value>(&data.value).ok()?;
+        self.resolve(&value).map(value_to_string)
+    }
+}
+
+/// Text form of a JSON value used for condition evaluation
+pub fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn sample(value: Value) -> DdsData {
+        DdsData {
+            name: "VehicleStatus".to_string(),
+            value: value.to_string(),
+            fields: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_paths() {
+        assert_eq!(
+            FieldPath::parse("lamps[2].state").unwrap().segments,
+            vec![
+                Segment::Key("lamps".to_string()),
+                Segment::Index(2),
+                Segment::Key("state".to_string()),
+            ]
+        );
+        assert_eq!(
+            FieldPath::parse("matrix[0][1]").unwrap().segments,
+            vec![
+                Segment::Key("matrix".to_string()),
+                Segment::Index(0),
+                Segment::Index(1),
+            ]
+        );
+
+        assert!(FieldPath::parse("").is_err());
+        assert!(FieldPath::parse("obstacle..distance").is_err());
+        assert!(FieldPath::parse("lamps[x]").is_err());
+        assert!(FieldPath::parse("lamps[-1]").is_err());
+        assert!(FieldPath::parse("lamps[2").is_err());
+        assert!(FieldPath::parse("lamps[2]state").is_err());
+    }
+
+    #[test]
+    fn test_lookup_nested_fields() {
+        let data = sample(json!({
+            "obstacle": { "distance": 12.5, "kind": "pedestrian" },
+            "lamps": [
+                { "state": "off" },
+                { "state": "off" },
+                { "state": "on" }
+            ]
+        }));
+
+        let lookup = |path: &str| FieldPath::parse(path).unwrap().lookup(&data);
+        assert_eq!(lookup("obstacle.distance"), Some("12.5".to_string()));
+        assert_eq!(lookup("obstacle.kind"), Some("pedestrian".to_string()));
+        assert_eq!(lookup("lamps[2].state"), Some("on".to_string()));
+        assert_eq!(lookup("lamps[3].state"), None);
+        assert_eq!(lookup("obstacle.speed"), None);
+    }
+
+    #[test]
+    fn test_lookup_prefers_flattened_field() {
+        let mut data = sample(json!({ "status": "warning" }));
+        data.fields
+            .insert("status".to_string(), "critical".to_string());
+
+        let path = FieldPath::parse("status").unwrap();
+        assert_eq!(path.lookup(&data), Some("critical".to_string()));
+
+        data.value = "not json".to_string();
+        data.fields.clear();
+        assert_eq!(path.lookup(&data), None);
+    }
+}
@@ -0,0 +1,472 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Offline evaluation of scenario conditions
+//!
+//! A script of timestamped samples is fed to the `Filter` of a scenario in
+//! virtual time, and the report says when its action would have been
+//! triggered. No DDS, gRPC or ActionController is involved, so conditions
+//! can be tested in CI with the same evaluator filtergateway runs.
+//!
+//! A YAML script is a list of samples:
+//!
+//! ```yaml
+//! - at: 0            # milliseconds since the start of the script
+//!   value: { state: park }
+//! - at: 1500
+//!   topic: rt/piccolo/gear_state   # defaults to the condition topic
+//!   value: { state: drive }
+//! ```
+//!
+//! A CSV script has an `at` column, an optional `topic` column and one
+//! column per field; dotted column names such as `door.open` build nested
+//! fields and empty cells are left out. Cells are not quoted.
+
+use super::trigger::Decision;
+use super::Filter;
+use crate::grpc::sender::actioncontroller::FilterGatewaySender;
+use crate::vehicle::dds::DdsData;
+use common::spec::artifact::{Artifact, Scenario};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Sample of a script
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ScriptStep {
+    /// Milliseconds since the start of the script
+    pub at: u64,
+    /// Topic of the sample, the topic of the scenario condition if unset
+    #[serde(default)]
+    pub topic: Option<String>,
+    /// Fields of the sample
+    pub value: Value,
+}
+
+/// Why the action was triggered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerCause {
+    /// The scenario has no condition and fires when it is launched
+    Launch,
+    /// The sample at this index of the script
+    Sample(usize),
+    /// The end of the `holdFor` time
+    HoldTime,
+}
+
+/// Time the action would have been triggered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimulatedTrigger {
+    /// Milliseconds since the start of the script
+    pub at: u64,
+    pub cause: TriggerCause,
+}
+
+/// Result of a simulation
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SimulationReport {
+    pub triggers: Vec<SimulatedTrigger>,
+    /// Samples the filter could not evaluate, by script index
+    pub errors: Vec<(usize, String)>,
+}
+
+impl SimulationReport {
+    /// Trigger times in milliseconds
+    pub fn trigger_times(&self) -> Vec<u64> {
+        self.triggers.iter().map(|t| t.at).collect()
+    }
+}
+
+/// Read the Scenario document of a YAML file
+///
+/// # Arguments
+///
+/// * `source` - YAML text, possibly with other artifacts
+///
+/// # Returns
+///
+/// * `Result<Scenario, String>` - The first Scenario of the text
+pub fn parse_scenario(source: &str) -> Result<Scenario, String> {
+    for document in serde_yaml::Deserializer::from_str(source) {
+        let value = Value::deserialize(document).map_err(|e| e.to_string())?;
+        if value.get("kind").and_then(Value::as_str) == Some("Scenario") {
+            return serde_json::from_value(value).map_err(|e| e.to_string());
+        }
+    }
+    Err("no Scenario in YAML".to_string())
+}
+
+/// Parse a YAML script
+pub fn parse_yaml_script(source: &str) -> Result<Vec<ScriptStep>, String> {
+    serde_yaml::from_str(source).map_err(|e| e.to_string())
+}
+
+/// Parse a CSV script
+pub fn parse_csv_script(source: &str) -> Result<Vec<ScriptStep>, String> {
+    let mut lines = source
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'));
+    let (_, header) = lines.next().ok_or("empty script")?;
+    let header: Vec<&str> = header.split(',').map(str::trim).collect();
+    let at_column = header
+        .iter()
+        .position(|name| *name == "at")
+        .ok_or("missing 'at' column")?;
+    let topic_column = header.iter().position(|name| *name == "topic");
+
+    let mut steps = Vec::new();
+    for (number, line) in lines {
+        let error = |e: &str| format!("line {}: {}", number + 1, e);
+        let cells: Vec<&str> = line.split(',').map(str::trim).collect();
+        if cells.len() != header.len() {
+            return Err(error(&format!("expected {} cells", header.len())));
+        }
+
+        let at = cells[at_column]
+            .parse()
+            .map_err(|_| error("invalid 'at' milliseconds"))?;
+        let mut value = Map::new();
+        for (column, cell) in cells.iter().enumerate() {
+            if column == at_column || Some(column) == topic_column || cell.is_empty() {
+                continue;
+            }
+            insert_field(&mut value, header[column], cell_value(cell));
+        }
+        steps.push(ScriptStep {
+            at,
+            topic: topic_column
+                .map(|column| cells[column].to_string())
+                .filter(|topic| !topic.is_empty()),
+            value: Value::Object(value),
+        });
+    }
+    Ok(steps)
+}
+
+/// Read a script file, as CSV if its extension is `csv` and YAML otherwise
+pub fn load_script(path: &Path) -> Result<Vec<ScriptStep>, String> {
+    let source = std::fs::read_to_string(path).map_err(|e| format!("{:?}: {}", path, e))?;
+    let steps = match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("csv") => parse_csv_script(&source),
+        _ => parse_yaml_script(&source),
+    };
+    steps.map_err(|e| format!("{:?}: {}", path, e))
+}
+
+/// JSON value of a CSV cell
+fn cell_value(cell: &str) -> Value {
+    if let Ok(n) = cell.parse::<i64>() {
+        return Value::from(n);
+    }
+    if let Ok(n) = cell.parse::<u64>() {
+        return Value::from(n);
+    }
+    if let Some(n) = cell
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+    {
+        return Value::Number(n);
+    }
+    match cell {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => Value::String(cell.to_string()),
+    }
+}
+
+/// Insert a field at a dotted path
+fn insert_field(object: &mut Map<String, Value>, path: &str, value: Value) {
+    match path.split_once('.') {
+        Some((head, rest)) => {
+            let child = object
+                .entry(head)
+                .or_insert_with(|| Value::Object(Map::new()));
+            if !child.is_object() {
+                *child = Value::Object(Map::new());
+            }
+            if let Value::Object(child) = child {
+                insert_field(child, rest, value);
+            }
+        }
+        None => {
+            object.insert(path.to_string(), value);
+        }
+    }
+}
+
+/// Filter of a scenario running in virtual time
+pub struct Simulator {
+    filter: Filter,
+    /// Topic of the scenario condition, `None` without condition
+    topic: Option<String>,
+    /// Virtual time of the start of the script
+    start: Instant,
+    /// Hold checks not done yet (milliseconds, epoch)
+    pending: Vec<(u64, u64)>,
+    /// Time of the previous sample
+    last: u64,
+    report: SimulationReport,
+}
+
+impl Simulator {
+    /// Create the filter of a scenario
+    ///
+    /// # Arguments
+    ///
+    /// * `scenario` - Scenario to evaluate
+    ///
+    /// # Returns
+    ///
+    /// * `common::Result<Simulator>` - Error if the condition is invalid
+    pub fn new(scenario: Scenario) -> common::Result<Self> {
+        Filter::validate(&scenario)?;
+        let topic = scenario
+            .get_conditions()
+            .map(|condition| condition.get_operand_value());
+        let filter = Filter::new(
+            scenario.get_name(),
+            scenario,
+            true,
+            FilterGatewaySender::new(),
+        )?;
+
+        let mut report = SimulationReport::default();
+        if topic.is_none() {
+            report.triggers.push(SimulatedTrigger {
+                at: 0,
+                cause: TriggerCause::Launch,
+            });
+        }
+        Ok(Self {
+            filter,
+            topic,
+            start: Instant::now(),
+            pending: Vec::new(),
+            last: 0,
+            report,
+        })
+    }
+
+    fn instant(&self, at: u64) -> Instant {
+        self.start + Duration::from_millis(at)
+    }
+
+    /// Run the hold checks due up to a time
+    fn run_pending(&mut self, until: u64) {
+        self.pending.sort_unstable();
+        while let Some(&(due, epoch)) = self.pending.first() {
+            if due > until {
+                break;
+            }
+            self.pending.remove(0);
+            if self.filter.hold_elapsed(epoch, self.instant(due)) {
+                self.report.triggers.push(SimulatedTrigger {
+                    at: due,
+                    cause: TriggerCause::HoldTime,
+                });
+            }
+        }
+    }
+
+    /// Feed a sample of the script
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Index of the sample in the script
+    /// * `step` - Sample, not earlier than the previous one
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - Error if the sample goes back in time
+    pub fn step(&mut self, index: usize, step: &ScriptStep) -> Result<(), String> {
+        if step.at < self.last {
+            return Err(format!(
+                "sample {} at {} ms is before the previous one at {} ms",
+                index, step.at, self.last
+            ));
+        }
+        self.last = step.at;
+        self.run_pending(step.at);
+
+        let Some(topic) = self.topic.as_ref() else {
+            return Ok(());
+        };
+        let name = step.topic.as_ref().unwrap_or(topic);
+        // Filters only receive the samples of their topics
+        if !self.filter.topics().contains(name) {
+            return Ok(());
+        }
+
+        let data = DdsData::from_json(name, step.value.to_string());
+        match self.filter.evaluate_at(&data, self.instant(step.at)) {
+            Ok(Decision::Fire) => self.report.triggers.push(SimulatedTrigger {
+                at: step.at,
+                cause: TriggerCause::Sample(index),
+            }),
+            Ok(Decision::Wait { after, epoch }) => {
+                self.pending
+                    .push((step.at + after.as_millis() as u64, epoch));
+            }
+            Ok(Decision::Idle) => {}
+            Err(e) => self.report.errors.push((index, e.to_string())),
+        }
+        Ok(())
+    }
+
+    /// End the script, running the hold checks still pending
+    pub fn finish(mut self) -> SimulationReport {
+        self.run_pending(u64::MAX);
+        self.report
+    }
+}
+
+/// Evaluate a scenario against a script
+///
+/// # Arguments
+///
+/// * `scenario` - Scenario to evaluate
+/// * `script` - Samples in time order
+///
+/// # Returns
+///
+/// * `common::Result<SimulationReport>` - When the action would have been
+///   triggered, or an error for an invalid condition or script
+pub fn simulate(scenario: Scenario, script: &[ScriptStep]) -> common::Result<SimulationReport> {
+    let mut simulator = Simulator::new(scenario)?;
+    for (index, step) in script.iter().enumerate() {
+        simulator.step(index, step)?;
+    }
+    Ok(simulator.finish())
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario(condition: &str) -> Scenario {
+        parse_scenario(&format!(
+            r#"
+apiVersion: v1
+kind: Scenario
+metadata:
+  name: parking
+spec:
+  condition:
+{}
+  action: update
+  target: helloworld
+"#,
+            condition
+        ))
+        .unwrap()
+    }
+
+    const GEAR: &str = r#"
+    express: eq
+    value: park
+    operands:
+      type: DDS
+      name: state
+      value: rt/piccolo/gear_state"#;
+
+    #[test]
+    fn test_parse_csv_script() {
+        let steps = parse_csv_script(
+            "at,topic,state,door.open\n# comment\n0,,park,true\n250,rt/other,drive,\n",
+        )
+        .unwrap();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].topic, None);
+        assert_eq!(
+            steps[0].value,
+            serde_json::json!({"state": "park", "door": {"open": true}})
+        );
+        assert_eq!(steps[1].topic.as_deref(), Some("rt/other"));
+        assert_eq!(steps[1].value, serde_json::json!({"state": "drive"}));
+
+        assert!(parse_csv_script("state\npark\n").is_err());
+        assert!(parse_csv_script("at,state\n0\n").is_err());
+        assert!(parse_csv_script("at,state\nsoon,park\n").is_err());
+    }
+
+    #[test]
+    fn test_simulate_rising_edges() {
+        let script = parse_yaml_script(
+            r#"
+- { at: 0, value: { state: drive } }
+- { at: 100, value: { state: park } }
+- { at: 200, value: { state: park } }
+- { at: 300, topic: rt/other, value: { state: drive } }
+- { at: 400, value: { state: drive } }
+- { at: 500, value: { state: park } }
+- { at: 600, value: { speed: 3 } }
+"#,
+        )
+        .unwrap();
+        let report = simulate(scenario(GEAR), &script).unwrap();
+        assert_eq!(
+            report.triggers,
+            vec![
+                SimulatedTrigger {
+                    at: 100,
+                    cause: TriggerCause::Sample(1)
+                },
+                SimulatedTrigger {
+                    at: 500,
+                    cause: TriggerCause::Sample(5)
+                },
+            ]
+        );
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].0, 6);
+    }
+
+    #[test]
+    fn test_simulate_hold_for() {
+        let condition = format!("{}\n    holdFor: 1000", GEAR);
+        let script = parse_yaml_script(
+            r#"
+- { at: 0, value: { state: park } }
+- { at: 500, value: { state: drive } }
+- { at: 600, value: { state: park } }
+- { at: 1000, value: { state: park } }
+"#,
+        )
+        .unwrap();
+        // The first hold is cut at 500 ms; the second ends after the script
+        let report = simulate(scenario(&condition), &script).unwrap();
+        assert_eq!(
+            report.triggers,
+            vec![SimulatedTrigger {
+                at: 1600,
+                cause: TriggerCause::HoldTime
+            }]
+        );
+    }
+
+    #[test]
+    fn test_simulate_rejects_invalid_input() {
+        let script = vec![
+            ScriptStep {
+                at: 100,
+                topic: None,
+                value: serde_json::json!({"state": "park"}),
+            },
+            ScriptStep {
+                at: 50,
+                topic: None,
+                value: serde_json::json!({"state": "park"}),
+            },
+        ];
+        assert!(simulate(scenario(GEAR), &script).is_err());
+
+        let condition = GEAR.replace("express: eq", "express: gt");
+        assert!(Simulator::new(scenario(&condition)).is_err());
+    }
+}
@@ -0,0 +1,290 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Edge detection and debouncing of scenario conditions
+//!
+//! A scenario fires when its condition goes from false to true, not on every
+//! sample that satisfies it. The transition can additionally be required to
+//! hold for some time, be followed by a cooldown, or be allowed only once.
+
+use common::spec::artifact::Condition;
+use std::time::{Duration, Instant};
+
+/// Trigger options declared in the Scenario condition
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TriggerOptions {
+    /// Time the condition must stay true before firing (`holdFor`)
+    pub hold_for: Duration,
+    /// Time after firing during which new transitions are ignored (`cooldown`)
+    pub cooldown: Duration,
+    /// Fire at most once (`oneShot`)
+    pub one_shot: bool,
+}
+
+impl From<&Condition> for TriggerOptions {
+    fn from(condition: &Condition) -> Self {
+        Self {
+            hold_for: Duration::from_millis(condition.get_hold_for()),
+            cooldown: Duration::from_millis(condition.get_cooldown()),
+            one_shot: condition.is_one_shot(),
+        }
+    }
+}
+
+/// What the filter has to do after a condition update
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Decision {
+    /// Trigger the scenario action now
+    Fire,
+    /// Check again with `on_timer(epoch)` after the given delay, in case no
+    /// new sample arrives before the hold time ends
+    Wait { after: Duration, epoch: u64 },
+    /// Nothing to do
+    Idle,
+}
+
+/// Condition state of one scenario filter
+#[derive(Debug, Default)]
+pub struct TriggerState {
+    options: TriggerOptions,
+    /// Start of the current period in which the condition is true
+    true_since: Option<Instant>,
+    /// Whether the action already fired in the current true period
+    fired: bool,
+    last_fired: Option<Instant>,
+    /// Set once a one-shot scenario has fired
+    exhausted: bool,
+    /// Incremented on every transition to invalidate scheduled checks
+    epoch: u64,
+    timer_pending: bool,
+}
+
+impl TriggerState {
+    pub fn new(options: TriggerOptions) -> Self {
+        Self {
+            options,
+            ..Default::default()
+        }
+    }
+
+    /// Record a new evaluation of the condition
+    ///
+    /// # Arguments
+    ///
+    /// * `satisfied` - Result of the condition for the received sample
+    /// * `now` - Time of the evaluation
+    ///
+    /// # Returns
+    ///
+    /// * `Decision` - Whether to fire, wait for the hold time, or do nothing
+    pub fn update(&mut self, satisfied: bool, now: Instant) -> Decision {
+        if !satisfied {
+            if self.true_since.is_some() {
+                self.epoch += 1;
+            }
+            self.true_since = None;
+            self.fired = false;
+            self.timer_pending = false;
+            return Decision::Idle;
+        }
+
+        if self.exhausted || self.fired {
+            return Decision::Idle;
+        }
+
+        let since = match self.true_since {
+            Some(since) => since,
+            None => {
+                self.epoch += 1;
+                self.true_since = Some(now);
+                if self.in_cooldown(now) {
+                    // A transition during the cooldown is dropped, not delayed
+                    self.fired = true;
+                    return Decision::Idle;
+                }
+                now
+            }
+        };
+
+        let held = now.saturating_duration_since(since);
+        if held < self.options.hold_for {
+            if self.timer_pending {
+                return Decision::Idle;
+            }
+            self.timer_pending = true;
+            return Decision::Wait {
+                after: self.options.hold_for - held,
+                epoch: self.epoch,
+            };
+        }
+
+        self.fire(now)
+    }
+
+    /// Handle the end of a hold time scheduled by `Decision::Wait`
+    ///
+    /// Returns `true` if the condition stayed true for the whole hold time and
+    /// the action must fire now.
+    pub fn on_timer(&mut self, epoch: u64, now: Instant) -> bool {
+        if epoch != self.epoch {
+            return false;
+        }
+        self.timer_pending = false;
+        self.update(true, now) == Decision::Fire
+    }
+
+    /// Forget the current true period and cancel the pending hold check
+    ///
+    /// Used when the filter is paused: after resuming, a condition that is
+    /// still true is handled as a new transition. The cooldown and one-shot
+    /// state are kept.
+    pub fn reset(&mut self) {
+        self.epoch += 1;
+        self.true_since = None;
+        self.fired = false;
+        self.timer_pending = false;
+    }
+
+    /// Whether the condition is true since its last transition
+    pub fn is_true(&self) -> bool {
+        self.true_since.is_some()
+    }
+
+    fn in_cooldown(&self, now: Instant) -> bool {
+        match self.last_fired {
+            Some(last) => now.saturating_duration_since(last) < self.options.cooldown,
+            None => false,
+        }
+    }
+
+    fn fire(&mut self, now: Instant) -> Decision {
+        self.fired = true;
+        self.timer_pending = false;
+        self.last_fired = Some(now);
+        if self.options.one_shot {
+            self.exhausted = true;
+        }
+        Decision::Fire
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_fires_only_on_rising_edge() {
+        let mut state = TriggerState::new(TriggerOptions::default());
+        let t0 = Instant::now();
+
+        assert_eq!(state.update(true, t0), Decision::Fire);
+        // 10 Hz samples keep the condition true without firing again
+        for i in 1..10 {
+            assert_eq!(state.update(true, t0 + ms(100 * i)), Decision::Idle);
+        }
+
+        assert_eq!(state.update(false, t0 + ms(1000)), Decision::Idle);
+        assert_eq!(state.update(true, t0 + ms(1100)), Decision::Fire);
+    }
+
+    #[test]
+    fn test_hold_for_fires_after_hold_time() {
+        let mut state = TriggerState::new(TriggerOptions {
+            hold_for: ms(500),
+            ..Default::default()
+        });
+        let t0 = Instant::now();
+
+        let Decision::Wait { after, epoch } = state.update(true, t0) else {
+            panic!("expected to wait for the hold time");
+        };
+        assert_eq!(after, ms(500));
+        // Further samples during the hold time do not schedule more checks
+        assert_eq!(state.update(true, t0 + ms(200)), Decision::Idle);
+
+        assert!(state.on_timer(epoch, t0 + ms(500)));
+        assert_eq!(state.update(true, t0 + ms(600)), Decision::Idle);
+    }
+
+    #[test]
+    fn test_hold_for_cancelled_when_condition_drops() {
+        let mut state = TriggerState::new(TriggerOptions {
+            hold_for: ms(500),
+            ..Default::default()
+        });
+        let t0 = Instant::now();
+
+        let Decision::Wait { epoch, .. } = state.update(true, t0) else {
+            panic!("expected to wait for the hold time");
+        };
+        state.update(false, t0 + ms(300));
+        assert!(!state.on_timer(epoch, t0 + ms(500)));
+
+        // A sample after the hold time fires even before the timer
+        assert!(matches!(
+            state.update(true, t0 + ms(400)),
+            Decision::Wait { .. }
+        ));
+        assert_eq!(state.update(true, t0 + ms(900)), Decision::Fire);
+    }
+
+    #[test]
+    fn test_cooldown_drops_transitions() {
+        let mut state = TriggerState::new(TriggerOptions {
+            cooldown: ms(1000),
+            ..Default::default()
+        });
+        let t0 = Instant::now();
+
+        assert_eq!(state.update(true, t0), Decision::Fire);
+        state.update(false, t0 + ms(100));
+        assert_eq!(state.update(true, t0 + ms(200)), Decision::Idle);
+        // Still the same true period, so no late firing after the cooldown
+        assert_eq!(state.update(true, t0 + ms(1500)), Decision::Idle);
+
+        state.update(false, t0 + ms(1600));
+        assert_eq!(state.update(true, t0 + ms(1700)), Decision::Fire);
+    }
+
+    #[test]
+    fn test_one_shot_fires_once() {
+        let mut state = TriggerState::new(TriggerOptions {
+            one_shot: true,
+            ..Default::default()
+        });
+        let t0 = Instant::now();
+
+        assert_eq!(state.update(true, t0), Decision::Fire);
+        state.update(false, t0 + ms(100));
+        assert_eq!(state.update(true, t0 + ms(200)), Decision::Idle);
+    }
+
+    #[test]
+    fn test_reset_cancels_hold_check() {
+        let mut state = TriggerState::new(TriggerOptions {
+            hold_for: ms(500),
+            ..Default::default()
+        });
+        let t0 = Instant::now();
+
+        let Decision::Wait { epoch, .. } = state.update(true, t0) else {
+            panic!("expected to wait for the hold time");
+        };
+        state.reset();
+        assert!(!state.on_timer(epoch, t0 + ms(500)));
+
+        // The hold time starts again from the first sample after the reset
+        assert!(matches!(
+            state.update(true, t0 + ms(600)),
+            Decision::Wait { .. }
+        ));
+        assert_eq!(state.update(true, t0 + ms(1100)), Decision::Fire);
+    }
+}
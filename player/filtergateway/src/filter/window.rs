@@ -0,0 +1,366 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Time-window aggregates of condition operands
+//!
+//! A condition with a `window` is evaluated against an aggregate of the
+//! samples received in the last `duration` milliseconds, e.g. the average
+//! speed over 10 s or how much the battery temperature rose in 60 s.
+//! Samples are kept in `SignalHistory`, one buffer per topic and field path,
+//! long enough for the largest window of the active filters.
+
+use super::operator::Number;
+use super::path::FieldPath;
+use crate::vehicle::dds::DdsData;
+use common::spec::artifact::Window;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Upper bound of samples kept per buffer, whatever the window length
+pub const MAX_SAMPLES: usize = 10_000;
+
+/// Aggregate computed over the samples of a window
+#[derive(Debug, Clone, PartialEq)]
+pub enum Aggregate {
+    Avg,
+    Min,
+    Max,
+    /// Number of samples, only those equal to the value if one is given
+    Count(Option<String>),
+    /// Last sample minus first sample
+    Delta,
+    /// `Delta` per second between the first and last sample
+    Rate,
+}
+
+/// Compiled `window` of a condition
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowSpec {
+    pub duration: Duration,
+    pub aggregate: Aggregate,
+}
+
+impl WindowSpec {
+    /// Compile the window of a scenario condition
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - Window declared in the condition
+    ///
+    /// # Returns
+    ///
+    /// * `Result<WindowSpec, String>` - Compiled window or the reason it is invalid
+    pub fn from_window(window: &Window) -> Result<WindowSpec, String> {
+        if window.get_duration() == 0 {
+            return Err("window duration must be a positive number of milliseconds".to_string());
+        }
+
+        let aggregate = window.get_aggregate();
+        let aggregate = match aggregate.to_ascii_lowercase().as_str() {
+            "avg" | "average" => Aggregate::Avg,
+            "min" => Aggregate::Min,
+            "max" => Aggregate::Max,
+            "count" => Aggregate::Count(window.get_count_of().map(|v| v.to_lowercase())),
+            "delta" => Aggregate::Delta,
+            "rate" => Aggregate::Rate,
+            _ => return Err(format!("unknown window aggregate '{}'", aggregate)),
+        };
+
+        Ok(WindowSpec {
+            duration: Duration::from_millis(window.get_duration()),
+            aggregate,
+        })
+    }
+
+    /// Compute the aggregate of the samples in the window
+    ///
+    /// # Arguments
+    ///
+    /// * `samples` - Samples of the window, oldest first
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, String>` - Aggregate as text for the condition
+    ///   operator, or an error if the window is empty or a sample is not a number
+    pub fn compute(&self, samples: &[(Instant, String)]) -> Result<String, String> {
+        if let Aggregate::Count(count_of) = &self.aggregate {
+            let count = samples
+                .iter()
+                .filter(|(_, v)| count_of.as_ref().is_none_or(|c| v.to_lowercase() == *c))
+                .count();
+            return Ok(count.to_string());
+        }
+
+        let (Some(first), Some(last)) = (samples.first(), samples.last()) else {
+            return Err("no samples in window".to_string());
+        };
+        let numbers = samples
+            .iter()
+            .map(|(_, v)| {
+                Number::parse(v).ok_or_else(|| format!("field value '{}' is not a number", v))
+            })
+            .collect::<Result<Vec<Number>, String>>()?;
+
+        let result = match self.aggregate {
+            Aggregate::Avg => {
+                let sum: f64 = numbers.iter().map(|n| n.as_f64()).sum();
+                Number::Float(sum / numbers.len() as f64)
+            }
+            Aggregate::Min => extreme(&numbers, std::cmp::Ordering::Less),
+            Aggregate::Max => extreme(&numbers, std::cmp::Ordering::Greater),
+            Aggregate::Delta => numbers[numbers.len() - 1].minus(numbers[0]),
+            Aggregate::Rate => {
+                let elapsed = last.0.saturating_duration_since(first.0).as_secs_f64();
+                if elapsed == 0.0 {
+                    Number::Float(0.0)
+                } else {
+                    let delta = numbers[numbers.len() - 1].minus(numbers[0]);
+                    Number::Float(delta.as_f64() / elapsed)
+                }
+            }
+            Aggregate::Count(_) => unreachable!(),
+        };
+        Ok(result.to_string())
+    }
+}
+
+fn extreme(numbers: &[Number], wanted: std::cmp::Ordering) -> Number {
+    numbers[1..].iter().fold(numbers[0], |best, n| {
+        if n.compare(best) == Some(wanted) {
+            *n
+        } else {
+            best
+        }
+    })
+}
+
+/// Samples of one field of a topic
+#[derive(Debug)]
+struct Series {
+    path: FieldPath,
+    /// Window length of each scenario reading this series
+    windows: HashMap<String, Duration>,
+    samples: VecDeque<(Instant, String)>,
+}
+
+impl Series {
+    fn retention(&self) -> Duration {
+        self.windows.values().copied().max().unwrap_or_default()
+    }
+
+    fn prune(&mut self, now: Instant) {
+        let retention = self.retention();
+        while let Some((at, _)) = self.samples.front() {
+            if now.saturating_duration_since(*at) > retention || self.samples.len() > MAX_SAMPLES {
+                self.samples.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+/// Recent samples of the fields used by windowed conditions, per topic
+#[derive(Debug, Default)]
+pub struct SignalHistory {
+    topics: HashMap<String, Vec<Series>>,
+}
+
+impl SignalHistory {
+    /// Keep samples of a field for a scenario window
+    ///
+    /// # Arguments
+    ///
+    /// * `scenario_name` - Scenario reading the field
+    /// * `topic` - Topic the field belongs to
+    /// * `path` - Field of the topic
+    /// * `duration` - Window length of the scenario
+    pub fn register(
+        &mut self,
+        scenario_name: &str,
+        topic: &str,
+        path: &FieldPath,
+        duration: Duration,
+    ) {
+        let series = self.topics.entry(topic.to_string()).or_default();
+        let index = match series.iter().position(|s| s.path == *path) {
+            Some(index) => index,
+            None => {
+                series.push(Series {
+                    path: path.clone(),
+                    windows: HashMap::new(),
+                    samples: VecDeque::new(),
+                });
+                series.len() - 1
+            }
+        };
+        series[index]
+            .windows
+            .insert(scenario_name.to_string(), duration);
+    }
+
+    /// Release the windows of a scenario, dropping buffers nobody reads anymore
+    pub fn unregister(&mut self, scenario_name: &str) {
+        for series in self.topics.values_mut() {
+            for s in series.iter_mut() {
+                s.windows.remove(scenario_name);
+            }
+            series.retain(|s| !s.windows.is_empty());
+        }
+        self.topics.retain(|_, series| !series.is_empty());
+    }
+
+    /// Store the registered fields of a received sample
+    pub fn record(&mut self, data: &DdsData, now: Instant) {
+        let Some(series) = self.topics.get_mut(&data.name) else {
+            return;
+        };
+        for s in series.iter_mut() {
+            if let Some(value) = s.path.lookup(data) {
+                s.samples.push_back((now, value));
+            }
+            s.prune(now);
+        }
+    }
+
+    /// Samples of a field received within `duration` before `now`, oldest first
+    pub fn window(
+        &self,
+        topic: &str,
+        path: &FieldPath,
+        duration: Duration,
+        now: Instant,
+    ) -> Vec<(Instant, String)> {
+        self.topics
+            .get(topic)
+            .and_then(|series| series.iter().find(|s| s.path == *path))
+            .map(|s| {
+                s.samples
+                    .iter()
+                    .filter(|(at, _)| now.saturating_duration_since(*at) <= duration)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Number of samples kept for a field
+    pub fn sample_count(&self, topic: &str, path: &FieldPath) -> usize {
+        self.topics
+            .get(topic)
+            .and_then(|series| series.iter().find(|s| s.path == *path))
+            .map_or(0, |s| s.samples.len())
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn spec(aggregate: Aggregate) -> WindowSpec {
+        WindowSpec {
+            duration: ms(60_000),
+            aggregate,
+        }
+    }
+
+    fn samples(t0: Instant, values: &[(u64, &str)]) -> Vec<(Instant, String)> {
+        values
+            .iter()
+            .map(|(at, v)| (t0 + ms(*at), v.to_string()))
+            .collect()
+    }
+
+    fn sample(topic: &str, temperature: &str) -> DdsData {
+        DdsData {
+            name: topic.to_string(),
+            value: format!("{{\"temperature\": {}}}", temperature),
+            fields: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_aggregates() {
+        let t0 = Instant::now();
+        let window = samples(t0, &[(0, "30"), (20_000, "28"), (40_000, "36")]);
+
+        assert_eq!(
+            spec(Aggregate::Avg).compute(&window).unwrap(),
+            "31.333333333333332"
+        );
+        assert_eq!(spec(Aggregate::Min).compute(&window).unwrap(), "28");
+        assert_eq!(spec(Aggregate::Max).compute(&window).unwrap(), "36");
+        assert_eq!(spec(Aggregate::Delta).compute(&window).unwrap(), "6");
+        assert_eq!(spec(Aggregate::Rate).compute(&window).unwrap(), "0.15");
+        assert_eq!(spec(Aggregate::Count(None)).compute(&window).unwrap(), "3");
+        assert_eq!(
+            spec(Aggregate::Count(Some("28".to_string())))
+                .compute(&window)
+                .unwrap(),
+            "1"
+        );
+
+        assert!(spec(Aggregate::Avg).compute(&[]).is_err());
+        assert_eq!(spec(Aggregate::Count(None)).compute(&[]).unwrap(), "0");
+        let text = samples(t0, &[(0, "open")]);
+        assert!(spec(Aggregate::Max).compute(&text).is_err());
+    }
+
+    #[test]
+    fn test_from_window() {
+        let window: Window = serde_yaml::from_str("duration: 10000\naggregate: AVG\n").unwrap();
+        assert_eq!(
+            WindowSpec::from_window(&window).unwrap(),
+            WindowSpec {
+                duration: ms(10_000),
+                aggregate: Aggregate::Avg,
+            }
+        );
+
+        let window: Window =
+            serde_yaml::from_str("duration: 10000\naggregate: count\ncountOf: Open\n").unwrap();
+        assert_eq!(
+            WindowSpec::from_window(&window).unwrap().aggregate,
+            Aggregate::Count(Some("open".to_string()))
+        );
+
+        let window: Window = serde_yaml::from_str("duration: 0\naggregate: avg\n").unwrap();
+        assert!(WindowSpec::from_window(&window).is_err());
+        let window: Window = serde_yaml::from_str("duration: 100\naggregate: median\n").unwrap();
+        assert!(WindowSpec::from_window(&window).is_err());
+    }
+
+    #[test]
+    fn test_history_keeps_largest_window() {
+        let path = FieldPath::parse("temperature").unwrap();
+        let mut history = SignalHistory::default();
+        history.register("short", "battery", &path, ms(1_000));
+        history.register("long", "battery", &path, ms(5_000));
+
+        let t0 = Instant::now();
+        for i in 0..10 {
+            history.record(&sample("battery", &i.to_string()), t0 + ms(1_000 * i));
+        }
+        // Unrelated topics are not stored
+        history.record(&sample("engine", "90"), t0);
+
+        let now = t0 + ms(9_000);
+        assert_eq!(history.sample_count("battery", &path), 6);
+        assert_eq!(history.window("battery", &path, ms(1_000), now).len(), 2);
+        assert!(history.window("engine", &path, ms(1_000), now).is_empty());
+
+        history.unregister("long");
+        history.record(&sample("battery", "10"), t0 + ms(10_000));
+        assert_eq!(history.sample_count("battery", &path), 2);
+
+        history.unregister("short");
+        assert_eq!(history.sample_count("battery", &path), 0);
+    }
+}
@@ -1,23 +1,29 @@
 use core::sync;
 use std::io::Error;
 
+use crate::filter::index::{FilterState, SharedFilterIndex};
+use crate::filter::Filter;
 use crate::manager::ScenarioParameter;
 use crate::vehicle::dds::DdsData;
 
 use common::spec::artifact::{Artifact, Scenario};
 use common::Result;
+use std::time::UNIX_EPOCH;
 use tokio::sync::mpsc::{self, error::SendError};
 use tonic::{Request, Response, Status};
 
 // Import the generated protobuf code from filtergateway.proto
 use common::filtergateway::{
     filter_gateway_connection_server::{FilterGatewayConnection, FilterGatewayConnectionServer},
-    HandleScenarioRequest, HandleScenarioResponse,
+    FilterStatus, HandleScenarioRequest, HandleScenarioResponse, ListFiltersRequest,
+    ListFiltersResponse, ScenarioFilterRequest, ScenarioFilterResponse,
 };
 
 /// FilterGateway gRPC service handler
 pub struct FilterGatewayReceiver {
     tx: mpsc::Sender<ScenarioParameter>,
+    /// Filters of the FilterGatewayManager, paused, resumed and listed in place
+    filters: SharedFilterIndex,
 }
 
 impl FilterGatewayReceiver {
@@ -31,7 +37,24 @@ impl FilterGatewayReceiver {
     ///
     /// A new FilterGatewayReceiver instance
     pub fn new(tx: mpsc::Sender<ScenarioParameter>) -> Self {
-        Self { tx }
+        Self {
+            tx,
+            filters: SharedFilterIndex::default(),
+        }
+    }
+
+    /// Serve the filters of a FilterGatewayManager
+    ///
+    /// # Arguments
+    ///
+    /// * `filters` - Filter index shared with the manager
+    ///
+    /// # Returns
+    ///
+    /// The receiver pausing, resuming and listing these filters
+    pub fn with_filters(mut self, filters: SharedFilterIndex) -> Self {
+        self.filters = filters;
+        self
     }
 
     /// Get the gRPC server for this receiver
@@ -62,6 +85,10 @@ impl FilterGatewayReceiver {
 
         // Parse the scenario YAML string into a Scenario struct
         let scenario = serde_yaml::from_str::<Scenario>(&scenario_yaml_str)?;
+        if action == 0 {
+            // Reject conditions that cannot be evaluated before accepting the scenario
+            Filter::validate(&scenario)?;
+        }
 
         let param = ScenarioParameter {
             action: action,
@@ -78,6 +105,61 @@ impl FilterGatewayReceiver {
 
         Ok(())
     }
+
+    /// Pause or resume the filter of a scenario
+    ///
+    /// A paused filter stays registered with its subscriptions but does not
+    /// evaluate its condition until it is resumed.
+    ///
+    /// # Arguments
+    ///
+    /// * `scenario_name` - Name of the scenario
+    /// * `active` - `false` to pause the filter, `true` to resume it
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - Error if the scenario has no filter
+    pub async fn set_filter_active(&self, scenario_name: &str, active: bool) -> Result<()> {
+        let filter = self
+            .filters
+            .lock()
+            .await
+            .get(scenario_name)
+            .ok_or_else(|| format!("no filter for scenario '{}'", scenario_name))?;
+        let mut filter = filter
+            .lock()
+            .map_err(|_| format!("filter of scenario '{}' is poisoned", scenario_name))?;
+        filter.set_active(active);
+        println!(
+            "Filter for scenario '{}' {}",
+            scenario_name,
+            if active { "resumed" } else { "paused" }
+        );
+        Ok(())
+    }
+
+    /// Current state of every scenario filter
+    pub async fn list_filters(&self) -> Vec<FilterState> {
+        self.filters.lock().await.states()
+    }
+}
+
+impl From<FilterState> for FilterStatus {
+    fn from(state: FilterState) -> Self {
+        let last_sample_time = state
+            .last_sample
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |elapsed| elapsed.as_millis() as i64);
+        FilterStatus {
+            scenario_name: state.scenario_name,
+            topic: state.topic,
+            active: state.active,
+            last_sample_time,
+            last_value: state.last_value.unwrap_or_default(),
+            last_result: state.last_result.unwrap_or_default(),
+            trigger_count: state.trigger_count,
+        }
+    }
 }
 
 #[tonic::async_trait]
@@ -107,6 +189,47 @@ impl FilterGatewayConnection for FilterGatewayReceiver {
             desc: "Successfully handled scenario".to_string(),
         }))
     }
+
+    async fn pause_scenario(
+        &self,
+        request: Request<ScenarioFilterRequest>,
+    ) -> std::result::Result<Response<ScenarioFilterResponse>, Status> {
+        let req = request.into_inner();
+        if let Err(e) = self.set_filter_active(&req.scenario_name, false).await {
+            return Err(Status::not_found(e.to_string()));
+        }
+        Ok(Response::new(ScenarioFilterResponse {
+            status: true,
+            desc: format!("Paused scenario {}", req.scenario_name),
+        }))
+    }
+
+    async fn resume_scenario(
+        &self,
+        request: Request<ScenarioFilterRequest>,
+    ) -> std::result::Result<Response<ScenarioFilterResponse>, Status> {
+        let req = request.into_inner();
+        if let Err(e) = self.set_filter_active(&req.scenario_name, true).await {
+            return Err(Status::not_found(e.to_string()));
+        }
+        Ok(Response::new(ScenarioFilterResponse {
+            status: true,
+            desc: format!("Resumed scenario {}", req.scenario_name),
+        }))
+    }
+
+    async fn list_filters(
+        &self,
+        _request: Request<ListFiltersRequest>,
+    ) -> std::result::Result<Response<ListFiltersResponse>, Status> {
+        let filters = self
+            .list_filters()
+            .await
+            .into_iter()
+            .map(FilterStatus::from)
+            .collect();
+        Ok(Response::new(ListFiltersResponse { filters }))
+    }
 }
 //Unit Test Cases
 #[cfg(test)]
@@ -188,6 +311,93 @@ mod tests {
         assert!(result.is_err());
     }
 
+    // Test case for rejecting a scenario whose condition cannot be compiled
+    #[tokio::test]
+    async fn test_handle_scenario_with_invalid_condition() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let receiver = FilterGatewayReceiver::new(tx);
+
+        let scenario_yaml = r#"
+        apiVersion: v1
+        kind: Scenario
+        metadata:
+          name: helloworld
+        spec:
+          condition:
+            express: gt
+            value: fast
+            operands:
+              type: DDS
+              name: speed
+              value: rt/pullpiri/speed
+          action: update
+          target: helloworld
+        "#;
+
+        let result = receiver.handle_scenario(scenario_yaml.to_string(), 0).await;
+        assert!(result.is_err());
+        assert!(rx.try_recv().is_err());
+    }
+
+    // Test case for rejecting a scenario shipping IDL that cannot be parsed
+    #[tokio::test]
+    async fn test_handle_scenario_with_invalid_idl() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let receiver = FilterGatewayReceiver::new(tx);
+
+        let scenario_yaml = r#"
+        apiVersion: v1
+        kind: Scenario
+        metadata:
+          name: horn
+        spec:
+          condition:
+            express: eq
+            value: "true"
+            operands:
+              type: DDS
+              name: on
+              value: HornState
+              idl: "struct HornState { boolean on; "
+          action: update
+          target: horn
+        "#;
+
+        let result = receiver.handle_scenario(scenario_yaml.to_string(), 0).await;
+        assert!(result.is_err());
+        assert!(rx.try_recv().is_err());
+    }
+
+    // Test case for rejecting a scenario requesting an unknown reader QoS
+    #[tokio::test]
+    async fn test_handle_scenario_with_invalid_qos() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let receiver = FilterGatewayReceiver::new(tx);
+
+        let scenario_yaml = r#"
+        apiVersion: v1
+        kind: Scenario
+        metadata:
+          name: parking
+        spec:
+          condition:
+            express: eq
+            value: "P"
+            operands:
+              type: DDS
+              name: gear
+              value: rt/piccolo/gear_state
+              qos:
+                durability: forever
+          action: update
+          target: parking
+        "#;
+
+        let result = receiver.handle_scenario(scenario_yaml.to_string(), 0).await;
+        assert!(result.is_err());
+        assert!(rx.try_recv().is_err());
+    }
+
     // Test case for handling empty YAML input
     #[tokio::test]
     async fn test_handle_scenario_with_empty_yaml() {
@@ -252,4 +462,49 @@ mod tests {
             .await;
         assert!(result.is_ok());
     }
+
+    // Test case for pausing, resuming and listing the shared filters
+    #[tokio::test]
+    async fn test_pause_resume_and_list_filters() {
+        use crate::filter::index::SharedFilterIndex;
+        use crate::filter::Filter;
+        use crate::grpc::sender::actioncontroller::FilterGatewaySender;
+        use common::spec::artifact::Scenario;
+
+        let scenario_yaml = r#"
+        apiVersion: v1
+        kind: Scenario
+        metadata:
+          name: parking
+        spec:
+          condition:
+            express: eq
+            value: "P"
+            operands:
+              type: DDS
+              name: gear
+              value: rt/piccolo/gear_state
+          action: update
+          target: parking
+        "#;
+        let scenario: Scenario = serde_yaml::from_str(scenario_yaml).unwrap();
+        let filter =
+            Filter::new("parking".into(), scenario, true, FilterGatewaySender::new()).unwrap();
+        let filters = SharedFilterIndex::default();
+        filters.lock().await.insert(filter);
+
+        let (tx, _rx) = mpsc::channel(1);
+        let receiver = FilterGatewayReceiver::new(tx).with_filters(filters);
+
+        assert!(receiver.set_filter_active("parking", false).await.is_ok());
+        let states = receiver.list_filters().await;
+        assert_eq!(states.len(), 1);
+        assert_eq!(states[0].scenario_name, "parking");
+        assert_eq!(states[0].topic, "rt/piccolo/gear_state");
+        assert!(!states[0].active);
+
+        assert!(receiver.set_filter_active("parking", true).await.is_ok());
+        assert!(receiver.list_filters().await[0].active);
+        assert!(receiver.set_filter_active("unknown", false).await.is_err());
+    }
 }
@@ -3,13 +3,70 @@ use common::Result;
 // Import the generated protobuf code from actioncontroller.proto
 
 use common::actioncontroller::connect_server;
+use common::transport::Component;
 
 // Import the generated protobuf code from actioncontroller.proto
 use common::actioncontroller::action_controller_connection_client::ActionControllerConnectionClient;
+use common::actioncontroller::TriggerActionRequest;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{mpsc, oneshot, Semaphore};
+use tonic::transport::Channel;
+use tonic::Code;
+
+/// Triggers waiting for delivery, further triggers are dead letters
+const QUEUE_CAPACITY: usize = 256;
+/// Triggers delivered at the same time
+const MAX_CONCURRENT_DELIVERIES: usize = 16;
+/// Attempts to deliver a trigger before it becomes a dead letter
+const MAX_ATTEMPTS: u32 = 5;
+/// Delay before the first retry, doubled after each failed attempt
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+/// Time given to ActionController to run the action of one trigger
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Sequence number of the idempotency keys of this process
+static TRIGGER_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// Trigger of a scenario action waiting in the outbound queue
+struct Trigger {
+    scenario_name: String,
+    /// Sent with every attempt so that ActionController runs the action once
+    idempotency_key: String,
+    reply: oneshot::Sender<std::result::Result<(), String>>,
+}
+
+/// Server address and queue of a delivery task that is not started yet
+type PendingDelivery = (String, mpsc::Receiver<Trigger>);
+
+/// Connection shared by the deliveries, `None` until connected or after an
+/// error that requires reconnecting
+type SharedClient = Arc<std::sync::Mutex<Option<ActionControllerConnectionClient<Channel>>>>;
+
+/// Why a delivery attempt failed
+enum Failure {
+    /// ActionController may accept the trigger later
+    Transient(String),
+    /// ActionController rejected the trigger
+    Permanent(String),
+}
 
 /// Sender for making gRPC requests to ActionController
+///
+/// Triggers are queued and delivered over a persistent connection, up to
+/// `MAX_CONCURRENT_DELIVERIES` at a time so that a slow action does not hold
+/// back the triggers of other scenarios. A trigger is retried with backoff
+/// until ActionController answers, and logged as a dead letter if it cannot
+/// be delivered.
 #[derive(Clone)]
-pub struct FilterGatewaySender {}
+pub struct FilterGatewaySender {
+    queue: mpsc::Sender<Trigger>,
+    /// Delivery task state, taken when the first trigger is sent
+    pending: Arc<std::sync::Mutex<Option<PendingDelivery>>>,
+}
 
 impl FilterGatewaySender {
     /// Create a new FilterGatewaySender
@@ -18,11 +75,23 @@ impl FilterGatewaySender {
     ///
     /// A new FilterGatewaySender instance
     pub fn new() -> Self {
-        Self {}
+        Self::with_address(connect_server())
+    }
+
+    /// Create a sender delivering the triggers to the given server address
+    pub(crate) fn with_address(address: String) -> Self {
+        let (queue, rx) = mpsc::channel(QUEUE_CAPACITY);
+        Self {
+            queue,
+            pending: Arc::new(std::sync::Mutex::new(Some((address, rx)))),
+        }
     }
 
     /// Trigger an action for a scenario
     ///
+    /// Waits until ActionController has handled the trigger, including the
+    /// retries needed while it is unreachable.
+    ///
     /// # Arguments
     ///
     /// * `scenario_name` - Name of the scenario
@@ -34,19 +103,182 @@ impl FilterGatewaySender {
         if scenario_name.trim().is_empty() {
             return Err("Invalid scenario name: cannot be empty".into());
         }
-        use common::actioncontroller::TriggerActionRequest;
-        let mut client = ActionControllerConnectionClient::connect(connect_server())
-            .await
-            .unwrap();
+        self.start_delivery();
 
-        let request = TriggerActionRequest { scenario_name };
+        let (reply, result) = oneshot::channel();
+        let trigger = Trigger {
+            idempotency_key: idempotency_key(&scenario_name),
+            scenario_name,
+            reply,
+        };
+        if let Err(e) = self.queue.try_send(trigger) {
+            let (reason, trigger) = match e {
+                TrySendError::Full(trigger) => ("outbound queue is full", trigger),
+                TrySendError::Closed(trigger) => ("delivery task stopped", trigger),
+            };
+            dead_letter(&trigger, 0, reason);
+            return Err(format!("Failed to trigger action: {}", reason).into());
+        }
 
-        client.trigger_action(request).await.map_err(|e| {
-            log::error!("Failed to trigger action: {:?}", e);
-            anyhow::anyhow!("Failed to trigger action: {:?}", e)
-        })?;
+        match result.await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) => Err(format!("Failed to trigger action: {}", e).into()),
+            Err(_) => Err("Failed to trigger action: delivery task stopped".into()),
+        }
+    }
 
-        Ok(())
+    /// Spawn the delivery task if it is not running yet
+    fn start_delivery(&self) {
+        let pending = match self.pending.lock() {
+            Ok(mut pending) => pending.take(),
+            Err(_) => None,
+        };
+        if let Some((address, queue)) = pending {
+            tokio::spawn(deliver(address, queue));
+        }
+    }
+}
+
+/// Key unique to one trigger of a scenario
+fn idempotency_key(scenario_name: &str) -> String {
+    let started = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    let sequence = TRIGGER_SEQUENCE.fetch_add(1, Ordering::Relaxed);
+    format!(
+        "{}-{}-{:x}-{}",
+        scenario_name,
+        std::process::id(),
+        started,
+        sequence
+    )
+}
+
+/// Log a trigger that will never reach ActionController
+fn dead_letter(trigger: &Trigger, attempts: u32, reason: &str) {
+    eprintln!(
+        "Dead letter: trigger of scenario '{}' ({}) not delivered after {} attempts: {}",
+        trigger.scenario_name, trigger.idempotency_key, attempts, reason
+    );
+}
+
+/// Deliver the queued triggers until every sender is dropped
+///
+/// Triggers are taken from the queue in order, each one as soon as a
+/// delivery slot is free.
+async fn deliver(address: String, mut queue: mpsc::Receiver<Trigger>) {
+    let address: Arc<str> = address.into();
+    let client = SharedClient::default();
+    let slots = Arc::new(Semaphore::new(MAX_CONCURRENT_DELIVERIES));
+    loop {
+        let Ok(slot) = Arc::clone(&slots).acquire_owned().await else {
+            break;
+        };
+        let Some(trigger) = queue.recv().await else {
+            break;
+        };
+        let address = Arc::clone(&address);
+        let client = Arc::clone(&client);
+        tokio::spawn(async move {
+            let result = deliver_with_retry(&address, &client, &trigger).await;
+            drop(slot);
+            if let Err((attempts, reason)) = &result {
+                dead_letter(&trigger, *attempts, reason);
+            }
+            let _ = trigger.reply.send(result.map_err(|(_, reason)| reason));
+        });
+    }
+}
+
+/// Send a trigger until ActionController answers or the attempts run out
+///
+/// # Returns
+///
+/// * `Result<(), (u32, String)>` - The number of attempts and the last error
+///   if the trigger was not delivered
+async fn deliver_with_retry(
+    address: &str,
+    client: &SharedClient,
+    trigger: &Trigger,
+) -> std::result::Result<(), (u32, String)> {
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        match send(address, client, trigger).await {
+            Ok(()) => return Ok(()),
+            Err(Failure::Permanent(e)) => return Err((attempt, e)),
+            Err(Failure::Transient(e)) if attempt >= MAX_ATTEMPTS => return Err((attempt, e)),
+            Err(Failure::Transient(e)) => {
+                println!(
+                    "Trigger of scenario '{}' failed, retrying in {:?}: {}",
+                    trigger.scenario_name, backoff, e
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Forget the shared connection so that the next attempt reconnects
+fn disconnect(client: &SharedClient) {
+    if let Ok(mut client) = client.lock() {
+        *client = None;
+    }
+}
+
+/// One delivery attempt, connecting first if needed
+async fn send(
+    address: &str,
+    client: &SharedClient,
+    trigger: &Trigger,
+) -> std::result::Result<(), Failure> {
+    let cached = client.lock().ok().and_then(|connected| connected.clone());
+    let mut connected = match cached {
+        Some(connected) => connected,
+        None => {
+            let channel =
+                common::transport::connect(Component::ActionController, address.to_string())
+                    .await
+                    .map_err(|e| {
+                        Failure::Transient(format!("Failed to connect to ActionController: {}", e))
+                    })?;
+            let connected = ActionControllerConnectionClient::new(channel);
+            if let Ok(mut client) = client.lock() {
+                *client = Some(connected.clone());
+            }
+            connected
+        }
+    };
+
+    let request = TriggerActionRequest {
+        scenario_name: trigger.scenario_name.clone(),
+        idempotency_key: trigger.idempotency_key.clone(),
+    };
+    let status =
+        match tokio::time::timeout(REQUEST_TIMEOUT, connected.trigger_action(request)).await {
+            Ok(Ok(_)) => return Ok(()),
+            Ok(Err(status)) => status,
+            Err(_) => {
+                disconnect(client);
+                return Err(Failure::Transient("request timed out".to_string()));
+            }
+        };
+
+    match status.code() {
+        Code::Unavailable => {
+            // Reconnect on the next attempt, ActionController may have restarted
+            disconnect(client);
+            Err(Failure::Transient(status.message().to_string()))
+        }
+        Code::Aborted | Code::DeadlineExceeded | Code::ResourceExhausted => {
+            Err(Failure::Transient(status.message().to_string()))
+        }
+        _ => {
+            log::error!("Failed to trigger action: {:?}", status);
+            Err(Failure::Permanent(format!("{:?}", status)))
+        }
     }
 }
 
@@ -62,6 +294,7 @@ mod tests {
         },
         ReconcileRequest, ReconcileResponse, TriggerActionRequest, TriggerActionResponse,
     };
+    use common::actioncontroller::{DrainNodeRequest, DrainNodeResponse};
     use std::net::SocketAddr;
     use std::panic::{catch_unwind, AssertUnwindSafe};
     use tokio::sync::oneshot;
@@ -107,6 +340,13 @@ mod tests {
                 acknowledged: true, // or false, depending on test needs
             }))
         }
+
+        async fn drain_node(
+            &self,
+            _request: Request<DrainNodeRequest>,
+        ) -> std::result::Result<Response<DrainNodeResponse>, Status> {
+            Ok(Response::new(DrainNodeResponse::default()))
+        }
     }
 
     async fn spawn_mock_server(
@@ -186,4 +426,140 @@ mod tests {
         // Assert that the connection attempt fails
         assert!(result.is_err());
     }
+
+    /// Mock recording the idempotency keys and rejecting unknown scenarios
+    #[derive(Default, Clone)]
+    struct RecordingActionController {
+        keys: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    #[tonic::async_trait]
+    impl ActionControllerConnection for RecordingActionController {
+        async fn trigger_action(
+            &self,
+            request: Request<TriggerActionRequest>,
+        ) -> std::result::Result<Response<TriggerActionResponse>, Status> {
+            let request = request.into_inner();
+            self.keys.lock().unwrap().push(request.idempotency_key);
+            if request.scenario_name == "unknown" {
+                return Err(Status::not_found("Scenario 'unknown' not found"));
+            }
+            if request.scenario_name == "slow" {
+                // An action that does not complete within the test
+                std::future::pending::<()>().await;
+            }
+            Ok(Response::new(TriggerActionResponse::default()))
+        }
+
+        async fn reconcile(
+            &self,
+            _request: Request<ReconcileRequest>,
+        ) -> std::result::Result<Response<ReconcileResponse>, Status> {
+            Ok(Response::new(ReconcileResponse::default()))
+        }
+
+        async fn complete_network_setting(
+            &self,
+            _request: Request<CompleteNetworkSettingRequest>,
+        ) -> std::result::Result<Response<CompleteNetworkSettingResponse>, Status> {
+            Ok(Response::new(CompleteNetworkSettingResponse::default()))
+        }
+
+        async fn drain_node(
+            &self,
+            _request: Request<DrainNodeRequest>,
+        ) -> std::result::Result<Response<DrainNodeResponse>, Status> {
+            Ok(Response::new(DrainNodeResponse::default()))
+        }
+    }
+
+    /// Address of a port that is free right now
+    fn free_address() -> SocketAddr {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap()
+    }
+
+    #[test]
+    fn test_idempotency_keys_are_unique() {
+        assert_ne!(idempotency_key("parking"), idempotency_key("parking"));
+        assert!(idempotency_key("parking").starts_with("parking-"));
+    }
+
+    /// A trigger sent while ActionController is down is delivered once it starts
+    #[tokio::test]
+    async fn test_trigger_action_retries_until_server_starts() {
+        let addr = free_address();
+        let mut sender = FilterGatewaySender::with_address(format!("http://{}", addr));
+        let pending = tokio::spawn(async move {
+            sender
+                .trigger_action("parking".to_string())
+                .await
+                .map_err(|e| e.to_string())
+        });
+
+        sleep(Duration::from_millis(50)).await;
+        let mock = RecordingActionController::default();
+        let keys = Arc::clone(&mock.keys);
+        tokio::spawn(
+            Server::builder()
+                .add_service(ActionControllerConnectionServer::new(mock))
+                .serve(addr),
+        );
+
+        assert_eq!(pending.await.unwrap(), Ok(()));
+        let keys = keys.lock().unwrap();
+        assert_eq!(keys.len(), 1);
+        assert!(keys[0].starts_with("parking-"));
+    }
+
+    /// A trigger rejected by ActionController is not retried
+    #[tokio::test]
+    async fn test_trigger_action_rejected_is_not_retried() {
+        let addr = free_address();
+        let mock = RecordingActionController::default();
+        let keys = Arc::clone(&mock.keys);
+        tokio::spawn(
+            Server::builder()
+                .add_service(ActionControllerConnectionServer::new(mock))
+                .serve(addr),
+        );
+        sleep(Duration::from_millis(100)).await;
+
+        let mut sender = FilterGatewaySender::with_address(format!("http://{}", addr));
+        assert!(sender.trigger_action("unknown".to_string()).await.is_err());
+        assert!(sender.trigger_action("parking".to_string()).await.is_ok());
+
+        let keys = keys.lock().unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_ne!(keys[0], keys[1]);
+    }
+
+    /// A slow action does not hold back the triggers of other scenarios
+    #[tokio::test]
+    async fn test_slow_trigger_does_not_block_others() {
+        let addr = free_address();
+        tokio::spawn(
+            Server::builder()
+                .add_service(ActionControllerConnectionServer::new(
+                    RecordingActionController::default(),
+                ))
+                .serve(addr),
+        );
+        sleep(Duration::from_millis(100)).await;
+
+        let sender = FilterGatewaySender::with_address(format!("http://{}", addr));
+        let mut slow = sender.clone();
+        tokio::spawn(async move {
+            let _ = slow.trigger_action("slow".to_string()).await;
+        });
+        sleep(Duration::from_millis(50)).await;
+
+        let mut parking = sender.clone();
+        let result = tokio::time::timeout(
+            Duration::from_secs(2),
+            parking.trigger_action("parking".to_string()),
+        )
+        .await;
+        assert!(matches!(result, Ok(Ok(()))));
+    }
 }
@@ -15,10 +15,16 @@
 
 use common::statemanager::{
     connect_server, state_manager_connection_client::StateManagerConnectionClient, ResourceType,
-    StateChange, StateChangeResponse,
+    ScenarioState, StateChange, StateChangeResponse,
 };
+use common::transport::Component;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
 use tonic::{Request, Status};
 
+/// Transitions waiting to be sent, further transitions are dropped
+const REPORT_QUEUE_CAPACITY: usize = 256;
+
 /// StateManager gRPC client for FilterGateway component.
 ///
 /// This client manages the gRPC connection to the StateManager service and provides
@@ -97,9 +103,9 @@ impl StateManagerSender {
     /// * `Status::unknown` - Connection establishment failed (network, service unavailable, etc.)
     async fn ensure_connected(&mut self) -> Result<(), Status> {
         if self.client.is_none() {
-            match StateManagerConnectionClient::connect(connect_server()).await {
-                Ok(client) => {
-                    self.client = Some(client);
+            match common::transport::connect(Component::StateManager, connect_server()).await {
+                Ok(channel) => {
+                    self.client = Some(StateManagerConnectionClient::new(channel));
                     Ok(())
                 }
                 Err(e) => Err(Status::unknown(format!(
@@ -327,6 +333,90 @@ impl StateManagerSender {
     }
 }
 
+/// Reporter of the condition progress of scenario filters
+///
+/// Transitions are queued and sent in order by one task, so that the filters
+/// never wait for the StateManager. A transition the StateManager cannot
+/// receive, or that does not fit in the queue while the StateManager is
+/// slow or unreachable, is logged and dropped.
+#[derive(Clone)]
+pub struct ScenarioStateReporter {
+    tx: mpsc::Sender<StateChange>,
+}
+
+impl ScenarioStateReporter {
+    /// Create a reporter and spawn its sending task
+    ///
+    /// # Returns
+    /// * `Self` - Reporter sending to the StateManager until it is dropped
+    pub fn new() -> Self {
+        let (tx, mut rx) = mpsc::channel::<StateChange>(REPORT_QUEUE_CAPACITY);
+        tokio::spawn(async move {
+            let mut sender = StateManagerSender::new();
+            while let Some(state_change) = rx.recv().await {
+                let name = state_change.resource_name.clone();
+                if let Err(e) = sender.send_state_change(state_change).await {
+                    println!(
+                        "Warning: Failed to report state of scenario {}: {}",
+                        name,
+                        e.message()
+                    );
+                }
+            }
+        });
+        Self { tx }
+    }
+
+    /// Queue a scenario state transition
+    ///
+    /// # Arguments
+    /// * `scenario_name` - Name of the scenario
+    /// * `current` - State last reported for the scenario
+    /// * `target` - New state of the scenario
+    pub fn report(&self, scenario_name: &str, current: ScenarioState, target: ScenarioState) {
+        let state_change = scenario_state_change(scenario_name, current, target);
+        if let Err(TrySendError::Full(state_change)) = self.tx.try_send(state_change) {
+            println!(
+                "Warning: Dropped state report of scenario {} ({} -> {}), queue is full",
+                state_change.resource_name, state_change.current_state, state_change.target_state
+            );
+        }
+    }
+}
+
+impl Default for ScenarioStateReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// StateChange of a scenario, with states named as in the StateManager tables
+fn scenario_state_change(
+    scenario_name: &str,
+    current: ScenarioState,
+    target: ScenarioState,
+) -> StateChange {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as i64);
+    let state_name = |state: ScenarioState| {
+        state
+            .as_str_name()
+            .trim_start_matches("SCENARIO_STATE_")
+            .to_ascii_lowercase()
+    };
+
+    StateChange {
+        resource_type: ResourceType::Scenario as i32,
+        resource_name: scenario_name.to_string(),
+        current_state: state_name(current),
+        target_state: state_name(target),
+        transition_id: format!("scenario-{}-{}", scenario_name, timestamp),
+        timestamp_ns: timestamp,
+        source: "filtergateway".to_string(),
+    }
+}
+
 // ========================================
 // UNIT TESTS
 // ========================================
@@ -338,6 +428,34 @@ mod tests {
     use common::statemanager::{ResourceType, StateChange};
     use std::time::Duration;
 
+    #[test]
+    fn test_scenario_state_change() {
+        let state_change =
+            scenario_state_change("parking", ScenarioState::Waiting, ScenarioState::Allowed);
+        assert_eq!(state_change.resource_type, ResourceType::Scenario as i32);
+        assert_eq!(state_change.resource_name, "parking");
+        assert_eq!(state_change.current_state, "waiting");
+        assert_eq!(state_change.target_state, "allowed");
+        assert!(state_change.transition_id.starts_with("scenario-parking-"));
+        assert_eq!(state_change.source, "filtergateway");
+    }
+
+    #[test]
+    fn test_reporter_drops_reports_when_full() {
+        // No task receives, as when the StateManager is unreachable
+        let (tx, mut rx) = mpsc::channel(REPORT_QUEUE_CAPACITY);
+        let reporter = ScenarioStateReporter { tx };
+        for _ in 0..REPORT_QUEUE_CAPACITY + 10 {
+            reporter.report("parking", ScenarioState::Waiting, ScenarioState::Allowed);
+        }
+
+        let mut queued = 0;
+        while rx.try_recv().is_ok() {
+            queued += 1;
+        }
+        assert_eq!(queued, REPORT_QUEUE_CAPACITY);
+    }
+
     /// Tests successful state change message transmission to StateManager.
     ///
     /// This test verifies the complete end-to-end communication flow between
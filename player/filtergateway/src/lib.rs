@@ -6,6 +6,7 @@ pub mod vehicle;
 // Re-export what you need in tests:
 pub use common::spec::artifact::Scenario;
 pub use common::Result;
+pub use filter::index::SharedFilterIndex;
 pub use filter::Filter;
 pub use grpc::receiver::FilterGatewayReceiver;
 pub use grpc::sender::actioncontroller::FilterGatewaySender;
@@ -15,7 +16,22 @@ pub use vehicle::dds::listener;
 pub use vehicle::dds::DdsData;
 pub use vehicle::dds::DdsTopicListener;
 pub async fn launch_manager(rx_grpc: Receiver<ScenarioParameter>) {
-    let manager = manager::FilterGatewayManager::new(rx_grpc).await;
+    launch_manager_with_filters(rx_grpc, SharedFilterIndex::default()).await;
+}
+
+/// Run the FilterGatewayManager with filters shared with the gRPC receiver
+///
+/// # Arguments
+///
+/// * `rx_grpc` - Receiver for scenario requests
+/// * `filters` - Filter index also given to `initialize_with_filters`
+pub async fn launch_manager_with_filters(
+    rx_grpc: Receiver<ScenarioParameter>,
+    filters: SharedFilterIndex,
+) {
+    let manager = manager::FilterGatewayManager::new(rx_grpc)
+        .await
+        .with_filters(filters);
 
     match manager.initialize().await {
         Ok(_) => {
@@ -39,22 +55,45 @@ pub async fn launch_manager(rx_grpc: Receiver<ScenarioParameter>) {
 /// # Returns
 ///
 pub async fn initialize(tx_grpc: Sender<manager::ScenarioParameter>) {
+    initialize_with_filters(tx_grpc, SharedFilterIndex::default()).await;
+}
+
+/// Serve the FilterGateway gRPC service for the filters of a manager
+///
+/// Pausing, resuming and listing filters only reach the manager started by
+/// `launch_manager_with_filters` with the same index.
+///
+/// # Arguments
+///
+/// * `tx_grpc` - Sender for scenario requests
+/// * `filters` - Filter index shared with the manager
+pub async fn initialize_with_filters(
+    tx_grpc: Sender<manager::ScenarioParameter>,
+    filters: SharedFilterIndex,
+) {
     // Set up logging
 
     // let mut manager = manager::FilterGatewayManager::new(rx_grpc, tx_dds, rx_dds);
     // manager.run().await;
 
     use common::filtergateway::filter_gateway_connection_server::FilterGatewayConnectionServer;
-    use tonic::transport::Server;
+    use common::transport::Component;
 
-    let server = crate::grpc::receiver::FilterGatewayReceiver::new(tx_grpc);
+    let server = crate::grpc::receiver::FilterGatewayReceiver::new(tx_grpc).with_filters(filters);
     let addr = common::filtergateway::open_server()
         .parse()
         .expect("gateway address parsing error");
 
     println!("Piccolod gateway listening on {}", addr);
 
-    let _ = Server::builder()
+    let mut builder = match common::transport::server(Component::FilterGateway) {
+        Ok(builder) => builder,
+        Err(e) => {
+            eprintln!("Failed to configure FilterGateway gRPC server: {}", e);
+            return;
+        }
+    };
+    let _ = builder
         .add_service(FilterGatewayConnectionServer::new(server))
         .serve(addr)
         .await;
@@ -15,20 +15,26 @@ mod vehicle;
 // Note: The `ScenarioParameter` type is re-exported from the manager module
 // via `lib.rs` to ensure a single source of truth and prevent type mismatches.
 use filtergateway::ScenarioParameter;
+#[cfg(test)]
 use filtergateway::{initialize, launch_manager};
+use filtergateway::{initialize_with_filters, launch_manager_with_filters, SharedFilterIndex};
 use tokio::sync::mpsc::{channel, Receiver, Sender};
 
 #[cfg(not(tarpaulin_include))]
 #[tokio::main]
 async fn main() {
     // Initialize tracing subscriber for logging
+    common::transport::set_local_component(common::transport::Component::FilterGateway);
 
     let (tx_grpc, rx_grpc): (Sender<ScenarioParameter>, Receiver<ScenarioParameter>) = channel(100);
+    // Filters paused, resumed and listed over gRPC are the manager's ones
+    let filters = SharedFilterIndex::default();
+
     // Launch the manager thread
-    let mgr = launch_manager(rx_grpc);
+    let mgr = launch_manager_with_filters(rx_grpc, filters.clone());
 
     // Initialize the application
-    let grpc = initialize(tx_grpc);
+    let grpc = initialize_with_filters(tx_grpc, filters);
 
     tokio::join!(mgr, grpc);
 }
@@ -1,11 +1,18 @@
+use crate::filter::index::{FilterIndex, SharedFilterIndex};
+use crate::filter::window::SignalHistory;
 use crate::filter::Filter;
 use crate::grpc::sender::actioncontroller::FilterGatewaySender;
+use crate::grpc::sender::statemanager::ScenarioStateReporter;
 use crate::vehicle::dds::DdsData;
+use crate::vehicle::recording::{self, Recorder};
+use crate::vehicle::source::SourceKind;
 use crate::vehicle::VehicleManager;
 use common::spec::artifact::Scenario;
+use common::statemanager::ScenarioState;
 use common::{spec::artifact::Artifact, Result};
 // use dust_dds::infrastructure::wait_set::Condition;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, Mutex};
 
 /// Manager for FilterGateway
@@ -23,17 +30,26 @@ pub struct ScenarioParameter {
     pub scenario: Scenario,
 }
 
+/// Interval between two reports of the filter evaluation latency
+const METRICS_INTERVAL: Duration = Duration::from_secs(60);
+
 pub struct FilterGatewayManager {
     /// Receiver for scenario information from gRPC
     pub rx_grpc: Arc<Mutex<mpsc::Receiver<ScenarioParameter>>>,
     /// Receiver for DDS data
     pub rx_dds: Arc<Mutex<mpsc::Receiver<DdsData>>>,
-    /// Active filters for scenarios
-    pub filters: Arc<Mutex<Vec<Filter>>>,
+    /// Active filters for scenarios, indexed by topic
+    pub filters: SharedFilterIndex,
     /// gRPC sender for action controller
     pub sender: Arc<Mutex<FilterGatewaySender>>,
     /// Vehicle manager for handling vehicle data
     pub vehicle_manager: Arc<Mutex<VehicleManager>>,
+    /// Recent samples of the fields used by windowed conditions
+    pub history: Arc<std::sync::Mutex<SignalHistory>>,
+    /// Recording of the samples passed to the filters, if enabled
+    pub recorder: Option<Arc<std::sync::Mutex<Recorder>>>,
+    /// Reporter of the scenario condition progress to the StateManager
+    pub state_reporter: ScenarioStateReporter,
 }
 
 impl FilterGatewayManager {
@@ -57,14 +73,52 @@ impl FilterGatewayManager {
             // Continue (already using default values in VehicleManager::init())
         }
 
+        let recording = vehicle_manager.get_recording();
+        if recording.record.is_some() && recording.replay.is_some() {
+            println!("Warning: Not recording vehicle samples while replaying a recording");
+        }
+        let recorder = recording
+            .record_path()
+            .and_then(|path| match Recorder::create(path) {
+                Ok(recorder) => {
+                    println!("Recording vehicle samples to {:?}", path);
+                    Some(Arc::new(std::sync::Mutex::new(recorder)))
+                }
+                Err(e) => {
+                    println!(
+                        "Warning: Cannot record vehicle samples to {:?}: {}",
+                        path, e
+                    );
+                    None
+                }
+            });
+
         Self {
             rx_grpc: Arc::new(Mutex::new(rx_grpc)),
             rx_dds: Arc::new(Mutex::new(rx_dds)),
-            filters: Arc::new(Mutex::new(Vec::new())),
+            filters: Arc::new(Mutex::new(FilterIndex::default())),
             sender: Arc::new(Mutex::new(FilterGatewaySender::new())),
             vehicle_manager: Arc::new(Mutex::new(vehicle_manager)),
+            history: Arc::new(std::sync::Mutex::new(SignalHistory::default())),
+            recorder,
+            state_reporter: ScenarioStateReporter::new(),
         }
     }
+
+    /// Keep the scenario filters in an index shared with the gRPC receiver
+    ///
+    /// # Arguments
+    ///
+    /// * `filters` - Filter index also given to `FilterGatewayReceiver::with_filters`
+    ///
+    /// # Returns
+    ///
+    /// The manager using the shared index
+    pub fn with_filters(mut self, filters: SharedFilterIndex) -> Self {
+        self.filters = filters;
+        self
+    }
+
     /// Function to initialize the FilterGatewayManager
     ///
     ///
@@ -84,27 +138,82 @@ impl FilterGatewayManager {
         for scenario in etcd_scenario {
             let scenario: Scenario = serde_yaml::from_str(&scenario)?;
             println!("Scenario: {:?}", scenario);
-            let topic_name = scenario
-                .get_conditions()
-                .as_ref()
-                .map(|cond| cond.get_operand_value())
-                .unwrap_or_default();
-            let data_type_name = scenario
-                .get_conditions()
-                .as_ref()
-                .map(|cond| cond.get_operand_value())
-                .unwrap_or_default();
-            let mut vehicle_manager = self.vehicle_manager.lock().await;
+            self.subscribe_and_launch(scenario).await;
+        }
+
+        Ok(())
+    }
+
+    /// Function to subscribe to the vehicle data of a scenario and launch its filter
+    ///
+    /// Errors are logged, and the subscription is dropped again when the
+    /// filter cannot be launched.
+    ///
+    /// # Arguments
+    ///
+    /// * `scenario` - Scenario to subscribe and launch
+    async fn subscribe_and_launch(&self, scenario: Scenario) {
+        let topic_name = scenario
+            .get_conditions()
+            .as_ref()
+            .map(|cond| cond.get_operand_value())
+            .unwrap_or_default();
+        let data_type_name = topic_name.clone();
+        let mut vehicle_manager = self.vehicle_manager.lock().await;
+        if let Some(source) = scenario
+            .get_conditions()
+            .and_then(|cond| cond.get_operand_idl())
+        {
+            if let Err(e) = vehicle_manager.register_idl(&source) {
+                eprintln!("Error loading scenario IDL: {:?}", e);
+            }
+        }
+        let kind = scenario
+            .get_conditions()
+            .map(|cond| SourceKind::from_operand_type(&cond.get_operand_type()))
+            .unwrap_or(SourceKind::Dds);
+        if let Err(e) = match kind {
+            SourceKind::Dds => {
+                vehicle_manager
+                    .subscribe_scenario(
+                        &scenario.get_name(),
+                        topic_name,
+                        data_type_name,
+                        scenario
+                            .get_conditions()
+                            .and_then(|cond| cond.get_operand_qos()),
+                    )
+                    .await
+            }
+            kind => {
+                vehicle_manager
+                    .subscribe_signal(&scenario.get_name(), kind, topic_name)
+                    .await
+            }
+        } {
+            eprintln!("Error subscribing to vehicle data: {:?}", e);
+        }
+        if let Some(schedule) = scenario
+            .get_conditions()
+            .and_then(|cond| cond.get_schedule())
+        {
             if let Err(e) = vehicle_manager
-                .subscribe_topic(topic_name, data_type_name)
+                .subscribe_signal(&scenario.get_name(), SourceKind::Schedule, schedule)
                 .await
             {
-                eprintln!("Error subscribing to vehicle data: {:?}", e);
+                eprintln!("Error subscribing to schedule: {:?}", e);
             }
-            self.launch_scenario_filter(scenario).await?;
         }
-
-        Ok(())
+        let scenario_name = scenario.get_name();
+        // The error is not Send, so it is logged before awaiting the cleanup
+        let launched = self
+            .launch_scenario_filter(scenario)
+            .await
+            .map_err(|e| eprintln!("Error launching scenario filter: {:?}", e))
+            .is_ok();
+        if !launched {
+            let _ = vehicle_manager.unsubscribe_scenario(&scenario_name).await;
+        }
     }
 
     /// Function to receive subscribed DDS data and pass it to filters
@@ -133,17 +242,38 @@ impl FilterGatewayManager {
                         );
                     }
 
-                    // Forward data to all active filters
-                    let mut filters = self.filters.lock().await;
-                    for filter in filters.iter_mut() {
-                        if filter.is_active() {
-                            // Pass DDS data to filter
-                            if let Err(e) = filter.process_data(&dds_data).await {
-                                println!(
-                                    "Error processing DDS data in filter {}: {:?}",
-                                    filter.scenario_name, e
-                                );
-                            }
+                    let received = Instant::now();
+                    if let Some(Ok(mut recorder)) = self.recorder.as_ref().map(|r| r.lock()) {
+                        if let Err(e) = recorder.record(&dds_data, received) {
+                            println!("Error recording DDS data: {:?}", e);
+                        }
+                    }
+
+                    // Keep the samples read by windowed conditions
+                    if let Ok(mut history) = self.history.lock() {
+                        history.record(&dds_data, received);
+                    }
+
+                    // Forward data to the active filters reading this topic
+                    let targets = self.filters.lock().await.for_topic(&dds_data.name);
+                    for filter in targets {
+                        let Ok(mut filter) = filter.lock() else {
+                            continue;
+                        };
+                        if !filter.is_active() {
+                            continue;
+                        }
+
+                        let start = Instant::now();
+                        let result = filter.evaluate(&dds_data);
+                        filter.record_evaluation(start.elapsed());
+                        match result {
+                            Ok(true) => filter.spawn_action(),
+                            Ok(false) => {}
+                            Err(e) => println!(
+                                "Error processing DDS data in filter {}: {:?}",
+                                filter.scenario_name, e
+                            ),
                         }
                     }
                 }
@@ -179,34 +309,14 @@ impl FilterGatewayManager {
                     match param.action {
                         0 => {
                             // Allow
-                            // Subscribe to vehicle data
-                            let topic_name = param
-                                .scenario
-                                .get_conditions()
-                                .as_ref()
-                                .map(|cond| cond.get_operand_value())
-                                .unwrap_or_default();
-                            let data_type_name = param
-                                .scenario
-                                .get_conditions()
-                                .as_ref()
-                                .map(|cond| cond.get_operand_value())
-                                .unwrap_or_default();
-                            let mut vehicle_manager = self.vehicle_manager.lock().await;
-                            if let Err(e) = vehicle_manager
-                                .subscribe_topic(topic_name, data_type_name)
-                                .await
-                            {
-                                eprintln!("Error subscribing to vehicle data: {:?}", e);
-                            }
-                            self.launch_scenario_filter(param.scenario).await?;
+                            self.subscribe_and_launch(param.scenario).await;
                         }
                         1 => {
                             // Withdraw
                             // Unsubscribe from vehicle data
                             let mut vehicle_manager = self.vehicle_manager.lock().await;
                             if let Err(e) = vehicle_manager
-                                .unsubscribe_topic(param.scenario.get_name().clone())
+                                .unsubscribe_scenario(&param.scenario.get_name())
                                 .await
                             {
                                 eprintln!("Error unsubscribing from vehicle data: {:?}", e);
@@ -228,6 +338,28 @@ impl FilterGatewayManager {
         Ok(())
     }
 
+    /// Periodically print the evaluation latency of every scenario filter
+    async fn report_filter_metrics(&self) {
+        let mut interval = tokio::time::interval(METRICS_INTERVAL);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            for (name, stats) in self.filters.lock().await.stats() {
+                if stats.count == 0 {
+                    continue;
+                }
+                println!(
+                    "Filter metrics: scenario={}, evaluations={}, avg={:?}, max={:?}, last={:?}",
+                    name,
+                    stats.count,
+                    stats.average(),
+                    stats.max,
+                    stats.last
+                );
+            }
+        }
+    }
+
     /// Start the manager processing
     ///
     /// This function processes incoming scenario requests and
@@ -254,8 +386,51 @@ impl FilterGatewayManager {
             }
         });
 
+        // Feed a recording instead of the live signals
+        let replay = {
+            let vehicle_manager = arc_self.vehicle_manager.lock().await;
+            let settings = vehicle_manager.get_recording();
+            settings
+                .replay
+                .clone()
+                .map(|path| (path, settings.speed, vehicle_manager.get_sender()))
+        };
+        if let Some((path, speed, tx)) = replay {
+            tokio::spawn(async move {
+                println!("Replaying {:?} at speed {}", path, speed);
+                match recording::replay(path.clone(), tx, speed).await {
+                    Ok(count) => println!("Replayed {} samples from {:?}", count, path),
+                    Err(e) => eprintln!("Error replaying {:?}: {:?}", path, e),
+                }
+            });
+        }
+
+        let gateway_metrics_manager = Arc::clone(&arc_self);
+        let metrics_reporter = tokio::spawn(async move {
+            gateway_metrics_manager.report_filter_metrics().await;
+        });
+
+        // Flush the recording even when no further sample arrives
+        let recorder = arc_self.recorder.clone();
+        let recording_flusher = tokio::spawn(async move {
+            let Some(recorder) = recorder else {
+                return;
+            };
+            let mut interval = tokio::time::interval(recording::FLUSH_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Ok(mut recorder) = recorder.lock() {
+                    if let Err(e) = recorder.flush() {
+                        println!("Error flushing recording: {:?}", e);
+                    }
+                }
+            }
+        });
+
         // 태스크 완료 대기
         let _ = tokio::try_join!(dds_processor, grpc_processor);
+        metrics_reporter.abort();
+        recording_flusher.abort();
 
         println!("FilterGatewayManager stopped");
 
@@ -273,7 +448,6 @@ impl FilterGatewayManager {
     ///
     /// * `Result<()>` - Success or error result
     pub async fn subscribe_vehicle_data(&self, vehicle_message: DdsData) -> Result<()> {
-        use std::time::Instant;
         let start = Instant::now();
 
         println!("subscribe vehicle data {}", vehicle_message.name);
@@ -324,14 +498,25 @@ impl FilterGatewayManager {
     ///
     /// * `Result<()>` - Success or error result
     pub async fn launch_scenario_filter(&self, scenario: Scenario) -> Result<()> {
-        use std::time::Instant;
         let start = Instant::now();
 
         // Check if the scenario has conditions
         if scenario.get_conditions().is_none() {
             println!("No conditions for scenario: {}", scenario.get_name());
-            let mut sender = self.sender.lock().await;
-            sender.trigger_action(scenario.get_name().clone()).await?;
+            let name = scenario.get_name();
+            self.state_reporter
+                .report(&name, ScenarioState::Idle, ScenarioState::Waiting);
+            // Delivery may take long, do not hold up the gRPC requests
+            let mut sender = self.sender.lock().await.clone();
+            let state_reporter = self.state_reporter.clone();
+            tokio::spawn(async move {
+                match sender.trigger_action(name.clone()).await {
+                    Ok(()) => {
+                        state_reporter.report(&name, ScenarioState::Waiting, ScenarioState::Allowed)
+                    }
+                    Err(e) => println!("Error triggering action: {:?}", e),
+                }
+            });
             let elapsed = start.elapsed();
             println!("launch_scenario_filter: elapsed = {:?}", elapsed);
             return Ok(());
@@ -341,16 +526,13 @@ impl FilterGatewayManager {
             let sender_guard = self.sender.lock().await;
             sender_guard.clone()
         };
-        let filter = Filter::new(scenario.get_name().to_string(), scenario, true, sender);
+        let filter = Filter::new(scenario.get_name().to_string(), scenario, true, sender)?;
 
         // Add the filter to our managed collection
         {
             // Prevent duplicate filters for the same scenario
             let mut filters = self.filters.lock().await;
-            if filters
-                .iter()
-                .any(|f| f.scenario_name == filter.scenario_name)
-            {
+            if filters.contains(&filter.scenario_name) {
                 println!(
                     "Filter for scenario '{}' already exists, skipping.",
                     filter.scenario_name
@@ -359,7 +541,15 @@ impl FilterGatewayManager {
                 println!("launch_scenario_filter: elapsed = {:?}", elapsed);
                 return Ok(());
             }
-            filters.push(filter);
+            let filter = filter
+                .with_shared_history(Arc::clone(&self.history))
+                .with_state_reporter(self.state_reporter.clone());
+            self.state_reporter.report(
+                &filter.scenario_name,
+                ScenarioState::Idle,
+                ScenarioState::Waiting,
+            );
+            filters.insert(filter);
         }
         let elapsed = start.elapsed();
         println!("launch_scenario_filter: elapsed = {:?}", elapsed);
@@ -380,13 +570,18 @@ impl FilterGatewayManager {
     pub async fn remove_scenario_filter(&self, scenario_name: String) -> Result<()> {
         println!("remove filter {}\n", scenario_name);
 
-        let arc_filters = Arc::clone(&self.filters);
-        let mut filters = arc_filters.lock().await;
-        let index = filters
-            .iter()
-            .position(|f| f.scenario_name == scenario_name);
-        if let Some(i) = index {
-            filters.remove(i);
+        let removed = self.filters.lock().await.remove(&scenario_name);
+        // Scenarios without condition have no filter and are allowed at once
+        let current = match removed {
+            Some(filter) => filter
+                .lock()
+                .map_or(ScenarioState::Waiting, |mut f| f.withdraw()),
+            None => ScenarioState::Allowed,
+        };
+        self.state_reporter
+            .report(&scenario_name, current, ScenarioState::Idle);
+        if let Ok(mut history) = self.history.lock() {
+            history.unregister(&scenario_name);
         }
         Ok(())
     }
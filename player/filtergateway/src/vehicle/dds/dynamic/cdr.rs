@@ -0,0 +1,305 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! CDR decoding of samples described by a runtime IDL type
+//!
+//! Handles the representations a DDS writer of a final or appendable type
+//! can send: XCDR1 (`CDR_BE`/`CDR_LE`), plain XCDR2 (`CDR2_BE`/`CDR2_LE`) and
+//! delimited XCDR2 (`D_CDR2_BE`/`D_CDR2_LE`). Mutable types (parameter
+//! lists) are rejected.
+
+use super::idl::TypeDesc;
+use serde_json::{Map, Number, Value};
+
+/// Decode a serialized sample, including its 4-byte encapsulation header
+///
+/// # Arguments
+///
+/// * `desc` - Type of the sample
+/// * `data` - Serialized data as received by the data reader
+///
+/// # Returns
+///
+/// * `Result<Value, String>` - Sample as JSON, structs as objects, sequences
+///   and arrays as lists and enums as the name of the enumerator
+pub fn decode(desc: &TypeDesc, data: &[u8]) -> Result<Value, String> {
+    if data.len() < 4 {
+        return Err("sample is shorter than the encapsulation header".to_string());
+    }
+    let (little_endian, xcdr2, delimited) = match [data[0], data[1]] {
+        [0x00, 0x00] => (false, false, false),
+        [0x00, 0x01] => (true, false, false),
+        [0x00, 0x06] => (false, true, false),
+        [0x00, 0x07] => (true, true, false),
+        [0x00, 0x08] => (false, true, true),
+        [0x00, 0x09] => (true, true, true),
+        id => {
+            return Err(format!(
+                "unsupported data representation {:02x}{:02x}",
+                id[0], id[1]
+            ))
+        }
+    };
+
+    let mut reader = Reader {
+        data: &data[4..],
+        pos: 0,
+        little_endian,
+        // XCDR2 aligns 8-byte values to 4 bytes
+        max_align: if xcdr2 { 4 } else { 8 },
+        xcdr2,
+        delimited,
+    };
+    reader.value(desc)
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    little_endian: bool,
+    max_align: usize,
+    xcdr2: bool,
+    delimited: bool,
+}
+
+impl Reader<'_> {
+    fn align(&mut self, size: usize) {
+        let align = size.min(self.max_align);
+        self.pos = self.pos.div_ceil(align) * align;
+    }
+
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        self.align(N);
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + N)
+            .ok_or_else(|| "sample ends before the end of its type".to_string())?;
+        self.pos += N;
+        let mut out: [u8; N] = bytes.try_into().unwrap_or([0; N]);
+        if self.little_endian != cfg!(target_endian = "little") {
+            out.reverse();
+        }
+        Ok(out)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        self.bytes::<4>().map(u32::from_ne_bytes)
+    }
+
+    /// Element count, checked against the bytes left so that a corrupt
+    /// sample cannot request a huge allocation
+    fn length(&mut self) -> Result<usize, String> {
+        let length = self.u32()? as usize;
+        if length > self.data.len().saturating_sub(self.pos) {
+            return Err(format!("length {} exceeds the sample size", length));
+        }
+        Ok(length)
+    }
+
+    /// XCDR2 prefixes sequences and arrays of non-primitive elements with
+    /// their size in bytes, which is not needed to decode them
+    fn dheader(&mut self, element: &TypeDesc) -> Result<(), String> {
+        if self.xcdr2 && !element.is_primitive() {
+            self.u32()?;
+        }
+        Ok(())
+    }
+
+    fn elements(&mut self, element: &TypeDesc, count: usize) -> Result<Value, String> {
+        (0..count)
+            .map(|_| self.value(element))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array)
+    }
+
+    fn value(&mut self, desc: &TypeDesc) -> Result<Value, String> {
+        let value = match desc {
+            TypeDesc::Bool => Value::Bool(self.bytes::<1>()?[0] != 0),
+            TypeDesc::Char => Value::String(char::from(self.bytes::<1>()?[0]).to_string()),
+            TypeDesc::Octet | TypeDesc::UInt8 => Value::from(self.bytes::<1>()?[0]),
+            TypeDesc::Int8 => Value::from(i8::from_ne_bytes(self.bytes::<1>()?)),
+            TypeDesc::Int16 => Value::from(i16::from_ne_bytes(self.bytes::<2>()?)),
+            TypeDesc::UInt16 => Value::from(u16::from_ne_bytes(self.bytes::<2>()?)),
+            TypeDesc::Int32 => Value::from(i32::from_ne_bytes(self.bytes::<4>()?)),
+            TypeDesc::UInt32 => Value::from(self.u32()?),
+            TypeDesc::Int64 => Value::from(i64::from_ne_bytes(self.bytes::<8>()?)),
+            TypeDesc::UInt64 => Value::from(u64::from_ne_bytes(self.bytes::<8>()?)),
+            TypeDesc::Float32 => {
+                // Through text so that 0.1f stays 0.1 instead of 0.10000000149011612
+                let value = f32::from_ne_bytes(self.bytes::<4>()?);
+                float(value.to_string().parse().unwrap_or(f64::NAN))
+            }
+            TypeDesc::Float64 => float(f64::from_ne_bytes(self.bytes::<8>()?)),
+            TypeDesc::String => {
+                let length = self.length()?;
+                let bytes = &self.data[self.pos..self.pos + length];
+                self.pos += length;
+                let text = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+                Value::String(String::from_utf8_lossy(text).into_owned())
+            }
+            TypeDesc::Sequence(element) => {
+                self.dheader(element)?;
+                let count = self.length()?;
+                self.elements(element, count)?
+            }
+            TypeDesc::Array(element, length) => {
+                self.dheader(element)?;
+                self.elements(element, *length)?
+            }
+            TypeDesc::Enum(desc) => {
+                let index = self.u32()?;
+                desc.enumerators
+                    .get(index as usize)
+                    .map(|name| Value::String(name.clone()))
+                    .unwrap_or_else(|| Value::from(index))
+            }
+            TypeDesc::Struct(desc) => {
+                if self.delimited {
+                    self.u32()?;
+                }
+                let mut object = Map::new();
+                for (name, member) in &desc.members {
+                    object.insert(name.clone(), self.value(member)?);
+                }
+                Value::Object(object)
+            }
+        };
+        Ok(value)
+    }
+}
+
+/// JSON number of a float, `null` for NaN and infinities
+fn float(value: f64) -> Value {
+    Number::from_f64(value).map_or(Value::Null, Value::Number)
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vehicle::dds::dynamic::idl;
+    use serde_json::json;
+
+    const IDL: &str = r#"
+        module vehicle {
+            enum Gear { PARK, REVERSE, NEUTRAL, DRIVE };
+            struct Wheel { string name; float pressure; };
+            struct Status {
+                boolean moving;
+                double speed;
+                Gear gear;
+                sequence<Wheel> wheels;
+                short temperatures[2];
+            };
+        };
+    "#;
+
+    fn status_type() -> TypeDesc {
+        idl::parse(IDL).unwrap()["vehicle::Status"].clone()
+    }
+
+    /// Writer producing the layout a DDS writer would send
+    struct Writer {
+        data: Vec<u8>,
+        max_align: usize,
+        big_endian: bool,
+    }
+
+    impl Writer {
+        fn new(header: [u8; 2], max_align: usize) -> Self {
+            Writer {
+                data: vec![header[0], header[1], 0, 0],
+                max_align,
+                big_endian: header[1].is_multiple_of(2),
+            }
+        }
+
+        fn put(&mut self, bytes: &[u8]) -> &mut Self {
+            let align = bytes.len().min(self.max_align);
+            while !(self.data.len() - 4).is_multiple_of(align) {
+                self.data.push(0);
+            }
+            if self.big_endian {
+                self.data.extend(bytes.iter().rev());
+            } else {
+                self.data.extend_from_slice(bytes);
+            }
+            self
+        }
+
+        fn string(&mut self, text: &str) -> &mut Self {
+            self.put(&(text.len() as u32 + 1).to_le_bytes());
+            self.data.extend_from_slice(text.as_bytes());
+            self.data.push(0);
+            self
+        }
+    }
+
+    fn expected() -> Value {
+        json!({
+            "moving": true,
+            "speed": 42.5,
+            "gear": "DRIVE",
+            "wheels": [{ "name": "front-left", "pressure": 2.3 }],
+            "temperatures": [-5, 30],
+        })
+    }
+
+    fn write_status(w: &mut Writer, xcdr2: bool) {
+        w.put(&[1])
+            .put(&42.5f64.to_le_bytes())
+            .put(&3u32.to_le_bytes());
+        if xcdr2 {
+            // DHEADER of the sequence of structs; its value is not checked
+            w.put(&0u32.to_le_bytes());
+        }
+        w.put(&1u32.to_le_bytes())
+            .string("front-left")
+            .put(&2.3f32.to_le_bytes())
+            .put(&(-5i16).to_le_bytes())
+            .put(&30i16.to_le_bytes());
+    }
+
+    #[test]
+    fn test_decode_xcdr1() {
+        let mut le = Writer::new([0, 1], 8);
+        write_status(&mut le, false);
+        // The double is aligned to 8 bytes after the boolean
+        assert_eq!(le.data.len(), 4 + 48);
+        assert_eq!(decode(&status_type(), &le.data).unwrap(), expected());
+
+        let mut be = Writer::new([0, 0], 8);
+        write_status(&mut be, false);
+        assert_eq!(decode(&status_type(), &be.data).unwrap(), expected());
+    }
+
+    #[test]
+    fn test_decode_xcdr2() {
+        let mut w = Writer::new([0, 7], 4);
+        write_status(&mut w, true);
+        assert_eq!(decode(&status_type(), &w.data).unwrap(), expected());
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let desc = status_type();
+        assert!(decode(&desc, &[0, 1]).is_err());
+        // Parameter list (mutable type)
+        assert!(decode(&desc, &[0, 3, 0, 0, 1]).is_err());
+
+        let mut w = Writer::new([0, 1], 8);
+        write_status(&mut w, false);
+        w.data.truncate(w.data.len() - 1);
+        assert!(decode(&desc, &w.data).is_err());
+
+        // Sequence length larger than the sample
+        let mut w = Writer::new([0, 1], 8);
+        w.put(&[0])
+            .put(&0f64.to_le_bytes())
+            .put(&0u32.to_le_bytes());
+        w.put(&u32::MAX.to_le_bytes());
+        assert!(decode(&desc, &w.data).is_err());
+    }
+}
@@ -0,0 +1,611 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Runtime IDL parser
+//!
+//! Supports the subset of IDL used for vehicle signals: modules, structs
+//! (with inheritance), enums, typedefs, integer constants, primitive types,
+//! bounded and unbounded strings, sequences and arrays. `@key` members are
+//! recorded, other annotations such as `@appendable` are accepted and
+//! ignored, preprocessor lines are skipped. Named types must be declared
+//! before they are used, as in IDL.
+//!
+//! The build script shares this parser to generate the compiled types.
+
+use std::collections::BTreeMap;
+
+/// Dynamic description of an IDL type
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeDesc {
+    Bool,
+    Char,
+    Octet,
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Int64,
+    UInt64,
+    Float32,
+    Float64,
+    String,
+    Sequence(Box<TypeDesc>),
+    Array(Box<TypeDesc>, usize),
+    Enum(EnumDesc),
+    Struct(StructDesc),
+}
+
+impl TypeDesc {
+    /// Whether XCDR2 encodes sequences and arrays of this type without a DHEADER
+    pub fn is_primitive(&self) -> bool {
+        match self {
+            TypeDesc::String | TypeDesc::Sequence(_) | TypeDesc::Struct(_) => false,
+            TypeDesc::Array(element, _) => element.is_primitive(),
+            _ => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumDesc {
+    pub name: String,
+    pub enumerators: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructDesc {
+    pub name: String,
+    pub members: Vec<(String, TypeDesc)>,
+    /// Members annotated with `@key`
+    pub keys: Vec<String>,
+}
+
+/// Parse IDL source into its named types
+///
+/// # Arguments
+///
+/// * `source` - Content of an IDL file
+///
+/// # Returns
+///
+/// * `Result<BTreeMap<String, TypeDesc>, String>` - Structs, enums and
+///   typedefs by fully qualified name (e.g. `vehicle::Door`), or the reason
+///   the source cannot be parsed
+pub fn parse(source: &str) -> Result<BTreeMap<String, TypeDesc>, String> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        pos: 0,
+        scope: Vec::new(),
+        types: BTreeMap::new(),
+        constants: BTreeMap::new(),
+    };
+    parser.definitions(false)?;
+    Ok(parser.types)
+}
+
+fn tokenize(source: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '#' {
+            // Preprocessor directives (#include, #pragma, ...) span one line
+            for c in chars.by_ref() {
+                if c == '\n' {
+                    break;
+                }
+            }
+        } else if c == '/' {
+            chars.next();
+            match chars.next() {
+                Some('/') => {
+                    for c in chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                }
+                Some('*') => {
+                    let mut prev = ' ';
+                    loop {
+                        match chars.next() {
+                            Some('/') if prev == '*' => break,
+                            Some(c) => prev = c,
+                            None => return Err("unterminated comment".to_string()),
+                        }
+                    }
+                }
+                _ => return Err("unexpected '/'".to_string()),
+            }
+        } else if c.is_ascii_alphanumeric() || c == '_' {
+            let mut token = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_ascii_alphanumeric() || c == '_' {
+                    token.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push(token);
+        } else if c == '"' {
+            // String literals only appear in annotation parameters
+            let mut token = String::from('"');
+            chars.next();
+            for c in chars.by_ref() {
+                token.push(c);
+                if c == '"' {
+                    break;
+                }
+            }
+            tokens.push(token);
+        } else if c == ':' {
+            chars.next();
+            if chars.peek() == Some(&':') {
+                chars.next();
+                tokens.push("::".to_string());
+            } else {
+                tokens.push(":".to_string());
+            }
+        } else if "{}();,<>[]=@-+".contains(c) {
+            tokens.push(c.to_string());
+            chars.next();
+        } else {
+            return Err(format!("unexpected character '{}'", c));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    pos: usize,
+    /// Enclosing module names
+    scope: Vec<String>,
+    types: BTreeMap<String, TypeDesc>,
+    constants: BTreeMap<String, i64>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Result<String, String> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| "unexpected end of IDL".to_string())?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn accept(&mut self, token: &str) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        let found = self.next()?;
+        if found == token {
+            Ok(())
+        } else {
+            Err(format!("expected '{}' but found '{}'", token, found))
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String, String> {
+        let token = self.next()?;
+        if token.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            Ok(token)
+        } else {
+            Err(format!("expected identifier but found '{}'", token))
+        }
+    }
+
+    /// Skip `@name` or `@name(...)` annotations
+    ///
+    /// Returns the annotation names, only `@key` is used.
+    fn annotations(&mut self) -> Result<Vec<String>, String> {
+        let mut names = Vec::new();
+        while self.accept("@") {
+            names.push(self.scoped_name()?);
+            if self.accept("(") {
+                let mut depth = 1;
+                while depth > 0 {
+                    match self.next()?.as_str() {
+                        "(" => depth += 1,
+                        ")" => depth -= 1,
+                        _ => {}
+                    }
+                }
+            }
+        }
+        Ok(names)
+    }
+
+    fn qualified(&self, name: &str) -> String {
+        let mut parts = self.scope.clone();
+        parts.push(name.to_string());
+        parts.join("::")
+    }
+
+    fn definitions(&mut self, in_module: bool) -> Result<(), String> {
+        loop {
+            self.annotations()?;
+            match self.peek() {
+                None if !in_module => return Ok(()),
+                None => return Err("missing '}' at end of module".to_string()),
+                Some("}") if in_module => return Ok(()),
+                Some(_) => self.definition()?,
+            }
+        }
+    }
+
+    fn definition(&mut self) -> Result<(), String> {
+        match self.next()?.as_str() {
+            "module" => {
+                let name = self.identifier()?;
+                self.expect("{")?;
+                self.scope.push(name);
+                self.definitions(true)?;
+                self.scope.pop();
+                self.expect("}")?;
+            }
+            "struct" => self.structure()?,
+            "enum" => self.enumeration()?,
+            "typedef" => {
+                let base = self.type_spec()?;
+                loop {
+                    let (name, desc) = self.declarator(base.clone())?;
+                    self.types.insert(self.qualified(&name), desc);
+                    if !self.accept(",") {
+                        break;
+                    }
+                }
+            }
+            "const" => {
+                self.type_spec()?;
+                let name = self.identifier()?;
+                self.expect("=")?;
+                let value = self.integer()?;
+                self.constants.insert(self.qualified(&name), value);
+            }
+            other => return Err(format!("unsupported IDL definition '{}'", other)),
+        }
+        self.expect(";")
+    }
+
+    fn structure(&mut self) -> Result<(), String> {
+        let name = self.identifier()?;
+        if self.peek() == Some(";") {
+            // Forward declaration
+            return Ok(());
+        }
+
+        let mut members = Vec::new();
+        let mut keys = Vec::new();
+        if self.accept(":") {
+            let base = self.scoped_name()?;
+            match self.lookup(&base)? {
+                TypeDesc::Struct(base) => {
+                    members.extend(base.members);
+                    keys.extend(base.keys);
+                }
+                _ => return Err(format!("base type '{}' is not a struct", base)),
+            }
+        }
+
+        self.expect("{")?;
+        loop {
+            let annotations = self.annotations()?;
+            if self.accept("}") {
+                break;
+            }
+            let base = self.type_spec()?;
+            loop {
+                let key = annotations
+                    .iter()
+                    .chain(&self.annotations()?)
+                    .any(|a| a == "key");
+                let (member, desc) = self.declarator(base.clone())?;
+                if key {
+                    keys.push(member.clone());
+                }
+                members.push((member, desc));
+                if !self.accept(",") {
+                    break;
+                }
+            }
+            self.expect(";")?;
+        }
+
+        let qualified = self.qualified(&name);
+        self.types.insert(
+            qualified.clone(),
+            TypeDesc::Struct(StructDesc {
+                name: qualified,
+                members,
+                keys,
+            }),
+        );
+        Ok(())
+    }
+
+    fn enumeration(&mut self) -> Result<(), String> {
+        let name = self.identifier()?;
+        self.expect("{")?;
+        let mut enumerators = Vec::new();
+        loop {
+            self.annotations()?;
+            enumerators.push(self.identifier()?);
+            if !self.accept(",") {
+                break;
+            }
+        }
+        self.expect("}")?;
+
+        let qualified = self.qualified(&name);
+        self.types.insert(
+            qualified.clone(),
+            TypeDesc::Enum(EnumDesc {
+                name: qualified,
+                enumerators,
+            }),
+        );
+        Ok(())
+    }
+
+    /// `name` or `name[N][M]` applied to the declared type
+    fn declarator(&mut self, base: TypeDesc) -> Result<(String, TypeDesc), String> {
+        let name = self.identifier()?;
+        let mut dimensions = Vec::new();
+        while self.accept("[") {
+            dimensions.push(self.size()?);
+            self.expect("]")?;
+        }
+        let desc = dimensions.into_iter().rev().fold(base, |element, length| {
+            TypeDesc::Array(Box::new(element), length)
+        });
+        Ok((name, desc))
+    }
+
+    fn type_spec(&mut self) -> Result<TypeDesc, String> {
+        let token = self.next()?;
+        let desc = match token.as_str() {
+            "boolean" => TypeDesc::Bool,
+            "char" | "wchar" => TypeDesc::Char,
+            "octet" => TypeDesc::Octet,
+            "int8" => TypeDesc::Int8,
+            "uint8" => TypeDesc::UInt8,
+            "short" | "int16" => TypeDesc::Int16,
+            "int32" => TypeDesc::Int32,
+            "int64" => TypeDesc::Int64,
+            "uint16" => TypeDesc::UInt16,
+            "uint32" => TypeDesc::UInt32,
+            "uint64" => TypeDesc::UInt64,
+            "float" => TypeDesc::Float32,
+            "double" => TypeDesc::Float64,
+            "long" => {
+                if self.accept("long") {
+                    TypeDesc::Int64
+                } else if self.peek() == Some("double") {
+                    return Err("long double is not supported".to_string());
+                } else {
+                    TypeDesc::Int32
+                }
+            }
+            "unsigned" => match self.next()?.as_str() {
+                "short" => TypeDesc::UInt16,
+                "long" if self.accept("long") => TypeDesc::UInt64,
+                "long" => TypeDesc::UInt32,
+                other => return Err(format!("unexpected 'unsigned {}'", other)),
+            },
+            "string" | "wstring" => {
+                if self.accept("<") {
+                    self.size()?;
+                    self.expect(">")?;
+                }
+                TypeDesc::String
+            }
+            "sequence" => {
+                self.expect("<")?;
+                let element = self.type_spec()?;
+                if self.accept(",") {
+                    self.size()?;
+                }
+                self.expect(">")?;
+                TypeDesc::Sequence(Box::new(element))
+            }
+            "union" | "any" | "fixed" | "map" | "bitset" | "bitmask" => {
+                return Err(format!("IDL type '{}' is not supported", token))
+            }
+            _ => {
+                self.pos -= 1;
+                let name = self.scoped_name()?;
+                self.lookup(&name)?
+            }
+        };
+        Ok(desc)
+    }
+
+    fn scoped_name(&mut self) -> Result<String, String> {
+        let mut name = String::new();
+        if self.accept("::") {
+            name.push_str("::");
+        }
+        name.push_str(&self.identifier()?);
+        while self.accept("::") {
+            name.push_str("::");
+            name.push_str(&self.identifier()?);
+        }
+        Ok(name)
+    }
+
+    /// Candidate qualified names of a reference, innermost scope first
+    fn candidates(&self, name: &str) -> Vec<String> {
+        if let Some(absolute) = name.strip_prefix("::") {
+            return vec![absolute.to_string()];
+        }
+        (0..=self.scope.len())
+            .rev()
+            .map(|depth| {
+                let mut parts = self.scope[..depth].to_vec();
+                parts.push(name.to_string());
+                parts.join("::")
+            })
+            .collect()
+    }
+
+    fn lookup(&self, name: &str) -> Result<TypeDesc, String> {
+        self.candidates(name)
+            .iter()
+            .find_map(|candidate| self.types.get(candidate).cloned())
+            .ok_or_else(|| format!("unknown type '{}'", name))
+    }
+
+    fn integer(&mut self) -> Result<i64, String> {
+        let negative = self.accept("-");
+        if !negative {
+            self.accept("+");
+        }
+        let token = self.next()?;
+        let value = if let Some(hex) = token
+            .strip_prefix("0x")
+            .or_else(|| token.strip_prefix("0X"))
+        {
+            i64::from_str_radix(hex, 16).ok()
+        } else if token.starts_with(|c: char| c.is_ascii_digit()) {
+            token.parse::<i64>().ok()
+        } else {
+            self.candidates(&token)
+                .iter()
+                .find_map(|candidate| self.constants.get(candidate).copied())
+        };
+        let value = value.ok_or_else(|| format!("'{}' is not an integer constant", token))?;
+        Ok(if negative { -value } else { value })
+    }
+
+    /// Array length or sequence/string bound
+    fn size(&mut self) -> Result<usize, String> {
+        let value = self.integer()?;
+        usize::try_from(value)
+            .ok()
+            .filter(|size| *size > 0)
+            .ok_or_else(|| format!("invalid size {}", value))
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDL: &str = r#"
+        #include "common.idl"
+        module vehicle {
+            const long LAMP_COUNT = 4;
+
+            enum LampState { OFF, ON, @value(5) BLINK };
+            typedef sequence<string, 8> Labels;
+
+            /* Position of a detected object */
+            @final
+            struct Point {
+                double x, y;
+            };
+
+            module adas {
+                @appendable
+                struct Obstacle : Point {
+                    @key unsigned long long id;
+                    float distance;
+                    Labels labels;
+                    LampState lamps[LAMP_COUNT];
+                    sequence<::vehicle::Point> outline;
+                };
+            };
+        };
+    "#;
+
+    #[test]
+    fn test_parse_nested_modules() {
+        let types = parse(IDL).unwrap();
+        assert_eq!(
+            types.keys().cloned().collect::<Vec<_>>(),
+            vec![
+                "vehicle::Labels",
+                "vehicle::LampState",
+                "vehicle::Point",
+                "vehicle::adas::Obstacle",
+            ]
+        );
+
+        let TypeDesc::Struct(obstacle) = &types["vehicle::adas::Obstacle"] else {
+            panic!("Obstacle is not a struct");
+        };
+        let names: Vec<&str> = obstacle.members.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["x", "y", "id", "distance", "labels", "lamps", "outline"]
+        );
+        assert_eq!(obstacle.keys, vec!["id".to_string()]);
+        assert_eq!(obstacle.members[2].1, TypeDesc::UInt64);
+        assert_eq!(
+            obstacle.members[4].1,
+            TypeDesc::Sequence(Box::new(TypeDesc::String))
+        );
+        let TypeDesc::Array(lamp, 4) = &obstacle.members[5].1 else {
+            panic!("lamps is not an array of 4");
+        };
+        assert!(matches!(**lamp, TypeDesc::Enum(ref e) if e.enumerators.len() == 3));
+        assert_eq!(
+            obstacle.members[6].1,
+            TypeDesc::Sequence(Box::new(types["vehicle::Point"].clone()))
+        );
+    }
+
+    #[test]
+    fn test_parse_vehicle_idl_files() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/vehicle/dds/idl");
+        let mut parsed = 0;
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let source = std::fs::read_to_string(&path).unwrap();
+            let types = parse(&source).unwrap_or_else(|e| panic!("{:?}: {}", path, e));
+            let stem = path.file_stem().unwrap().to_string_lossy();
+            // Each file declares one struct whose name starts with the file name
+            assert_eq!(types.len(), 1, "{:?}", path);
+            let (name, desc) = types.iter().next().unwrap();
+            assert!(name.starts_with(stem.as_ref()), "{}", name);
+            let TypeDesc::Struct(desc) = desc else {
+                panic!("{} is not a struct", name);
+            };
+            assert!(!desc.members.is_empty());
+            parsed += 1;
+        }
+        assert!(parsed > 0);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("struct A { Unknown b; };").is_err());
+        assert!(parse("struct A { long b; }").is_err());
+        assert!(parse("module m { struct A { long b; };").is_err());
+        assert!(parse("union U switch (long) { case 1: long a; };").is_err());
+        assert!(parse("struct A { long b[0]; };").is_err());
+        assert!(parse("/* open comment").is_err());
+        assert!(parse("").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,155 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! DDS types loaded at runtime
+//!
+//! Types generated by `build_scripts` need filtergateway to be rebuilt for
+//! every new signal. IDL files found in the `dds.runtime_idl_path` directory
+//! of the settings, or shipped in the `idl` operand of a Scenario, are parsed
+//! into a `DynamicTypeRegistry` instead. A topic whose type is only known
+//! here is read as raw bytes and decoded to JSON with `cdr::decode`.
+
+pub mod cdr;
+pub mod idl;
+
+use dust_dds::infrastructure::error::DdsResult;
+use dust_dds::topic_definition::type_support::{DdsDeserialize, DdsType, TypeSupport};
+use dust_dds::xtypes::dynamic_type::DynamicType;
+use idl::TypeDesc;
+use log::info;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Struct types parsed from runtime IDL, by fully qualified name
+#[derive(Debug, Default)]
+pub struct DynamicTypeRegistry {
+    types: BTreeMap<String, TypeDesc>,
+}
+
+impl DynamicTypeRegistry {
+    /// Add the types of an IDL source, replacing types of the same name
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - IDL text
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<String>, String>` - Names of the added struct types, or
+    ///   the parse error, in which case the registry is unchanged
+    pub fn load_str(&mut self, source: &str) -> Result<Vec<String>, String> {
+        let mut names = Vec::new();
+        for (name, desc) in idl::parse(source)? {
+            if matches!(desc, TypeDesc::Struct(_)) {
+                names.push(name.clone());
+                self.types.insert(name, desc);
+            }
+        }
+        Ok(names)
+    }
+
+    /// Add the types of every `.idl` file of a directory
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Directory containing IDL files
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<String>>` - Names of the added struct types
+    pub fn load_directory(&mut self, dir: &Path) -> common::Result<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_file() && path.extension().is_some_and(|ext| ext == "idl") {
+                let source = fs::read_to_string(&path)?;
+                let loaded = self
+                    .load_str(&source)
+                    .map_err(|e| format!("{}: {}", path.display(), e))?;
+                info!("Loaded runtime IDL {:?}: {:?}", path, loaded);
+                names.extend(loaded);
+            }
+        }
+        Ok(names)
+    }
+
+    /// Find the type of a topic
+    ///
+    /// The name may be fully qualified (`vehicle::Door`) or, if it is not
+    /// ambiguous, the bare struct name (`Door`).
+    pub fn find(&self, type_name: &str) -> Option<&TypeDesc> {
+        if let Some(desc) = self.types.get(type_name) {
+            return Some(desc);
+        }
+        let suffix = format!("::{}", type_name);
+        let mut matches = self
+            .types
+            .iter()
+            .filter(|(name, _)| name.ends_with(&suffix));
+        match (matches.next(), matches.next()) {
+            (Some((_, desc)), None) => Some(desc),
+            _ => None,
+        }
+    }
+
+    /// Names of the loaded struct types
+    pub fn type_names(&self) -> Vec<String> {
+        self.types.keys().cloned().collect()
+    }
+}
+
+/// Only used for its type description, which has no member and so no key
+#[derive(DdsType)]
+struct Opaque;
+
+/// Sample of a runtime type, kept serialized for `cdr::decode`
+#[derive(Debug, Clone, Default)]
+pub struct RawSample(pub Vec<u8>);
+
+impl TypeSupport for RawSample {
+    fn get_type_name() -> &'static str {
+        "RawSample"
+    }
+
+    fn get_type() -> impl DynamicType {
+        Opaque::get_type()
+    }
+}
+
+impl<'de> DdsDeserialize<'de> for RawSample {
+    fn deserialize_data(serialized_data: &'de [u8]) -> DdsResult<Self> {
+        Ok(RawSample(serialized_data.to_vec()))
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_find() {
+        let mut registry = DynamicTypeRegistry::default();
+        registry
+            .load_str("module body { enum State { OPEN, CLOSED }; struct Door { State state; }; };")
+            .unwrap();
+        registry
+            .load_str("module trailer { struct Door { boolean open; }; };")
+            .unwrap();
+        registry.load_str("struct Wiper { long speed; };").unwrap();
+        assert!(registry.load_str("struct Broken {").is_err());
+
+        assert_eq!(
+            registry.type_names(),
+            vec!["Wiper", "body::Door", "trailer::Door"]
+        );
+        assert!(registry.find("body::Door").is_some());
+        assert!(registry.find("Wiper").is_some());
+        // Ambiguous bare name and enums are not topic types
+        assert!(registry.find("Door").is_none());
+        assert!(registry.find("State").is_none());
+    }
+}
@@ -1,3 +1,6 @@
+use crate::vehicle::dds::dynamic::cdr::decode;
+use crate::vehicle::dds::dynamic::idl::TypeDesc;
+use crate::vehicle::dds::dynamic::RawSample;
 use crate::vehicle::dds::DdsData;
 use common::Result;
 use std::collections::HashMap;
@@ -9,24 +12,33 @@ pub trait DdsTopicListener: Send + Sync {
     async fn stop(&mut self) -> Result<()>;
     fn get_topic_name(&self) -> &str;
     fn is_topic(&self, topic_name: &str) -> bool;
+    /// Set the QoS of the reader, before the listener is started
+    fn set_qos(&mut self, _qos: DataReaderQos) {}
 }
 
 use dust_dds::{
     domain::domain_participant::DomainParticipant,
     domain::domain_participant_factory::{DomainId, DomainParticipantFactory},
     infrastructure::{
-        qos::QosKind,
+        qos::{DataReaderQos, QosKind},
         qos_policy::{DataRepresentationQosPolicy, XCDR2_DATA_REPRESENTATION},
-        status::{StatusKind, NO_STATUS},
+        status::{
+            LivelinessChangedStatus, RequestedDeadlineMissedStatus, RequestedIncompatibleQosStatus,
+            StatusKind, NO_STATUS,
+        },
         time::Duration,
     },
     subscription::data_reader::DataReader,
+    subscription::data_reader_listener::DataReaderListener,
     subscription::sample_info::{ANY_INSTANCE_STATE, ANY_SAMPLE_STATE, ANY_VIEW_STATE},
     subscription::subscriber::Subscriber,
     topic_definition::type_support::{DdsDeserialize, DdsType, TypeSupport},
 };
 
+use std::marker::PhantomData;
+use std::sync::Arc;
 use tokio::sync::mpsc::Sender;
+use tokio::sync::Notify;
 use tokio::task::JoinHandle;
 use tokio::time;
 
@@ -211,6 +223,162 @@ impl TopicListener {
     }
 }
 
+/// Samples taken from the reader at once
+const TAKE_BATCH: i32 = 32;
+
+/// 리더 이벤트 리스너
+///
+/// 샘플이 도착하면 리더 태스크를 깨우고, QoS 관련 상태 변화를 로그로 남긴다.
+struct ReaderEvents<T> {
+    topic_name: String,
+    notify: Arc<Notify>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: 'static> DataReaderListener<'a> for ReaderEvents<T> {
+    type Foo = T;
+
+    fn on_data_available(&mut self, _the_reader: DataReader<T>) {
+        self.notify.notify_one();
+    }
+
+    fn on_requested_deadline_missed(
+        &mut self,
+        _the_reader: DataReader<T>,
+        status: RequestedDeadlineMissedStatus,
+    ) {
+        warn!(
+            "Deadline missed on topic '{}' ({} times)",
+            self.topic_name, status.total_count
+        );
+    }
+
+    fn on_liveliness_changed(
+        &mut self,
+        _the_reader: DataReader<T>,
+        status: LivelinessChangedStatus,
+    ) {
+        info!(
+            "Liveliness of topic '{}' changed: {} writers alive",
+            self.topic_name, status.alive_count
+        );
+    }
+
+    fn on_requested_incompatible_qos(
+        &mut self,
+        _the_reader: DataReader<T>,
+        status: RequestedIncompatibleQosStatus,
+    ) {
+        warn!(
+            "Writers of topic '{}' offer a QoS incompatible with the reader ({} times)",
+            self.topic_name, status.total_count
+        );
+    }
+}
+
+/// 토픽 샘플 수신 루프
+///
+/// 100ms 폴링 대신 data-available 리스너가 태스크를 깨우고, 쌓여 있는 샘플을
+/// 모두 가져오므로 버스트를 놓치거나 지연이 생기지 않는다.
+///
+/// # Arguments
+///
+/// * `topic_name` - Topic name, also used as the DDS type name
+/// * `domain_id` - DDS domain ID
+/// * `qos` - Reader QoS, the subscriber default if `None`
+/// * `tx` - Data transmission channel
+/// * `to_data` - Conversion of a sample, `None` to skip it
+async fn read_topic<T, F>(
+    topic_name: &str,
+    domain_id: i32,
+    qos: Option<DataReaderQos>,
+    tx: &Sender<DdsData>,
+    mut to_data: F,
+) -> Result<()>
+where
+    T: TypeSupport + for<'de> DdsDeserialize<'de> + Send + 'static,
+    F: FnMut(T) -> Option<DdsData>,
+{
+    // 도메인 참여자 생성
+    let domain_participant_factory = DomainParticipantFactory::get_instance();
+    let participant = domain_participant_factory
+        .create_participant(domain_id, QosKind::Default, None, NO_STATUS)
+        .map_err(|e| anyhow!("Failed to create domain participant: {:?}", e))?;
+
+    // 구독자 생성
+    let subscriber = participant
+        .create_subscriber(QosKind::Default, None, NO_STATUS)
+        .map_err(|e| anyhow!("Failed to create subscriber: {:?}", e))?;
+    // 토픽 생성 (컴파일된 타입과 같은 타입 이름을 사용해야 writer와 매칭된다)
+    let topic = participant
+        .create_topic::<T>(topic_name, topic_name, QosKind::Default, None, NO_STATUS)
+        .map_err(|e| anyhow!("Failed to create topic: {:?}", e))?;
+
+    // 데이터 리더 생성
+    let notify = Arc::new(Notify::new());
+    let events = ReaderEvents::<T> {
+        topic_name: topic_name.to_string(),
+        notify: notify.clone(),
+        _marker: PhantomData,
+    };
+    let reader_qos = match qos {
+        Some(qos) => QosKind::Specific(qos),
+        None => QosKind::Default,
+    };
+    let data_reader = subscriber
+        .create_datareader::<T>(
+            &topic,
+            reader_qos,
+            Some(Box::new(events)),
+            &[
+                StatusKind::DataAvailable,
+                StatusKind::RequestedDeadlineMissed,
+                StatusKind::LivelinessChanged,
+                StatusKind::RequestedIncompatibleQos,
+            ],
+        )
+        .map_err(|e| anyhow!("Failed to create data reader: {:?}", e))?;
+
+    info!("Created data reader for topic '{}'", topic_name);
+
+    loop {
+        // 대기 중인 샘플을 모두 가져옴 (transient-local writer의 이전 샘플 포함)
+        loop {
+            let samples = match data_reader.take(
+                TAKE_BATCH,
+                ANY_SAMPLE_STATE,
+                ANY_VIEW_STATE,
+                ANY_INSTANCE_STATE,
+            ) {
+                Ok(samples) => samples,
+                Err(e) => {
+                    debug!("No new samples available: {:?}", e);
+                    break;
+                }
+            };
+
+            for sample in samples {
+                let Some(dds_data) = sample.data().ok().and_then(&mut to_data) else {
+                    continue;
+                };
+                // Send data through channel
+                if tx.send(dds_data).await.is_err() {
+                    warn!("Channel closed, stopping listener for {}", topic_name);
+                    return Ok(());
+                }
+            }
+        }
+
+        tokio::select! {
+            _ = notify.notified() => {}
+            _ = tx.closed() => {
+                warn!("Channel closed, stopping listener for {}", topic_name);
+                return Ok(());
+            }
+        }
+    }
+}
+
 /// 타입별 DDS 토픽 리스너 베이스 구현
 ///
 /// TypeSupport 특성으로 다양한 DDS 데이터 타입 처리
@@ -236,6 +404,8 @@ pub struct GenericTopicListener<
     listener_task: Option<JoinHandle<()>>,
     /// Running state
     is_running: bool,
+    /// Reader QoS, the subscriber default if `None`
+    qos: Option<DataReaderQos>,
     /// Type marker (for generic type specification)
     _marker: std::marker::PhantomData<T>,
 }
@@ -265,6 +435,7 @@ impl<
             domain_id,
             listener_task: None,
             is_running: false,
+            qos: None,
             _marker: std::marker::PhantomData,
         }
     }
@@ -275,81 +446,19 @@ impl<
         data_type_name: String,
         tx: Sender<DdsData>,
         domain_id: i32,
+        qos: Option<DataReaderQos>,
     ) -> Result<()> {
-        // 도메인 참여자 생성
-        let domain_participant_factory = DomainParticipantFactory::get_instance();
-        let participant = domain_participant_factory
-            .create_participant(domain_id, QosKind::Default, None, NO_STATUS)
-            .map_err(|e| anyhow!("Failed to create domain participant: {:?}", e))?;
-
-        // 구독자 생성
-        let subscriber = participant
-            .create_subscriber(QosKind::Default, None, NO_STATUS)
-            .map_err(|e| anyhow!("Failed to create subscriber: {:?}", e))?;
-        // 토픽 생성
-        let topic = participant
-            .create_topic::<T>(&topic_name, &topic_name, QosKind::Default, None, NO_STATUS)
-            .map_err(|e| anyhow!("Failed to create topic: {:?}", e))?;
-
-        // 데이터 리더 생성
-        let data_reader = subscriber
-            .create_datareader::<T>(&topic, QosKind::Default, None, NO_STATUS)
-            .map_err(|e| anyhow!("Failed to create data reader: {:?}", e))?;
-
-        println!(
-            "Successfully created data reader for topic '{}'",
-            topic_name
-        );
-
-        // 메시지 수신 루프
-        let mut interval = time::interval(time::Duration::from_millis(100));
-
-        loop {
-            interval.tick().await;
-
-            // 새 샘플 확인
-            let result = data_reader
-                .take(1, ANY_SAMPLE_STATE, ANY_VIEW_STATE, ANY_INSTANCE_STATE)
-                .map_err(|e| anyhow!("Failed to read samples: {:?}", e));
-
-            match result {
-                Ok(samples) => {
-                    for sample in samples {
-                        if let Ok(data) = sample.data() {
-                            // 데이터를 JSON으로 직렬화
-                            let json_value = serde_json::to_string(&data)
-                                .map_err(|e| anyhow!("Failed to serialize data: {:?}", e))?;
-
-                            // json_value를 key, value로 파싱해서 fields에 추가
-                            let mut fields = HashMap::new();
-                            if let Ok(map) =
-                                serde_json::from_str::<serde_json::Map<String, Value>>(&json_value)
-                            {
-                                for (k, v) in map {
-                                    fields.insert(k, v.to_string());
-                                }
-                            }
-
-                            // DdsData 객체 생성 및 전송
-                            let dds_data = DdsData {
-                                name: data_type_name.clone(),
-                                value: json_value,
-                                fields,
-                            };
-
-                            // Send data through channel
-                            if tx.send(dds_data).await.is_err() {
-                                warn!("Channel closed, stopping listener for {}", topic_name);
-                                return Ok(());
-                            }
-                        }
-                    }
-                }
+        read_topic::<T, _>(&topic_name, domain_id, qos, &tx, |data| {
+            // 데이터를 JSON으로 직렬화
+            match serde_json::to_string(&data) {
+                Ok(json_value) => Some(DdsData::from_json(&data_type_name, json_value)),
                 Err(e) => {
-                    debug!("No new samples available: {:?}", e);
+                    warn!("Failed to serialize sample of '{}': {:?}", topic_name, e);
+                    None
                 }
             }
-        }
+        })
+        .await
     }
 }
 
@@ -378,11 +487,13 @@ impl<
         let data_type_name = self.data_type_name.clone();
         let tx = self.tx.clone();
         let domain_id = self.domain_id;
+        let qos = self.qos.clone();
 
         // 리스너 태스크 시작
         let task = tokio::spawn(async move {
             if let Err(e) =
-                Self::typed_listener_loop(topic_name.clone(), data_type_name, tx, domain_id).await
+                Self::typed_listener_loop(topic_name.clone(), data_type_name, tx, domain_id, qos)
+                    .await
             {
                 error!("Error in typed listener loop for {}: {:?}", topic_name, e);
             }
@@ -410,7 +521,145 @@ impl<
     fn is_topic(&self, topic_name: &str) -> bool {
         self.topic_name == topic_name
     }
+
+    fn set_qos(&mut self, qos: DataReaderQos) {
+        self.qos = Some(qos);
+    }
+}
+
+/// 런타임에 로드한 IDL 타입의 토픽 리스너
+///
+/// 샘플을 직렬화된 그대로 받아 `dynamic::cdr::decode`로 JSON으로 변환한다.
+pub struct DynamicTopicListener {
+    /// Topic name
+    topic_name: String,
+    /// Data type name
+    data_type_name: String,
+    /// Type description parsed from the IDL
+    desc: TypeDesc,
+    /// Data transmission channel
+    tx: Sender<DdsData>,
+    /// DDS domain ID
+    domain_id: i32,
+    /// Listener task handle
+    listener_task: Option<JoinHandle<()>>,
+    /// Running state
+    is_running: bool,
+    /// Reader QoS, the subscriber default if `None`
+    qos: Option<DataReaderQos>,
+}
+
+impl DynamicTopicListener {
+    /// 새 런타임 타입 리스너 생성
+    pub fn new(
+        topic_name: String,
+        data_type_name: String,
+        desc: TypeDesc,
+        tx: Sender<DdsData>,
+        domain_id: i32,
+    ) -> Self {
+        Self {
+            topic_name,
+            data_type_name,
+            desc,
+            tx,
+            domain_id,
+            listener_task: None,
+            is_running: false,
+            qos: None,
+        }
+    }
+
+    /// 런타임 타입 리스너 루프
+    async fn dynamic_listener_loop(
+        topic_name: String,
+        data_type_name: String,
+        desc: TypeDesc,
+        tx: Sender<DdsData>,
+        domain_id: i32,
+        qos: Option<DataReaderQos>,
+    ) -> Result<()> {
+        read_topic::<RawSample, _>(
+            &topic_name,
+            domain_id,
+            qos,
+            &tx,
+            |RawSample(bytes)| match decode(&desc, &bytes) {
+                Ok(value) => Some(DdsData::from_json(&data_type_name, value.to_string())),
+                Err(e) => {
+                    warn!("Failed to decode sample of '{}': {}", topic_name, e);
+                    None
+                }
+            },
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl DdsTopicListener for DynamicTopicListener {
+    fn is_running(&self) -> bool {
+        self.is_running
+    }
+
+    async fn start(&mut self) -> Result<()> {
+        if self.is_running {
+            return Ok(());
+        }
+
+        let topic_name = self.topic_name.clone();
+        let data_type_name = self.data_type_name.clone();
+        let desc = self.desc.clone();
+        let tx = self.tx.clone();
+        let domain_id = self.domain_id;
+        let qos = self.qos.clone();
+
+        let task = tokio::spawn(async move {
+            if let Err(e) = Self::dynamic_listener_loop(
+                topic_name.clone(),
+                data_type_name,
+                desc,
+                tx,
+                domain_id,
+                qos,
+            )
+            .await
+            {
+                error!(
+                    "Error in runtime type listener loop for {}: {:?}",
+                    topic_name, e
+                );
+            }
+        });
+
+        self.listener_task = Some(task);
+        self.is_running = true;
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        if self.is_running {
+            if let Some(task) = self.listener_task.take() {
+                task.abort();
+            }
+            self.is_running = false;
+        }
+        Ok(())
+    }
+
+    fn get_topic_name(&self) -> &str {
+        &self.topic_name
+    }
+
+    fn is_topic(&self, topic_name: &str) -> bool {
+        self.topic_name == topic_name
+    }
+
+    fn set_qos(&mut self, qos: DataReaderQos) {
+        self.qos = Some(qos);
+    }
 }
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -507,6 +756,7 @@ mod tests {
                 "ADASObstacleDetectionIsWarning".to_string(),
                 tx,
                 100,
+                None,
             )
             .await
         })
@@ -698,6 +948,7 @@ mod tests {
                 "DDS".to_string(),
                 tx,
                 100,
+                None,
             )
             .await
             .map_err(|e| anyhow::anyhow!(e.to_string())) // convert error to string before anyhow
@@ -775,6 +1026,7 @@ mod tests {
                 "DDS".to_string(),
                 tx,
                 100, // Will work if DDS setup is OK, but reading might fail (no data)
+                None,
             )
             .await;
         });
@@ -796,6 +1048,7 @@ mod tests {
                 "DDS".to_string(),
                 tx,
                 100,
+                None,
             )
             .await;
 
@@ -834,6 +1087,7 @@ mod tests {
             "DDS".to_string(),
             tx,
             100,
+            None,
         )
         .await;
 
@@ -1,4 +1,5 @@
 use anyhow::anyhow;
+use common::spec::artifact::TopicQos;
 use common::Result;
 use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
@@ -9,10 +10,14 @@ use std::path::{Path, PathBuf};
 use tokio::sync::mpsc::{self, Receiver, Sender};
 use tokio::sync::Mutex;
 
+pub mod dynamic;
 pub mod listener;
+pub mod qos;
 
 // Re-export the modules
-pub use listener::{create_idl_listener, DdsTopicListener};
+pub use dynamic::DynamicTypeRegistry;
+pub use listener::{create_idl_listener, DdsTopicListener, DynamicTopicListener};
+pub use qos::QosSettings;
 
 // DdsData structure to represent parsed IDL data
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +27,29 @@ pub struct DdsData {
     pub fields: HashMap<String, String>,
 }
 
+impl DdsData {
+    /// 수신한 샘플의 JSON 문자열을 DdsData로 변환
+    ///
+    /// json_value를 key, value로 파싱해서 fields에 추가한다
+    /// (중첩 필드는 조건의 field path로 json_value에서 조회)
+    pub fn from_json(name: &str, json_value: String) -> Self {
+        let mut fields = HashMap::new();
+        if let Ok(map) =
+            serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&json_value)
+        {
+            for (k, v) in map {
+                fields.insert(k, crate::filter::path::value_to_string(&v));
+            }
+        }
+
+        DdsData {
+            name: name.to_string(),
+            value: json_value,
+            fields,
+        }
+    }
+}
+
 /// DDS Manager - Manages multiple DDS listeners
 pub struct DdsManager {
     /// Active listener map (topic name → listener)
@@ -32,6 +60,17 @@ pub struct DdsManager {
     rx: Mutex<Receiver<DdsData>>,
     /// DDS domain ID
     domain_id: i32,
+    /// Types loaded from IDL at runtime
+    dynamic_types: DynamicTypeRegistry,
+    /// Reader QoS from the settings
+    qos_settings: QosSettings,
+}
+
+/// Path of the settings file, from `PICCOLO_SETTINGS_PATH` if set
+pub fn default_settings_path() -> PathBuf {
+    env::var("PICCOLO_SETTINGS_PATH")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("/home/edo/2025/projects/pullpiri/src/settings.yaml"))
 }
 
 impl DdsManager {
@@ -44,6 +83,8 @@ impl DdsManager {
             tx,
             rx: Mutex::new(mpsc::channel(100).1),
             domain_id: 100,
+            dynamic_types: DynamicTypeRegistry::default(),
+            qos_settings: QosSettings::default(),
         }
     }
     /// Scan and process IDL directory at runtime
@@ -77,6 +118,28 @@ impl DdsManager {
         &mut self,
         topic_name: String,
         data_type_name: String,
+    ) -> Result<()> {
+        self.create_typed_listener_with_qos(topic_name, data_type_name, None)
+            .await
+    }
+
+    /// Create a listener of a topic whose reader uses the given QoS
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_name` - Name of the topic
+    /// * `data_type_name` - Type name of the data
+    /// * `requested_qos` - QoS requested by the scenario, merged over the
+    ///   `dds.qos` settings
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - Success or error result
+    pub async fn create_typed_listener_with_qos(
+        &mut self,
+        topic_name: String,
+        data_type_name: String,
+        requested_qos: Option<&TopicQos>,
     ) -> Result<()> {
         // 이미 존재하는 리스너인지 확인
         if self.listeners.contains_key(&topic_name) {
@@ -87,6 +150,10 @@ impl DdsManager {
             "DDSManager - Creating typed listener for topic '{}'",
             topic_name
         );
+        let reader_qos = match self.qos_settings.resolve(&topic_name, requested_qos) {
+            Some(qos) => Some(qos::reader_qos(&qos)?),
+            None => None,
+        };
 
         // 레지스트리를 통한 타입별 리스너 생성 시도
         if let Some(mut typed_listener) = dds_type_registry::create_typed_listener(
@@ -95,6 +162,9 @@ impl DdsManager {
             self.tx.clone(),
             self.domain_id,
         ) {
+            if let Some(qos) = reader_qos.clone() {
+                typed_listener.set_qos(qos);
+            }
             // 리스너 시작
             typed_listener
                 .start()
@@ -111,6 +181,31 @@ impl DdsManager {
             return Ok(());
         }
 
+        // 빌드 시 생성되지 않은 타입은 런타임에 로드한 IDL로 디코딩
+        if let Some(desc) = self.dynamic_types.find(&data_type_name) {
+            let mut listener: Box<dyn DdsTopicListener> = Box::new(DynamicTopicListener::new(
+                topic_name.clone(),
+                data_type_name.clone(),
+                desc.clone(),
+                self.tx.clone(),
+                self.domain_id,
+            ));
+            if let Some(qos) = reader_qos {
+                listener.set_qos(qos);
+            }
+            listener
+                .start()
+                .await
+                .map_err(|e| anyhow!("Failed to start runtime type listener: {:?}", e))?;
+
+            info!(
+                "Started runtime type listener for {} with type {}",
+                topic_name, data_type_name
+            );
+            self.listeners.insert(topic_name, listener);
+            return Ok(());
+        }
+
         // Create generic listener if no type-specific listener is found
         warn!(
             "No specific type handler for '{}', using generic listener",
@@ -121,7 +216,27 @@ impl DdsManager {
 
     /// Get list of available DDS types
     pub fn list_available_types(&self) -> Vec<String> {
-        dds_type_metadata::get_available_types()
+        let mut types = dds_type_metadata::get_available_types();
+        types.extend(self.dynamic_types.type_names());
+        types
+    }
+
+    /// Load DDS types from IDL text at runtime
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - IDL text, e.g. shipped with a Scenario
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<String>>` - Names of the loaded struct types
+    pub fn register_idl(&mut self, source: &str) -> Result<Vec<String>> {
+        Ok(self.dynamic_types.load_str(source)?)
+    }
+
+    /// Load DDS types from the `.idl` files of a directory at runtime
+    pub fn load_idl_directory(&mut self, dir: &Path) -> Result<Vec<String>> {
+        self.dynamic_types.load_directory(dir)
     }
 
     /// Set DDS domain ID
@@ -209,19 +324,13 @@ impl DdsManager {
         info!("Initializing DDS Manager");
         let default_domain_id = 0;
 
-        let settings_path = settings_path.into().unwrap_or_else(|| {
-            env::var("PICCOLO_SETTINGS_PATH")
-                .map(PathBuf::from)
-                .unwrap_or_else(|_| {
-                    PathBuf::from("/home/edo/2025/projects/pullpiri/src/settings.yaml")
-                })
-        });
+        let settings_path = settings_path.into().unwrap_or_else(default_settings_path);
 
         info!("Reading settings from {:?}", settings_path);
         let content = fs::read_to_string(&settings_path)?;
 
         // JSON 또는 YAML 파싱
-        let settings = serde_json::from_str::<serde_json::Value>(&content)?;
+        let settings = serde_yaml::from_str::<serde_json::Value>(&content)?;
 
         let domain_id = settings
             .get("dds")
@@ -243,6 +352,22 @@ impl DdsManager {
 
         self.domain_id = domain_id;
 
+        // 런타임에 로드할 IDL 디렉토리 (filtergateway 재빌드 없이 타입 추가)
+        if let Some(dir) = settings
+            .get("dds")
+            .and_then(|dds| dds.get("runtime_idl_path"))
+            .and_then(|path| path.as_str())
+        {
+            let loaded = self.load_idl_directory(Path::new(dir))?;
+            info!("Loaded {} runtime IDL types from {}", loaded.len(), dir);
+        }
+
+        // 토픽별 리더 QoS
+        if let Some(qos) = settings.get("dds").and_then(|dds| dds.get("qos")) {
+            self.qos_settings = QosSettings::from_value(qos)?;
+            info!("Reader QoS from settings: {:?}", self.qos_settings);
+        }
+
         Ok(())
     }
 }
@@ -665,6 +790,43 @@ mod tests {
         // listeners map should be empty after stop_all
         assert!(manager.listeners.is_empty());
     }
+    #[tokio::test]
+    async fn test_init_loads_runtime_idl_directory() {
+        let (tx, _) = mpsc::channel(100);
+        let mut manager = DdsManager::new(tx);
+
+        let idl_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            idl_dir.path().join("Wiper.idl"),
+            "module body { struct Wiper { long speed; }; };",
+        )
+        .unwrap();
+        let temp_file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            temp_file.path(),
+            format!(
+                "dds:\n  domain_id: 7\n  runtime_idl_path: {}\n",
+                idl_dir.path().display()
+            ),
+        )
+        .unwrap();
+
+        let result = manager.init_with_path(Some(temp_file.path().into())).await;
+        assert!(result.is_ok(), "Init failed: {:?}", result.unwrap_err());
+        assert_eq!(manager.domain_id, 7);
+        assert!(manager
+            .list_available_types()
+            .contains(&"body::Wiper".to_string()));
+
+        assert_eq!(
+            manager
+                .register_idl("struct Horn { boolean on; };")
+                .unwrap(),
+            vec!["Horn".to_string()]
+        );
+        assert!(manager.register_idl("struct Horn {").is_err());
+    }
+
     #[tokio::test]
     async fn test_init_with_path_default_domain_id() {
         let (tx, _) = mpsc::channel(100);
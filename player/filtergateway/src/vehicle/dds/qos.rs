@@ -0,0 +1,224 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! QoS of topic readers
+//!
+//! The QoS of a reader is built from, in increasing priority, the DDS
+//! defaults, the `dds.qos.default` and `dds.qos.topics.<topic>` sections of
+//! the settings, and the `qos` block of the Scenario operand. A reader is
+//! shared by every scenario using its topic, so the QoS requested by the
+//! first subscribed scenario is used until the reader is removed.
+
+use common::spec::artifact::TopicQos;
+use dust_dds::infrastructure::qos::DataReaderQos;
+use dust_dds::infrastructure::qos_policy::{
+    DeadlineQosPolicy, DurabilityQosPolicy, DurabilityQosPolicyKind, HistoryQosPolicy,
+    HistoryQosPolicyKind, LivelinessQosPolicy, LivelinessQosPolicyKind, ReliabilityQosPolicy,
+    ReliabilityQosPolicyKind,
+};
+use dust_dds::infrastructure::time::{Duration, DurationKind};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// `dds.qos` section of the settings
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct QosSettings {
+    /// QoS of every topic reader
+    #[serde(default)]
+    pub default: TopicQos,
+    /// QoS of the reader of a topic, by topic name
+    #[serde(default)]
+    pub topics: HashMap<String, TopicQos>,
+}
+
+impl QosSettings {
+    /// Parse the `dds.qos` section and check every QoS in it
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Content of the `dds.qos` section
+    ///
+    /// # Returns
+    ///
+    /// * `Result<QosSettings, String>` - The settings or the first invalid policy
+    pub fn from_value(value: &serde_json::Value) -> Result<QosSettings, String> {
+        let settings: QosSettings =
+            serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+        reader_qos(&settings.default)?;
+        for (topic_name, qos) in &settings.topics {
+            reader_qos(qos).map_err(|e| format!("topic '{}': {}", topic_name, e))?;
+        }
+        Ok(settings)
+    }
+
+    /// QoS of the reader of a topic
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_name` - Name of the topic
+    /// * `requested` - QoS requested by the scenario, if any
+    ///
+    /// # Returns
+    ///
+    /// * `Option<TopicQos>` - The merged QoS, `None` if nothing overrides the
+    ///   DDS defaults
+    pub fn resolve(&self, topic_name: &str, requested: Option<&TopicQos>) -> Option<TopicQos> {
+        let mut qos = self.default.clone();
+        if let Some(topic_qos) = self.topics.get(topic_name) {
+            qos = qos.merge(topic_qos);
+        }
+        if let Some(requested) = requested {
+            qos = qos.merge(requested);
+        }
+        (qos != TopicQos::default()).then_some(qos)
+    }
+}
+
+/// Convert milliseconds to a DDS duration
+fn millis(ms: u64) -> DurationKind {
+    DurationKind::Finite(Duration::new(
+        (ms / 1000).min(i32::MAX as u64) as i32,
+        ((ms % 1000) * 1_000_000) as u32,
+    ))
+}
+
+/// Build the DDS QoS of a reader
+///
+/// # Arguments
+///
+/// * `qos` - QoS of the topic subscription
+///
+/// # Returns
+///
+/// * `Result<DataReaderQos, String>` - The reader QoS or the invalid policy
+pub fn reader_qos(qos: &TopicQos) -> Result<DataReaderQos, String> {
+    let mut reader = DataReaderQos::default();
+
+    if let Some(reliability) = qos.get_reliability() {
+        let kind = match reliability.as_str() {
+            "reliable" => ReliabilityQosPolicyKind::Reliable,
+            "bestEffort" => ReliabilityQosPolicyKind::BestEffort,
+            other => return Err(format!("unknown reliability '{}'", other)),
+        };
+        reader.reliability = ReliabilityQosPolicy {
+            kind,
+            max_blocking_time: reader.reliability.max_blocking_time,
+        };
+    }
+
+    if let Some(durability) = qos.get_durability() {
+        let kind = match durability.as_str() {
+            "volatile" => DurabilityQosPolicyKind::Volatile,
+            "transientLocal" => DurabilityQosPolicyKind::TransientLocal,
+            other => return Err(format!("unknown durability '{}'", other)),
+        };
+        reader.durability = DurabilityQosPolicy { kind };
+    }
+
+    if let Some(depth) = qos.get_history_depth() {
+        reader.history = HistoryQosPolicy {
+            kind: match depth {
+                0 => HistoryQosPolicyKind::KeepAll,
+                depth => HistoryQosPolicyKind::KeepLast(depth),
+            },
+        };
+    }
+
+    if let Some(deadline) = qos.get_deadline() {
+        if deadline == 0 {
+            return Err("deadline must be greater than 0".to_string());
+        }
+        reader.deadline = DeadlineQosPolicy {
+            period: millis(deadline),
+        };
+    }
+
+    if let Some(lease) = qos.get_liveliness_lease() {
+        if lease == 0 {
+            return Err("livelinessLease must be greater than 0".to_string());
+        }
+        reader.liveliness = LivelinessQosPolicy {
+            kind: LivelinessQosPolicyKind::Automatic,
+            lease_duration: millis(lease),
+        };
+    }
+
+    Ok(reader)
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn qos(yaml: &str) -> TopicQos {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_reader_qos() {
+        let reader = reader_qos(&qos(
+            "reliability: reliable\ndurability: transientLocal\nhistoryDepth: 1\ndeadline: 1500\nlivelinessLease: 3000",
+        ))
+        .unwrap();
+        assert_eq!(reader.reliability.kind, ReliabilityQosPolicyKind::Reliable);
+        assert_eq!(
+            reader.durability.kind,
+            DurabilityQosPolicyKind::TransientLocal
+        );
+        assert_eq!(reader.history.kind, HistoryQosPolicyKind::KeepLast(1));
+        assert_eq!(reader.deadline.period, millis(1500));
+        assert_eq!(
+            reader.deadline.period,
+            DurationKind::Finite(Duration::new(1, 500_000_000))
+        );
+        assert_eq!(reader.liveliness.lease_duration, millis(3000));
+
+        let reader = reader_qos(&qos("historyDepth: 0")).unwrap();
+        assert_eq!(reader.history.kind, HistoryQosPolicyKind::KeepAll);
+        assert_eq!(
+            reader.reliability.kind,
+            DataReaderQos::default().reliability.kind
+        );
+
+        assert!(reader_qos(&qos("reliability: sometimes")).is_err());
+        assert!(reader_qos(&qos("durability: persistent")).is_err());
+        assert!(reader_qos(&qos("deadline: 0")).is_err());
+    }
+
+    #[test]
+    fn test_qos_settings_resolve() {
+        let settings = QosSettings::from_value(&json!({
+            "default": { "reliability": "bestEffort", "historyDepth": 4 },
+            "topics": {
+                "rt/piccolo/gear_state": { "durability": "transientLocal", "historyDepth": 1 }
+            }
+        }))
+        .unwrap();
+
+        let resolved = settings.resolve("rt/piccolo/gear_state", None).unwrap();
+        assert_eq!(resolved.get_reliability().as_deref(), Some("bestEffort"));
+        assert_eq!(resolved.get_durability().as_deref(), Some("transientLocal"));
+        assert_eq!(resolved.get_history_depth(), Some(1));
+
+        // The scenario operand has the last word
+        let requested = qos("reliability: reliable");
+        let resolved = settings
+            .resolve("rt/piccolo/speed", Some(&requested))
+            .unwrap();
+        assert_eq!(resolved.get_reliability().as_deref(), Some("reliable"));
+        assert_eq!(resolved.get_history_depth(), Some(4));
+        assert!(resolved.get_durability().is_none());
+
+        assert!(QosSettings::default()
+            .resolve("rt/piccolo/speed", None)
+            .is_none());
+        assert!(QosSettings::from_value(&json!({
+            "topics": { "rt/piccolo/speed": { "durability": "forever" } }
+        }))
+        .is_err());
+    }
+}
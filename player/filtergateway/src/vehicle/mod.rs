@@ -1,15 +1,29 @@
 pub mod dds;
+pub mod recording;
+pub mod source;
+pub mod subscription;
 
+use common::spec::artifact::TopicQos;
 use common::Result;
 use dds::DdsData;
+use recording::RecordingSettings;
+use source::{SourceKind, SourceManager};
+use subscription::SubscriptionTable;
 use tokio::sync::mpsc::Sender;
 
 /// Vehicle data management module
 ///
-/// Manages vehicle data through DDS communication
+/// Manages vehicle data through DDS communication and the other signal
+/// sources
 pub struct VehicleManager {
     /// DDS Manager instance
     dds_manager: dds::DdsManager,
+    /// Readers of the signals that do not come from DDS
+    sources: SourceManager,
+    /// Scenarios using each subscribed topic
+    subscriptions: SubscriptionTable,
+    /// Recording and replay of the received samples
+    recording: RecordingSettings,
 }
 
 impl VehicleManager {
@@ -20,7 +34,10 @@ impl VehicleManager {
     /// A new VehicleManager instance
     pub fn new(tx: Sender<DdsData>) -> Self {
         Self {
+            sources: SourceManager::new(tx.clone()),
             dds_manager: dds::DdsManager::new(tx),
+            subscriptions: SubscriptionTable::default(),
+            recording: RecordingSettings::default(),
         }
     }
 
@@ -42,9 +59,38 @@ impl VehicleManager {
         }
         self.set_domain_id(100); // Set default domain ID
 
+        if let Err(e) = self.load_settings(dds::default_settings_path()) {
+            log::warn!("No signal sources or recording configured: {}", e);
+        }
+
+        Ok(())
+    }
+
+    /// Reads the `sources` and `recording` sections of a settings file
+    ///
+    /// # Arguments
+    ///
+    /// * `settings_path` - Path of the settings file
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - Error if the file cannot be read or is malformed
+    pub fn load_settings(&mut self, settings_path: std::path::PathBuf) -> Result<()> {
+        let content = std::fs::read_to_string(settings_path)?;
+        let settings = serde_yaml::from_str::<serde_json::Value>(&content)?;
+        self.sources.load_settings(&settings)?;
+        if let Some(recording) = settings.get("recording") {
+            self.recording = serde_json::from_value(recording.clone())?;
+            log::info!("Recording from settings: {:?}", self.recording);
+        }
         Ok(())
     }
 
+    /// Recording and replay settings
+    pub fn get_recording(&self) -> &RecordingSettings {
+        &self.recording
+    }
+
     /// Subscribes to a vehicle data topic
     ///
     /// # Arguments
@@ -59,12 +105,37 @@ impl VehicleManager {
         &mut self,
         topic_name: String,
         data_type_name: String,
+    ) -> Result<()> {
+        self.subscribe_topic_with_qos(topic_name, data_type_name, None)
+            .await
+    }
+
+    /// Subscribes to a vehicle data topic with a reader QoS
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_name` - Name of the topic to subscribe to
+    /// * `data_type_name` - Type name of the data
+    /// * `qos` - QoS requested for the reader, merged over the settings
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - Success or error result
+    pub async fn subscribe_topic_with_qos(
+        &mut self,
+        topic_name: String,
+        data_type_name: String,
+        qos: Option<&TopicQos>,
     ) -> Result<()> {
         use std::time::Instant;
         let start = Instant::now();
 
+        if self.recording.replay.is_some() {
+            log::info!("Replaying a recording, not reading topic '{}'", topic_name);
+            return Ok(());
+        }
         self.dds_manager
-            .create_typed_listener(topic_name, data_type_name)
+            .create_typed_listener_with_qos(topic_name, data_type_name, qos)
             .await?;
 
         let elapsed = start.elapsed();
@@ -73,11 +144,124 @@ impl VehicleManager {
         Ok(())
     }
 
+    /// Subscribes a scenario to a vehicle data topic
+    ///
+    /// The DDS reader of a topic is shared by all scenarios using it and is
+    /// only created for the first one, with the QoS it requested.
+    ///
+    /// # Arguments
+    ///
+    /// * `scenario_name` - Name of the scenario
+    /// * `topic_name` - Name of the topic to subscribe to
+    /// * `data_type_name` - Type name of the data
+    /// * `qos` - QoS requested for the reader by the scenario
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - Success or error result
+    pub async fn subscribe_scenario(
+        &mut self,
+        scenario_name: &str,
+        topic_name: String,
+        data_type_name: String,
+        qos: Option<TopicQos>,
+    ) -> Result<()> {
+        if !self.subscriptions.add(scenario_name, &topic_name) {
+            return Ok(());
+        }
+
+        if let Err(e) = self
+            .subscribe_topic_with_qos(topic_name.clone(), data_type_name, qos.as_ref())
+            .await
+        {
+            self.subscriptions.remove(scenario_name, &topic_name);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Subscribes a scenario to a signal that does not come from DDS
+    ///
+    /// The source of a signal is shared by all scenarios using it, like the
+    /// DDS reader of a topic.
+    ///
+    /// # Arguments
+    ///
+    /// * `scenario_name` - Name of the scenario
+    /// * `kind` - Source of the signal
+    /// * `signal_name` - `value` of the condition operand
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - Success or error result
+    pub async fn subscribe_signal(
+        &mut self,
+        scenario_name: &str,
+        kind: SourceKind,
+        signal_name: String,
+    ) -> Result<()> {
+        if !self.subscriptions.add(scenario_name, &signal_name) {
+            return Ok(());
+        }
+
+        if self.recording.replay.is_some() {
+            log::info!(
+                "Replaying a recording, not reading signal '{}'",
+                signal_name
+            );
+            return Ok(());
+        }
+        if let Err(e) = self.sources.create_source(kind, signal_name.clone()).await {
+            self.subscriptions.remove(scenario_name, &signal_name);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Unsubscribes a scenario from its vehicle data topics
+    ///
+    /// The DDS reader of a topic is removed when no other scenario uses it.
+    ///
+    /// # Arguments
+    ///
+    /// * `scenario_name` - Name of the withdrawn scenario
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - Success or error result
+    pub async fn unsubscribe_scenario(&mut self, scenario_name: &str) -> Result<()> {
+        for topic_name in self.subscriptions.remove_scenario(scenario_name) {
+            self.unsubscribe_topic(topic_name).await?;
+        }
+        Ok(())
+    }
+
+    /// Scenarios currently subscribed to a topic
+    pub fn topic_subscribers(&self, topic_name: &str) -> Vec<String> {
+        self.subscriptions.scenarios(topic_name)
+    }
+
     /// Get list of available DDS types
     pub fn list_available_types(&self) -> Vec<String> {
         self.dds_manager.list_available_types()
     }
 
+    /// Loads DDS types from IDL text at runtime
+    ///
+    /// Topics of these types can then be subscribed without rebuilding
+    /// filtergateway.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - IDL text shipped with a scenario
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<String>>` - Names of the loaded struct types
+    pub fn register_idl(&mut self, source: &str) -> Result<Vec<String>> {
+        self.dds_manager.register_idl(source)
+    }
+
     /// Unsubscribes from a vehicle data topic
     ///
     /// # Arguments
@@ -88,8 +272,9 @@ impl VehicleManager {
     ///
     /// * `Result<()>` - Success or error result
     pub async fn unsubscribe_topic(&mut self, topic_name: String) -> Result<()> {
-        // TODO: Implementation
-        self.dds_manager.remove_listener(&topic_name).await?;
+        if !self.sources.remove_source(&topic_name).await? {
+            self.dds_manager.remove_listener(&topic_name).await?;
+        }
         Ok(())
     }
 
@@ -153,6 +338,97 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test] // Test that scenarios share a topic until the last one is withdrawn
+    async fn test_vehicle_manager_scenario_subscriptions_are_shared() {
+        let (tx, _rx) = mpsc::channel(10);
+        let mut vehicle_manager = VehicleManager::new(tx);
+        for scenario in ["antipinch", "welcome-light"] {
+            vehicle_manager
+                .subscribe_scenario(
+                    scenario,
+                    "vehicle_data".to_string(),
+                    "VehicleType".to_string(),
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+        assert_eq!(vehicle_manager.topic_subscribers("vehicle_data").len(), 2);
+
+        vehicle_manager
+            .unsubscribe_scenario("antipinch")
+            .await
+            .unwrap();
+        assert_eq!(
+            vehicle_manager.topic_subscribers("vehicle_data"),
+            vec!["welcome-light".to_string()]
+        );
+
+        vehicle_manager
+            .unsubscribe_scenario("welcome-light")
+            .await
+            .unwrap();
+        assert!(vehicle_manager.topic_subscribers("vehicle_data").is_empty());
+    }
+
+    #[tokio::test] // Test subscribing scenarios to a signal of a configured source
+    async fn test_vehicle_manager_subscribe_signal() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = dir.path().join("settings.yaml");
+        std::fs::write(
+            &settings,
+            format!(
+                "sources:\n  unix:\n    path: {:?}\n",
+                dir.path().join("feed.sock")
+            ),
+        )
+        .unwrap();
+
+        let (tx, _rx) = mpsc::channel(10);
+        let mut vehicle_manager = VehicleManager::new(tx);
+        assert!(vehicle_manager
+            .subscribe_signal("antipinch", SourceKind::Mqtt, "door".to_string())
+            .await
+            .is_err());
+        assert!(vehicle_manager.topic_subscribers("door").is_empty());
+
+        vehicle_manager.load_settings(settings).unwrap();
+        vehicle_manager
+            .subscribe_signal("antipinch", SourceKind::Unix, "door".to_string())
+            .await
+            .unwrap();
+        assert_eq!(vehicle_manager.topic_subscribers("door").len(), 1);
+
+        vehicle_manager
+            .unsubscribe_scenario("antipinch")
+            .await
+            .unwrap();
+        assert!(vehicle_manager.topic_subscribers("door").is_empty());
+    }
+
+    #[tokio::test] // Test that no live reader is needed while replaying a recording
+    async fn test_vehicle_manager_replay_skips_readers() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = dir.path().join("settings.yaml");
+        std::fs::write(
+            &settings,
+            "recording:\n  replay: /var/log/piccolo/signals.fgr\n  speed: 10\n",
+        )
+        .unwrap();
+
+        let (tx, _rx) = mpsc::channel(10);
+        let mut vehicle_manager = VehicleManager::new(tx);
+        vehicle_manager.load_settings(settings).unwrap();
+        assert_eq!(vehicle_manager.get_recording().speed, 10.0);
+
+        // MQTT is not configured, but the signal comes from the recording
+        vehicle_manager
+            .subscribe_signal("antipinch", SourceKind::Mqtt, "door".to_string())
+            .await
+            .unwrap();
+        assert_eq!(vehicle_manager.topic_subscribers("door").len(), 1);
+    }
+
     #[test] // Test listing all available vehicle types
     fn test_vehicle_manager_list_available_types() {
         let (tx, _rx) = mpsc::channel(10);
@@ -0,0 +1,458 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Recording and replay of the vehicle samples seen by the filters
+//!
+//! A recording starts with a header holding the wall-clock start time, then
+//! a sequence of entries:
+//!
+//! * a topic entry gives the next topic index to a topic name, the first
+//!   time a sample of that topic is recorded
+//! * a sample entry holds the microseconds since the previous sample, the
+//!   topic index and the JSON payload
+//!
+//! Integers are LEB128 varints, so a sample costs a few bytes over its
+//! payload. A recording cut by a crash is read up to its last whole sample.
+
+use crate::vehicle::dds::DdsData;
+use log::warn;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc::Sender;
+
+const MAGIC: &[u8; 5] = b"FGREC";
+const VERSION: u8 = 1;
+const TOPIC: u8 = 1;
+const SAMPLE: u8 = 2;
+/// Interval at which the owner of a recorder calls `Recorder::flush`, the
+/// longest time recorded samples stay in the write buffer
+pub const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+fn default_speed() -> f64 {
+    1.0
+}
+
+/// `recording` section of the settings
+#[derive(Debug, Clone, Deserialize)]
+pub struct RecordingSettings {
+    /// File the received samples are recorded to
+    #[serde(default)]
+    pub record: Option<PathBuf>,
+    /// Recording fed to the filters instead of the live signals
+    #[serde(default)]
+    pub replay: Option<PathBuf>,
+    /// Replay speed factor, `0` to replay without waiting
+    #[serde(default = "default_speed")]
+    pub speed: f64,
+}
+
+impl RecordingSettings {
+    /// File to record to, `None` while replaying
+    ///
+    /// A replay would only be recorded again, and recording to the replayed
+    /// file would truncate it before it is read.
+    pub fn record_path(&self) -> Option<&Path> {
+        match self.replay {
+            Some(_) => None,
+            None => self.record.as_deref(),
+        }
+    }
+}
+
+impl Default for RecordingSettings {
+    fn default() -> Self {
+        Self {
+            record: None,
+            replay: None,
+            speed: default_speed(),
+        }
+    }
+}
+
+fn write_varint(out: &mut impl Write, mut value: u64) -> io::Result<()> {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            return out.write_all(&[byte]);
+        }
+        out.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_varint(input: &mut impl Read) -> io::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0u8];
+        input.read_exact(&mut byte)?;
+        value |= ((byte[0] & 0x7F) as u64) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "varint too long",
+    ))
+}
+
+fn read_bytes(input: &mut impl Read) -> io::Result<Vec<u8>> {
+    let len = read_varint(input)?;
+    let mut bytes = Vec::new();
+    input.take(len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(bytes)
+}
+
+/// Writer of a recording
+pub struct Recorder {
+    out: BufWriter<File>,
+    /// Index of each topic already written
+    topics: HashMap<String, u64>,
+    started: Instant,
+    /// Microseconds from the start to the previous sample
+    last: u64,
+}
+
+impl Recorder {
+    /// Create a recording, replacing any file at the path
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the recording
+    ///
+    /// # Returns
+    ///
+    /// * `io::Result<Recorder>` - The recorder or the error creating the file
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let mut out = BufWriter::new(File::create(path)?);
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        out.write_all(MAGIC)?;
+        out.write_all(&[VERSION])?;
+        out.write_all(&(started.as_micros() as u64).to_be_bytes())?;
+        out.flush()?;
+
+        Ok(Self {
+            out,
+            topics: HashMap::new(),
+            started: Instant::now(),
+            last: 0,
+        })
+    }
+
+    /// Append a sample
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Received sample
+    /// * `at` - When it was received
+    ///
+    /// # Returns
+    ///
+    /// * `io::Result<()>` - Error if the recording cannot be written
+    pub fn record(&mut self, data: &DdsData, at: Instant) -> io::Result<()> {
+        let next = self.topics.len() as u64;
+        let topic = *self.topics.entry(data.name.clone()).or_insert(next);
+        if topic == next {
+            self.out.write_all(&[TOPIC])?;
+            write_varint(&mut self.out, data.name.len() as u64)?;
+            self.out.write_all(data.name.as_bytes())?;
+        }
+
+        let offset = at.saturating_duration_since(self.started).as_micros() as u64;
+        let delta = offset.saturating_sub(self.last);
+        self.last = self.last.max(offset);
+
+        self.out.write_all(&[SAMPLE])?;
+        write_varint(&mut self.out, delta)?;
+        write_varint(&mut self.out, topic)?;
+        write_varint(&mut self.out, data.value.len() as u64)?;
+        self.out.write_all(data.value.as_bytes())
+    }
+
+    /// Write the buffered samples to the file
+    ///
+    /// Samples are buffered to keep recording cheap, so this is called every
+    /// `FLUSH_INTERVAL` whether or not samples keep arriving.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        let _ = self.out.flush();
+    }
+}
+
+/// Sample read from a recording
+#[derive(Debug, Clone)]
+pub struct RecordedSample {
+    /// Time since the start of the recording
+    pub offset: Duration,
+    pub data: DdsData,
+}
+
+/// Reader of a recording
+pub struct RecordReader<R: Read> {
+    input: R,
+    topics: Vec<String>,
+    started: SystemTime,
+    /// Microseconds from the start to the previous sample
+    last: u64,
+}
+
+impl RecordReader<BufReader<File>> {
+    /// Open a recording file
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> RecordReader<R> {
+    /// Read the header of a recording
+    pub fn new(mut input: R) -> io::Result<Self> {
+        let mut header = [0u8; 14];
+        input.read_exact(&mut header)?;
+        if &header[..5] != MAGIC || header[5] != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a filtergateway recording",
+            ));
+        }
+        let micros = u64::from_be_bytes(header[6..].try_into().unwrap_or_default());
+        Ok(Self {
+            input,
+            topics: Vec::new(),
+            started: UNIX_EPOCH + Duration::from_micros(micros),
+            last: 0,
+        })
+    }
+
+    /// Wall-clock time the recording started
+    pub fn started(&self) -> SystemTime {
+        self.started
+    }
+
+    /// Read the next sample
+    ///
+    /// # Returns
+    ///
+    /// * `io::Result<Option<RecordedSample>>` - The sample, `None` at the end
+    ///   of the recording or at a sample cut by the end of the file
+    pub fn next_sample(&mut self) -> io::Result<Option<RecordedSample>> {
+        match self.read_entry() {
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+            result => result,
+        }
+    }
+
+    fn read_entry(&mut self) -> io::Result<Option<RecordedSample>> {
+        loop {
+            let mut tag = [0u8];
+            if self.input.read(&mut tag)? == 0 {
+                return Ok(None);
+            }
+            match tag[0] {
+                TOPIC => {
+                    let name = String::from_utf8(read_bytes(&mut self.input)?)
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                    self.topics.push(name);
+                }
+                SAMPLE => {
+                    self.last += read_varint(&mut self.input)?;
+                    let topic = read_varint(&mut self.input)? as usize;
+                    let value = String::from_utf8(read_bytes(&mut self.input)?)
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                    let name = self.topics.get(topic).ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidData, "unknown topic index")
+                    })?;
+                    return Ok(Some(RecordedSample {
+                        offset: Duration::from_micros(self.last),
+                        data: DdsData::from_json(name, value),
+                    }));
+                }
+                other => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("unknown entry {}", other),
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// Feed a recording to the filters
+///
+/// # Arguments
+///
+/// * `path` - Path of the recording
+/// * `tx` - Sender of the vehicle samples
+/// * `speed` - Speed factor, `0` to send the samples without waiting
+///
+/// # Returns
+///
+/// * `anyhow::Result<usize>` - Number of samples sent
+pub async fn replay(path: PathBuf, tx: Sender<DdsData>, speed: f64) -> anyhow::Result<usize> {
+    let mut reader = RecordReader::open(&path)?;
+    let start = tokio::time::Instant::now();
+    let mut count = 0;
+
+    while let Some(sample) = reader.next_sample()? {
+        if speed > 0.0 {
+            tokio::time::sleep_until(start + sample.offset.div_f64(speed)).await;
+        }
+        if tx.send(sample.data).await.is_err() {
+            warn!("Replay of {:?} stopped, nothing receives samples", path);
+            break;
+        }
+        count += 1;
+    }
+    Ok(count)
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    fn sample(name: &str, value: &str) -> DdsData {
+        DdsData::from_json(name, value.to_string())
+    }
+
+    #[test]
+    fn test_varint_round_trip() {
+        for value in [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let mut buf = Vec::new();
+            write_varint(&mut buf, value).unwrap();
+            assert_eq!(read_varint(&mut buf.as_slice()).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_record_path_disabled_while_replaying() {
+        let mut settings = RecordingSettings {
+            record: Some(PathBuf::from("/tmp/signals.fgr")),
+            ..Default::default()
+        };
+        assert_eq!(settings.record_path(), Some(Path::new("/tmp/signals.fgr")));
+        settings.replay = settings.record.clone();
+        assert_eq!(settings.record_path(), None);
+    }
+
+    #[test]
+    fn test_record_and_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("signals.fgr");
+        let mut recorder = Recorder::create(&path).unwrap();
+        let start = recorder.started;
+        recorder
+            .record(&sample("gear", r#"{"state":"park"}"#), start)
+            .unwrap();
+        recorder
+            .record(
+                &sample("speed", r#"{"kmh":12}"#),
+                start + Duration::from_millis(40),
+            )
+            .unwrap();
+        recorder
+            .record(
+                &sample("gear", r#"{"state":"drive"}"#),
+                start + Duration::from_millis(250),
+            )
+            .unwrap();
+        drop(recorder);
+
+        let mut reader = RecordReader::open(&path).unwrap();
+        assert!(reader.started() <= SystemTime::now());
+        let samples: Vec<_> = std::iter::from_fn(|| reader.next_sample().unwrap()).collect();
+        assert_eq!(samples.len(), 3);
+        assert_eq!(samples[1].offset, Duration::from_millis(40));
+        assert_eq!(samples[1].data.name, "speed");
+        assert_eq!(samples[2].offset, Duration::from_millis(250));
+        assert_eq!(
+            samples[2].data.fields.get("state").map(String::as_str),
+            Some("drive")
+        );
+
+        // A sample cut by a crash ends the recording
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes.truncate(bytes.len() - 3);
+        let mut reader = RecordReader::new(bytes.as_slice()).unwrap();
+        assert_eq!(
+            std::iter::from_fn(|| reader.next_sample().unwrap()).count(),
+            2
+        );
+
+        assert!(RecordReader::new(&b"not a recording"[..]).is_err());
+    }
+
+    #[test]
+    fn test_flush_writes_buffered_samples() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("signals.fgr");
+        let mut recorder = Recorder::create(&path).unwrap();
+        let start = recorder.started;
+        recorder
+            .record(&sample("gear", r#"{"state":"park"}"#), start)
+            .unwrap();
+        recorder.flush().unwrap();
+
+        // Readable while the recorder is still open
+        let mut reader = RecordReader::open(&path).unwrap();
+        let sample = reader.next_sample().unwrap().unwrap();
+        assert_eq!(sample.data.name, "gear");
+        assert!(reader.next_sample().unwrap().is_none());
+        drop(recorder);
+    }
+
+    #[tokio::test]
+    async fn test_replay_keeps_timing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("signals.fgr");
+        let mut recorder = Recorder::create(&path).unwrap();
+        let start = recorder.started;
+        for (i, state) in ["park", "drive", "park"].iter().enumerate() {
+            let value = format!(r#"{{"state":"{}"}}"#, state);
+            recorder
+                .record(
+                    &sample("gear", &value),
+                    start + Duration::from_millis(200 * i as u64),
+                )
+                .unwrap();
+        }
+        drop(recorder);
+
+        let (tx, mut rx) = mpsc::channel(10);
+        let begin = std::time::Instant::now();
+        let count = replay(path.clone(), tx, 2.0).await.unwrap();
+        assert_eq!(count, 3);
+        // 400 ms of recording at twice the speed
+        let elapsed = begin.elapsed();
+        assert!(elapsed >= Duration::from_millis(200), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(400), "{:?}", elapsed);
+        let mut states = Vec::new();
+        while let Ok(data) = rx.try_recv() {
+            states.push(data.fields["state"].clone());
+        }
+        assert_eq!(states, ["park", "drive", "park"]);
+
+        // Without waiting
+        let (tx, _rx) = mpsc::channel(10);
+        let begin = std::time::Instant::now();
+        assert_eq!(replay(path, tx, 0.0).await.unwrap(), 3);
+        assert!(begin.elapsed() < Duration::from_millis(200));
+    }
+}
@@ -0,0 +1,303 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Signals of CAN frames read through SocketCAN
+//!
+//! The operand `value` is the name of a message of the DBC file of the
+//! `sources.can` settings. Each source opens a raw CAN socket that the
+//! kernel filters on the message ID, and sends the decoded signals of every
+//! frame. It can be tried on a virtual bus:
+//!
+//! ```text
+//! ip link add dev vcan0 type vcan && ip link set up vcan0
+//! ```
+
+use super::dbc::{Dbc, Message};
+use super::{SignalSource, SourceTask};
+use crate::vehicle::dds::DdsData;
+use async_trait::async_trait;
+use common::Result;
+use log::{info, warn};
+use serde::Deserialize;
+use std::ffi::CString;
+use std::io;
+use std::mem::{size_of, zeroed};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::path::PathBuf;
+use tokio::io::unix::AsyncFd;
+use tokio::sync::mpsc::Sender;
+
+/// `sources.can` settings
+#[derive(Debug, Clone, Deserialize)]
+pub struct CanSettings {
+    /// CAN network interface, e.g. `can0` or `vcan0`
+    pub interface: String,
+    /// DBC file describing the messages
+    pub dbc: PathBuf,
+}
+
+/// Reader of one CAN message
+pub struct CanSource {
+    interface: String,
+    message: Message,
+    tx: Sender<DdsData>,
+    task: SourceTask,
+}
+
+impl CanSource {
+    /// Create the source of a message of the DBC file
+    ///
+    /// # Arguments
+    ///
+    /// * `signal_name` - Name of the message in the DBC file
+    /// * `settings` - `sources.can` settings
+    /// * `tx` - Sender for samples
+    ///
+    /// # Returns
+    ///
+    /// * `Result<CanSource>` - Error if the DBC file cannot be read or has no
+    ///   such message
+    pub fn new(signal_name: String, settings: &CanSettings, tx: Sender<DdsData>) -> Result<Self> {
+        let source = std::fs::read_to_string(&settings.dbc)?;
+        let dbc = Dbc::parse(&source).map_err(|e| format!("{:?}: {}", settings.dbc, e))?;
+        let message = dbc
+            .messages
+            .get(&signal_name)
+            .ok_or_else(|| format!("no message '{}' in {:?}", signal_name, settings.dbc))?
+            .clone();
+
+        Ok(Self {
+            interface: settings.interface.clone(),
+            message,
+            tx,
+            task: SourceTask::default(),
+        })
+    }
+}
+
+/// Open a non-blocking raw CAN socket only receiving the frames of a message
+pub fn open_socket(interface: &str, message: &Message) -> io::Result<OwnedFd> {
+    let name =
+        CString::new(interface).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    // SAFETY: `name` is a valid NUL-terminated string
+    let ifindex = unsafe { libc::if_nametoindex(name.as_ptr()) };
+    if ifindex == 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // SAFETY: plain socket creation, the descriptor is owned right after
+    let fd = unsafe {
+        libc::socket(
+            libc::PF_CAN,
+            libc::SOCK_RAW | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+            libc::CAN_RAW,
+        )
+    };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: `fd` is a new descriptor nothing else owns
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    let filter = if message.extended {
+        libc::can_filter {
+            can_id: message.id | libc::CAN_EFF_FLAG,
+            can_mask: libc::CAN_EFF_FLAG | libc::CAN_RTR_FLAG | libc::CAN_EFF_MASK,
+        }
+    } else {
+        libc::can_filter {
+            can_id: message.id,
+            can_mask: libc::CAN_EFF_FLAG | libc::CAN_RTR_FLAG | libc::CAN_SFF_MASK,
+        }
+    };
+    // SAFETY: the option value points to a `can_filter` of the given size
+    let result = unsafe {
+        libc::setsockopt(
+            fd.as_raw_fd(),
+            libc::SOL_CAN_RAW,
+            libc::CAN_RAW_FILTER,
+            &filter as *const libc::can_filter as *const libc::c_void,
+            size_of::<libc::can_filter>() as libc::socklen_t,
+        )
+    };
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // SAFETY: an all-zero `sockaddr_can` is valid, the family and interface are set below
+    let mut address: libc::sockaddr_can = unsafe { zeroed() };
+    address.can_family = libc::AF_CAN as libc::sa_family_t;
+    address.can_ifindex = ifindex as libc::c_int;
+    // SAFETY: the address points to a `sockaddr_can` of the given size
+    let result = unsafe {
+        libc::bind(
+            fd.as_raw_fd(),
+            &address as *const libc::sockaddr_can as *const libc::sockaddr,
+            size_of::<libc::sockaddr_can>() as libc::socklen_t,
+        )
+    };
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(fd)
+}
+
+/// Read one frame, returning its payload
+fn read_frame(fd: &OwnedFd) -> io::Result<Vec<u8>> {
+    // SAFETY: an all-zero `can_frame` is valid
+    let mut frame: libc::can_frame = unsafe { zeroed() };
+    // SAFETY: the buffer is a `can_frame` of the given size
+    let len = unsafe {
+        libc::read(
+            fd.as_raw_fd(),
+            &mut frame as *mut libc::can_frame as *mut libc::c_void,
+            size_of::<libc::can_frame>(),
+        )
+    };
+    if len < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let dlc = (frame.can_dlc as usize).min(frame.data.len());
+    Ok(frame.data[..dlc].to_vec())
+}
+
+async fn read_frames(
+    interface: String,
+    message: Message,
+    tx: Sender<DdsData>,
+) -> anyhow::Result<()> {
+    let socket = AsyncFd::new(open_socket(&interface, &message)?)?;
+    info!("Receiving CAN message '{}' on {}", message.name, interface);
+
+    loop {
+        let mut guard = socket.readable().await?;
+        let data = match guard.try_io(|socket| read_frame(socket.get_ref())) {
+            Ok(Ok(data)) => data,
+            Ok(Err(e)) => return Err(e.into()),
+            Err(_would_block) => continue,
+        };
+        let Some(value) = message.decode(&data) else {
+            warn!(
+                "Frame of '{}' too short: {} bytes",
+                message.name,
+                data.len()
+            );
+            continue;
+        };
+        if tx
+            .send(DdsData::from_json(&message.name, value.to_string()))
+            .await
+            .is_err()
+        {
+            return Ok(());
+        }
+    }
+}
+
+#[async_trait]
+impl SignalSource for CanSource {
+    fn is_running(&self) -> bool {
+        self.task.is_running()
+    }
+
+    async fn start(&mut self) -> Result<()> {
+        if !self.task.is_running() {
+            let reader = read_frames(
+                self.interface.clone(),
+                self.message.clone(),
+                self.tx.clone(),
+            );
+            self.task.spawn(self.message.name.clone(), reader);
+        }
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        self.task.stop();
+        Ok(())
+    }
+
+    fn get_signal_name(&self) -> &str {
+        &self.message.name
+    }
+
+    fn is_signal(&self, signal_name: &str) -> bool {
+        self.message.name == signal_name
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::path::Path;
+    use tokio::sync::mpsc;
+    use tokio::time::{timeout, Duration};
+
+    const DBC: &str = r#"
+BO_ 256 GearStatus: 8 Gateway
+ SG_ Gear : 0|4@1+ (1,0) [0|15] "" Body
+"#;
+
+    fn settings(dir: &Path) -> CanSettings {
+        let path = dir.join("vehicle.dbc");
+        std::fs::File::create(&path)
+            .unwrap()
+            .write_all(DBC.as_bytes())
+            .unwrap();
+        CanSettings {
+            interface: "vcan0".to_string(),
+            dbc: path,
+        }
+    }
+
+    #[test]
+    fn test_can_source_needs_known_message() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = settings(dir.path());
+        assert!(CanSource::new("GearStatus".to_string(), &settings, mpsc::channel(1).0).is_ok());
+        assert!(CanSource::new("DoorStatus".to_string(), &settings, mpsc::channel(1).0).is_err());
+    }
+
+    // Needs a `vcan0` interface, see the module documentation
+    #[tokio::test]
+    async fn test_can_source_reads_vcan() {
+        if !Path::new("/sys/class/net/vcan0").exists() {
+            eprintln!("vcan0 not available, skipping");
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, mut rx) = mpsc::channel(10);
+        let mut source =
+            CanSource::new("GearStatus".to_string(), &settings(dir.path()), tx).unwrap();
+        source.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let writer = open_socket("vcan0", &source.message).unwrap();
+        // SAFETY: an all-zero `can_frame` is valid
+        let mut frame: libc::can_frame = unsafe { zeroed() };
+        frame.can_id = 256;
+        frame.can_dlc = 8;
+        frame.data[0] = 3;
+        // SAFETY: the buffer is a `can_frame` of the given size
+        let written = unsafe {
+            libc::write(
+                writer.as_raw_fd(),
+                &frame as *const libc::can_frame as *const libc::c_void,
+                size_of::<libc::can_frame>(),
+            )
+        };
+        assert_eq!(written as usize, size_of::<libc::can_frame>());
+
+        let data = timeout(Duration::from_secs(2), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(data.name, "GearStatus");
+        assert_eq!(data.fields.get("Gear").map(String::as_str), Some("3"));
+        source.stop().await.unwrap();
+    }
+}
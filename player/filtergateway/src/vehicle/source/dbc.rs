@@ -0,0 +1,308 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! DBC files describing the signals of CAN frames
+//!
+//! Only the `BO_` message and `SG_` signal lines are read. Signals may be
+//! little-endian (`@1`) or big-endian (`@0`), signed, scaled, and
+//! multiplexed by a `M` signal of the same message.
+
+use serde_json::{Map, Number, Value};
+use std::collections::BTreeMap;
+
+/// Bit set in a DBC message ID for 29-bit identifiers
+const EXTENDED_ID: u32 = 0x8000_0000;
+
+/// Signal of a CAN message
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signal {
+    pub name: String,
+    pub start_bit: u32,
+    pub length: u32,
+    pub little_endian: bool,
+    pub signed: bool,
+    pub factor: f64,
+    pub offset: f64,
+    /// `Some(None)` for the multiplexer, `Some(Some(n))` for a signal only
+    /// present when the multiplexer is `n`
+    pub multiplex: Option<Option<u64>>,
+}
+
+/// CAN message
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub name: String,
+    /// CAN identifier, without the DBC extended flag
+    pub id: u32,
+    pub extended: bool,
+    pub signals: Vec<Signal>,
+}
+
+/// Messages of a DBC file, by name
+#[derive(Debug, Clone, Default)]
+pub struct Dbc {
+    pub messages: BTreeMap<String, Message>,
+}
+
+impl Dbc {
+    /// Parse the content of a DBC file
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - DBC text
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Dbc, String>` - The messages or the first malformed line
+    pub fn parse(source: &str) -> Result<Dbc, String> {
+        let mut dbc = Dbc::default();
+        let mut current: Option<String> = None;
+
+        for (number, line) in source.lines().enumerate() {
+            let line = line.trim();
+            let error = |e: String| format!("line {}: {}", number + 1, e);
+            if let Some(rest) = line.strip_prefix("BO_ ") {
+                let message = parse_message(rest).map_err(error)?;
+                current = Some(message.name.clone());
+                dbc.messages.insert(message.name.clone(), message);
+            } else if let Some(rest) = line.strip_prefix("SG_ ") {
+                let signal = parse_signal(rest).map_err(error)?;
+                let message = current
+                    .as_ref()
+                    .and_then(|name| dbc.messages.get_mut(name))
+                    .ok_or_else(|| error("signal outside of a message".to_string()))?;
+                message.signals.push(signal);
+            } else if !line.is_empty() {
+                current = None;
+            }
+        }
+        Ok(dbc)
+    }
+}
+
+/// `<id> <name>: <dlc> <transmitter>`
+fn parse_message(rest: &str) -> Result<Message, String> {
+    let (head, _) = rest
+        .split_once(':')
+        .ok_or_else(|| "missing ':' in message".to_string())?;
+    let mut parts = head.split_whitespace();
+    let id: u32 = parts
+        .next()
+        .and_then(|id| id.parse().ok())
+        .ok_or_else(|| "invalid message ID".to_string())?;
+    let name = parts
+        .next()
+        .ok_or_else(|| "missing message name".to_string())?;
+    Ok(Message {
+        name: name.to_string(),
+        id: id & !EXTENDED_ID,
+        extended: id & EXTENDED_ID != 0,
+        signals: Vec::new(),
+    })
+}
+
+/// `<name> [M|m<n>] : <start>|<length>@<order><sign> (<factor>,<offset>) [<min>|<max>] "<unit>" <receivers>`
+fn parse_signal(rest: &str) -> Result<Signal, String> {
+    let (head, layout) = rest
+        .split_once(':')
+        .ok_or_else(|| "missing ':' in signal".to_string())?;
+    let mut head = head.split_whitespace();
+    let name = head
+        .next()
+        .ok_or_else(|| "missing signal name".to_string())?;
+    let multiplex = match head.next() {
+        None => None,
+        Some("M") => Some(None),
+        Some(m) => Some(Some(
+            m.strip_prefix('m')
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(|| format!("invalid multiplexer '{}'", m))?,
+        )),
+    };
+
+    let invalid = || format!("invalid layout of signal '{}'", name);
+    let layout = layout.trim();
+    let (bits, scale) = layout.split_once(' ').ok_or_else(invalid)?;
+    let (start_bit, rest) = bits.split_once('|').ok_or_else(invalid)?;
+    let (length, format) = rest.split_once('@').ok_or_else(invalid)?;
+    let mut format = format.chars();
+    let little_endian = match format.next() {
+        Some('1') => true,
+        Some('0') => false,
+        _ => return Err(invalid()),
+    };
+    let signed = match format.next() {
+        Some('-') => true,
+        Some('+') => false,
+        _ => return Err(invalid()),
+    };
+
+    let scale = scale.trim_start();
+    let (factor, offset) = scale
+        .strip_prefix('(')
+        .and_then(|s| s.split_once(')'))
+        .and_then(|(s, _)| s.split_once(','))
+        .ok_or_else(invalid)?;
+
+    let signal = Signal {
+        name: name.to_string(),
+        start_bit: start_bit.parse().map_err(|_| invalid())?,
+        length: length.parse().map_err(|_| invalid())?,
+        little_endian,
+        signed,
+        factor: factor.trim().parse().map_err(|_| invalid())?,
+        offset: offset.trim().parse().map_err(|_| invalid())?,
+        multiplex,
+    };
+    if signal.length == 0 || signal.length > 64 {
+        return Err(invalid());
+    }
+    Ok(signal)
+}
+
+impl Signal {
+    /// Raw bits of the signal in a frame, `None` if the frame is too short
+    pub fn raw(&self, data: &[u8]) -> Option<u64> {
+        let mut raw = 0u64;
+        if self.little_endian {
+            for i in (0..self.length).rev() {
+                let bit = self.start_bit + i;
+                let byte = *data.get((bit / 8) as usize)?;
+                raw = (raw << 1) | ((byte >> (bit % 8)) & 1) as u64;
+            }
+        } else {
+            // The start bit is the most significant one, numbered within its byte
+            let mut bit = self.start_bit;
+            for _ in 0..self.length {
+                let byte = *data.get((bit / 8) as usize)?;
+                raw = (raw << 1) | ((byte >> (bit % 8)) & 1) as u64;
+                bit = if bit.is_multiple_of(8) {
+                    bit + 15
+                } else {
+                    bit - 1
+                };
+            }
+        }
+        Some(raw)
+    }
+
+    /// Physical value of the signal in a frame
+    pub fn decode(&self, data: &[u8]) -> Option<Value> {
+        let raw = self.raw(data)?;
+        let raw = if self.signed && self.length < 64 && raw >> (self.length - 1) & 1 == 1 {
+            (raw | (u64::MAX << self.length)) as i64
+        } else {
+            raw as i64
+        };
+
+        if self.factor == 1.0 && self.offset == 0.0 {
+            return Some(if self.signed {
+                Value::from(raw)
+            } else {
+                Value::from(raw as u64)
+            });
+        }
+        let value = raw as f64 * self.factor + self.offset;
+        Some(Number::from_f64(value).map_or(Value::Null, Value::Number))
+    }
+}
+
+impl Message {
+    /// Decode the signals present in a frame of the message
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Frame payload
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Value>` - The signals as a JSON object, `None` if the frame
+    ///   is shorter than the signals
+    pub fn decode(&self, data: &[u8]) -> Option<Value> {
+        let multiplexer = match self.signals.iter().find(|s| s.multiplex == Some(None)) {
+            Some(signal) => Some(signal.raw(data)?),
+            None => None,
+        };
+
+        let mut object = Map::new();
+        for signal in &self.signals {
+            if let Some(Some(n)) = signal.multiplex {
+                if multiplexer != Some(n) {
+                    continue;
+                }
+            }
+            object.insert(signal.name.clone(), signal.decode(data)?);
+        }
+        Some(Value::Object(object))
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const DBC: &str = r#"
+VERSION ""
+
+BU_: Body Gateway
+
+BO_ 256 GearStatus: 8 Gateway
+ SG_ Gear : 0|4@1+ (1,0) [0|15] "" Body
+ SG_ Speed : 8|16@1+ (0.01,0) [0|655.35] "km/h" Body
+ SG_ Temperature : 24|8@1- (1,-40) [-168|87] "degC" Body
+
+BO_ 2566914048 DoorStatus: 8 Body
+ SG_ Counter : 7|12@0+ (1,0) [0|4095] "" Gateway
+ SG_ Page M : 16|2@1+ (1,0) [0|3] "" Gateway
+ SG_ FrontLeft m0 : 24|1@1+ (1,0) [0|1] "" Gateway
+ SG_ RearLeft m1 : 24|1@1+ (1,0) [0|1] "" Gateway
+
+CM_ SG_ 256 Gear "Selected gear";
+"#;
+
+    #[test]
+    fn test_parse_dbc() {
+        let dbc = Dbc::parse(DBC).unwrap();
+        let gear = &dbc.messages["GearStatus"];
+        assert_eq!(gear.id, 256);
+        assert!(!gear.extended);
+        assert_eq!(gear.signals.len(), 3);
+        assert_eq!(gear.signals[1].factor, 0.01);
+        assert!(gear.signals[2].signed);
+
+        let door = &dbc.messages["DoorStatus"];
+        assert_eq!(door.id, 0x1900_0000);
+        assert!(door.extended);
+        assert!(!door.signals[0].little_endian);
+        assert_eq!(door.signals[1].multiplex, Some(None));
+        assert_eq!(door.signals[3].multiplex, Some(Some(1)));
+
+        assert!(Dbc::parse(" SG_ Orphan : 0|1@1+ (1,0) [0|1] \"\" X").is_err());
+        assert!(Dbc::parse("BO_ 1 M: 8 X\n SG_ S : 0|1@2+ (1,0) [0|1] \"\" X").is_err());
+    }
+
+    #[test]
+    fn test_decode_message() {
+        let dbc = Dbc::parse(DBC).unwrap();
+
+        // Gear 3, speed 12.34 km/h, temperature -41 (raw 0xFF = -1)
+        let frame = [0x03, 0xD2, 0x04, 0xFF, 0, 0, 0, 0];
+        assert_eq!(
+            dbc.messages["GearStatus"].decode(&frame),
+            Some(json!({"Gear": 3, "Speed": 12.34, "Temperature": -41.0}))
+        );
+
+        // Big-endian counter 0xABC over bits 7..0 and 15..12, page 1
+        let frame = [0xAB, 0xC0, 0x01, 0x01, 0, 0, 0, 0];
+        assert_eq!(
+            dbc.messages["DoorStatus"].decode(&frame),
+            Some(json!({"Counter": 0xABC, "Page": 1, "RearLeft": 1}))
+        );
+
+        assert!(dbc.messages["GearStatus"].decode(&[0x03]).is_none());
+    }
+}
@@ -0,0 +1,285 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Vehicle signals that do not come from DDS
+//!
+//! The `type` of a condition operand selects where its signal is read from.
+//! `DDS` topics are handled by `DdsManager`; the other types are read by a
+//! `SignalSource` configured in the `sources` section of the settings:
+//!
+//! * `SOMEIP` - SOME/IP notifications received over UDP
+//! * `CAN` - SocketCAN frames decoded with a DBC file
+//! * `MQTT` - JSON messages of an MQTT broker
+//! * `UNIX` - JSON lines of a local Unix socket
+//! * `Timer` and `Schedule` - filtergateway's own clock, see `timer`
+//!
+//! Any other type, such as `DDS`, names a DDS topic.
+//!
+//! Every source sends its samples as `DdsData` named after the operand
+//! `value`, so filters evaluate them like DDS samples.
+
+pub mod can;
+pub mod dbc;
+pub mod mqtt;
+pub mod someip;
+pub mod timer;
+pub mod unix;
+
+use crate::vehicle::dds::DdsData;
+use async_trait::async_trait;
+use common::Result;
+use log::{error, info};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+use tokio::task::JoinHandle;
+
+/// Delay before reconnecting a source whose connection was lost
+pub const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Reader of one vehicle signal
+#[async_trait]
+pub trait SignalSource: Send + Sync {
+    fn is_running(&self) -> bool;
+    async fn start(&mut self) -> Result<()>;
+    async fn stop(&mut self) -> Result<()>;
+    fn get_signal_name(&self) -> &str;
+    fn is_signal(&self, signal_name: &str) -> bool;
+}
+
+/// Where the signal of a condition operand is read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceKind {
+    Dds,
+    SomeIp,
+    Can,
+    Mqtt,
+    Unix,
+    Timer,
+    Schedule,
+}
+
+impl SourceKind {
+    /// Source of an operand from its `type`, case-insensitively
+    ///
+    /// Types other than the ones of the non-DDS sources name DDS topics, as
+    /// they always did.
+    ///
+    /// # Arguments
+    ///
+    /// * `operand_type` - `type` of the operand
+    ///
+    /// # Returns
+    ///
+    /// * `SourceKind` - Where the signal is read from
+    pub fn from_operand_type(operand_type: &str) -> SourceKind {
+        match operand_type.to_ascii_uppercase().as_str() {
+            "SOMEIP" | "SOME/IP" => SourceKind::SomeIp,
+            "CAN" => SourceKind::Can,
+            "MQTT" => SourceKind::Mqtt,
+            "UNIX" => SourceKind::Unix,
+            "TIMER" => SourceKind::Timer,
+            "SCHEDULE" => SourceKind::Schedule,
+            _ => SourceKind::Dds,
+        }
+    }
+}
+
+/// `sources` section of the settings
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SourceSettings {
+    #[serde(default)]
+    pub someip: Option<someip::SomeIpSettings>,
+    #[serde(default)]
+    pub can: Option<can::CanSettings>,
+    #[serde(default)]
+    pub mqtt: Option<mqtt::MqttSettings>,
+    #[serde(default)]
+    pub unix: Option<unix::UnixSettings>,
+}
+
+/// Background task of a source
+#[derive(Default)]
+pub struct SourceTask {
+    handle: Option<JoinHandle<()>>,
+}
+
+impl SourceTask {
+    /// Run the reading loop of a signal until it fails or is stopped
+    pub fn spawn<F>(&mut self, signal_name: String, reader: F)
+    where
+        F: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        self.handle = Some(tokio::spawn(async move {
+            if let Err(e) = reader.await {
+                error!("Error reading signal '{}': {:?}", signal_name, e);
+            }
+        }));
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.abort();
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.handle.is_some()
+    }
+}
+
+/// Non-DDS sources of the subscribed signals
+pub struct SourceManager {
+    /// Active sources (signal name → source)
+    sources: HashMap<String, Box<dyn SignalSource>>,
+    /// Channel for sending samples
+    tx: Sender<DdsData>,
+    /// Configuration of each source type
+    settings: SourceSettings,
+}
+
+impl SourceManager {
+    /// Create a source manager without configured sources
+    pub fn new(tx: Sender<DdsData>) -> Self {
+        Self {
+            sources: HashMap::new(),
+            tx,
+            settings: SourceSettings::default(),
+        }
+    }
+
+    /// Read the `sources` section of the settings
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - Whole settings document
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - Error if the section is malformed
+    pub fn load_settings(&mut self, settings: &serde_json::Value) -> Result<()> {
+        if let Some(sources) = settings.get("sources") {
+            self.settings = serde_json::from_value(sources.clone())?;
+            info!("Signal sources from settings: {:?}", self.settings);
+        }
+        Ok(())
+    }
+
+    /// Start reading a signal
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - Source of the signal, not `SourceKind::Dds`
+    /// * `signal_name` - `value` of the condition operand
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - Error if the source is not configured or cannot start
+    pub async fn create_source(&mut self, kind: SourceKind, signal_name: String) -> Result<()> {
+        if self.sources.contains_key(&signal_name) {
+            return Ok(());
+        }
+
+        let tx = self.tx.clone();
+        let not_configured = || format!("{:?} source is not configured", kind);
+        let mut source: Box<dyn SignalSource> = match kind {
+            SourceKind::SomeIp => {
+                let settings = self.settings.someip.as_ref().ok_or_else(not_configured)?;
+                Box::new(someip::SomeIpSource::new(
+                    signal_name.clone(),
+                    settings,
+                    tx,
+                )?)
+            }
+            SourceKind::Can => {
+                let settings = self.settings.can.as_ref().ok_or_else(not_configured)?;
+                Box::new(can::CanSource::new(signal_name.clone(), settings, tx)?)
+            }
+            SourceKind::Mqtt => {
+                let settings = self.settings.mqtt.as_ref().ok_or_else(not_configured)?;
+                Box::new(mqtt::MqttSource::new(signal_name.clone(), settings, tx))
+            }
+            SourceKind::Unix => {
+                let settings = self.settings.unix.as_ref().ok_or_else(not_configured)?;
+                Box::new(unix::UnixSource::new(signal_name.clone(), settings, tx))
+            }
+            SourceKind::Timer | SourceKind::Schedule => {
+                Box::new(timer::TimerSource::new(kind, signal_name.clone(), tx)?)
+            }
+            SourceKind::Dds => return Err("DDS topics are read by DdsManager".into()),
+        };
+
+        source.start().await?;
+        info!("Started {:?} source for signal '{}'", kind, signal_name);
+        self.sources.insert(signal_name, source);
+        Ok(())
+    }
+
+    /// Stop reading a signal
+    ///
+    /// # Returns
+    ///
+    /// * `Result<bool>` - Whether a source was reading the signal
+    pub async fn remove_source(&mut self, signal_name: &str) -> Result<bool> {
+        match self.sources.remove(signal_name) {
+            Some(mut source) => {
+                source.stop().await?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn test_source_kind_from_operand_type() {
+        assert_eq!(SourceKind::from_operand_type("DDS"), SourceKind::Dds);
+        assert_eq!(SourceKind::from_operand_type("some/ip"), SourceKind::SomeIp);
+        assert_eq!(SourceKind::from_operand_type("Can"), SourceKind::Can);
+        assert_eq!(SourceKind::from_operand_type("mqtt"), SourceKind::Mqtt);
+        assert_eq!(SourceKind::from_operand_type("UNIX"), SourceKind::Unix);
+        assert_eq!(SourceKind::from_operand_type("Timer"), SourceKind::Timer);
+        assert_eq!(
+            SourceKind::from_operand_type("schedule"),
+            SourceKind::Schedule
+        );
+        assert_eq!(SourceKind::from_operand_type("plain"), SourceKind::Dds);
+    }
+
+    #[tokio::test]
+    async fn test_source_manager_lifecycle() {
+        let (tx, _rx) = mpsc::channel(10);
+        let mut manager = SourceManager::new(tx);
+
+        // Sources must be configured before use
+        assert!(manager
+            .create_source(SourceKind::Unix, "door".to_string())
+            .await
+            .is_err());
+        assert!(manager
+            .create_source(SourceKind::Dds, "door".to_string())
+            .await
+            .is_err());
+
+        manager
+            .load_settings(&json!({ "sources": { "unix": { "path": "/nonexistent/feed.sock" } } }))
+            .unwrap();
+        manager
+            .create_source(SourceKind::Unix, "door".to_string())
+            .await
+            .unwrap();
+        assert!(manager.remove_source("door").await.unwrap());
+        assert!(!manager.remove_source("door").await.unwrap());
+    }
+}
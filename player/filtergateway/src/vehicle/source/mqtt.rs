@@ -0,0 +1,380 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Signals of an MQTT broker
+//!
+//! The operand `value` is the MQTT topic of the signal, which may contain the
+//! `+` and `#` wildcards. Each source is a minimal MQTT 3.1.1 client
+//! subscribing to its topic; a JSON object payload is used as the sample
+//! fields, any other payload becomes a `value` field. The client reconnects
+//! when the broker connection is lost.
+
+use super::{SignalSource, SourceTask, RECONNECT_DELAY};
+use crate::vehicle::dds::DdsData;
+use anyhow::{anyhow, bail};
+use async_trait::async_trait;
+use common::Result;
+use log::{info, warn};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::{self, Sender};
+use tokio::task::JoinSet;
+use tokio::time::{interval_at, Duration, Instant};
+
+/// Keep-alive interval announced to the broker
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const PUBACK: u8 = 0x40;
+const SUBSCRIBE: u8 = 0x82;
+const SUBACK: u8 = 0x90;
+const PINGREQ: u8 = 0xC0;
+
+fn default_client_id() -> String {
+    "filtergateway".to_string()
+}
+
+/// `sources.mqtt` settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MqttSettings {
+    /// Broker address, e.g. `127.0.0.1:1883`
+    pub broker: String,
+    /// Prefix of the client identifiers, completed with the topic
+    #[serde(default = "default_client_id")]
+    pub client_id: String,
+}
+
+/// Subscriber of one MQTT topic
+pub struct MqttSource {
+    topic: String,
+    settings: MqttSettings,
+    tx: Sender<DdsData>,
+    task: SourceTask,
+}
+
+impl MqttSource {
+    pub fn new(signal_name: String, settings: &MqttSettings, tx: Sender<DdsData>) -> Self {
+        Self {
+            topic: signal_name,
+            settings: settings.clone(),
+            tx,
+            task: SourceTask::default(),
+        }
+    }
+}
+
+/// Append a length-prefixed string
+fn put_str(buf: &mut Vec<u8>, s: &str) {
+    buf.extend_from_slice(&(s.len() as u16).to_be_bytes());
+    buf.extend_from_slice(s.as_bytes());
+}
+
+/// Build a packet from its first byte and body
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = vec![header];
+    let mut len = body.len();
+    loop {
+        let byte = (len % 128) as u8;
+        len /= 128;
+        if len == 0 {
+            packet.push(byte);
+            break;
+        }
+        packet.push(byte | 0x80);
+    }
+    packet.extend_from_slice(body);
+    packet
+}
+
+/// Read a packet, returning its first byte and body
+///
+/// Not cancel safe: a packet partially read when the future is dropped is
+/// lost and the stream is no longer framed.
+async fn read_packet<R: AsyncRead + Unpin>(stream: &mut R) -> anyhow::Result<(u8, Vec<u8>)> {
+    let header = stream.read_u8().await?;
+    let mut len = 0usize;
+    for shift in 0..4 {
+        let byte = stream.read_u8().await?;
+        len |= ((byte & 0x7F) as usize) << (7 * shift);
+        if byte & 0x80 == 0 {
+            let mut body = vec![0; len];
+            stream.read_exact(&mut body).await?;
+            return Ok((header, body));
+        }
+    }
+    bail!("malformed remaining length")
+}
+
+/// Topic and payload of a PUBLISH packet, acknowledged if needed
+async fn parse_publish<W: AsyncWrite + Unpin>(
+    stream: &mut W,
+    header: u8,
+    body: &[u8],
+) -> anyhow::Result<(String, Vec<u8>)> {
+    let invalid = || anyhow!("malformed PUBLISH");
+    let len = u16::from_be_bytes(body.get(..2).ok_or_else(invalid)?.try_into()?) as usize;
+    let topic = std::str::from_utf8(body.get(2..2 + len).ok_or_else(invalid)?)?.to_string();
+    let mut rest = &body[2 + len..];
+    if (header >> 1) & 0x03 > 0 {
+        let id = rest.get(..2).ok_or_else(invalid)?;
+        stream.write_all(&packet(PUBACK, id)).await?;
+        rest = &rest[2..];
+    }
+    Ok((topic, rest.to_vec()))
+}
+
+/// Whether a topic name matches a topic filter with `+` and `#` wildcards
+fn topic_matches(filter: &str, name: &str) -> bool {
+    // Wildcards at the first level do not match system topics
+    if name.starts_with('$') && !filter.starts_with('$') {
+        return false;
+    }
+    let mut levels = name.split('/');
+    for level in filter.split('/') {
+        match (level, levels.next()) {
+            ("#", _) => return true,
+            ("+", Some(_)) => {}
+            (level, Some(name)) if level == name => {}
+            _ => return false,
+        }
+    }
+    levels.next().is_none()
+}
+
+/// Client identifier of the subscriber of a topic
+///
+/// Brokers only have to accept letters and digits, so the other characters
+/// of the topic, such as `/` and wildcards, are replaced.
+fn client_id(prefix: &str, topic: &str) -> String {
+    let topic: String = topic
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    format!("{}-{}", prefix, topic)
+}
+
+/// Sample fields of a payload
+fn payload_value(payload: &[u8]) -> Value {
+    match serde_json::from_slice::<Value>(payload) {
+        Ok(value @ Value::Object(_)) => value,
+        Ok(value) => serde_json::json!({ "value": value }),
+        Err(_) => serde_json::json!({ "value": String::from_utf8_lossy(payload) }),
+    }
+}
+
+/// Connect and subscribe to the topic
+async fn connect(settings: &MqttSettings, topic: &str) -> anyhow::Result<TcpStream> {
+    let mut stream = TcpStream::connect(&settings.broker).await?;
+
+    let mut body = Vec::new();
+    put_str(&mut body, "MQTT");
+    body.push(4); // protocol level 3.1.1
+    body.push(0x02); // clean session
+    body.extend_from_slice(&(KEEP_ALIVE.as_secs() as u16).to_be_bytes());
+    put_str(&mut body, &client_id(&settings.client_id, topic));
+    stream.write_all(&packet(CONNECT, &body)).await?;
+
+    let (header, body) = read_packet(&mut stream).await?;
+    if header != CONNACK || body.get(1) != Some(&0) {
+        bail!("connection refused: {:?}", body);
+    }
+
+    let mut body = vec![0, 1]; // packet identifier
+    put_str(&mut body, topic);
+    body.push(0); // QoS 0
+    stream.write_all(&packet(SUBSCRIBE, &body)).await?;
+
+    let (header, body) = read_packet(&mut stream).await?;
+    if header != SUBACK || body.get(2).is_none_or(|code| *code == 0x80) {
+        bail!("subscription to '{}' refused", topic);
+    }
+    Ok(stream)
+}
+
+/// Forward the messages of the topic until the connection is lost or
+/// nothing receives samples anymore
+async fn receive(stream: TcpStream, topic: &str, tx: &Sender<DdsData>) -> anyhow::Result<()> {
+    let (mut reader, mut writer) = stream.into_split();
+
+    // Packets are read in their own task since read_packet cannot be raced
+    // against the keep-alive timer; the task is aborted with the set.
+    let (packet_tx, mut packets) = mpsc::channel(16);
+    let mut read_task = JoinSet::new();
+    read_task.spawn(async move {
+        loop {
+            let packet = read_packet(&mut reader).await;
+            let failed = packet.is_err();
+            if packet_tx.send(packet).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    let mut ping = interval_at(Instant::now() + KEEP_ALIVE / 2, KEEP_ALIVE / 2);
+    loop {
+        tokio::select! {
+            packet = packets.recv() => {
+                let (header, body) = packet.ok_or_else(|| anyhow!("packet reader stopped"))??;
+                if header & 0xF0 != PUBLISH {
+                    continue;
+                }
+                let (name, payload) = parse_publish(&mut writer, header, &body).await?;
+                if !topic_matches(topic, &name) {
+                    continue;
+                }
+                let value = payload_value(&payload);
+                if tx.send(DdsData::from_json(topic, value.to_string())).await.is_err() {
+                    return Ok(());
+                }
+            }
+            _ = ping.tick() => {
+                writer.write_all(&packet(PINGREQ, &[])).await?;
+            }
+        }
+    }
+}
+
+async fn subscribe(
+    topic: String,
+    settings: MqttSettings,
+    tx: Sender<DdsData>,
+) -> anyhow::Result<()> {
+    loop {
+        match connect(&settings, &topic).await {
+            Ok(stream) => {
+                info!(
+                    "Subscribed to MQTT topic '{}' on {}",
+                    topic, settings.broker
+                );
+                match receive(stream, &topic, &tx).await {
+                    Ok(()) => return Ok(()),
+                    Err(e) => warn!("MQTT connection for '{}' lost: {}", topic, e),
+                }
+            }
+            Err(e) => warn!("Cannot connect to MQTT broker {}: {}", settings.broker, e),
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+#[async_trait]
+impl SignalSource for MqttSource {
+    fn is_running(&self) -> bool {
+        self.task.is_running()
+    }
+
+    async fn start(&mut self) -> Result<()> {
+        if !self.task.is_running() {
+            let reader = subscribe(self.topic.clone(), self.settings.clone(), self.tx.clone());
+            self.task.spawn(self.topic.clone(), reader);
+        }
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        self.task.stop();
+        Ok(())
+    }
+
+    fn get_signal_name(&self) -> &str {
+        &self.topic
+    }
+
+    fn is_signal(&self, signal_name: &str) -> bool {
+        self.topic == signal_name
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+    use tokio::time::timeout;
+
+    #[test]
+    fn test_payload_value() {
+        assert_eq!(
+            payload_value(br#"{"speed": 42}"#),
+            serde_json::json!({"speed": 42})
+        );
+        assert_eq!(payload_value(b"42"), serde_json::json!({"value": 42}));
+        assert_eq!(payload_value(b"on"), serde_json::json!({"value": "on"}));
+    }
+
+    #[test]
+    fn test_topic_matches() {
+        assert!(topic_matches("vehicle/speed", "vehicle/speed"));
+        assert!(!topic_matches("vehicle/speed", "vehicle/speed/front"));
+        assert!(topic_matches("vehicle/+/speed", "vehicle/front/speed"));
+        assert!(!topic_matches("vehicle/+", "vehicle/front/speed"));
+        assert!(topic_matches("vehicle/#", "vehicle"));
+        assert!(topic_matches("vehicle/#", "vehicle/front/speed"));
+        assert!(topic_matches("#", "vehicle/speed"));
+        assert!(!topic_matches("#", "$SYS/uptime"));
+        assert!(!topic_matches("+/uptime", "$SYS/uptime"));
+    }
+
+    #[test]
+    fn test_client_id() {
+        assert_eq!(
+            client_id("filtergateway", "vehicle/+/speed"),
+            "filtergateway-vehicle---speed"
+        );
+        assert_eq!(client_id("fg", "door1"), "fg-door1");
+    }
+
+    #[tokio::test]
+    async fn test_mqtt_source_receives_publish() {
+        let broker = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let settings = MqttSettings {
+            broker: broker.local_addr().unwrap().to_string(),
+            client_id: default_client_id(),
+        };
+        let (tx, mut rx) = mpsc::channel(10);
+        let mut source = MqttSource::new("vehicle/speed".to_string(), &settings, tx);
+        source.start().await.unwrap();
+
+        // Fake broker accepting the connection and the subscription
+        let (mut client, _) = broker.accept().await.unwrap();
+        let (header, body) = read_packet(&mut client).await.unwrap();
+        assert_eq!(header, CONNECT);
+        assert!(body.ends_with(b"filtergateway-vehicle-speed"));
+        client.write_all(&packet(CONNACK, &[0, 0])).await.unwrap();
+
+        let (header, body) = read_packet(&mut client).await.unwrap();
+        assert_eq!(header, SUBSCRIBE);
+        assert!(body.ends_with(b"vehicle/speed\0"));
+        client.write_all(&packet(SUBACK, &[0, 1, 0])).await.unwrap();
+
+        // QoS 1 message, which must be acknowledged
+        let mut body = Vec::new();
+        put_str(&mut body, "vehicle/speed");
+        body.extend_from_slice(&[0, 7]);
+        body.extend_from_slice(br#"{"speed": 42}"#);
+        client
+            .write_all(&packet(PUBLISH | 0x02, &body))
+            .await
+            .unwrap();
+
+        let data = timeout(Duration::from_secs(2), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(data.name, "vehicle/speed");
+        assert_eq!(data.fields.get("speed").map(String::as_str), Some("42"));
+
+        let (header, body) = read_packet(&mut client).await.unwrap();
+        assert_eq!((header, body), (PUBACK, vec![0, 7]));
+
+        source.stop().await.unwrap();
+    }
+}
@@ -0,0 +1,370 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Signals of SOME/IP notifications received over UDP
+//!
+//! Each event of the `sources.someip` settings names a service and event ID
+//! and the layout of its payload. Fields are read big-endian, as SOME/IP
+//! serializes them; an event without fields carries a JSON payload.
+//!
+//! Sources of several events share the UDP port. Events sent to a multicast
+//! `group` reach every source; unicast notifications are only delivered to
+//! one socket of the port, so each unicast event needs its own `bind` port.
+
+use super::{SignalSource, SourceTask};
+use crate::vehicle::dds::DdsData;
+use async_trait::async_trait;
+use common::Result;
+use log::{info, warn};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::net::{Ipv4Addr, SocketAddr};
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc::Sender;
+
+/// SOME/IP header length up to the payload
+const HEADER_LEN: usize = 16;
+/// Message type of a notification
+const NOTIFICATION: u8 = 0x02;
+
+/// `sources.someip` section of the settings
+#[derive(Debug, Clone, Deserialize)]
+pub struct SomeIpSettings {
+    /// Local address notifications are received on
+    #[serde(default = "default_bind")]
+    pub bind: SocketAddr,
+    /// Multicast group the notifications are sent to
+    #[serde(default)]
+    pub group: Option<Ipv4Addr>,
+    pub events: Vec<SomeIpEvent>,
+}
+
+fn default_bind() -> SocketAddr {
+    SocketAddr::from(([0, 0, 0, 0], 30490))
+}
+
+/// Event read as a signal
+#[derive(Debug, Clone, Deserialize)]
+pub struct SomeIpEvent {
+    /// Signal name used by condition operands
+    pub name: String,
+    pub service: u16,
+    pub event: u16,
+    /// Local address of this event, instead of the shared one
+    #[serde(default)]
+    pub bind: Option<SocketAddr>,
+    /// Payload layout, a JSON payload if empty
+    #[serde(default)]
+    pub fields: Vec<PayloadField>,
+}
+
+/// Field of a notification payload
+#[derive(Debug, Clone, Deserialize)]
+pub struct PayloadField {
+    pub name: String,
+    /// `bool`, `u8`, `i8`, `u16`, `i16`, `u32`, `i32`, `u64`, `i64`, `f32` or `f64`
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Byte offset in the payload
+    pub offset: usize,
+}
+
+/// Size of a payload field type
+fn field_size(kind: &str) -> Option<usize> {
+    match kind {
+        "bool" | "u8" | "i8" => Some(1),
+        "u16" | "i16" => Some(2),
+        "u32" | "i32" | "f32" => Some(4),
+        "u64" | "i64" | "f64" => Some(8),
+        _ => None,
+    }
+}
+
+/// Read a big-endian field of a payload
+fn decode_field(field: &PayloadField, payload: &[u8]) -> Option<Value> {
+    let bytes = payload.get(field.offset..field.offset + field_size(&field.kind)?)?;
+    let value = match field.kind.as_str() {
+        "bool" => Value::from(bytes[0] != 0),
+        "u8" => Value::from(bytes[0]),
+        "i8" => Value::from(bytes[0] as i8),
+        "u16" => Value::from(u16::from_be_bytes(bytes.try_into().ok()?)),
+        "i16" => Value::from(i16::from_be_bytes(bytes.try_into().ok()?)),
+        "u32" => Value::from(u32::from_be_bytes(bytes.try_into().ok()?)),
+        "i32" => Value::from(i32::from_be_bytes(bytes.try_into().ok()?)),
+        "u64" => Value::from(u64::from_be_bytes(bytes.try_into().ok()?)),
+        "i64" => Value::from(i64::from_be_bytes(bytes.try_into().ok()?)),
+        "f32" => serde_json::Number::from_f64(f32::from_be_bytes(bytes.try_into().ok()?) as f64)
+            .map_or(Value::Null, Value::Number),
+        "f64" => serde_json::Number::from_f64(f64::from_be_bytes(bytes.try_into().ok()?))
+            .map_or(Value::Null, Value::Number),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Decode a datagram if it is a notification of the event
+///
+/// # Arguments
+///
+/// * `event` - Event of the source
+/// * `packet` - Received datagram
+///
+/// # Returns
+///
+/// * `Option<Value>` - The payload as a JSON object, `None` for other
+///   messages and malformed payloads
+pub fn decode_notification(event: &SomeIpEvent, packet: &[u8]) -> Option<Value> {
+    let header = packet.get(..HEADER_LEN)?;
+    let service = u16::from_be_bytes([header[0], header[1]]);
+    let method = u16::from_be_bytes([header[2], header[3]]);
+    if service != event.service || method != event.event || header[14] != NOTIFICATION {
+        return None;
+    }
+
+    // The length counts the bytes following it: the end of the header and the payload
+    let length = u32::from_be_bytes(header[4..8].try_into().ok()?) as usize;
+    let payload = packet.get(HEADER_LEN..(8 + length).max(HEADER_LEN))?;
+
+    if event.fields.is_empty() {
+        return serde_json::from_slice(payload).ok();
+    }
+    let mut object = Map::new();
+    for field in &event.fields {
+        object.insert(field.name.clone(), decode_field(field, payload)?);
+    }
+    Some(Value::Object(object))
+}
+
+/// Reader of one event
+pub struct SomeIpSource {
+    event: SomeIpEvent,
+    bind: SocketAddr,
+    group: Option<Ipv4Addr>,
+    tx: Sender<DdsData>,
+    task: SourceTask,
+}
+
+impl SomeIpSource {
+    /// Create the source of an event of the settings
+    ///
+    /// # Arguments
+    ///
+    /// * `signal_name` - Name of the event in the settings
+    /// * `settings` - `sources.someip` settings
+    /// * `tx` - Sender for samples
+    ///
+    /// # Returns
+    ///
+    /// * `Result<SomeIpSource>` - Error if the event is unknown or its layout invalid
+    pub fn new(
+        signal_name: String,
+        settings: &SomeIpSettings,
+        tx: Sender<DdsData>,
+    ) -> Result<Self> {
+        let event = settings
+            .events
+            .iter()
+            .find(|event| event.name == signal_name)
+            .ok_or_else(|| format!("unknown SOME/IP event '{}'", signal_name))?
+            .clone();
+        if let Some(field) = event.fields.iter().find(|f| field_size(&f.kind).is_none()) {
+            return Err(format!("unknown type '{}' of field '{}'", field.kind, field.name).into());
+        }
+
+        Ok(Self {
+            bind: event.bind.unwrap_or(settings.bind),
+            group: settings.group,
+            event,
+            tx,
+            task: SourceTask::default(),
+        })
+    }
+}
+
+/// Bind a UDP socket that other sources can bind too
+fn bind_shared(bind: SocketAddr, group: Option<Ipv4Addr>) -> std::io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::for_address(bind), Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    socket.set_reuse_port(true)?;
+    socket.bind(&bind.into())?;
+    if let Some(group) = group {
+        socket.join_multicast_v4(&group, &Ipv4Addr::UNSPECIFIED)?;
+    }
+    socket.set_nonblocking(true)?;
+    UdpSocket::from_std(socket.into())
+}
+
+async fn read_notifications(
+    event: SomeIpEvent,
+    bind: SocketAddr,
+    group: Option<Ipv4Addr>,
+    tx: Sender<DdsData>,
+) -> anyhow::Result<()> {
+    let socket = bind_shared(bind, group)?;
+    info!("Receiving SOME/IP event '{}' on {}", event.name, bind);
+
+    let mut buffer = vec![0u8; 65536];
+    loop {
+        let len = match socket.recv(&mut buffer).await {
+            Ok(len) => len,
+            Err(e) => {
+                warn!("Error receiving SOME/IP event '{}': {}", event.name, e);
+                continue;
+            }
+        };
+        let Some(value) = decode_notification(&event, &buffer[..len]) else {
+            continue;
+        };
+        if tx
+            .send(DdsData::from_json(&event.name, value.to_string()))
+            .await
+            .is_err()
+        {
+            return Ok(());
+        }
+    }
+}
+
+#[async_trait]
+impl SignalSource for SomeIpSource {
+    fn is_running(&self) -> bool {
+        self.task.is_running()
+    }
+
+    async fn start(&mut self) -> Result<()> {
+        if !self.task.is_running() {
+            let reader =
+                read_notifications(self.event.clone(), self.bind, self.group, self.tx.clone());
+            self.task.spawn(self.event.name.clone(), reader);
+        }
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        self.task.stop();
+        Ok(())
+    }
+
+    fn get_signal_name(&self) -> &str {
+        &self.event.name
+    }
+
+    fn is_signal(&self, signal_name: &str) -> bool {
+        self.event.name == signal_name
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::sync::mpsc;
+    use tokio::time::{timeout, Duration};
+
+    fn door_event() -> SomeIpEvent {
+        serde_yaml::from_str(
+            r#"
+name: DoorState
+service: 0x1234
+event: 0x8001
+fields:
+  - { name: open, type: bool, offset: 0 }
+  - { name: angle, type: f32, offset: 1 }
+  - { name: count, type: u16, offset: 5 }
+"#,
+        )
+        .unwrap()
+    }
+
+    fn notification(service: u16, event: u16, message_type: u8, payload: &[u8]) -> Vec<u8> {
+        let mut packet = Vec::new();
+        packet.extend_from_slice(&service.to_be_bytes());
+        packet.extend_from_slice(&event.to_be_bytes());
+        packet.extend_from_slice(&(8 + payload.len() as u32).to_be_bytes());
+        // Client ID, session ID, protocol and interface versions, type, return code
+        packet.extend_from_slice(&[0, 0, 0, 1, 1, 1, message_type, 0]);
+        packet.extend_from_slice(payload);
+        packet
+    }
+
+    fn door_payload() -> Vec<u8> {
+        let mut payload = vec![1];
+        payload.extend_from_slice(&12.5f32.to_be_bytes());
+        payload.extend_from_slice(&7u16.to_be_bytes());
+        payload
+    }
+
+    #[test]
+    fn test_decode_notification() {
+        let event = door_event();
+        assert_eq!(
+            decode_notification(&event, &notification(0x1234, 0x8001, 2, &door_payload())),
+            Some(json!({"open": true, "angle": 12.5, "count": 7}))
+        );
+
+        // Other events, requests and truncated payloads are ignored
+        assert!(
+            decode_notification(&event, &notification(0x1234, 0x8002, 2, &door_payload()))
+                .is_none()
+        );
+        assert!(
+            decode_notification(&event, &notification(0x1234, 0x8001, 0, &door_payload()))
+                .is_none()
+        );
+        assert!(decode_notification(&event, &notification(0x1234, 0x8001, 2, &[1, 0])).is_none());
+        assert!(decode_notification(&event, &[0x12, 0x34]).is_none());
+
+        let json_event = SomeIpEvent {
+            fields: Vec::new(),
+            ..door_event()
+        };
+        assert_eq!(
+            decode_notification(
+                &json_event,
+                &notification(0x1234, 0x8001, 2, br#"{"open":false}"#)
+            ),
+            Some(json!({"open": false}))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_someip_source_receives_notifications() {
+        let port = std::net::UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let settings = SomeIpSettings {
+            bind: SocketAddr::from(([127, 0, 0, 1], port)),
+            group: None,
+            events: vec![door_event()],
+        };
+        assert!(SomeIpSource::new("Unknown".to_string(), &settings, mpsc::channel(1).0).is_err());
+
+        let (tx, mut rx) = mpsc::channel(10);
+        let mut source = SomeIpSource::new("DoorState".to_string(), &settings, tx).unwrap();
+        source.start().await.unwrap();
+
+        let sender = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let packet = notification(0x1234, 0x8001, 2, &door_payload());
+        let data = timeout(Duration::from_secs(2), async {
+            // The source binds its socket once its task runs
+            loop {
+                sender.send_to(&packet, settings.bind).await.unwrap();
+                if let Ok(data) = timeout(Duration::from_millis(100), rx.recv()).await {
+                    return data.unwrap();
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(data.name, "DoorState");
+        assert_eq!(data.fields.get("angle").map(String::as_str), Some("12.5"));
+
+        source.stop().await.unwrap();
+    }
+}
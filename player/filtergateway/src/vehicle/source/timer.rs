@@ -0,0 +1,362 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Time signals of filtergateway's own clock
+//!
+//! `Timer` operands read a clock instead of a vehicle signal; the operand
+//! `value` selects it:
+//!
+//! * `uptime` - `seconds` and `minutes` since the system started
+//! * `clock` - local `time` (`HH:MM`), `hour`, `minute`, `day` and
+//!   `weekday` (0 is Sunday)
+//!
+//! `Schedule` operands take a cron expression (`minute hour day month
+//! weekday`) as `value`; their `due` field is `true` during the minutes the
+//! expression matches. The `schedule` of a condition is read the same way.
+//!
+//! The clock is checked every second and a sample is sent when it changes,
+//! so a time condition is evaluated like any signal published on change.
+
+use super::{SignalSource, SourceKind, SourceTask};
+use crate::vehicle::dds::DdsData;
+use async_trait::async_trait;
+use chrono::{Datelike, Local, NaiveDateTime, Timelike};
+use common::Result;
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::Sender;
+
+/// Period of the clock checks
+const TICK: Duration = Duration::from_secs(1);
+
+/// Cron expression of a `Schedule` operand or of a condition `schedule`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// The day of month is `*`, so only the weekday restricts the day
+    any_day: bool,
+    /// The weekday is `*`, so only the day of month restricts the day
+    any_weekday: bool,
+}
+
+impl CronSchedule {
+    /// Parse a cron expression
+    ///
+    /// Each of the five fields is `*` or a comma-separated list of values
+    /// and `a-b` ranges, optionally stepped with `/n`. Weekday 7 is Sunday,
+    /// like 0. As in cron, a day matches either the day of month or the
+    /// weekday when both are restricted.
+    ///
+    /// # Arguments
+    ///
+    /// * `expression` - `minute hour day month weekday`
+    ///
+    /// # Returns
+    ///
+    /// * `Result<CronSchedule, String>` - Error if a field is malformed
+    pub fn parse(expression: &str) -> std::result::Result<Self, String> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "schedule '{}' must have 5 fields: minute hour day month weekday",
+                expression
+            ));
+        };
+        let mut weekdays = parse_field(weekday, 0, 7)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)?,
+            days: parse_field(day, 1, 31)?,
+            months: parse_field(month, 1, 12)?,
+            weekdays,
+            any_day: day.starts_with('*'),
+            any_weekday: weekday.starts_with('*'),
+        })
+    }
+
+    /// Whether the minute of a local time matches the expression
+    pub fn matches(&self, time: &NaiveDateTime) -> bool {
+        let day = self.days & (1 << time.day()) != 0;
+        let weekday = self.weekdays & (1 << time.weekday().num_days_from_sunday()) != 0;
+        let day = match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        };
+        day && self.minutes & (1 << time.minute()) != 0
+            && self.hours & (1 << time.hour()) != 0
+            && self.months & (1 << time.month()) != 0
+    }
+}
+
+/// Bit set of the values of one cron field
+fn parse_field(field: &str, min: u32, max: u32) -> std::result::Result<u64, String> {
+    let invalid = || format!("invalid schedule field '{}'", field);
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(invalid());
+        }
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (
+                    start.parse().map_err(|_| invalid())?,
+                    end.parse().map_err(|_| invalid())?,
+                ),
+                None => {
+                    let value = range.parse().map_err(|_| invalid())?;
+                    // `a/n` runs from `a` to the end of the field
+                    (value, if part.contains('/') { max } else { value })
+                }
+            },
+        };
+        if start < min || end > max || start > end {
+            return Err(format!(
+                "schedule field '{}' is outside {}-{}",
+                field, min, max
+            ));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+/// Clock read by a time signal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimerSpec {
+    Uptime,
+    Clock,
+    Schedule(CronSchedule),
+}
+
+impl TimerSpec {
+    /// Clock of a `Timer` or `Schedule` operand
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - `SourceKind::Timer` or `SourceKind::Schedule`
+    /// * `value` - `value` of the operand
+    ///
+    /// # Returns
+    ///
+    /// * `Result<TimerSpec, String>` - Error if the value names no clock
+    pub fn parse(kind: SourceKind, value: &str) -> std::result::Result<Self, String> {
+        match kind {
+            SourceKind::Schedule => CronSchedule::parse(value).map(TimerSpec::Schedule),
+            SourceKind::Timer => match value.trim().to_ascii_lowercase().as_str() {
+                "uptime" => Ok(TimerSpec::Uptime),
+                "clock" => Ok(TimerSpec::Clock),
+                _ => Err(format!(
+                    "unknown timer '{}', expected 'uptime' or 'clock'",
+                    value
+                )),
+            },
+            _ => Err(format!("{:?} operands are not time signals", kind)),
+        }
+    }
+
+    /// Fields of the signal at a local time and uptime
+    pub fn sample(&self, time: &NaiveDateTime, uptime: Duration) -> Value {
+        match self {
+            TimerSpec::Uptime => json!({
+                "seconds": uptime.as_secs(),
+                "minutes": uptime.as_secs() / 60,
+            }),
+            TimerSpec::Clock => json!({
+                "time": time.format("%H:%M").to_string(),
+                "hour": time.hour(),
+                "minute": time.minute(),
+                "day": time.day(),
+                "weekday": time.weekday().num_days_from_sunday(),
+            }),
+            TimerSpec::Schedule(schedule) => json!({ "due": schedule.matches(time) }),
+        }
+    }
+}
+
+/// Time since the system started, from `/proc/uptime`
+fn system_uptime() -> Option<Duration> {
+    let uptime = std::fs::read_to_string("/proc/uptime").ok()?;
+    let seconds: f64 = uptime.split_whitespace().next()?.parse().ok()?;
+    Some(Duration::from_secs_f64(seconds))
+}
+
+/// Sender of one time signal
+pub struct TimerSource {
+    signal_name: String,
+    spec: TimerSpec,
+    tx: Sender<DdsData>,
+    task: SourceTask,
+}
+
+impl TimerSource {
+    pub fn new(kind: SourceKind, signal_name: String, tx: Sender<DdsData>) -> Result<Self> {
+        let spec = TimerSpec::parse(kind, &signal_name)?;
+        Ok(Self {
+            signal_name,
+            spec,
+            tx,
+            task: SourceTask::default(),
+        })
+    }
+}
+
+async fn run_timer(
+    signal_name: String,
+    spec: TimerSpec,
+    tx: Sender<DdsData>,
+) -> anyhow::Result<()> {
+    // Without /proc/uptime, the uptime is counted from the start of the timer
+    let uptime = system_uptime().unwrap_or_default();
+    let start = Instant::now();
+    let mut ticks = tokio::time::interval(TICK);
+    let mut last = None;
+    loop {
+        ticks.tick().await;
+        let sample = spec.sample(&Local::now().naive_local(), uptime + start.elapsed());
+        if last.as_ref() == Some(&sample) {
+            continue;
+        }
+        if tx
+            .send(DdsData::from_json(&signal_name, sample.to_string()))
+            .await
+            .is_err()
+        {
+            return Ok(());
+        }
+        last = Some(sample);
+    }
+}
+
+#[async_trait]
+impl SignalSource for TimerSource {
+    fn is_running(&self) -> bool {
+        self.task.is_running()
+    }
+
+    async fn start(&mut self) -> Result<()> {
+        if !self.task.is_running() {
+            let timer = run_timer(self.signal_name.clone(), self.spec.clone(), self.tx.clone());
+            self.task.spawn(self.signal_name.clone(), timer);
+        }
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        self.task.stop();
+        Ok(())
+    }
+
+    fn get_signal_name(&self) -> &str {
+        &self.signal_name
+    }
+
+    fn is_signal(&self, signal_name: &str) -> bool {
+        self.signal_name == signal_name
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use tokio::sync::mpsc;
+    use tokio::time::timeout;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_cron_schedule_matches() {
+        // Nightly at 02:00
+        let nightly = CronSchedule::parse("0 2 * * *").unwrap();
+        assert!(nightly.matches(&at(2026, 10, 18, 2, 0)));
+        assert!(!nightly.matches(&at(2026, 10, 18, 2, 1)));
+        assert!(!nightly.matches(&at(2026, 10, 18, 14, 0)));
+
+        // Every 15 minutes during working hours on weekdays; 2026-10-18 is a Sunday
+        let office = CronSchedule::parse("*/15 9-17 * * 1-5").unwrap();
+        assert!(office.matches(&at(2026, 10, 19, 9, 45)));
+        assert!(!office.matches(&at(2026, 10, 19, 9, 40)));
+        assert!(!office.matches(&at(2026, 10, 18, 9, 45)));
+
+        // Restricted day of month and weekday match either of them
+        let either = CronSchedule::parse("0 0 1 * 7").unwrap();
+        assert!(either.matches(&at(2026, 10, 1, 0, 0)));
+        assert!(either.matches(&at(2026, 10, 18, 0, 0)));
+        assert!(!either.matches(&at(2026, 10, 19, 0, 0)));
+
+        assert!(CronSchedule::parse("0 2 * *").is_err());
+        assert!(CronSchedule::parse("60 2 * * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+        assert!(CronSchedule::parse("5-1 * * * *").is_err());
+        assert!(CronSchedule::parse("a * * * *").is_err());
+    }
+
+    #[test]
+    fn test_timer_spec_samples() {
+        let time = at(2026, 10, 18, 2, 5);
+        let uptime = Duration::from_secs(125);
+
+        let spec = TimerSpec::parse(SourceKind::Timer, "Uptime").unwrap();
+        assert_eq!(
+            spec.sample(&time, uptime),
+            json!({ "seconds": 125, "minutes": 2 })
+        );
+        let spec = TimerSpec::parse(SourceKind::Timer, "clock").unwrap();
+        assert_eq!(
+            spec.sample(&time, uptime),
+            json!({ "time": "02:05", "hour": 2, "minute": 5, "day": 18, "weekday": 0 })
+        );
+        let spec = TimerSpec::parse(SourceKind::Schedule, "5 2 * * 0").unwrap();
+        assert_eq!(spec.sample(&time, uptime), json!({ "due": true }));
+
+        assert!(TimerSpec::parse(SourceKind::Timer, "0 2 * * *").is_err());
+        assert!(TimerSpec::parse(SourceKind::Schedule, "clock").is_err());
+        assert!(TimerSpec::parse(SourceKind::Dds, "clock").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_timer_source_sends_the_clock() {
+        let (tx, mut rx) = mpsc::channel(10);
+        let mut source =
+            TimerSource::new(SourceKind::Schedule, "* * * * *".to_string(), tx).unwrap();
+        source.start().await.unwrap();
+        assert!(source.is_running());
+
+        let data = timeout(Duration::from_secs(2), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(data.name, "* * * * *");
+        assert_eq!(data.fields.get("due").map(String::as_str), Some("true"));
+
+        source.stop().await.unwrap();
+        assert!(!source.is_running());
+        assert!(
+            TimerSource::new(SourceKind::Timer, "sundial".to_string(), mpsc::channel(1).0).is_err()
+        );
+    }
+}
@@ -0,0 +1,173 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Signals of a local Unix socket JSON feed
+//!
+//! The feed is a stream of lines, each one a JSON object
+//! `{"name": "<signal>", "value": {...}}`. A source connects to the feed and
+//! forwards the values of its signal; it reconnects when the feed restarts.
+
+use super::{SignalSource, SourceTask, RECONNECT_DELAY};
+use crate::vehicle::dds::DdsData;
+use async_trait::async_trait;
+use common::Result;
+use log::{debug, warn};
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::PathBuf;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::net::UnixStream;
+use tokio::sync::mpsc::Sender;
+
+/// `sources.unix` section of the settings
+#[derive(Debug, Clone, Deserialize)]
+pub struct UnixSettings {
+    /// Path of the feed socket
+    pub path: PathBuf,
+}
+
+/// Reader of one signal of the feed
+pub struct UnixSource {
+    signal_name: String,
+    path: PathBuf,
+    tx: Sender<DdsData>,
+    task: SourceTask,
+}
+
+impl UnixSource {
+    pub fn new(signal_name: String, settings: &UnixSettings, tx: Sender<DdsData>) -> Self {
+        Self {
+            signal_name,
+            path: settings.path.clone(),
+            tx,
+            task: SourceTask::default(),
+        }
+    }
+}
+
+/// Value of a feed line if it belongs to the signal
+fn parse_line(signal_name: &str, line: &str) -> Option<Value> {
+    let mut message: serde_json::Map<String, Value> = serde_json::from_str(line).ok()?;
+    if message.get("name")?.as_str()? != signal_name {
+        return None;
+    }
+    message.remove("value")
+}
+
+async fn read_feed(signal_name: String, path: PathBuf, tx: Sender<DdsData>) -> anyhow::Result<()> {
+    loop {
+        let stream = match UnixStream::connect(&path).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                debug!("Cannot connect to feed {:?}: {}", path, e);
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                continue;
+            }
+        };
+
+        let mut lines = BufReader::new(stream).lines();
+        loop {
+            match lines.next_line().await {
+                Ok(Some(line)) => {
+                    let Some(value) = parse_line(&signal_name, &line) else {
+                        continue;
+                    };
+                    if tx
+                        .send(DdsData::from_json(&signal_name, value.to_string()))
+                        .await
+                        .is_err()
+                    {
+                        return Ok(());
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    warn!("Error reading feed {:?}: {}", path, e);
+                    break;
+                }
+            }
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+#[async_trait]
+impl SignalSource for UnixSource {
+    fn is_running(&self) -> bool {
+        self.task.is_running()
+    }
+
+    async fn start(&mut self) -> Result<()> {
+        if !self.task.is_running() {
+            let reader = read_feed(self.signal_name.clone(), self.path.clone(), self.tx.clone());
+            self.task.spawn(self.signal_name.clone(), reader);
+        }
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        self.task.stop();
+        Ok(())
+    }
+
+    fn get_signal_name(&self) -> &str {
+        &self.signal_name
+    }
+
+    fn is_signal(&self, signal_name: &str) -> bool {
+        self.signal_name == signal_name
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::UnixListener;
+    use tokio::sync::mpsc;
+    use tokio::time::{timeout, Duration};
+
+    #[test]
+    fn test_parse_line() {
+        let line = r#"{"name": "door", "value": {"open": true}}"#;
+        assert_eq!(
+            parse_line("door", line),
+            Some(serde_json::json!({"open": true}))
+        );
+        assert!(parse_line("window", line).is_none());
+        assert!(parse_line("door", "not json").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unix_source_forwards_its_signal() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("feed.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let (tx, mut rx) = mpsc::channel(10);
+        let mut source = UnixSource::new("door".to_string(), &UnixSettings { path }, tx);
+        source.start().await.unwrap();
+        assert!(source.is_running());
+
+        let (mut feed, _) = listener.accept().await.unwrap();
+        feed.write_all(
+            b"{\"name\": \"window\", \"value\": {\"open\": true}}\n{\"name\": \"door\", \"value\": {\"open\": true, \"angle\": 30}}\n",
+        )
+        .await
+        .unwrap();
+
+        let data = timeout(Duration::from_secs(2), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(data.name, "door");
+        assert_eq!(data.fields.get("open").map(String::as_str), Some("true"));
+        assert_eq!(data.fields.get("angle").map(String::as_str), Some("30"));
+
+        source.stop().await.unwrap();
+        assert!(!source.is_running());
+    }
+}
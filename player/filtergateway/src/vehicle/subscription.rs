@@ -0,0 +1,121 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Reference counting of topic subscriptions
+//!
+//! Several scenarios can watch the same topic. Only one DDS reader is created
+//! per topic, its samples are passed to every filter, and the reader is
+//! removed when the last scenario using the topic is withdrawn.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Scenarios subscribed to each topic
+#[derive(Debug, Default)]
+pub struct SubscriptionTable {
+    topics: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl SubscriptionTable {
+    /// Record that a scenario uses a topic
+    ///
+    /// # Arguments
+    ///
+    /// * `scenario_name` - Name of the scenario
+    /// * `topic_name` - Topic read by the scenario
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` if no scenario used the topic before, meaning a
+    ///   reader has to be created
+    pub fn add(&mut self, scenario_name: &str, topic_name: &str) -> bool {
+        let scenarios = self.topics.entry(topic_name.to_string()).or_default();
+        let first = scenarios.is_empty();
+        scenarios.insert(scenario_name.to_string());
+        first
+    }
+
+    /// Forget every topic use of a scenario
+    ///
+    /// # Arguments
+    ///
+    /// * `scenario_name` - Name of the withdrawn scenario
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - Topics no scenario uses anymore, whose readers have
+    ///   to be removed
+    pub fn remove_scenario(&mut self, scenario_name: &str) -> Vec<String> {
+        let mut unused = Vec::new();
+        self.topics.retain(|topic, scenarios| {
+            if scenarios.remove(scenario_name) && scenarios.is_empty() {
+                unused.push(topic.clone());
+                return false;
+            }
+            !scenarios.is_empty()
+        });
+        unused
+    }
+
+    /// Undo `add` after the reader of a new topic failed to start
+    pub fn remove(&mut self, scenario_name: &str, topic_name: &str) {
+        if let Some(scenarios) = self.topics.get_mut(topic_name) {
+            scenarios.remove(scenario_name);
+            if scenarios.is_empty() {
+                self.topics.remove(topic_name);
+            }
+        }
+    }
+
+    /// Scenarios currently using a topic
+    pub fn scenarios(&self, topic_name: &str) -> Vec<String> {
+        self.topics
+            .get(topic_name)
+            .map(|s| s.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Topics with at least one scenario
+    pub fn topics(&self) -> Vec<String> {
+        self.topics.keys().cloned().collect()
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_topic_is_released_by_last_scenario() {
+        let mut table = SubscriptionTable::default();
+        assert!(table.add("antipinch", "rt/pullpiri/door"));
+        assert!(!table.add("welcome-light", "rt/pullpiri/door"));
+        // Applying the same scenario again does not add a reference
+        assert!(!table.add("antipinch", "rt/pullpiri/door"));
+        assert_eq!(
+            table.scenarios("rt/pullpiri/door"),
+            vec!["antipinch".to_string(), "welcome-light".to_string()]
+        );
+
+        assert!(table.remove_scenario("antipinch").is_empty());
+        assert_eq!(
+            table.remove_scenario("welcome-light"),
+            vec!["rt/pullpiri/door".to_string()]
+        );
+        assert!(table.topics().is_empty());
+    }
+
+    #[test]
+    fn test_remove_unknown_scenario() {
+        let mut table = SubscriptionTable::default();
+        table.add("antipinch", "rt/pullpiri/door");
+
+        assert!(table.remove_scenario("unknown").is_empty());
+        assert_eq!(table.topics(), vec!["rt/pullpiri/door".to_string()]);
+
+        table.remove("antipinch", "rt/pullpiri/door");
+        assert!(table.topics().is_empty());
+    }
+}
@@ -88,7 +88,7 @@ spec:
     let dds = build_dds_data("TestTopic", "temperature", "true");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("test_eq".into(), scenario, true, sender);
+    let mut filter = Filter::new("test_eq".into(), scenario, true, sender).unwrap();
 
     assert!(filter.process_data(&dds).await.is_ok());
     common::etcd::delete("Scenario/test_eq").await.unwrap();
@@ -136,7 +136,7 @@ spec:
     let dds = build_dds_data("TestTopic_wrong", "temperature", "true");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("test_eq1".into(), scenario, true, sender);
+    let mut filter = Filter::new("test_eq1".into(), scenario, true, sender).unwrap();
 
     assert!(filter.meet_scenario_condition(&dds).await.is_err());
     common::etcd::delete("Scenario/test_eq1").await.unwrap();
@@ -184,7 +184,7 @@ spec:
     let dds = build_dds_data("TestTopic", "temperature", "5");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("test_lt".into(), scenario, true, sender);
+    let mut filter = Filter::new("test_lt".into(), scenario, true, sender).unwrap();
 
     assert!(filter.process_data(&dds).await.is_ok());
     common::etcd::delete("Scenario/test_lt").await.unwrap();
@@ -234,11 +234,14 @@ spec:
     let dds = build_dds_data("TestTopic", "temperature", "abc");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("test_field_parse".into(), scenario, true, sender);
+    let mut filter = Filter::new("test_field_parse".into(), scenario, true, sender).unwrap();
 
     let result = filter.meet_scenario_condition(&dds).await;
     assert!(result.is_err());
-    assert_eq!(result.unwrap_err().to_string(), "field_value parse error");
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "field value 'abc' is not a number"
+    );
     common::etcd::delete("Scenario/test_field_parse")
         .await
         .unwrap();
@@ -264,7 +267,7 @@ spec:
     let dds = build_dds_data("TestTopic", "temperature", "abc");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("test_field_parse".into(), scenario, true, sender);
+    let mut filter = Filter::new("test_field_parse".into(), scenario, true, sender).unwrap();
 
     let result = filter.process_data(&dds).await;
     assert!(true);
@@ -311,7 +314,7 @@ spec:
     let dds = build_dds_data("TestTopic", "temperature", "10");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("test_le".into(), scenario, true, sender);
+    let mut filter = Filter::new("test_le".into(), scenario, true, sender).unwrap();
 
     assert!(filter.process_data(&dds).await.is_ok());
     common::etcd::delete("Scenario/test_le").await.unwrap();
@@ -359,7 +362,7 @@ spec:
     let dds = build_dds_data("TestTopic", "temperature", "11");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("test_ge".into(), scenario, true, sender);
+    let mut filter = Filter::new("test_ge".into(), scenario, true, sender).unwrap();
 
     assert!(filter.process_data(&dds).await.is_ok());
     common::etcd::delete("Scenario/test_ge").await.unwrap();
@@ -407,7 +410,7 @@ spec:
     let dds = build_dds_data("TestTopic", "temperature", "15");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("test_gt".into(), scenario, true, sender);
+    let mut filter = Filter::new("test_gt".into(), scenario, true, sender).unwrap();
 
     assert!(filter.process_data(&dds).await.is_ok());
     common::etcd::delete("Scenario/test_gt").await.unwrap();
@@ -456,13 +459,10 @@ spec:
         .await
         .unwrap();
     let scenario = build_scenario_yaml_with_expression("invalid_expr", "unknown_expr", "on");
-    let dds = build_dds_data("TestTopic", "temperature", "on");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("invalid_expr".into(), scenario, true, sender);
-
-    // Should log error but still return Ok from process_data
-    assert!(filter.process_data(&dds).await.is_ok());
+    // Unknown expressions are rejected when the filter is created
+    assert!(Filter::new("invalid_expr".into(), scenario, true, sender).is_err());
     common::etcd::delete("Scenario/invalid_expr").await.unwrap();
     common::etcd::delete("Package/invalid_expr").await.unwrap();
 }
@@ -510,7 +510,7 @@ spec:
     let dds = build_dds_data("WrongTopic", "temperature", "true");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("topic_mismatch".into(), scenario, true, sender);
+    let mut filter = Filter::new("topic_mismatch".into(), scenario, true, sender).unwrap();
 
     assert!(filter.process_data(&dds).await.is_ok());
     common::etcd::delete("Scenario/topic_mismatch")
@@ -562,7 +562,7 @@ spec:
     let dds = build_dds_data("TestTopic", "temperature", "15");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("test_gt".into(), scenario, true, sender);
+    let mut filter = Filter::new("test_gt".into(), scenario, true, sender).unwrap();
 
     assert!(filter.process_data(&dds).await.is_ok());
     common::etcd::delete("Scenario/test_gt").await.unwrap();
@@ -611,7 +611,7 @@ spec:
     let dds = build_dds_data("TestTopic", "unknown_field", "true");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("missing_field".into(), scenario, true, sender);
+    let mut filter = Filter::new("missing_field".into(), scenario, true, sender).unwrap();
 
     // Logs error, returns Ok
     assert!(filter.process_data(&dds).await.is_ok());
@@ -663,12 +663,10 @@ spec:
         .await
         .unwrap();
     let scenario = build_scenario_yaml_with_expression("parse_error", "lt", "not_a_number");
-    let dds = build_dds_data("TestTopic", "temperature", "5");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("parse_error".into(), scenario, true, sender);
-
-    assert!(filter.process_data(&dds).await.is_ok());
+    // Invalid target values are rejected when the filter is created
+    assert!(Filter::new("parse_error".into(), scenario, true, sender).is_err());
     common::etcd::delete("Scenario/parse_field_error")
         .await
         .unwrap();
@@ -717,12 +715,10 @@ spec:
         .await
         .unwrap();
     let scenario = build_scenario_yaml_with_expression("parse_error", "le", "not_a_number");
-    let dds = build_dds_data("TestTopic", "temperature", "5");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("parse_error".into(), scenario, true, sender);
-
-    assert!(filter.process_data(&dds).await.is_ok());
+    // Invalid target values are rejected when the filter is created
+    assert!(Filter::new("parse_error".into(), scenario, true, sender).is_err());
     common::etcd::delete("Scenario/parse_field_error")
         .await
         .unwrap();
@@ -771,12 +767,10 @@ spec:
         .await
         .unwrap();
     let scenario = build_scenario_yaml_with_expression("parse_error", "gt", "not_a_number");
-    let dds = build_dds_data("TestTopic", "temperature", "5");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("parse_error".into(), scenario, true, sender);
-
-    assert!(filter.process_data(&dds).await.is_ok());
+    // Invalid target values are rejected when the filter is created
+    assert!(Filter::new("parse_error".into(), scenario, true, sender).is_err());
     common::etcd::delete("Scenario/parse_field_error")
         .await
         .unwrap();
@@ -825,12 +819,10 @@ spec:
         .await
         .unwrap();
     let scenario = build_scenario_yaml_with_expression("parse_error", "ge", "not_a_number");
-    let dds = build_dds_data("TestTopic", "temperature", "5");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("parse_error".into(), scenario, true, sender);
-
-    assert!(filter.process_data(&dds).await.is_ok());
+    // Invalid target values are rejected when the filter is created
+    assert!(Filter::new("parse_error".into(), scenario, true, sender).is_err());
     common::etcd::delete("Scenario/parse_field_error")
         .await
         .unwrap();
@@ -882,7 +874,7 @@ spec:
     let dds = build_dds_data("TestTopic", "temperature", "not_a_number");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("parse_field_error".into(), scenario, true, sender);
+    let mut filter = Filter::new("parse_field_error".into(), scenario, true, sender).unwrap();
 
     assert!(filter.process_data(&dds).await.is_ok());
     common::etcd::delete("Scenario/parse_field_error")
@@ -936,7 +928,7 @@ spec:
     let dds = build_dds_data("TestTopic", "temperature", "not_a_number");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("parse_field_error".into(), scenario, true, sender);
+    let mut filter = Filter::new("parse_field_error".into(), scenario, true, sender).unwrap();
 
     assert!(filter.process_data(&dds).await.is_ok());
     common::etcd::delete("Scenario/parse_field_error")
@@ -990,7 +982,7 @@ spec:
     let dds = build_dds_data("TestTopic", "temperature", "not_a_number");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("parse_field_error".into(), scenario, true, sender);
+    let mut filter = Filter::new("parse_field_error".into(), scenario, true, sender).unwrap();
 
     assert!(filter.process_data(&dds).await.is_ok());
     common::etcd::delete("Scenario/parse_field_error")
@@ -1044,7 +1036,7 @@ spec:
     let dds = build_dds_data("TestTopic", "temperature", "not_a_number");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("parse_field_error".into(), scenario, true, sender);
+    let mut filter = Filter::new("parse_field_error".into(), scenario, true, sender).unwrap();
 
     assert!(filter.process_data(&dds).await.is_ok());
     common::etcd::delete("Scenario/parse_field_error")
@@ -1099,7 +1091,7 @@ spec:
     let dds = build_dds_data("TestTopic", "temperature", "on");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("inactive".into(), scenario, false, sender);
+    let mut filter = Filter::new("inactive".into(), scenario, false, sender).unwrap();
 
     assert!(filter.process_data(&dds).await.is_ok());
     common::etcd::delete("Scenario/inactive").await.unwrap();
@@ -1151,7 +1143,7 @@ spec:
         .unwrap();
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("pause_resume".into(), scenario, true, sender);
+    let mut filter = Filter::new("pause_resume".into(), scenario, true, sender).unwrap();
 
     assert!(filter.is_active());
     filter.pause_scenario_filter().await.unwrap();
@@ -1210,9 +1202,105 @@ spec:
     let dds = build_dds_data("TestTopic", "status", "true");
 
     let sender = FilterGatewaySender::new();
-    let mut filter = Filter::new("helloworld".into(), scenario, true, sender);
+    let mut filter = Filter::new("helloworld".into(), scenario, true, sender).unwrap();
 
     assert!(filter.process_data(&dds).await.is_ok());
     common::etcd::delete("Scenario/helloworld").await.unwrap();
     common::etcd::delete("Package/helloworld").await.unwrap();
 }
+
+#[tokio::test]
+async fn test_filter_state_tracks_evaluations() {
+    let scenario = build_scenario_yaml_with_expression("filter_state", "eq", "on");
+    let sender = FilterGatewaySender::new();
+    let mut filter = Filter::new("filter_state".into(), scenario, true, sender).unwrap();
+
+    let state = filter.state();
+    assert_eq!(state.topic, "TestTopic");
+    assert!(state.active);
+    assert!(state.last_sample.is_none());
+    assert_eq!(state.trigger_count, 0);
+
+    assert!(!filter
+        .evaluate(&build_dds_data("TestTopic", "temperature", "off"))
+        .unwrap());
+    assert!(filter
+        .evaluate(&build_dds_data("TestTopic", "temperature", "on"))
+        .unwrap());
+
+    let state = filter.state();
+    assert!(state.last_sample.is_some());
+    assert_eq!(state.last_value.as_deref(), Some("on"));
+    assert_eq!(state.last_result, Some(true));
+    assert_eq!(state.trigger_count, 1);
+
+    filter.pause_scenario_filter().await.unwrap();
+    assert!(!filter.state().active);
+}
+
+#[tokio::test]
+async fn test_schedule_limits_condition() {
+    let scenario = build_scenario_from_yaml(
+        r#"
+apiVersion: v1
+kind: Scenario
+metadata:
+  name: nightly_upload
+spec:
+  condition:
+    express: eq
+    value: "P"
+    operands:
+      type: DDS
+      name: "gear"
+      value: "GearState"
+    schedule: "0 2 * * *"
+  action: update
+  target: test_target
+"#,
+    );
+    let sender = FilterGatewaySender::new();
+    let mut filter = Filter::new("nightly_upload".into(), scenario, true, sender).unwrap();
+    assert_eq!(filter.topics(), vec!["GearState", "0 2 * * *"]);
+
+    // Parked outside the schedule
+    assert!(!filter
+        .evaluate(&build_dds_data("GearState", "gear", "P"))
+        .unwrap());
+    assert_eq!(filter.state().last_result, Some(false));
+
+    // The schedule becomes due while the vehicle stays parked
+    assert!(filter
+        .evaluate(&build_dds_data("0 2 * * *", "due", "true"))
+        .unwrap());
+    assert_eq!(filter.state().last_result, Some(true));
+
+    assert!(!filter
+        .evaluate(&build_dds_data("0 2 * * *", "due", "false"))
+        .unwrap());
+    assert!(!filter
+        .evaluate(&build_dds_data("GearState", "gear", "D"))
+        .unwrap());
+    assert_eq!(filter.state().trigger_count, 1);
+
+    let invalid = build_scenario_from_yaml(
+        r#"
+apiVersion: v1
+kind: Scenario
+metadata:
+  name: invalid_schedule
+spec:
+  condition:
+    express: eq
+    value: "true"
+    operands:
+      type: Timer
+      name: "minutes"
+      value: "sundial"
+    schedule: "0 25 * * *"
+  action: update
+  target: test_target
+"#,
+    );
+    assert!(Filter::validate(&invalid).is_err());
+}
@@ -0,0 +1,126 @@
+// Tests of the build-time DDS type generator
+#[allow(dead_code)]
+#[path = "../build_scripts/mod.rs"]
+mod build_scripts;
+
+use build_scripts::generator::{generate_module_source, registry_entries};
+use build_scripts::idl::{collect_idl_files, IdlParser};
+use std::path::Path;
+
+const OBSTACLE_IDL: &str = r#"
+module vehicle {
+    enum LampState { OFF, ON, BLINK };
+    typedef sequence<string, 8> Labels;
+    struct Point { double x, y; };
+
+    module adas {
+        struct Obstacle {
+            @key unsigned long long id;
+            unsigned long count;
+            Labels labels;
+            LampState lamps[4];
+            sequence<::vehicle::Point> outline;
+            string type;
+        };
+    };
+};
+"#;
+
+fn parse_source(name: &str, source: &str) -> build_scripts::idl::IdlFile {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(format!("{}.idl", name));
+    std::fs::write(&path, source).unwrap();
+    IdlParser::parse_idl_file(&path).unwrap()
+}
+
+#[test]
+fn test_generate_vehicle_idl_files() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/vehicle/dds/idl");
+    let files = collect_idl_files(&dir).unwrap();
+    assert!(!files.is_empty());
+
+    let parsed: Vec<_> = files
+        .iter()
+        .map(|path| IdlParser::parse_idl_file(path).unwrap())
+        .collect();
+    let trunk = parsed.iter().find(|f| f.module == "BodyTrunk").unwrap();
+    let source = generate_module_source(trunk).unwrap();
+    // Fields keep the declaration order of the IDL, which is the CDR layout
+    assert!(source.contains(
+        "pub struct BodyTrunkStatus {\n    pub command: i32,\n    pub status: i32,\n    pub progress: i32,\n    pub uistatus: i32,\n}"
+    ));
+
+    let names: Vec<String> = registry_entries(&parsed)
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    assert!(names.contains(&"BodyTrunkStatus".to_string()));
+    assert!(names.contains(&"ADASObstacleDetectionIsWarning".to_string()));
+}
+
+#[test]
+fn test_generate_modules_enums_and_keys() {
+    let file = parse_source("Obstacle", OBSTACLE_IDL);
+    let source = generate_module_source(&file).unwrap();
+
+    for expected in [
+        "pub mod vehicle {",
+        "    pub mod adas {",
+        "    pub type Labels = Vec<String>;",
+        "    pub enum LampState {\n        #[default]\n        OFF,\n        ON,\n        BLINK,\n    }",
+        "        use super::super::vehicle::Point as vehicle_Point;",
+        "            #[dust_dds(key)]\n            pub id: u64,",
+        "            pub count: u32,",
+        "            pub labels: Vec<String>,",
+        "            pub lamps: [vehicle_LampState; 4],",
+        "            pub outline: Vec<vehicle_Point>,",
+        "            pub r#type: String,",
+    ] {
+        assert!(source.contains(expected), "missing {:?} in\n{}", expected, source);
+    }
+
+    assert_eq!(
+        registry_entries(&[file]),
+        vec![
+            (
+                "vehicle::Point".to_string(),
+                "Obstacle::vehicle::Point".to_string()
+            ),
+            ("Point".to_string(), "Obstacle::vehicle::Point".to_string()),
+            (
+                "vehicle::adas::Obstacle".to_string(),
+                "Obstacle::vehicle::adas::Obstacle".to_string()
+            ),
+            (
+                "Obstacle".to_string(),
+                "Obstacle::vehicle::adas::Obstacle".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_ambiguous_names_are_only_qualified() {
+    let body = parse_source("Body", "module body { struct Door { boolean open; }; };");
+    let trailer = parse_source(
+        "Trailer",
+        "module trailer { struct Door { boolean open; }; };",
+    );
+
+    let names: Vec<String> = registry_entries(&[body, trailer])
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(names, vec!["body::Door", "trailer::Door"]);
+}
+
+#[test]
+fn test_unsupported_idl_is_rejected() {
+    let file = parse_source("Long", "struct Samples { long values[64]; };");
+    assert!(generate_module_source(&file).is_err());
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("Broken.idl");
+    std::fs::write(&path, "struct Broken { Unknown value; };").unwrap();
+    assert!(IdlParser::parse_idl_file(&path).is_err());
+}
@@ -110,7 +110,7 @@ spec:
     assert!(result.is_ok());
 
     let filters = manager.filters.lock().await;
-    assert!(filters.iter().any(|f| f.scenario_name == "helloworld"));
+    assert!(filters.contains("helloworld"));
 }
 
 #[tokio::test(flavor = "multi_thread")]
@@ -0,0 +1,65 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+use filtergateway::filter::simulator::{self, TriggerCause};
+use std::path::PathBuf;
+use std::process::Command;
+
+fn example(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../../examples/resources")
+        .join(name)
+}
+
+/// Checks a scenario of the examples against a CSV script through the library API
+#[test]
+fn test_simulate_example_scenario() {
+    let source = std::fs::read_to_string(example("helloworld.yaml")).unwrap();
+    let scenario = simulator::parse_scenario(&source).unwrap();
+    let script =
+        simulator::parse_csv_script("at,value\n0,false\n100,true\n200,true\n300,false\n400,true\n")
+            .unwrap();
+
+    let report = simulator::simulate(scenario, &script).unwrap();
+    assert_eq!(report.trigger_times(), vec![100, 400]);
+    assert_eq!(report.triggers[1].cause, TriggerCause::Sample(4));
+    assert!(report.errors.is_empty());
+}
+
+/// Scenarios without condition fire when they are launched
+#[test]
+fn test_simulate_scenario_without_condition() {
+    let source = std::fs::read_to_string(example("helloworld_no_condition.yaml")).unwrap();
+    let scenario = simulator::parse_scenario(&source).unwrap();
+    let report = simulator::simulate(scenario, &[]).unwrap();
+    assert_eq!(report.triggers[0].cause, TriggerCause::Launch);
+}
+
+/// Runs the CLI the way a CI job would
+#[test]
+fn test_scenario_sim_cli_checks_expected_triggers() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = dir.path().join("signals.yaml");
+    std::fs::write(
+        &script,
+        "- { at: 0, value: { value: false } }\n- { at: 250, value: { value: true } }\n",
+    )
+    .unwrap();
+
+    let run = |expect: &str| {
+        Command::new(env!("CARGO_BIN_EXE_scenario-sim"))
+            .arg(example("helloworld.yaml"))
+            .arg(&script)
+            .arg("--expect")
+            .arg(expect)
+            .output()
+            .unwrap()
+    };
+
+    let output = run("250");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("250 ms  sample 1"));
+    assert!(!run("0,250").status.success());
+}
@@ -98,3 +98,79 @@ async fn test_listener_detects_closed_channel_and_exits() {
     listener.stop().await.expect("Failed to stop listener");
     assert!(!listener.is_running());
 }
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize, PartialEq, DdsType)]
+pub struct BurstSample {
+    pub seq: i32,
+}
+
+#[tokio::test]
+async fn test_typed_listener_receives_burst_with_reliable_qos() {
+    use common::spec::artifact::TopicQos;
+    use dust_dds::domain::domain_participant_factory::DomainParticipantFactory;
+    use dust_dds::infrastructure::qos::{DataWriterQos, QosKind};
+    use dust_dds::infrastructure::qos_policy::{
+        HistoryQosPolicy, HistoryQosPolicyKind, ReliabilityQosPolicy, ReliabilityQosPolicyKind,
+    };
+    use dust_dds::infrastructure::status::NO_STATUS;
+    use dust_dds::infrastructure::time::DurationKind;
+    use filtergateway::vehicle::dds::qos::reader_qos;
+
+    let domain_id = 121;
+    let topic_name = "BurstSample";
+    let (tx, mut rx) = mpsc::channel::<DdsData>(64);
+    let mut listener = GenericTopicListener::<BurstSample>::new(
+        topic_name.to_string(),
+        topic_name.to_string(),
+        tx,
+        domain_id,
+    );
+    let qos: TopicQos = serde_yaml::from_str("reliability: reliable\nhistoryDepth: 0").unwrap();
+    listener.set_qos(reader_qos(&qos).unwrap());
+    listener.start().await.unwrap();
+
+    let participant = DomainParticipantFactory::get_instance()
+        .create_participant(domain_id, QosKind::Default, None, NO_STATUS)
+        .unwrap();
+    let topic = participant
+        .create_topic::<BurstSample>(topic_name, topic_name, QosKind::Default, None, NO_STATUS)
+        .unwrap();
+    let publisher = participant
+        .create_publisher(QosKind::Default, None, NO_STATUS)
+        .unwrap();
+    let writer_qos = DataWriterQos {
+        reliability: ReliabilityQosPolicy {
+            kind: ReliabilityQosPolicyKind::Reliable,
+            max_blocking_time: DurationKind::Infinite,
+        },
+        history: HistoryQosPolicy {
+            kind: HistoryQosPolicyKind::KeepAll,
+        },
+        ..Default::default()
+    };
+    let writer = publisher
+        .create_datawriter::<BurstSample>(&topic, QosKind::Specific(writer_qos), None, NO_STATUS)
+        .unwrap();
+
+    // Wait for the reader to be discovered before writing the burst
+    for _ in 0..100 {
+        if !writer.get_matched_subscriptions().unwrap().is_empty() {
+            break;
+        }
+        sleep(Duration::from_millis(50)).await;
+    }
+    for seq in 0..20 {
+        writer.write(&BurstSample { seq }, None).unwrap();
+    }
+
+    // Every sample of the burst arrives, in order
+    for seq in 0..20 {
+        let data = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("sample not received")
+            .unwrap();
+        assert_eq!(data.fields.get("seq"), Some(&seq.to_string()));
+    }
+
+    listener.stop().await.unwrap();
+}
@@ -0,0 +1,359 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Transition tables loaded from a YAML definition
+//!
+//! The tables of the state machine are described in YAML, one section per
+//! resource type:
+//!
+//! ```yaml
+//! package:
+//!   initial: [unspecified]
+//!   transitions:
+//!     - { from: unspecified, event: launch_request, to: initializing, action: start_model_creation_allocate_resources }
+//!     - { from: initializing, event: initialization_complete, to: running, guard: all_models_normal, action: update_state_announce_availability }
+//! ```
+//!
+//! The tables in `transitions.yaml` are built in. At startup, a definition
+//! file at `PICCOLO_STATE_MACHINE_PATH` replaces the tables of the resource
+//! types it defines, so that a vehicle program can adjust one lifecycle
+//! without restating the others.
+//!
+//! A definition is rejected if it names unknown states or guards, has two
+//! transitions for the same state and event, or has states that cannot be
+//! reached from its `initial` states. Guards are listed in the `guard`
+//! module.
+
+use crate::guard::Guard;
+use crate::types::StateTransition;
+use common::statemanager::{ModelState, PackageState, ResourceType, ScenarioState};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+/// Tables built into the StateManager
+const DEFAULT_DEFINITION: &str = include_str!("transitions.yaml");
+
+/// Transition table of each defined resource type
+pub type TransitionTables = HashMap<ResourceType, Vec<StateTransition>>;
+
+/// Path of the definition file, from `PICCOLO_STATE_MACHINE_PATH` if set
+pub fn definition_path() -> PathBuf {
+    std::env::var("PICCOLO_STATE_MACHINE_PATH")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("/etc/piccolo/state_machine.yaml"))
+}
+
+/// Whole definition file
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StateMachineDefinition {
+    #[serde(default)]
+    scenario: Option<LifecycleDefinition>,
+    #[serde(default)]
+    package: Option<LifecycleDefinition>,
+    #[serde(default)]
+    model: Option<LifecycleDefinition>,
+}
+
+/// Lifecycle of one resource type
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LifecycleDefinition {
+    /// States a resource can be first reported in
+    initial: Vec<String>,
+    /// All states of the lifecycle; if set, transitions may only use them
+    #[serde(default)]
+    states: Vec<String>,
+    transitions: Vec<TransitionDefinition>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TransitionDefinition {
+    from: String,
+    event: String,
+    to: String,
+    /// Condition that must hold for the transition to happen
+    #[serde(default)]
+    guard: Option<String>,
+    action: String,
+}
+
+/// Parse and validate a definition
+///
+/// # Arguments
+///
+/// * `yaml` - Definition text
+///
+/// # Returns
+///
+/// * `Result<TransitionTables, String>` - Tables of the defined resource
+///   types, or the first problem found
+pub fn parse(yaml: &str) -> Result<TransitionTables, String> {
+    let definition: StateMachineDefinition =
+        serde_yaml::from_str(yaml).map_err(|e| format!("invalid state machine definition: {e}"))?;
+
+    let mut tables = TransitionTables::new();
+    for (resource_type, lifecycle) in [
+        (ResourceType::Scenario, definition.scenario),
+        (ResourceType::Package, definition.package),
+        (ResourceType::Model, definition.model),
+    ] {
+        if let Some(lifecycle) = lifecycle {
+            let table = lifecycle
+                .compile(resource_type)
+                .map_err(|e| format!("{}: {e}", section_name(resource_type)))?;
+            tables.insert(resource_type, table);
+        }
+    }
+    Ok(tables)
+}
+
+/// Tables built into the StateManager
+pub fn default_tables() -> TransitionTables {
+    parse(DEFAULT_DEFINITION).expect("built-in state machine definition is valid")
+}
+
+/// Read the definition file, if there is one
+///
+/// # Arguments
+///
+/// * `path` - Path of the definition file
+///
+/// # Returns
+///
+/// * `Result<Option<TransitionTables>, String>` - `None` if the file does
+///   not exist, an error if it cannot be read or is invalid
+pub fn load(path: &Path) -> Result<Option<TransitionTables>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let yaml = std::fs::read_to_string(path).map_err(|e| format!("cannot read {path:?}: {e}"))?;
+    parse(&yaml)
+        .map(Some)
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+fn section_name(resource_type: ResourceType) -> &'static str {
+    match resource_type {
+        ResourceType::Scenario => "scenario",
+        ResourceType::Package => "package",
+        ResourceType::Model => "model",
+        _ => "unknown",
+    }
+}
+
+/// Proto enum value of a state name such as `idle` or `crash-loop-back-off`
+pub fn parse_state(resource_type: ResourceType, name: &str) -> Option<i32> {
+    let name = name.trim().to_ascii_uppercase().replace('-', "_");
+    match resource_type {
+        ResourceType::Scenario => {
+            ScenarioState::from_str_name(&format!("SCENARIO_STATE_{name}")).map(|s| s as i32)
+        }
+        ResourceType::Package => {
+            PackageState::from_str_name(&format!("PACKAGE_STATE_{name}")).map(|s| s as i32)
+        }
+        ResourceType::Model => {
+            ModelState::from_str_name(&format!("MODEL_STATE_{name}")).map(|s| s as i32)
+        }
+        _ => None,
+    }
+}
+
+impl LifecycleDefinition {
+    /// Build the transition table of a resource type and validate it
+    fn compile(&self, resource_type: ResourceType) -> Result<Vec<StateTransition>, String> {
+        let state = |name: &str| {
+            parse_state(resource_type, name).ok_or_else(|| format!("unknown state '{name}'"))
+        };
+        if self.initial.is_empty() {
+            return Err("no initial state".to_string());
+        }
+
+        let mut names = HashMap::new();
+        let declared = self
+            .states
+            .iter()
+            .map(|name| {
+                let value = state(name)?;
+                names.insert(value, name.clone());
+                Ok(value)
+            })
+            .collect::<Result<HashSet<i32>, String>>()?;
+        let mut use_state = |name: &str| -> Result<i32, String> {
+            let value = state(name)?;
+            if !declared.is_empty() && !declared.contains(&value) {
+                return Err(format!("state '{name}' is not declared in states"));
+            }
+            names.entry(value).or_insert_with(|| name.to_string());
+            Ok(value)
+        };
+
+        let initial = self
+            .initial
+            .iter()
+            .map(|name| use_state(name))
+            .collect::<Result<Vec<i32>, String>>()?;
+
+        let mut table = Vec::with_capacity(self.transitions.len());
+        let mut seen = HashSet::new();
+        for transition in &self.transitions {
+            if transition.event.trim().is_empty() || transition.action.trim().is_empty() {
+                return Err(format!(
+                    "transition from '{}' to '{}' needs an event and an action",
+                    transition.from, transition.to
+                ));
+            }
+            if let Some(guard) = &transition.guard {
+                match Guard::parse(guard) {
+                    Some(parsed) if parsed.resource_type() == resource_type => {}
+                    Some(_) => {
+                        return Err(format!(
+                            "guard '{guard}' does not apply to {}",
+                            section_name(resource_type)
+                        ))
+                    }
+                    None => return Err(format!("unknown guard '{guard}'")),
+                }
+            }
+            let from_state = use_state(&transition.from)?;
+            let to_state = use_state(&transition.to)?;
+            if !seen.insert((from_state, transition.event.clone())) {
+                return Err(format!(
+                    "duplicate transition from '{}' on event '{}'",
+                    transition.from, transition.event
+                ));
+            }
+            table.push(StateTransition {
+                from_state,
+                event: transition.event.clone(),
+                to_state,
+                condition: transition.guard.clone(),
+                action: transition.action.clone(),
+            });
+        }
+
+        // Every state must be reachable from an initial state
+        let mut reached: HashSet<i32> = initial.iter().copied().collect();
+        let mut queue: VecDeque<i32> = initial.into_iter().collect();
+        while let Some(current) = queue.pop_front() {
+            for transition in table.iter().filter(|t| t.from_state == current) {
+                if reached.insert(transition.to_state) {
+                    queue.push_back(transition.to_state);
+                }
+            }
+        }
+        let mut unreachable: Vec<&String> = names
+            .iter()
+            .filter(|(value, _)| !reached.contains(value))
+            .map(|(_, name)| name)
+            .collect();
+        if !unreachable.is_empty() {
+            unreachable.sort();
+            let unreachable: Vec<&str> = unreachable.iter().map(|name| name.as_str()).collect();
+            return Err(format!("unreachable states: {}", unreachable.join(", ")));
+        }
+        Ok(table)
+    }
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_tables() {
+        let tables = default_tables();
+        assert_eq!(tables.len(), 3);
+        let scenario = &tables[&ResourceType::Scenario];
+        assert_eq!(scenario.len(), 12);
+        assert_eq!(
+            scenario[0],
+            StateTransition {
+                from_state: ScenarioState::Idle as i32,
+                event: "scenario_activation".to_string(),
+                to_state: ScenarioState::Waiting as i32,
+                condition: None,
+                action: "start_condition_evaluation".to_string(),
+            }
+        );
+        let model = &tables[&ResourceType::Model];
+        assert!(model
+            .iter()
+            .any(|t| t.to_state == ModelState::CrashLoopBackOff as i32
+                && t.condition.as_deref() == Some("consecutive_restart_failures")));
+    }
+
+    #[test]
+    fn test_partial_definition() {
+        let tables = parse(
+            r#"
+scenario:
+  initial: [idle]
+  states: [idle, waiting, playing]
+  transitions:
+    - { from: idle, event: scenario_activation, to: waiting, action: start_condition_evaluation }
+    - { from: waiting, event: condition_met, to: playing, action: execute_action_on_target_package }
+    - { from: playing, event: scenario_withdrawal, to: idle, action: stop_condition_evaluation }
+"#,
+        )
+        .unwrap();
+        assert_eq!(tables.len(), 1);
+        let scenario = &tables[&ResourceType::Scenario];
+        assert_eq!(scenario[1].to_state, ScenarioState::Playing as i32);
+        assert_eq!(scenario[1].condition, None);
+    }
+
+    #[test]
+    fn test_invalid_definitions() {
+        let cases = [
+            (
+                "scenario: { initial: [idle], transitions: [{ from: idle, event: go, to: sleeping, action: a }] }",
+                "unknown state 'sleeping'",
+            ),
+            (
+                "scenario: { initial: [idle], transitions: [{ from: idle, event: go, to: waiting, action: a }, { from: idle, event: go, to: allowed, action: b }] }",
+                "duplicate transition from 'idle' on event 'go'",
+            ),
+            (
+                "scenario: { initial: [idle], transitions: [{ from: idle, event: go, to: waiting, action: a }, { from: denied, event: withdraw, to: idle, action: b }] }",
+                "unreachable states: denied",
+            ),
+            (
+                "scenario: { initial: [idle], states: [idle, waiting, denied], transitions: [{ from: idle, event: go, to: waiting, action: a }] }",
+                "unreachable states: denied",
+            ),
+            (
+                "scenario: { initial: [idle], states: [idle], transitions: [{ from: idle, event: go, to: waiting, action: a }] }",
+                "state 'waiting' is not declared in states",
+            ),
+            (
+                "model: { initial: [], transitions: [] }",
+                "model: no initial state",
+            ),
+            ("volume: { initial: [pending], transitions: [] }", "unknown field"),
+            (
+                "scenario: { initial: [idle], transitions: [{ from: idle, event: go, to: waiting, guard: driver_present, action: a }] }",
+                "unknown guard 'driver_present'",
+            ),
+            (
+                "model: { initial: [pending], transitions: [{ from: pending, event: go, to: running, guard: all_models_normal, action: a }] }",
+                "guard 'all_models_normal' does not apply to model",
+            ),
+        ];
+        for (yaml, expected) in cases {
+            let error = parse(yaml).unwrap_err();
+            assert!(error.contains(expected), "{yaml}: {error}");
+        }
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let dir = std::env::temp_dir().join("statemanager-no-definition");
+        assert!(load(&dir.join("state_machine.yaml")).unwrap().is_none());
+    }
+}
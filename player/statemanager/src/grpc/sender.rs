@@ -7,11 +7,13 @@ use common::actioncontroller::{
     action_controller_connection_client::ActionControllerConnectionClient, connect_server,
     ReconcileRequest, ReconcileResponse,
 };
+use common::transport::Component;
 use tonic::{Request, Response, Status};
 
 pub async fn _send(condition: ReconcileRequest) -> Result<Response<ReconcileResponse>, Status> {
-    let mut client = ActionControllerConnectionClient::connect(connect_server())
+    let channel = common::transport::connect(Component::ActionController, connect_server())
         .await
-        .unwrap();
+        .map_err(|e| Status::unavailable(format!("Failed to connect to ActionController: {e}")))?;
+    let mut client = ActionControllerConnectionClient::new(channel);
     client.reconcile(Request::new(condition)).await
 }
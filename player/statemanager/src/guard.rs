@@ -0,0 +1,542 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Guards of state transitions
+//!
+//! A transition of the definition can name a guard that must hold for the
+//! transition to happen. Guards are evaluated against what the StateManager
+//! knows about the system, collected in [`GuardFacts`]:
+//!
+//! - the models of each package and whether they are `critical`, from the
+//!   package specs in etcd
+//! - the current state of each model, from the state machine
+//! - how often each model was restarted by the state machine
+//! - the containers last reported by each node
+//! - whether each node is ready, from the node registry
+//!
+//! Package guards:
+//!
+//! | Guard | Holds if |
+//! |---|---|
+//! | `all_models_normal`, `all_models_recovered` | every model is running or succeeded |
+//! | `critical_models_normal` | every critical model is running or succeeded |
+//! | `critical_models_failed`, `critical_model_issues`, `critical_models_affected` | a critical model has failed |
+//! | `non_critical_model_issues` | a non-critical model has failed and no critical one has |
+//!
+//! Model guards:
+//!
+//! | Guard | Holds if |
+//! |---|---|
+//! | `timeout_or_error` | the node is not ready, or the model has been pending for too long |
+//! | `all_containers_started`, `restart_successful` | the model has containers and all of them are running |
+//! | `depends_on_actual_state` | as above, and the node is not reported as not ready |
+//! | `one_time_task` | all containers of the model exited with code 0 |
+//! | `unexpected_termination` | a container of the model exited with an error, was killed or is dead |
+//! | `node_communication_issues` | the node of the model is not ready |
+//! | `consecutive_restart_failures` | the model was restarted `MAX_CONSECUTIVE_RESTARTS` times |
+//! | `retry_limit_reached` | the model was restarted `MAX_RESTART_ATTEMPTS` times |
+//! | `according_to_restart_policy` | the model was restarted less than `MAX_RESTART_ATTEMPTS` times |
+//!
+//! A model has failed if it is failed, in crash loop back-off or unknown.
+//! Package guards never hold for a package whose spec is not known. A
+//! container belongs to a model if its name starts with the model name and a
+//! dash, as podman names the containers of a kube pod.
+
+use common::monitoringserver::ContainerInfo;
+use common::spec::artifact::Package;
+use common::statemanager::{ModelState, ResourceType};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Restarts after which a running model is in a crash loop
+pub const MAX_CONSECUTIVE_RESTARTS: u32 = 3;
+
+/// Restarts after which a model is no longer restarted
+pub const MAX_RESTART_ATTEMPTS: u32 = 5;
+
+/// Time a model may stay pending before node allocation has failed
+const PENDING_TIMEOUT_SECS: u64 = 60;
+
+/// Condition of a state transition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Guard {
+    AllModelsNormal,
+    CriticalModelsNormal,
+    CriticalModelsFailed,
+    NonCriticalModelIssues,
+    TimeoutOrError,
+    AllContainersStarted,
+    OneTimeTask,
+    UnexpectedTermination,
+    ConsecutiveRestartFailures,
+    NodeCommunicationIssues,
+    RetryLimitReached,
+    ActualState,
+    RestartPolicy,
+}
+
+impl Guard {
+    /// Guard of a name used in the definition, if there is one
+    pub fn parse(name: &str) -> Option<Guard> {
+        let guard = match name.trim() {
+            "all_models_normal" | "all_models_recovered" => Guard::AllModelsNormal,
+            "critical_models_normal" => Guard::CriticalModelsNormal,
+            "critical_models_failed" | "critical_model_issues" | "critical_models_affected" => {
+                Guard::CriticalModelsFailed
+            }
+            "non_critical_model_issues" => Guard::NonCriticalModelIssues,
+            "timeout_or_error" => Guard::TimeoutOrError,
+            "all_containers_started" | "restart_successful" => Guard::AllContainersStarted,
+            "one_time_task" => Guard::OneTimeTask,
+            "unexpected_termination" => Guard::UnexpectedTermination,
+            "consecutive_restart_failures" => Guard::ConsecutiveRestartFailures,
+            "node_communication_issues" => Guard::NodeCommunicationIssues,
+            "retry_limit_reached" => Guard::RetryLimitReached,
+            "depends_on_actual_state" => Guard::ActualState,
+            "according_to_restart_policy" => Guard::RestartPolicy,
+            _ => return None,
+        };
+        Some(guard)
+    }
+
+    /// Resource type whose transitions can use the guard
+    pub fn resource_type(&self) -> ResourceType {
+        match self {
+            Guard::AllModelsNormal
+            | Guard::CriticalModelsNormal
+            | Guard::CriticalModelsFailed
+            | Guard::NonCriticalModelIssues => ResourceType::Package,
+            _ => ResourceType::Model,
+        }
+    }
+}
+
+/// Model of a package spec
+#[derive(Debug, Clone, PartialEq)]
+struct PackageModel {
+    name: String,
+    node: String,
+    critical: bool,
+}
+
+/// Container of a node report, reduced to what guards need
+#[derive(Debug, Clone, PartialEq)]
+struct ContainerStatus {
+    names: Vec<String>,
+    running: bool,
+    exited: bool,
+    exit_code: i64,
+    killed: bool,
+}
+
+impl ContainerStatus {
+    fn from_info(container: &ContainerInfo) -> Self {
+        let flag = |key: &str| container.state.get(key).is_some_and(|v| v == "true");
+        ContainerStatus {
+            names: container
+                .names
+                .iter()
+                .map(|name| name.trim_start_matches('/').to_string())
+                .collect(),
+            running: flag("Running"),
+            exited: container
+                .state
+                .get("Status")
+                .is_some_and(|status| status.eq_ignore_ascii_case("exited")),
+            exit_code: container
+                .state
+                .get("ExitCode")
+                .and_then(|code| code.parse().ok())
+                .unwrap_or(0),
+            killed: flag("OOMKilled") || flag("Dead"),
+        }
+    }
+
+    fn belongs_to(&self, model: &str) -> bool {
+        let prefix = format!("{model}-");
+        self.names.iter().any(|name| name.starts_with(&prefix))
+    }
+}
+
+/// What the StateManager knows about packages, models and nodes
+#[derive(Debug, Default)]
+pub struct GuardFacts {
+    /// Models of each package, by package name
+    packages: HashMap<String, Vec<PackageModel>>,
+    /// Containers last reported by each node
+    containers: HashMap<String, Vec<ContainerStatus>>,
+    /// Whether each node is ready, by node id and host name
+    nodes: HashMap<String, bool>,
+    /// Restarts of each model since it was created or left a crash loop
+    restarts: HashMap<String, u32>,
+}
+
+impl GuardFacts {
+    /// Remember the models of a package
+    pub fn register_package(&mut self, package: &Package) {
+        use common::spec::artifact::Artifact;
+
+        let models = package
+            .get_models()
+            .iter()
+            .map(|model| PackageModel {
+                name: model.get_name(),
+                node: model.get_node(),
+                critical: model.is_critical(),
+            })
+            .collect();
+        self.packages.insert(package.get_name(), models);
+    }
+
+    /// Whether the model belongs to a known package
+    pub fn knows_model(&self, model: &str) -> bool {
+        self.find_model(model).is_some()
+    }
+
+    /// Replace the containers of a node with its latest report
+    pub fn record_containers(&mut self, node: &str, containers: &[ContainerInfo]) {
+        let containers = containers.iter().map(ContainerStatus::from_info).collect();
+        self.containers.insert(node.to_string(), containers);
+    }
+
+    /// Remember whether a node is ready
+    pub fn set_node_ready(&mut self, node: &str, ready: bool) {
+        self.nodes.insert(node.to_string(), ready);
+    }
+
+    /// Count restarts of a model from its state transitions
+    ///
+    /// A model is restarted when it is recreated after a failure. The count
+    /// starts over when the model is created and when it leaves a crash loop.
+    pub fn record_model_transition(&mut self, model: &str, from_state: i32, to_state: i32) {
+        let from = ModelState::try_from(from_state).unwrap_or(ModelState::Unspecified);
+        let to = ModelState::try_from(to_state).unwrap_or(ModelState::Unspecified);
+        match (from, to) {
+            (ModelState::Unspecified, ModelState::Pending)
+            | (ModelState::CrashLoopBackOff, ModelState::Running) => {
+                self.restarts.remove(model);
+            }
+            (ModelState::Failed, ModelState::Pending) => {
+                *self.restarts.entry(model.to_string()).or_insert(0) += 1;
+            }
+            _ => {}
+        }
+    }
+
+    /// Number of restarts of a model
+    pub fn restarts(&self, model: &str) -> u32 {
+        self.restarts.get(model).copied().unwrap_or(0)
+    }
+
+    /// Evaluate a guard for a resource
+    ///
+    /// # Arguments
+    ///
+    /// * `guard` - Guard to evaluate
+    /// * `resource_name` - Package or model the transition is for
+    /// * `model_state` - Current state of a model and the time since it
+    ///   entered it, if the model is tracked
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the guard holds
+    pub fn evaluate(
+        &self,
+        guard: Guard,
+        resource_name: &str,
+        model_state: impl Fn(&str) -> Option<(i32, Duration)>,
+    ) -> bool {
+        match guard {
+            Guard::AllModelsNormal => self.package_models(resource_name, |models| {
+                !models.is_empty()
+                    && models
+                        .iter()
+                        .all(|model| is_normal(model_state(&model.name)))
+            }),
+            Guard::CriticalModelsNormal => self.package_models(resource_name, |models| {
+                models
+                    .iter()
+                    .filter(|model| model.critical)
+                    .all(|model| is_normal(model_state(&model.name)))
+            }),
+            Guard::CriticalModelsFailed => self.package_models(resource_name, |models| {
+                models
+                    .iter()
+                    .any(|model| model.critical && is_failed(model_state(&model.name)))
+            }),
+            Guard::NonCriticalModelIssues => self.package_models(resource_name, |models| {
+                let failed = |critical: bool| {
+                    models.iter().any(|model| {
+                        model.critical == critical && is_failed(model_state(&model.name))
+                    })
+                };
+                failed(false) && !failed(true)
+            }),
+            Guard::TimeoutOrError => {
+                self.node_ready(resource_name) == Some(false)
+                    || model_state(resource_name).is_some_and(|(state, elapsed)| {
+                        state == ModelState::Pending as i32
+                            && elapsed >= Duration::from_secs(PENDING_TIMEOUT_SECS)
+                    })
+            }
+            Guard::AllContainersStarted => self.containers_started(resource_name),
+            Guard::ActualState => {
+                self.node_ready(resource_name) != Some(false)
+                    && self.containers_started(resource_name)
+            }
+            Guard::OneTimeTask => {
+                let containers = self.model_containers(resource_name);
+                !containers.is_empty()
+                    && containers
+                        .iter()
+                        .all(|c| !c.running && c.exited && c.exit_code == 0)
+            }
+            Guard::UnexpectedTermination => self
+                .model_containers(resource_name)
+                .iter()
+                .any(|c| c.killed || (!c.running && c.exit_code != 0)),
+            Guard::NodeCommunicationIssues => self.node_ready(resource_name) == Some(false),
+            Guard::ConsecutiveRestartFailures => {
+                self.restarts(resource_name) >= MAX_CONSECUTIVE_RESTARTS
+            }
+            Guard::RetryLimitReached => self.restarts(resource_name) >= MAX_RESTART_ATTEMPTS,
+            Guard::RestartPolicy => self.restarts(resource_name) < MAX_RESTART_ATTEMPTS,
+        }
+    }
+
+    /// Apply a check to the models of a package, false if it is not known
+    fn package_models(&self, package: &str, check: impl Fn(&[PackageModel]) -> bool) -> bool {
+        self.packages
+            .get(package)
+            .is_some_and(|models| check(models))
+    }
+
+    fn find_model(&self, model: &str) -> Option<&PackageModel> {
+        self.packages
+            .values()
+            .flatten()
+            .find(|candidate| candidate.name == model)
+    }
+
+    /// Whether the node of a model is ready, `None` if that is not known
+    fn node_ready(&self, model: &str) -> Option<bool> {
+        let node = &self.find_model(model)?.node;
+        self.nodes.get(node).copied()
+    }
+
+    /// Containers of a model on its node, or on any node if it is not known
+    fn model_containers(&self, model: &str) -> Vec<&ContainerStatus> {
+        let node = self
+            .find_model(model)
+            .map(|model| model.node.as_str())
+            .filter(|node| !node.is_empty());
+        self.containers
+            .iter()
+            .filter(|(name, _)| node.is_none_or(|node| node == name.as_str()))
+            .flat_map(|(_, containers)| containers)
+            .filter(|container| container.belongs_to(model))
+            .collect()
+    }
+
+    fn containers_started(&self, model: &str) -> bool {
+        let containers = self.model_containers(model);
+        !containers.is_empty() && containers.iter().all(|c| c.running)
+    }
+}
+
+fn is_normal(state: Option<(i32, Duration)>) -> bool {
+    state.is_some_and(|(state, _)| {
+        state == ModelState::Running as i32 || state == ModelState::Succeeded as i32
+    })
+}
+
+fn is_failed(state: Option<(i32, Duration)>) -> bool {
+    state.is_some_and(|(state, _)| {
+        state == ModelState::Failed as i32
+            || state == ModelState::CrashLoopBackOff as i32
+            || state == ModelState::Unknown as i32
+    })
+}
+
+//Unit Test Cases
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn facts() -> GuardFacts {
+        let package: Package = serde_yaml::from_str(
+            r#"
+apiVersion: v1
+kind: Package
+metadata:
+  name: infotainment
+spec:
+  pattern:
+    - type: plain
+  models:
+    - name: navigation
+      node: HPC
+      critical: true
+      resources:
+        volume:
+        network:
+    - name: weather
+      node: ZONE
+      resources:
+        volume:
+        network:
+"#,
+        )
+        .unwrap();
+        let mut facts = GuardFacts::default();
+        facts.register_package(&package);
+        facts
+    }
+
+    fn container(name: &str, state: &[(&str, &str)]) -> ContainerInfo {
+        ContainerInfo {
+            names: vec![name.to_string()],
+            state: state
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn states(entries: &[(&'static str, ModelState)]) -> impl Fn(&str) -> Option<(i32, Duration)> {
+        let entries: HashMap<&str, i32> = entries.iter().map(|(n, s)| (*n, *s as i32)).collect();
+        move |name| entries.get(name).map(|state| (*state, Duration::ZERO))
+    }
+
+    #[test]
+    fn test_parse_guard() {
+        assert_eq!(
+            Guard::parse("critical_model_issues"),
+            Some(Guard::CriticalModelsFailed)
+        );
+        assert_eq!(
+            Guard::parse("restart_successful").map(|g| g.resource_type()),
+            Some(ResourceType::Model)
+        );
+        assert_eq!(Guard::parse("driver_present"), None);
+        // No facts back these names, so they are not aliases of other guards
+        for name in [
+            "depends_on_recovery_level",
+            "depends_on_previous_state",
+            "depends_on_rollback_settings",
+            "sufficient_resources",
+        ] {
+            assert_eq!(Guard::parse(name), None);
+        }
+    }
+
+    #[test]
+    fn test_package_guards() {
+        let facts = facts();
+        let eval = |guard, models: &[(&'static str, ModelState)]| {
+            facts.evaluate(guard, "infotainment", states(models))
+        };
+
+        let running = [
+            ("navigation", ModelState::Running),
+            ("weather", ModelState::Running),
+        ];
+        assert!(eval(Guard::AllModelsNormal, &running));
+        assert!(!eval(Guard::NonCriticalModelIssues, &running));
+
+        let weather_failed = [
+            ("navigation", ModelState::Running),
+            ("weather", ModelState::Failed),
+        ];
+        assert!(!eval(Guard::AllModelsNormal, &weather_failed));
+        assert!(eval(Guard::CriticalModelsNormal, &weather_failed));
+        assert!(eval(Guard::NonCriticalModelIssues, &weather_failed));
+        assert!(!eval(Guard::CriticalModelsFailed, &weather_failed));
+
+        let navigation_failed = [
+            ("navigation", ModelState::CrashLoopBackOff),
+            ("weather", ModelState::Failed),
+        ];
+        assert!(eval(Guard::CriticalModelsFailed, &navigation_failed));
+        assert!(!eval(Guard::NonCriticalModelIssues, &navigation_failed));
+
+        // Models that are not tracked yet are neither normal nor failed
+        assert!(!eval(Guard::AllModelsNormal, &[]));
+        assert!(!eval(Guard::CriticalModelsFailed, &[]));
+        assert!(!facts.evaluate(Guard::AllModelsNormal, "unknown", states(&running)));
+    }
+
+    #[test]
+    fn test_model_guards() {
+        let mut facts = facts();
+        let eval = |facts: &GuardFacts, guard| facts.evaluate(guard, "navigation", states(&[]));
+
+        assert!(!eval(&facts, Guard::AllContainersStarted));
+
+        facts.record_containers(
+            "HPC",
+            &[
+                container("navigation-map", &[("Running", "true")]),
+                container("/navigation-gps", &[("Running", "true")]),
+                container("weather-forecast", &[("Status", "exited")]),
+            ],
+        );
+        assert!(eval(&facts, Guard::AllContainersStarted));
+        assert!(eval(&facts, Guard::ActualState));
+        assert!(!eval(&facts, Guard::UnexpectedTermination));
+        // Containers of a model on another node are ignored
+        assert!(!facts.evaluate(Guard::OneTimeTask, "weather", states(&[])));
+
+        facts.record_containers(
+            "HPC",
+            &[
+                container("navigation-map", &[("Running", "true")]),
+                container(
+                    "navigation-gps",
+                    &[("Status", "exited"), ("ExitCode", "137")],
+                ),
+            ],
+        );
+        assert!(!eval(&facts, Guard::AllContainersStarted));
+        assert!(eval(&facts, Guard::UnexpectedTermination));
+
+        facts.set_node_ready("HPC", false);
+        assert!(!eval(&facts, Guard::ActualState));
+        assert!(eval(&facts, Guard::NodeCommunicationIssues));
+        assert!(eval(&facts, Guard::TimeoutOrError));
+    }
+
+    #[test]
+    fn test_restart_guards() {
+        let mut facts = facts();
+        let failed = ModelState::Failed as i32;
+        let pending = ModelState::Pending as i32;
+        let eval = |facts: &GuardFacts, guard| facts.evaluate(guard, "weather", states(&[]));
+
+        facts.record_model_transition("weather", ModelState::Unspecified as i32, pending);
+        assert!(eval(&facts, Guard::RestartPolicy));
+        assert!(!eval(&facts, Guard::ConsecutiveRestartFailures));
+
+        for _ in 0..MAX_CONSECUTIVE_RESTARTS {
+            facts.record_model_transition("weather", failed, pending);
+        }
+        assert!(eval(&facts, Guard::ConsecutiveRestartFailures));
+        assert!(!eval(&facts, Guard::RetryLimitReached));
+
+        for _ in MAX_CONSECUTIVE_RESTARTS..MAX_RESTART_ATTEMPTS {
+            facts.record_model_transition("weather", failed, pending);
+        }
+        assert!(eval(&facts, Guard::RetryLimitReached));
+        assert!(!eval(&facts, Guard::RestartPolicy));
+
+        facts.record_model_transition(
+            "weather",
+            ModelState::CrashLoopBackOff as i32,
+            ModelState::Running as i32,
+        );
+        assert_eq!(facts.restarts("weather"), 0);
+    }
+}
@@ -16,10 +16,12 @@ use common::monitoringserver::ContainerList;
 use common::statemanager::{
     state_manager_connection_server::StateManagerConnectionServer, StateChange,
 };
+use common::transport::Component;
 use tokio::sync::mpsc::{channel, Receiver, Sender};
-use tonic::transport::Server;
 
+pub mod definition;
 pub mod grpc;
+pub mod guard;
 pub mod manager;
 pub mod state_machine;
 pub mod types;
@@ -126,7 +128,14 @@ async fn initialize_grpc_server(
 
     // Start the gRPC server with comprehensive error handling
     println!("Starting StateManager gRPC server...");
-    match Server::builder()
+    let mut builder = match common::transport::server(Component::StateManager) {
+        Ok(builder) => builder,
+        Err(e) => {
+            eprintln!("Failed to configure StateManager gRPC server: {e:?}");
+            return;
+        }
+    };
+    match builder
         .add_service(StateManagerConnectionServer::new(server))
         .serve(addr)
         .await
@@ -174,6 +183,7 @@ async fn main() {
     println!("         PICCOLO StateManager           ");
     println!("========================================");
     println!("Starting StateManager service...");
+    common::transport::set_local_component(Component::StateManager);
 
     // Create async channels for communication between gRPC server and processing engine
     // Buffer size of 100 provides good throughput while preventing excessive memory usage
@@ -13,9 +13,13 @@
 //! state transitions, monitoring, reconciliation, and recovery for all resource types
 //! (Scenario, Package, Model, Volume, Network, Node).
 
+use crate::definition;
 use crate::state_machine::StateMachine;
 use crate::types::{ActionCommand, TransitionResult};
+use common::apiserver::NodeInfo;
 use common::monitoringserver::ContainerList;
+use common::nodeagent::NodeStatus;
+use common::spec::artifact::Package;
 
 use common::statemanager::{
     ErrorCode, ModelState, PackageState, ResourceType, ScenarioState, StateChange,
@@ -91,11 +95,13 @@ impl StateManagerManager {
     /// Performs startup operations required before beginning message processing:
     /// - Loads initial resource states from persistent storage
     /// - Initializes state machine engines for each resource type
+    /// - Loads the transition tables of the definition file, if there is one
     /// - Sets up monitoring and health check systems
     /// - Prepares recovery and reconciliation systems
     ///
     /// # Returns
-    /// * `Result<()>` - Success or initialization error
+    /// * `Result<()>` - Success or initialization error, such as an invalid
+    ///   definition file
     ///
     /// # Future Enhancements
     /// - Load persisted resource states from storage (etcd, database)
@@ -108,6 +114,11 @@ impl StateManagerManager {
         // Initialize the state machine with async action executor
         let action_receiver = {
             let mut state_machine = self.state_machine.lock().await;
+            let path = definition::definition_path();
+            if let Some(tables) = definition::load(&path)? {
+                println!("Transition tables loaded from {}", path.display());
+                state_machine.load_transition_tables(tables);
+            }
             state_machine.initialize_action_executor()
         };
 
@@ -294,6 +305,8 @@ impl StateManagerManager {
         // - Condition evaluation for conditional transitions
         // - Action scheduling for follow-up operations
         // - Error detection and reporting
+        self.refresh_guard_facts(resource_type, &state_change.resource_name)
+            .await;
         let result = {
             // Acquire exclusive lock on the state machine for this transition
             // Note: This serializes all state transitions to maintain consistency
@@ -434,6 +447,13 @@ impl StateManagerManager {
         println!("  Node Name: {}", container_list.node_name);
         println!("  Container Count: {}", container_list.containers.len());
 
+        // Containers are what the model guards of the state machine check
+        self.state_machine
+            .lock()
+            .await
+            .guard_facts_mut()
+            .record_containers(&container_list.node_name, &container_list.containers);
+
         // Process each container for health status analysis
         for (i, container) in container_list.containers.iter().enumerate() {
             // container.names is a Vec<String>, so join them for display
@@ -489,6 +509,77 @@ impl StateManagerManager {
         println!("=====================================");
     }
 
+    /// Update the facts that guards are evaluated against before a transition
+    ///
+    /// Reads the spec of the package, or of all packages if a model is not
+    /// part of a known one, and the node registry from etcd. Failures are
+    /// logged and the guards are then evaluated against the facts known
+    /// before.
+    ///
+    /// # Arguments
+    /// * `resource_type` - Type of the resource that changes state
+    /// * `resource_name` - Name of the resource
+    async fn refresh_guard_facts(&self, resource_type: ResourceType, resource_name: &str) {
+        let packages = match resource_type {
+            ResourceType::Package => {
+                match common::etcd::get(&format!("Package/{resource_name}")).await {
+                    Ok(yaml) => vec![yaml],
+                    Err(e) => {
+                        eprintln!("Cannot read package '{resource_name}' for guards: {e:?}");
+                        Vec::new()
+                    }
+                }
+            }
+            ResourceType::Model => {
+                let known = self
+                    .state_machine
+                    .lock()
+                    .await
+                    .guard_facts()
+                    .knows_model(resource_name);
+                if known {
+                    Vec::new()
+                } else {
+                    match common::etcd::get_all_with_prefix("Package/").await {
+                        Ok(kvs) => kvs.into_iter().map(|kv| kv.value).collect(),
+                        Err(e) => {
+                            eprintln!("Cannot read packages for guards: {e:?}");
+                            Vec::new()
+                        }
+                    }
+                }
+            }
+            _ => return,
+        };
+
+        let nodes: Vec<NodeInfo> = match common::etcd::get_all_with_prefix("cluster/nodes/").await {
+            Ok(kvs) => kvs
+                .into_iter()
+                .filter_map(|kv| decode_node_info(&kv.value))
+                .collect(),
+            Err(e) => {
+                eprintln!("Cannot read node registry for guards: {e:?}");
+                Vec::new()
+            }
+        };
+
+        let mut state_machine = self.state_machine.lock().await;
+        let facts = state_machine.guard_facts_mut();
+        for yaml in packages {
+            match serde_yaml::from_str::<Package>(&yaml) {
+                Ok(package) => facts.register_package(&package),
+                Err(e) => eprintln!("Cannot parse package for guards: {e}"),
+            }
+        }
+        for node in nodes {
+            // Nodes in maintenance are cordoned but still run their models
+            let ready = node.status != i32::from(NodeStatus::NotReady)
+                && node.status != i32::from(NodeStatus::Terminating);
+            facts.set_node_ready(&node.node_id, ready);
+            facts.set_node_ready(&node.hostname, ready);
+        }
+    }
+
     /// Main message processing loop for handling gRPC requests.
     ///
     /// Spawns dedicated async tasks for processing different message types:
@@ -641,6 +732,17 @@ impl StateManagerManager {
     }
 }
 
+/// Decode a node of the registry, stored by apiserver as base64 protobuf
+fn decode_node_info(value: &str) -> Option<NodeInfo> {
+    use base64::Engine;
+    use prost::Message;
+
+    let buf = base64::engine::general_purpose::STANDARD
+        .decode(value)
+        .ok()?;
+    NodeInfo::decode(&buf[..]).ok()
+}
+
 /// Async action executor - runs in separate task
 ///
 /// This function handles the execution of actions triggered by state transitions.
@@ -673,6 +775,12 @@ async fn execute_action(command: ActionCommand) {
             );
             // Would integrate with policy engine or condition evaluator
         }
+        "stop_condition_evaluation" => {
+            println!(
+                " Stopping condition evaluation for scenario: {}",
+                command.resource_key
+            );
+        }
         "start_policy_verification" => {
             println!(
                 " Starting policy verification for scenario: {}",
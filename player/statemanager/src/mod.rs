@@ -7,7 +7,9 @@
 //!
 //! This module provides the public interface for the StateManager component
 
+pub mod definition;
 pub mod grpc;
+pub mod guard;
 pub mod manager;
 pub mod state_machine;
 pub mod types;
@@ -12,7 +12,8 @@
 //! # Architecture Overview
 //!
 //! The state machine follows a table-driven approach where each resource type (Scenario, Package, Model)
-//! has its own transition table defining valid state changes. The system supports:
+//! has its own transition table defining valid state changes. The tables are loaded from a YAML
+//! definition, see the `definition` module. The system supports:
 //! - Conditional transitions based on resource state
 //! - Action execution during state changes non-blocking
 //! - Health monitoring and failure handling
@@ -26,6 +27,8 @@
 //! let result = state_machine.process_state_change(state_change);
 //! ```
 
+use crate::definition::{self, TransitionTables};
+use crate::guard::{Guard, GuardFacts};
 use crate::types::{ActionCommand, HealthStatus, ResourceState, StateTransition, TransitionResult};
 use common::statemanager::{
     ErrorCode, ModelState, PackageState, ResourceType, ScenarioState, StateChange,
@@ -106,13 +109,17 @@ pub struct StateMachine {
 
     /// Action command sender for async execution
     action_sender: Option<mpsc::UnboundedSender<ActionCommand>>,
+
+    /// Packages, containers, nodes and restarts that guards are evaluated against
+    guard_facts: GuardFacts,
 }
 
 impl StateMachine {
     /// Creates a new StateMachine with predefined transition tables
     ///
-    /// Initializes the state machine with empty resource tracking and
-    /// populates the transition tables for all supported resource types.
+    /// Initializes the state machine with empty resource tracking and the
+    /// built-in transition tables of all supported resource types, see
+    /// `definition`.
     ///
     /// # Returns
     /// A fully configured StateMachine ready to process state changes
@@ -122,19 +129,14 @@ impl StateMachine {
     /// let state_machine = StateMachine::new();
     /// ```
     pub fn new() -> Self {
-        let mut state_machine = StateMachine {
-            transition_tables: HashMap::new(),
+        StateMachine {
+            // Built-in transition tables for each resource type
+            transition_tables: definition::default_tables(),
             resource_states: HashMap::new(),
             backoff_timers: HashMap::new(),
             action_sender: None,
-        };
-
-        // Initialize transition tables for each resource type
-        state_machine.initialize_scenario_transitions();
-        state_machine.initialize_package_transitions();
-        state_machine.initialize_model_transitions();
-
-        state_machine
+            guard_facts: GuardFacts::default(),
+        }
     }
 
     /// Initialize async action executor
@@ -144,291 +146,25 @@ impl StateMachine {
         receiver
     }
 
-    // ========================================
-    // STATE TRANSITION TABLE INITIALIZATION
-    // ========================================
-
-    /// Initialize the state transition table for Scenario resources
+    /// Replace the transition tables of some resource types
     ///
-    /// Populates the transition table with all valid state changes for Scenario resources
-    /// according to the PICCOLO specification. This includes transitions for:
-    /// - Creation and initialization
-    /// - Activation and deactivation
-    /// - Error handling and recovery
-    /// - Cleanup and termination
+    /// Used at startup to apply a definition file; the tables of the
+    /// resource types it does not define are kept.
     ///
-    /// # Implementation Note
-    /// This method should define transitions like:
-    /// - "Inactive" -> "Active" on "activate" event
-    /// - "Active" -> "Inactive" on "deactivate" event
-    /// - Any state -> "Failed" on "error" event
-    fn initialize_scenario_transitions(&mut self) {
-        let scenario_transitions = vec![
-            StateTransition {
-                from_state: ScenarioState::Idle as i32,
-                event: "scenario_activation".to_string(),
-                to_state: ScenarioState::Waiting as i32,
-                condition: None,
-                action: "start_condition_evaluation".to_string(),
-            },
-            StateTransition {
-                from_state: ScenarioState::Waiting as i32,
-                event: "condition_met".to_string(),
-                to_state: ScenarioState::Allowed as i32,
-                condition: None,
-                action: "start_policy_verification".to_string(),
-            },
-            StateTransition {
-                from_state: ScenarioState::Allowed as i32,
-                event: "policy_verification_success".to_string(),
-                to_state: ScenarioState::Playing as i32,
-                condition: None,
-                action: "execute_action_on_target_package".to_string(),
-            },
-            StateTransition {
-                from_state: ScenarioState::Allowed as i32,
-                event: "policy_verification_failure".to_string(),
-                to_state: ScenarioState::Denied as i32,
-                condition: None,
-                action: "log_denial_generate_alert".to_string(),
-            },
-        ];
-        self.transition_tables
-            .insert(ResourceType::Scenario, scenario_transitions);
+    /// # Parameters
+    /// - `tables`: Validated tables from `definition::parse` or `definition::load`
+    pub fn load_transition_tables(&mut self, tables: TransitionTables) {
+        self.transition_tables.extend(tables);
     }
 
-    /// Initialize the state transition table for Package resources
-    ///
-    /// Configures all valid state transitions for Package resources, including:
-    /// - Download and installation states
-    /// - Verification and validation phases
-    /// - Update and rollback mechanisms
-    /// - Cleanup and removal operations
-    ///
-    /// # Implementation Note
-    /// Package transitions typically involve more complex workflows due to
-    /// dependency management and rollback requirements.
-    fn initialize_package_transitions(&mut self) {
-        let package_transitions = vec![
-            StateTransition {
-                from_state: PackageState::Unspecified as i32,
-                event: "launch_request".to_string(),
-                to_state: PackageState::Initializing as i32,
-                condition: None,
-                action: "start_model_creation_allocate_resources".to_string(),
-            },
-            StateTransition {
-                from_state: PackageState::Initializing as i32,
-                event: "initialization_complete".to_string(),
-                to_state: PackageState::Running as i32,
-                condition: Some("all_models_normal".to_string()),
-                action: "update_state_announce_availability".to_string(),
-            },
-            StateTransition {
-                from_state: PackageState::Initializing as i32,
-                event: "partial_initialization_failure".to_string(),
-                to_state: PackageState::Degraded as i32,
-                condition: Some("critical_models_normal".to_string()),
-                action: "log_warning_activate_partial_functionality".to_string(),
-            },
-            StateTransition {
-                from_state: PackageState::Initializing as i32,
-                event: "critical_initialization_failure".to_string(),
-                to_state: PackageState::Error as i32,
-                condition: Some("critical_models_failed".to_string()),
-                action: "log_error_attempt_recovery".to_string(),
-            },
-            StateTransition {
-                from_state: PackageState::Running as i32,
-                event: "model_issue_detected".to_string(),
-                to_state: PackageState::Degraded as i32,
-                condition: Some("non_critical_model_issues".to_string()),
-                action: "log_warning_maintain_partial_functionality".to_string(),
-            },
-            StateTransition {
-                from_state: PackageState::Running as i32,
-                event: "critical_issue_detected".to_string(),
-                to_state: PackageState::Error as i32,
-                condition: Some("critical_model_issues".to_string()),
-                action: "log_error_attempt_recovery".to_string(),
-            },
-            StateTransition {
-                from_state: PackageState::Running as i32,
-                event: "pause_request".to_string(),
-                to_state: PackageState::Paused as i32,
-                condition: None,
-                action: "pause_models_preserve_state".to_string(),
-            },
-            StateTransition {
-                from_state: PackageState::Degraded as i32,
-                event: "model_recovery".to_string(),
-                to_state: PackageState::Running as i32,
-                condition: Some("all_models_recovered".to_string()),
-                action: "update_state_restore_full_functionality".to_string(),
-            },
-            StateTransition {
-                from_state: PackageState::Degraded as i32,
-                event: "additional_model_issues".to_string(),
-                to_state: PackageState::Error as i32,
-                condition: Some("critical_models_affected".to_string()),
-                action: "log_error_attempt_recovery".to_string(),
-            },
-            StateTransition {
-                from_state: PackageState::Degraded as i32,
-                event: "pause_request".to_string(),
-                to_state: PackageState::Paused as i32,
-                condition: None,
-                action: "pause_models_preserve_state".to_string(),
-            },
-            StateTransition {
-                from_state: PackageState::Error as i32,
-                event: "recovery_successful".to_string(),
-                to_state: PackageState::Running as i32,
-                condition: Some("depends_on_recovery_level".to_string()),
-                action: "update_state_announce_functionality_restoration".to_string(),
-            },
-            StateTransition {
-                from_state: PackageState::Paused as i32,
-                event: "resume_request".to_string(),
-                to_state: PackageState::Running as i32,
-                condition: Some("depends_on_previous_state".to_string()),
-                action: "resume_models_restore_state".to_string(),
-            },
-            StateTransition {
-                from_state: PackageState::Running as i32,
-                event: "update_request".to_string(),
-                to_state: PackageState::Updating as i32,
-                condition: None,
-                action: "start_update_process".to_string(),
-            },
-            StateTransition {
-                from_state: PackageState::Updating as i32,
-                event: "update_successful".to_string(),
-                to_state: PackageState::Running as i32,
-                condition: None,
-                action: "activate_new_version_update_state".to_string(),
-            },
-            StateTransition {
-                from_state: PackageState::Updating as i32,
-                event: "update_failed".to_string(),
-                to_state: PackageState::Error as i32,
-                condition: Some("depends_on_rollback_settings".to_string()),
-                action: "rollback_or_error_handling".to_string(),
-            },
-        ];
-
-        self.transition_tables
-            .insert(ResourceType::Package, package_transitions);
+    /// Facts that guards are evaluated against
+    pub fn guard_facts(&self) -> &GuardFacts {
+        &self.guard_facts
     }
 
-    /// Initialize the state transition table for Model resources
-    ///
-    /// Sets up state transitions specific to Model resources, covering:
-    /// - Model loading and initialization
-    /// - Training and inference states
-    /// - Model versioning and updates
-    /// - Resource allocation and cleanup
-    ///
-    /// # Implementation Note
-    /// Model transitions may include resource-intensive operations and
-    /// should account for memory and compute constraints.
-    fn initialize_model_transitions(&mut self) {
-        let model_transitions = vec![
-            StateTransition {
-                from_state: ModelState::Unspecified as i32,
-                event: "creation_request".to_string(),
-                to_state: ModelState::Pending as i32,
-                condition: None,
-                action: "start_node_selection_and_allocation".to_string(),
-            },
-            StateTransition {
-                from_state: ModelState::Pending as i32,
-                event: "node_allocation_complete".to_string(),
-                to_state: ModelState::ContainerCreating as i32,
-                condition: Some("sufficient_resources".to_string()),
-                action: "pull_container_images_mount_volumes".to_string(),
-            },
-            StateTransition {
-                from_state: ModelState::Pending as i32,
-                event: "node_allocation_failed".to_string(),
-                to_state: ModelState::Failed as i32,
-                condition: Some("timeout_or_error".to_string()),
-                action: "log_error_retry_or_reschedule".to_string(),
-            },
-            StateTransition {
-                from_state: ModelState::ContainerCreating as i32,
-                event: "container_creation_complete".to_string(),
-                to_state: ModelState::Running as i32,
-                condition: Some("all_containers_started".to_string()),
-                action: "update_state_start_readiness_checks".to_string(),
-            },
-            StateTransition {
-                from_state: ModelState::ContainerCreating as i32,
-                event: "container_creation_failed".to_string(),
-                to_state: ModelState::Failed as i32,
-                condition: None,
-                action: "log_error_retry_or_reschedule".to_string(),
-            },
-            StateTransition {
-                from_state: ModelState::Running as i32,
-                event: "temporary_task_complete".to_string(),
-                to_state: ModelState::Succeeded as i32,
-                condition: Some("one_time_task".to_string()),
-                action: "log_completion_clean_up_resources".to_string(),
-            },
-            StateTransition {
-                from_state: ModelState::Running as i32,
-                event: "container_termination".to_string(),
-                to_state: ModelState::Failed as i32,
-                condition: Some("unexpected_termination".to_string()),
-                action: "log_error_evaluate_automatic_restart".to_string(),
-            },
-            StateTransition {
-                from_state: ModelState::Running as i32,
-                event: "repeated_crash_detection".to_string(),
-                to_state: ModelState::CrashLoopBackOff as i32,
-                condition: Some("consecutive_restart_failures".to_string()),
-                action: "set_backoff_timer_collect_logs".to_string(),
-            },
-            StateTransition {
-                from_state: ModelState::Running as i32,
-                event: "monitoring_failure".to_string(),
-                to_state: ModelState::Unknown as i32,
-                condition: Some("node_communication_issues".to_string()),
-                action: "attempt_diagnostics_restore_communication".to_string(),
-            },
-            StateTransition {
-                from_state: ModelState::CrashLoopBackOff as i32,
-                event: "backoff_time_elapsed".to_string(),
-                to_state: ModelState::Running as i32,
-                condition: Some("restart_successful".to_string()),
-                action: "resume_monitoring_reset_counter".to_string(),
-            },
-            StateTransition {
-                from_state: ModelState::CrashLoopBackOff as i32,
-                event: "maximum_retries_exceeded".to_string(),
-                to_state: ModelState::Failed as i32,
-                condition: Some("retry_limit_reached".to_string()),
-                action: "log_error_notify_for_manual_intervention".to_string(),
-            },
-            StateTransition {
-                from_state: ModelState::Unknown as i32,
-                event: "state_check_recovered".to_string(),
-                to_state: ModelState::Running as i32,
-                condition: Some("depends_on_actual_state".to_string()),
-                action: "synchronize_state_recover_if_needed".to_string(),
-            },
-            StateTransition {
-                from_state: ModelState::Failed as i32,
-                event: "manual_automatic_recovery".to_string(),
-                to_state: ModelState::Pending as i32,
-                condition: Some("according_to_restart_policy".to_string()),
-                action: "start_model_recreation".to_string(),
-            },
-        ];
-
-        self.transition_tables
-            .insert(ResourceType::Model, model_transitions);
+    /// Facts that guards are evaluated against, for the manager to update
+    pub fn guard_facts_mut(&mut self) -> &mut GuardFacts {
+        &mut self.guard_facts
     }
 
     // ========================================
@@ -520,7 +256,7 @@ impl StateMachine {
         ) {
             // Check conditions if any
             if let Some(ref condition) = transition.condition {
-                if !self.evaluate_condition(condition, &state_change) {
+                if !self.evaluate_condition(condition, resource_type, &state_change) {
                     return TransitionResult {
                         new_state: current_state,
                         error_code: ErrorCode::PreconditionFailed,
@@ -807,238 +543,72 @@ impl StateMachine {
         }
     }
 
-    /// Infer the appropriate event type from state transition
+    /// Infer the event of a state change from the transition tables
     ///
-    /// When an explicit event is not provided, this method attempts to
-    /// determine the most appropriate event based on the current and target states.
+    /// State changes name their current and target states only, so the event
+    /// is the one of the transition between these states. If several events
+    /// lead from one state to the other, the first transition of the table
+    /// is used.
     ///
     /// # Parameters
     /// - `current_state`: The current state of the resource
     /// - `target_state`: The desired target state
+    /// - `resource_type`: The type of the resource
     ///
     /// # Returns
     /// A string representing the inferred event type
     ///
-    /// # Examples
-    /// - "Inactive" -> "Active" might infer "activate"
-    /// - "Running" -> "Stopped" might infer "stop"
-    /// - Any state -> "Failed" might infer "error"
-    ///
     /// # Fallback Behavior
-    /// If no specific event can be inferred, returns a generic event name
-    /// based on the target state (e.g., "transition_to_active").
+    /// If no transition leads from the current to the target state, returns
+    /// a generic event name (e.g., "transition_1_2") that matches no transition.
     fn infer_event_from_states(
         &self,
         current_state: i32,
         target_state: i32,
         resource_type: ResourceType,
     ) -> String {
-        match resource_type {
-            ResourceType::Scenario => match (current_state, target_state) {
-                (x, y) if x == ScenarioState::Idle as i32 && y == ScenarioState::Waiting as i32 => {
-                    "scenario_activation".to_string()
-                }
-                (x, y)
-                    if x == ScenarioState::Waiting as i32 && y == ScenarioState::Allowed as i32 =>
-                {
-                    "condition_met".to_string()
-                }
-                (x, y)
-                    if x == ScenarioState::Allowed as i32 && y == ScenarioState::Playing as i32 =>
-                {
-                    "policy_verification_success".to_string()
-                }
-                (x, y)
-                    if x == ScenarioState::Allowed as i32 && y == ScenarioState::Denied as i32 =>
-                {
-                    "policy_verification_failure".to_string()
-                }
-                _ => format!("transition_{current_state}_{target_state}"),
-            },
-            ResourceType::Package => match (current_state, target_state) {
-                (x, y)
-                    if x == PackageState::Unspecified as i32
-                        && y == PackageState::Initializing as i32 =>
-                {
-                    "launch_request".to_string()
-                }
-                (x, y)
-                    if x == PackageState::Initializing as i32
-                        && y == PackageState::Running as i32 =>
-                {
-                    "initialization_complete".to_string()
-                }
-                (x, y)
-                    if x == PackageState::Initializing as i32
-                        && y == PackageState::Degraded as i32 =>
-                {
-                    "partial_initialization_failure".to_string()
-                }
-                (x, y)
-                    if x == PackageState::Initializing as i32
-                        && y == PackageState::Error as i32 =>
-                {
-                    "critical_initialization_failure".to_string()
-                }
-                (x, y)
-                    if x == PackageState::Running as i32 && y == PackageState::Degraded as i32 =>
-                {
-                    "model_issue_detected".to_string()
-                }
-                (x, y) if x == PackageState::Running as i32 && y == PackageState::Error as i32 => {
-                    "critical_issue_detected".to_string()
-                }
-                (x, y) if x == PackageState::Running as i32 && y == PackageState::Paused as i32 => {
-                    "pause_request".to_string()
-                }
-                (x, y)
-                    if x == PackageState::Running as i32 && y == PackageState::Updating as i32 =>
-                {
-                    "update_request".to_string()
-                }
-                (x, y)
-                    if x == PackageState::Degraded as i32 && y == PackageState::Running as i32 =>
-                {
-                    "model_recovery".to_string()
-                }
-                (x, y) if x == PackageState::Degraded as i32 && y == PackageState::Error as i32 => {
-                    "additional_model_issues".to_string()
-                }
-                (x, y)
-                    if x == PackageState::Degraded as i32 && y == PackageState::Paused as i32 =>
-                {
-                    "pause_request".to_string()
-                }
-                (x, y) if x == PackageState::Error as i32 && y == PackageState::Running as i32 => {
-                    "recovery_successful".to_string()
-                }
-                (x, y) if x == PackageState::Paused as i32 && y == PackageState::Running as i32 => {
-                    "resume_request".to_string()
-                }
-                (x, y)
-                    if x == PackageState::Updating as i32 && y == PackageState::Running as i32 =>
-                {
-                    "update_successful".to_string()
-                }
-                (x, y) if x == PackageState::Updating as i32 && y == PackageState::Error as i32 => {
-                    "update_failed".to_string()
-                }
-                _ => format!("transition_{current_state}_{target_state}"),
-            },
-            ResourceType::Model => match (current_state, target_state) {
-                (x, y)
-                    if x == ModelState::Unspecified as i32 && y == ModelState::Pending as i32 =>
-                {
-                    "creation_request".to_string()
-                }
-                (x, y)
-                    if x == ModelState::Pending as i32
-                        && y == ModelState::ContainerCreating as i32 =>
-                {
-                    "node_allocation_complete".to_string()
-                }
-                (x, y) if x == ModelState::Pending as i32 && y == ModelState::Failed as i32 => {
-                    "node_allocation_failed".to_string()
-                }
-                (x, y)
-                    if x == ModelState::ContainerCreating as i32
-                        && y == ModelState::Running as i32 =>
-                {
-                    "container_creation_complete".to_string()
-                }
-                (x, y)
-                    if x == ModelState::ContainerCreating as i32
-                        && y == ModelState::Failed as i32 =>
-                {
-                    "container_creation_failed".to_string()
-                }
-                (x, y) if x == ModelState::Running as i32 && y == ModelState::Succeeded as i32 => {
-                    "temporary_task_complete".to_string()
-                }
-                (x, y) if x == ModelState::Running as i32 && y == ModelState::Failed as i32 => {
-                    "container_termination".to_string()
-                }
-                (x, y)
-                    if x == ModelState::Running as i32
-                        && y == ModelState::CrashLoopBackOff as i32 =>
-                {
-                    "repeated_crash_detection".to_string()
-                }
-                (x, y) if x == ModelState::Running as i32 && y == ModelState::Unknown as i32 => {
-                    "monitoring_failure".to_string()
-                }
-                (x, y)
-                    if x == ModelState::CrashLoopBackOff as i32
-                        && y == ModelState::Running as i32 =>
-                {
-                    "backoff_time_elapsed".to_string()
-                }
-                (x, y)
-                    if x == ModelState::CrashLoopBackOff as i32
-                        && y == ModelState::Failed as i32 =>
-                {
-                    "maximum_retries_exceeded".to_string()
-                }
-                (x, y) if x == ModelState::Unknown as i32 && y == ModelState::Running as i32 => {
-                    "state_check_recovered".to_string()
-                }
-                (x, y) if x == ModelState::Failed as i32 && y == ModelState::Pending as i32 => {
-                    "manual_automatic_recovery".to_string()
-                }
-                _ => format!("transition_{current_state}_{target_state}"),
-            },
-            _ => format!("transition_{current_state}_{target_state}"),
-        }
+        self.transition_tables
+            .get(&resource_type)
+            .and_then(|transitions| {
+                transitions.iter().find(|transition| {
+                    transition.from_state == current_state && transition.to_state == target_state
+                })
+            })
+            .map(|transition| transition.event.clone())
+            .unwrap_or_else(|| format!("transition_{current_state}_{target_state}"))
     }
 
     /// Evaluate whether a transition condition is satisfied
     ///
-    /// Processes conditional logic attached to state transitions to determine
-    /// if the transition should be allowed to proceed.
+    /// Conditions are the guards of the `guard` module, evaluated against
+    /// the tracked model states and the facts collected by the manager.
     ///
     /// # Parameters
-    /// - `condition`: The condition string to evaluate (e.g., "resource_count > 0")
-    /// - `_state_change`: The state change request providing context for evaluation
+    /// - `condition`: The guard name of the transition
+    /// - `resource_type`: The type of the resource
+    /// - `state_change`: The state change request providing the resource name
     ///
     /// # Returns
-    /// - `true`: If the condition is satisfied or no condition exists
-    /// - `false`: If the condition fails evaluation
-    ///
-    /// # Supported Conditions
-    /// The condition language should support:
-    /// - Resource property comparisons
-    /// - Metadata key existence checks
-    /// - Numeric and string comparisons
-    /// - Boolean logic operators
-    ///
-    /// # Error Handling
-    /// Malformed conditions should be logged and default to `false` for safety.
-    fn evaluate_condition(&self, condition: &str, _state_change: &StateChange) -> bool {
-        // TODO: Implement real condition evaluation logic
-        match condition {
-            "all_models_normal" => true,
-            "critical_models_normal" => true,
-            "critical_models_failed" => false,
-            "non_critical_model_issues" => true,
-            "critical_model_issues" => false,
-            "all_models_recovered" => true,
-            "critical_models_affected" => false,
-            "depends_on_recovery_level" => true,
-            "depends_on_previous_state" => true,
-            "depends_on_rollback_settings" => true,
-            "sufficient_resources" => true,
-            "timeout_or_error" => false,
-            "all_containers_started" => true,
-            "one_time_task" => true,
-            "unexpected_termination" => false,
-            "consecutive_restart_failures" => false,
-            "node_communication_issues" => false,
-            "restart_successful" => true,
-            "retry_limit_reached" => false,
-            "depends_on_actual_state" => true,
-            "according_to_restart_policy" => true,
-            _ => true, // Default to allow transition for unknown conditions
-        }
+    /// - `true`: If the guard holds
+    /// - `false`: If the guard does not hold, or is not a guard of the
+    ///   resource type; definitions with such guards are rejected when
+    ///   loaded, so this only happens for hand-built tables
+    fn evaluate_condition(
+        &self,
+        condition: &str,
+        resource_type: ResourceType,
+        state_change: &StateChange,
+    ) -> bool {
+        let Some(guard) = Guard::parse(condition).filter(|g| g.resource_type() == resource_type)
+        else {
+            eprintln!("Unknown guard '{condition}' for {resource_type:?}");
+            return false;
+        };
+        self.guard_facts
+            .evaluate(guard, &state_change.resource_name, |model| {
+                self.get_resource_state(model, ResourceType::Model)
+                    .map(|state| (state.current_state, state.last_transition_time.elapsed()))
+            })
     }
 
     /// Update the internal resource state after a successful transition
@@ -1057,6 +627,7 @@ impl StateMachine {
     /// - Increments transition counter
     /// - Updates last transition timestamp
     /// - Clears any active backoff timers on successful transition
+    /// - Counts restarts of models for the restart guards
     /// - Updates health status if applicable
     fn update_resource_state(
         &mut self,
@@ -1093,6 +664,7 @@ impl StateMachine {
                 },
             });
 
+        let previous_state = resource_state.current_state;
         resource_state.current_state = new_state;
         resource_state.last_transition_time = now;
         resource_state.transition_count += 1;
@@ -1103,6 +675,14 @@ impl StateMachine {
         resource_state
             .metadata
             .insert("source".to_string(), state_change.source.clone());
+
+        if resource_type == ResourceType::Model {
+            self.guard_facts.record_model_transition(
+                &state_change.resource_name,
+                previous_state,
+                new_state,
+            );
+        }
     }
 
     // ========================================
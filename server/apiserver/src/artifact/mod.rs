@@ -110,6 +110,83 @@ pub async fn withdraw(body: &str) -> common::Result<String> {
     Err("There is not any scenario in yaml string".into())
 }
 
+/// Collect the nodes that models of packages in an artifact are placed on
+///
+/// ### Parametets
+/// * `body: &str` - whole yaml string of piccolo artifact
+/// ### Returns
+/// * `Vec<String>` - node names without duplicates
+/// ### Description
+/// Documents that cannot be parsed are skipped, `apply()` reports them.
+pub fn get_model_nodes(body: &str) -> Vec<String> {
+    let mut nodes: Vec<String> = Vec::new();
+    for doc in body.split("---") {
+        let Ok(value) = serde_yaml::from_str::<serde_yaml::Value>(doc) else {
+            continue;
+        };
+        if value.get("kind").and_then(|k| k.as_str()) != Some("Package") {
+            continue;
+        }
+        let Ok(package) = serde_yaml::from_value::<Package>(value) else {
+            continue;
+        };
+        for model in package.get_models() {
+            let node = model.get_node();
+            if !nodes.contains(&node) {
+                nodes.push(node);
+            }
+        }
+    }
+    nodes
+}
+
+/// Move a model of a stored package to another node
+///
+/// ### Parametets
+/// * `package_name: &str` - name of the package in etcd
+/// * `model_name: &str` - name of the model in the package
+/// * `node_name: &str` - new node of the model
+/// ### Description
+/// Used after a drain so that later actions target the node the model
+/// was moved to.
+pub async fn move_model(
+    package_name: &str,
+    model_name: &str,
+    node_name: &str,
+) -> common::Result<()> {
+    let key = format!("Package/{}", package_name);
+    let package_str = data::read_from_etcd(&key).await?;
+    let updated = set_model_node(&package_str, model_name, node_name)?;
+    data::write_to_etcd(&key, &updated).await
+}
+
+/// Rewrite the node of a model in a package yaml string
+///
+/// ### Parametets
+/// * `package_str: &str` - yaml string of a package
+/// * `model_name: &str` - name of the model in the package
+/// * `node_name: &str` - new node of the model
+pub fn set_model_node(
+    package_str: &str,
+    model_name: &str,
+    node_name: &str,
+) -> common::Result<String> {
+    let mut value: serde_yaml::Value = serde_yaml::from_str(package_str)?;
+    let models = value
+        .get_mut("spec")
+        .and_then(|spec| spec.get_mut("models"))
+        .and_then(|models| models.as_sequence_mut())
+        .ok_or("There is not any model in package")?;
+
+    let model = models
+        .iter_mut()
+        .find(|m| m.get("name").and_then(|n| n.as_str()) == Some(model_name))
+        .ok_or_else(|| format!("Model {} is not in package", model_name))?;
+    model["node"] = serde_yaml::Value::from(node_name);
+
+    Ok(serde_yaml::to_string(&value)?)
+}
+
 //UNIT TEST CASES
 
 #[cfg(test)]
@@ -290,4 +367,26 @@ spec:
             "withdraw() unexpectedly succeeded with empty YAML"
         );
     }
+
+    /// Test get_model_nodes() with valid and empty YAML
+    #[test]
+    fn test_get_model_nodes() {
+        assert_eq!(
+            get_model_nodes(VALID_ARTIFACT_YAML),
+            vec!["HPC".to_string()]
+        );
+        assert!(get_model_nodes(INVALID_YAML_EMPTY).is_empty());
+        assert!(get_model_nodes(INVALID_YAML_UNKNOWN_ARTIFACT).is_empty());
+    }
+
+    /// Test set_model_node() moves only the requested model
+    #[test]
+    fn test_set_model_node() {
+        let package = VALID_ARTIFACT_YAML.split("---").nth(1).unwrap();
+
+        let updated = set_model_node(package, "helloworld-core", "ZONE").unwrap();
+        assert_eq!(get_model_nodes(&updated), vec!["ZONE".to_string()]);
+
+        assert!(set_model_node(package, "unknown-model", "ZONE").is_err());
+    }
 }
@@ -3,137 +3,16 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 
+use crate::node::{NodeManager, NodeRegistry};
 use common::apiserver::api_server_connection_server::ApiServerConnection;
 use common::apiserver::{
-    ClusterTopology, GetNodeRequest, GetNodeResponse, GetNodesRequest, GetNodesResponse,
-    GetTopologyRequest, GetTopologyResponse, NodeInfo, TopologyType, UpdateTopologyRequest,
-    UpdateTopologyResponse,
+    GetNodeRequest, GetNodeResponse, GetNodesRequest, GetNodesResponse, GetTopologyRequest,
+    GetTopologyResponse, NodeMaintenanceRequest, NodeMaintenanceResponse, PushConfigRequest,
+    PushConfigResponse, UpdateTopologyRequest, UpdateTopologyResponse,
 };
-use common::etcd;
-use common::nodeagent::{NodeRegistrationRequest, NodeRegistrationResponse, NodeStatus};
-use prost::Message;
+use common::nodeagent::{NodeRegistrationRequest, NodeRegistrationResponse};
 use tonic::{Request, Response, Status};
 
-/// Simple node manager embedded in receiver
-#[derive(Clone)]
-struct NodeManager;
-
-impl NodeManager {
-    async fn register_node(
-        &self,
-        request: NodeRegistrationRequest,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        let node_key = format!("cluster/nodes/{}", request.node_id);
-
-        let node_info = NodeInfo {
-            node_id: request.node_id.clone(),
-            hostname: request.hostname.clone(),
-            ip_address: request.ip_address.clone(),
-            role: request.role,
-            status: NodeStatus::Pending.into(),
-            resources: request.resources,
-            last_heartbeat: chrono::Utc::now().timestamp(),
-            created_at: chrono::Utc::now().timestamp(),
-            metadata: request.metadata,
-        };
-
-        let mut buf = Vec::new();
-        prost::Message::encode(&node_info, &mut buf)?;
-        let encoded = base64::encode(&buf);
-        etcd::put(&node_key, &encoded).await?;
-
-        println!("Node {} registered successfully", request.node_id);
-        Ok(format!("cluster-token-{}", request.node_id))
-    }
-
-    async fn get_all_nodes(
-        &self,
-    ) -> Result<Vec<NodeInfo>, Box<dyn std::error::Error + Send + Sync>> {
-        let prefix = "cluster/nodes/";
-        let kvs = etcd::get_all_with_prefix(prefix).await?;
-
-        let mut nodes = Vec::new();
-        for kv in kvs {
-            match base64::decode(&kv.value) {
-                Ok(buf) => match NodeInfo::decode(&buf[..]) {
-                    Ok(node) => nodes.push(node),
-                    Err(e) => {
-                        eprintln!("Failed to decode node data for key {}: {}", kv.key, e);
-                        continue;
-                    }
-                },
-                Err(e) => {
-                    eprintln!("Failed to decode base64 for key {}: {}", kv.key, e);
-                    continue;
-                }
-            }
-        }
-
-        Ok(nodes)
-    }
-
-    async fn get_node(
-        &self,
-        node_id: &str,
-    ) -> Result<Option<NodeInfo>, Box<dyn std::error::Error + Send + Sync>> {
-        let node_key = format!("cluster/nodes/{}", node_id);
-
-        match etcd::get(&node_key).await {
-            Ok(encoded) => {
-                let buf = base64::decode(&encoded)?;
-                let node_info = NodeInfo::decode(&buf[..])?;
-                Ok(Some(node_info))
-            }
-            Err(_) => Ok(None),
-        }
-    }
-}
-
-/// Simple registry embedded in receiver
-#[derive(Clone)]
-struct NodeRegistry;
-
-impl NodeRegistry {
-    async fn get_topology(
-        &self,
-    ) -> Result<ClusterTopology, Box<dyn std::error::Error + Send + Sync>> {
-        let topology_key = "cluster/topology";
-
-        match etcd::get(topology_key).await {
-            Ok(encoded) => {
-                let buf = base64::decode(&encoded)?;
-                let topology = ClusterTopology::decode(&buf[..])?;
-                Ok(topology)
-            }
-            Err(_) => Ok(ClusterTopology {
-                cluster_id: "default-cluster".to_string(),
-                cluster_name: "PICCOLO Cluster".to_string(),
-                r#type: TopologyType::Embedded.into(),
-                master_nodes: vec![],
-                sub_nodes: vec![],
-                parent_cluster: String::new(),
-                config: std::collections::HashMap::new(),
-            }),
-        }
-    }
-
-    async fn update_topology(
-        &self,
-        topology: ClusterTopology,
-    ) -> Result<ClusterTopology, Box<dyn std::error::Error + Send + Sync>> {
-        let topology_key = "cluster/topology";
-
-        let mut buf = Vec::new();
-        prost::Message::encode(&topology, &mut buf)?;
-        let encoded = base64::encode(&buf);
-
-        etcd::put(topology_key, &encoded).await?;
-
-        println!("Updated cluster topology: {}", topology.cluster_name);
-        Ok(topology)
-    }
-}
-
 /// API Server gRPC service handler for clustering functionality
 #[derive(Clone)]
 pub struct ApiServerReceiver {
@@ -241,20 +120,19 @@ impl ApiServerConnection for ApiServerReceiver {
 
         match self.node_manager.register_node(req).await {
             Ok(cluster_token) => {
+                // The node applies these settings right after registering
+                let cluster_config = match self.registry.get_cluster_config().await {
+                    Ok(cluster_config) => Some(cluster_config),
+                    Err(e) => {
+                        println!("Failed to read cluster config: {}", e);
+                        None
+                    }
+                };
                 let response = NodeRegistrationResponse {
                     success: true,
                     message: "Node registered successfully".to_string(),
                     cluster_token,
-                    cluster_config: Some(common::nodeagent::ClusterConfig {
-                        master_endpoint: common::apiserver::connect_grpc_server(),
-                        heartbeat_interval: 30,
-                        settings: {
-                            let mut settings = std::collections::HashMap::new();
-                            settings
-                                .insert("cluster_id".to_string(), "default-cluster".to_string());
-                            settings
-                        },
-                    }),
+                    cluster_config,
                 };
                 Ok(Response::new(response))
             }
@@ -270,6 +148,83 @@ impl ApiServerConnection for ApiServerReceiver {
         }
     }
 
+    /// Cordon a node so that no new models are placed on it
+    async fn cordon_node(
+        &self,
+        request: Request<NodeMaintenanceRequest>,
+    ) -> Result<Response<NodeMaintenanceResponse>, Status> {
+        println!("Received CordonNode request");
+        let req = request.into_inner();
+
+        let response = match self.node_manager.cordon_node(&req.node_id).await {
+            Ok(node) => NodeMaintenanceResponse {
+                node: Some(node),
+                success: true,
+                message: format!("Node {} cordoned", req.node_id),
+                ..Default::default()
+            },
+            Err(e) => NodeMaintenanceResponse {
+                success: false,
+                message: format!("Failed to cordon node: {}", e),
+                ..Default::default()
+            },
+        };
+        Ok(Response::new(response))
+    }
+
+    /// Release a cordoned node
+    async fn uncordon_node(
+        &self,
+        request: Request<NodeMaintenanceRequest>,
+    ) -> Result<Response<NodeMaintenanceResponse>, Status> {
+        println!("Received UncordonNode request");
+        let req = request.into_inner();
+
+        let response = match self.node_manager.uncordon_node(&req.node_id).await {
+            Ok(node) => NodeMaintenanceResponse {
+                node: Some(node),
+                success: true,
+                message: format!("Node {} uncordoned", req.node_id),
+                ..Default::default()
+            },
+            Err(e) => NodeMaintenanceResponse {
+                success: false,
+                message: format!("Failed to uncordon node: {}", e),
+                ..Default::default()
+            },
+        };
+        Ok(Response::new(response))
+    }
+
+    /// Cordon a node and stop or move its workloads
+    async fn drain_node(
+        &self,
+        request: Request<NodeMaintenanceRequest>,
+    ) -> Result<Response<NodeMaintenanceResponse>, Status> {
+        println!("Received DrainNode request");
+        let req = request.into_inner();
+
+        let response = match crate::manager::drain_node(&req.node_id).await {
+            Ok((node, drained)) => NodeMaintenanceResponse {
+                node: Some(node),
+                success: true,
+                message: format!("Node {} drained", req.node_id),
+                stopped_models: drained.stopped_models,
+                moved_models: drained
+                    .moved_models
+                    .into_iter()
+                    .map(|m| format!("{}/{} -> {}", m.package_name, m.model_name, m.node_name))
+                    .collect(),
+            },
+            Err(e) => NodeMaintenanceResponse {
+                success: false,
+                message: format!("Failed to drain node: {}", e),
+                ..Default::default()
+            },
+        };
+        Ok(Response::new(response))
+    }
+
     /// Get the current cluster topology
     async fn get_topology(
         &self,
@@ -334,6 +289,35 @@ impl ApiServerConnection for ApiServerReceiver {
             Ok(Response::new(response))
         }
     }
+
+    /// Push the cluster settings to one node or to all registered nodes
+    async fn push_config(
+        &self,
+        request: Request<PushConfigRequest>,
+    ) -> Result<Response<PushConfigResponse>, Status> {
+        println!("Received PushConfig request");
+        let req = request.into_inner();
+        let node_id = Some(req.node_id.as_str()).filter(|id| !id.is_empty());
+
+        let response = match self.registry.push_config(node_id).await {
+            Ok(failed_nodes) if failed_nodes.is_empty() => PushConfigResponse {
+                success: true,
+                message: "Configuration pushed successfully".to_string(),
+                failed_nodes,
+            },
+            Ok(failed_nodes) => PushConfigResponse {
+                success: false,
+                message: "Configuration was not applied on some nodes".to_string(),
+                failed_nodes,
+            },
+            Err(e) => PushConfigResponse {
+                success: false,
+                message: format!("Failed to push configuration: {}", e),
+                failed_nodes: vec![],
+            },
+        };
+        Ok(Response::new(response))
+    }
 }
 
 #[cfg(test)]
@@ -383,4 +367,19 @@ mod tests {
         assert!(response.success);
         assert!(!response.cluster_token.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_cordon_unknown_node() {
+        let receiver = ApiServerReceiver::new();
+        let request = Request::new(NodeMaintenanceRequest {
+            node_id: "unknown-node".to_string(),
+        });
+
+        let response = receiver.cordon_node(request).await;
+        assert!(response.is_ok());
+
+        let response = response.unwrap().into_inner();
+        assert!(!response.success);
+        assert!(response.node.is_none());
+    }
 }
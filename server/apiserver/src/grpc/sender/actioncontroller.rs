@@ -0,0 +1,30 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Running gRPC message sending to actioncontroller
+
+use common::actioncontroller::{
+    action_controller_connection_client::ActionControllerConnectionClient, connect_server,
+    DrainNodeRequest, DrainNodeResponse,
+};
+use common::transport::Component;
+use tonic::{Request, Response, Status};
+
+/// Request actioncontroller to stop or move the workloads of a node
+///
+/// ### Parametets
+/// * `req: DrainNodeRequest` - node to drain and nodes not to move to
+/// ### Description
+/// Models placed on the node are stopped, and moved to an alternate node
+/// when their package allows it.
+pub async fn drain_node(req: DrainNodeRequest) -> Result<Response<DrainNodeResponse>, Status> {
+    let channel = common::transport::connect(Component::ActionController, connect_server())
+        .await
+        .map_err(|e| {
+            Status::unavailable(format!("Failed to connect to actioncontroller: {}", e))
+        })?;
+    let mut client = ActionControllerConnectionClient::new(channel);
+    client.drain_node(Request::new(req)).await
+}
@@ -7,10 +7,21 @@
 
 use common::filtergateway::{
     connect_server, filter_gateway_connection_client::FilterGatewayConnectionClient,
-    HandleScenarioRequest, HandleScenarioResponse,
+    HandleScenarioRequest, HandleScenarioResponse, ListFiltersRequest, ListFiltersResponse,
+    ScenarioFilterRequest, ScenarioFilterResponse,
 };
+use common::transport::Component;
+use tonic::transport::Channel;
 use tonic::{Request, Response, Status};
 
+/// Connect to filtergateway
+async fn client() -> Result<FilterGatewayConnectionClient<Channel>, Status> {
+    let channel = common::transport::connect(Component::FilterGateway, connect_server())
+        .await
+        .map_err(|e| Status::unavailable(format!("Failed to connect to filtergateway: {}", e)))?;
+    Ok(FilterGatewayConnectionClient::new(channel))
+}
+
 /// Send scenario information to filtergateway via gRPC
 ///
 /// ### Parametets
@@ -24,10 +35,10 @@ pub async fn send(
     use std::time::Instant;
     let start = Instant::now();
 
-    let mut client = FilterGatewayConnectionClient::connect(connect_server())
-        .await
-        .unwrap();
-    let response = client.handle_scenario(Request::new(scenario)).await;
+    let response = client()
+        .await?
+        .handle_scenario(Request::new(scenario))
+        .await;
 
     let elapsed = start.elapsed();
     println!("send: elapsed = {:?}", elapsed);
@@ -35,6 +46,37 @@ pub async fn send(
     response
 }
 
+/// Stop evaluating the condition of a scenario without withdrawing it
+///
+/// ### Parametets
+/// * `req: ScenarioFilterRequest` - name of the scenario to pause
+pub async fn pause_scenario(
+    req: ScenarioFilterRequest,
+) -> Result<Response<ScenarioFilterResponse>, Status> {
+    client().await?.pause_scenario(Request::new(req)).await
+}
+
+/// Evaluate the condition of a paused scenario again
+///
+/// ### Parametets
+/// * `req: ScenarioFilterRequest` - name of the scenario to resume
+pub async fn resume_scenario(
+    req: ScenarioFilterRequest,
+) -> Result<Response<ScenarioFilterResponse>, Status> {
+    client().await?.resume_scenario(Request::new(req)).await
+}
+
+/// Get the scenario filters of filtergateway with their condition state
+///
+/// ### Parametets
+/// None
+pub async fn list_filters() -> Result<Response<ListFiltersResponse>, Status> {
+    client()
+        .await?
+        .list_filters(Request::new(ListFiltersRequest {}))
+        .await
+}
+
 //UNIT TEST CASES
 
 #[cfg(test)]
@@ -44,7 +86,8 @@ mod tests {
         filter_gateway_connection_server::{
             FilterGatewayConnection, FilterGatewayConnectionServer,
         },
-        Action, HandleScenarioRequest, HandleScenarioResponse,
+        Action, FilterStatus, HandleScenarioRequest, HandleScenarioResponse, ListFiltersRequest,
+        ListFiltersResponse, ScenarioFilterRequest, ScenarioFilterResponse,
     };
     use std::net::SocketAddr;
     use tokio::net::TcpListener;
@@ -122,6 +165,41 @@ spec:
                 desc: format!("Mock handled: {:?}", req.action),
             }))
         }
+
+        /// PauseScenario only knows the `helloworld` scenario
+        async fn pause_scenario(
+            &self,
+            request: Request<ScenarioFilterRequest>,
+        ) -> Result<Response<ScenarioFilterResponse>, Status> {
+            let req = request.into_inner();
+            if req.scenario_name != "helloworld" {
+                return Err(Status::not_found("no filter"));
+            }
+            Ok(Response::new(ScenarioFilterResponse {
+                status: true,
+                desc: "Paused".to_string(),
+            }))
+        }
+
+        async fn resume_scenario(
+            &self,
+            request: Request<ScenarioFilterRequest>,
+        ) -> Result<Response<ScenarioFilterResponse>, Status> {
+            self.pause_scenario(request).await
+        }
+
+        async fn list_filters(
+            &self,
+            _request: Request<ListFiltersRequest>,
+        ) -> Result<Response<ListFiltersResponse>, Status> {
+            Ok(Response::new(ListFiltersResponse {
+                filters: vec![FilterStatus {
+                    scenario_name: "helloworld".to_string(),
+                    active: true,
+                    ..Default::default()
+                }],
+            }))
+        }
     }
 
     /// Starts a mock gRPC server on a random available port
@@ -269,4 +347,34 @@ spec:
         let result = send_mocked(scenario, addr).await;
         assert!(result.is_ok());
     }
+
+    /// Test pausing a known and an unknown scenario and listing the filters
+    #[tokio::test]
+    async fn test_pause_and_list_filters() {
+        let addr = start_mock_server().await;
+        let mut client = FilterGatewayConnectionClient::connect(format!("http://{}", addr))
+            .await
+            .unwrap();
+
+        let request = |name: &str| {
+            Request::new(ScenarioFilterRequest {
+                scenario_name: name.to_string(),
+            })
+        };
+        assert!(client.pause_scenario(request("helloworld")).await.is_ok());
+        let status = client
+            .resume_scenario(request("unknown"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+
+        let filters = client
+            .list_filters(Request::new(ListFiltersRequest {}))
+            .await
+            .unwrap()
+            .into_inner()
+            .filters;
+        assert_eq!(filters.len(), 1);
+        assert_eq!(filters[0].scenario_name, "helloworld");
+    }
 }
@@ -1,21 +1,37 @@
 use common::nodeagent::{
     connect_guest_server, connect_server, node_agent_connection_client::NodeAgentConnectionClient,
-    HandleYamlRequest, HandleYamlResponse,
+    ConfigRequest, ConfigResponse, HandleYamlRequest, HandleYamlResponse,
 };
+use common::transport::Component;
 use tonic::{Request, Response, Status};
 
 pub async fn send(action: HandleYamlRequest) -> Result<Response<HandleYamlResponse>, Status> {
-    let mut client: NodeAgentConnectionClient<tonic::transport::Channel> =
-        NodeAgentConnectionClient::connect(connect_server())
-            .await
-            .unwrap();
-    client.handle_yaml(Request::new(action)).await
+    send_to(connect_server(), action).await
 }
 
 pub async fn send_guest(action: HandleYamlRequest) -> Result<Response<HandleYamlResponse>, Status> {
-    let mut client: NodeAgentConnectionClient<tonic::transport::Channel> =
-        NodeAgentConnectionClient::connect(connect_guest_server())
-            .await
-            .unwrap();
+    send_to(connect_guest_server(), action).await
+}
+
+async fn send_to(
+    addr: String,
+    action: HandleYamlRequest,
+) -> Result<Response<HandleYamlResponse>, Status> {
+    let channel = common::transport::connect(Component::NodeAgent, addr)
+        .await
+        .map_err(|e| Status::unavailable(format!("Failed to connect to nodeagent: {}", e)))?;
+    let mut client = NodeAgentConnectionClient::new(channel);
     client.handle_yaml(Request::new(action)).await
 }
+
+/// Push cluster settings to the NodeAgent listening at `addr`
+pub async fn send_config(
+    addr: String,
+    config: ConfigRequest,
+) -> Result<Response<ConfigResponse>, Status> {
+    let channel = common::transport::connect(Component::NodeAgent, addr)
+        .await
+        .map_err(|e| Status::unavailable(format!("Failed to connect to nodeagent: {}", e)))?;
+    let mut client = NodeAgentConnectionClient::new(channel);
+    client.receive_config(Request::new(config)).await
+}
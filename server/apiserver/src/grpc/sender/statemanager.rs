@@ -17,6 +17,7 @@ use common::statemanager::{
     connect_server, state_manager_connection_client::StateManagerConnectionClient, ResourceType,
     StateChange, StateChangeResponse,
 };
+use common::transport::Component;
 use tonic::{Request, Status};
 
 /// StateManager gRPC client for ApiServer component.
@@ -93,9 +94,9 @@ impl StateManagerSender {
     /// - Add connection pooling for high-throughput scenarios
     async fn ensure_connected(&mut self) -> Result<(), Status> {
         if self.client.is_none() {
-            match StateManagerConnectionClient::connect(connect_server()).await {
-                Ok(client) => {
-                    self.client = Some(client);
+            match common::transport::connect(Component::StateManager, connect_server()).await {
+                Ok(channel) => {
+                    self.client = Some(StateManagerConnectionClient::new(channel));
                     Ok(())
                 }
                 Err(e) => Err(Status::unknown(format!(
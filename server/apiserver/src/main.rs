@@ -17,12 +17,14 @@
 mod artifact;
 mod grpc;
 mod manager;
+mod node;
 mod route;
 
 /// Main function of Piccolo API Server
 #[cfg(not(tarpaulin_include))]
 #[tokio::main]
 async fn main() {
+    common::transport::set_local_component(common::transport::Component::ApiServer);
     manager::initialize().await
 }
 
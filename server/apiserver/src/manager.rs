@@ -5,10 +5,12 @@
 
 //! Controls the flow of data between each module.
 
+use common::actioncontroller::{DrainNodeRequest, DrainNodeResponse};
 use common::apiserver::api_server_connection_server::ApiServerConnectionServer;
+use common::apiserver::NodeInfo;
 use common::filtergateway::{Action, HandleScenarioRequest};
 use common::nodeagent::HandleYamlRequest;
-use tonic::transport::Server;
+use common::transport::Component;
 
 /// Launch REST API listener, gRPC server, and reload scenario data in etcd
 pub async fn initialize() {
@@ -29,7 +31,14 @@ async fn start_grpc_server() {
 
     println!("ApiServer gRPC listening on {}", addr);
 
-    let _ = Server::builder()
+    let mut builder = match common::transport::server(Component::ApiServer) {
+        Ok(builder) => builder,
+        Err(e) => {
+            eprintln!("Failed to configure ApiServer gRPC server: {}", e);
+            return;
+        }
+    };
+    let _ = builder
         .add_service(ApiServerConnectionServer::new(grpc_service))
         .serve(addr)
         .await;
@@ -72,10 +81,18 @@ async fn reload() {
 /// ### Parametets
 /// * `body: &str` - whole yaml string of piccolo artifact
 /// ### Description
+/// bind models that only define a placement to a registered node
 /// write artifact in etcd
 /// (optional) make yaml, kube files for Bluechi
 /// send a gRPC message to gateway
 pub async fn apply_artifact(body: &str) -> common::Result<()> {
+    let body = crate::node::NodeScheduler
+        .schedule_artifact(body)
+        .await
+        .map_err(|e| format!("Failed to schedule models: {}", e))?;
+    let body = body.as_str();
+
+    check_nodes_schedulable(body).await?;
     let scenario = crate::artifact::apply(body).await?;
 
     let handle_yaml = HandleYamlRequest {
@@ -120,6 +137,71 @@ pub async fn withdraw_artifact(body: &str) -> common::Result<()> {
     Ok(())
 }
 
+/// Reject artifacts that place models on cordoned nodes
+///
+/// ### Parametets
+/// * `body: &str` - whole yaml string of piccolo artifact
+/// ### Description
+/// Nodes that are not registered in the cluster are not checked.
+async fn check_nodes_schedulable(body: &str) -> common::Result<()> {
+    let node_manager = crate::node::NodeManager;
+
+    for name in crate::artifact::get_model_nodes(body) {
+        let node = node_manager
+            .find_node(&name)
+            .await
+            .map_err(|e| format!("Failed to look up node {}: {}", name, e))?;
+        if let Some(node) = node {
+            if !crate::node::NodeManager::is_schedulable(&node) {
+                return Err(format!("Node {} is cordoned for maintenance", name).into());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Drain a node for maintenance
+///
+/// ### Parametets
+/// * `node_id: &str` - ID of the node to drain
+/// ### Returns
+/// * `(NodeInfo, DrainNodeResponse)` - cordoned node and the stopped or
+///   moved models reported by actioncontroller
+/// ### Description
+/// cordon the node so that it does not accept new models
+/// ask actioncontroller to stop its workloads, moving them to an alternate
+/// node that is not cordoned when the package allows it
+/// record the new node of moved models in etcd
+pub async fn drain_node(
+    node_id: &str,
+) -> Result<(NodeInfo, DrainNodeResponse), Box<dyn std::error::Error + Send + Sync>> {
+    let node_manager = crate::node::NodeManager;
+    let node = node_manager.cordon_node(node_id).await?;
+
+    let unavailable_nodes = node_manager
+        .get_unschedulable_nodes()
+        .await?
+        .into_iter()
+        .filter(|n| n.node_id != node.node_id)
+        .map(|n| n.hostname)
+        .collect();
+    let req = DrainNodeRequest {
+        node_name: node.hostname.clone(),
+        unavailable_nodes,
+    };
+    let response = crate::grpc::sender::actioncontroller::drain_node(req)
+        .await?
+        .into_inner();
+
+    for moved in &response.moved_models {
+        crate::artifact::move_model(&moved.package_name, &moved.model_name, &moved.node_name)
+            .await
+            .map_err(|e| format!("Failed to record moved model {}: {}", moved.model_name, e))?;
+    }
+
+    Ok((node, response))
+}
+
 //UNIT Test Cases
 #[cfg(test)]
 mod tests {
@@ -129,7 +211,8 @@ mod tests {
         filter_gateway_connection_server::{
             FilterGatewayConnection, FilterGatewayConnectionServer,
         },
-        Action, HandleScenarioRequest, HandleScenarioResponse,
+        Action, HandleScenarioRequest, HandleScenarioResponse, ListFiltersRequest,
+        ListFiltersResponse, ScenarioFilterRequest, ScenarioFilterResponse,
     };
     use std::net::SocketAddr;
     use tokio::net::TcpListener;
@@ -582,6 +665,28 @@ spec:
                 desc: "Success".to_string(),
             }))
         }
+
+        /// Filters are not used by the manager tests
+        async fn pause_scenario(
+            &self,
+            _request: Request<ScenarioFilterRequest>,
+        ) -> Result<Response<ScenarioFilterResponse>, Status> {
+            Err(Status::unimplemented("pause_scenario"))
+        }
+
+        async fn resume_scenario(
+            &self,
+            _request: Request<ScenarioFilterRequest>,
+        ) -> Result<Response<ScenarioFilterResponse>, Status> {
+            Err(Status::unimplemented("resume_scenario"))
+        }
+
+        async fn list_filters(
+            &self,
+            _request: Request<ListFiltersRequest>,
+        ) -> Result<Response<ListFiltersResponse>, Status> {
+            Ok(Response::new(ListFiltersResponse::default()))
+        }
     }
 
     /// Starts the mock gRPC server asynchronously on a random port.
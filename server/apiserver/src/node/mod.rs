@@ -7,8 +7,10 @@
 
 pub mod manager;
 pub mod registry;
+pub mod scheduler;
 pub mod status;
 
 pub use manager::NodeManager;
 pub use registry::NodeRegistry;
+pub use scheduler::NodeScheduler;
 pub use status::{ClusterHealthSummary, ClusterStatus, NodeStatusManager};
@@ -4,10 +4,24 @@
  */
 
 //! Node registry for cluster membership management
+//!
+//! Only the cluster attributes (ID, name, type, parent cluster and config)
+//! are stored under `cluster/topology`. Master and sub nodes are always
+//! derived from the registered nodes by their `NodeRole`, so the topology
+//! cannot drift from the nodes that actually joined the cluster.
+//!
+//! The `config` map of the topology holds the cluster settings. They are
+//! handed to nodes at registration and pushed to them through
+//! `ReceiveConfig` whenever they change or on demand.
 
-use common::apiserver::{ClusterTopology, TopologyType};
+use super::NodeManager;
+use common::apiserver::{ClusterTopology, NodeInfo, TopologyType};
 use common::etcd;
+use common::nodeagent::{ClusterConfig, ConfigRequest, NodeRole};
 use prost::Message;
+use std::collections::HashMap;
+
+const TOPOLOGY_KEY: &str = "cluster/topology";
 
 /// Node registry for managing cluster topology
 #[derive(Clone)]
@@ -15,77 +29,327 @@ pub struct NodeRegistry;
 
 impl NodeRegistry {
     /// Get the current cluster topology
+    ///
+    /// Registered nodes with the `MASTER` role are listed as master nodes,
+    /// all others as sub nodes.
     pub async fn get_topology(
         &self,
     ) -> Result<ClusterTopology, Box<dyn std::error::Error + Send + Sync>> {
-        let topology_key = "cluster/topology";
+        let mut topology = self.load_topology().await?;
+        let nodes = NodeManager.get_all_nodes().await?;
+        assign_members(&mut topology, nodes);
+        Ok(topology)
+    }
 
-        match etcd::get(topology_key).await {
-            Ok(encoded) => {
-                let buf = base64::decode(&encoded)?;
-                let topology = ClusterTopology::decode(&buf[..])?;
-                Ok(topology)
+    /// Update the cluster topology
+    ///
+    /// Nodes listed in `master_nodes` and `sub_nodes` are identified by their
+    /// node ID and get the corresponding role. Nodes that are not listed keep
+    /// their role. The update is rejected without any change when it refers
+    /// to unknown nodes or when the resulting topology is not valid, see
+    /// `validate_topology()`. When storing the update fails, the roles
+    /// already changed are set back.
+    pub async fn update_topology(
+        &self,
+        topology: ClusterTopology,
+    ) -> Result<ClusterTopology, Box<dyn std::error::Error + Send + Sync>> {
+        let nodes = NodeManager.get_all_nodes().await?;
+        let members = apply_roles(&topology, &nodes)?;
+        validate_topology(&topology, &members)?;
+
+        let mut changed = Vec::new();
+        let (previous, mut stored) = match self
+            .write_topology(topology, &members, &nodes, &mut changed)
+            .await
+        {
+            Ok(written) => written,
+            Err(e) => {
+                for (node_id, role) in changed.into_iter().rev() {
+                    if let Err(undo) = NodeManager.set_role(&node_id, role).await {
+                        println!("Cannot restore role of node {}: {}", node_id, undo);
+                    }
+                }
+                return Err(e);
             }
-            Err(_) => {
-                // Return default topology if not found
-                Ok(ClusterTopology {
-                    cluster_id: "default-cluster".to_string(),
-                    cluster_name: "PICCOLO Cluster".to_string(),
-                    r#type: TopologyType::Embedded.into(),
-                    master_nodes: vec![],
-                    sub_nodes: vec![],
-                    parent_cluster: String::new(),
-                    config: std::collections::HashMap::new(),
-                })
+        };
+
+        println!("Updated cluster topology: {}", stored.cluster_name);
+        if previous.config != stored.config {
+            let failed = push_settings(&cluster_settings(&stored), &members).await;
+            if !failed.is_empty() {
+                println!("Cluster config not applied on nodes: {:?}", failed);
             }
         }
+
+        assign_members(&mut stored, members);
+        Ok(stored)
     }
 
-    /// Update the cluster topology
-    pub async fn update_topology(
+    /// Write the roles of the members and the topology
+    ///
+    /// Each node whose role is changed is added to `changed` with its
+    /// previous role. Returns the previously stored and the new topology.
+    async fn write_topology(
         &self,
         topology: ClusterTopology,
-    ) -> Result<ClusterTopology, Box<dyn std::error::Error + Send + Sync>> {
-        let topology_key = "cluster/topology";
+        members: &[NodeInfo],
+        nodes: &[NodeInfo],
+        changed: &mut Vec<(String, NodeRole)>,
+    ) -> Result<(ClusterTopology, ClusterTopology), Box<dyn std::error::Error + Send + Sync>> {
+        for member in members {
+            let Some(node) = nodes
+                .iter()
+                .find(|node| node.node_id == member.node_id && node.role != member.role)
+            else {
+                continue;
+            };
+            NodeManager.set_role(&member.node_id, member.role()).await?;
+            changed.push((node.node_id.clone(), node.role()));
+        }
 
-        let mut buf = Vec::new();
-        prost::Message::encode(&topology, &mut buf)?;
-        let encoded = base64::encode(&buf);
+        let previous = self.load_topology().await?;
+        let mut stored = topology;
+        stored.master_nodes.clear();
+        stored.sub_nodes.clear();
+        self.store_topology(&stored).await?;
+        Ok((previous, stored))
+    }
 
-        etcd::put(topology_key, &encoded).await?;
+    /// Get the cluster configuration handed to nodes at registration
+    pub async fn get_cluster_config(
+        &self,
+    ) -> Result<ClusterConfig, Box<dyn std::error::Error + Send + Sync>> {
+        let topology = self.load_topology().await?;
+        let settings = cluster_settings(&topology);
+        let heartbeat_interval = settings
+            .get("heartbeat_interval")
+            .and_then(|interval| interval.parse().ok())
+            .unwrap_or(30);
 
-        println!("Updated cluster topology: {}", topology.cluster_name);
-        Ok(topology)
+        Ok(ClusterConfig {
+            master_endpoint: common::apiserver::connect_grpc_server(),
+            heartbeat_interval,
+            settings,
+        })
+    }
+
+    /// Push the cluster settings to one node or to all registered nodes
+    ///
+    /// Returns the IDs of the nodes that could not be reached or rejected
+    /// the settings.
+    pub async fn push_config(
+        &self,
+        node_id: Option<&str>,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let topology = self.load_topology().await?;
+        let nodes = match node_id {
+            Some(node_id) => vec![NodeManager
+                .get_node(node_id)
+                .await?
+                .ok_or_else(|| format!("Node {} not found", node_id))?],
+            None => NodeManager.get_all_nodes().await?,
+        };
+
+        Ok(push_settings(&cluster_settings(&topology), &nodes).await)
     }
 
     /// Initialize default cluster topology
     pub async fn initialize_default_topology(
         &self,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let default_topology = ClusterTopology {
-            cluster_id: "default-cluster".to_string(),
-            cluster_name: "PICCOLO Cluster".to_string(),
-            r#type: TopologyType::Embedded.into(),
-            master_nodes: vec![],
-            sub_nodes: vec![],
-            parent_cluster: String::new(),
-            config: {
-                let mut config = std::collections::HashMap::new();
-                config.insert("heartbeat_interval".to_string(), "30".to_string());
-                config.insert("max_nodes".to_string(), "10".to_string());
-                config
-            },
-        };
+        let mut default_topology = default_topology();
+        default_topology
+            .config
+            .insert("heartbeat_interval".to_string(), "30".to_string());
+        default_topology
+            .config
+            .insert("max_nodes".to_string(), "10".to_string());
+
+        self.store_topology(&default_topology).await
+    }
+
+    async fn load_topology(
+        &self,
+    ) -> Result<ClusterTopology, Box<dyn std::error::Error + Send + Sync>> {
+        match etcd::get(TOPOLOGY_KEY).await {
+            Ok(encoded) => {
+                let buf = base64::decode(&encoded)?;
+                let topology = ClusterTopology::decode(&buf[..])?;
+                Ok(topology)
+            }
+            // Return default topology if not found
+            Err(_) => Ok(default_topology()),
+        }
+    }
 
-        self.update_topology(default_topology).await?;
+    async fn store_topology(
+        &self,
+        topology: &ClusterTopology,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut buf = Vec::new();
+        prost::Message::encode(topology, &mut buf)?;
+        let encoded = base64::encode(&buf);
+
+        etcd::put(TOPOLOGY_KEY, &encoded).await?;
         Ok(())
     }
 }
 
+fn default_topology() -> ClusterTopology {
+    ClusterTopology {
+        cluster_id: "default-cluster".to_string(),
+        cluster_name: "PICCOLO Cluster".to_string(),
+        r#type: TopologyType::Embedded.into(),
+        master_nodes: vec![],
+        sub_nodes: vec![],
+        parent_cluster: String::new(),
+        config: HashMap::new(),
+    }
+}
+
+/// Settings of the cluster, the topology config with the cluster ID
+fn cluster_settings(topology: &ClusterTopology) -> HashMap<String, String> {
+    let mut settings = topology.config.clone();
+    settings
+        .entry("cluster_id".to_string())
+        .or_insert_with(|| topology.cluster_id.clone());
+    settings
+}
+
+/// Send settings to nodes and return the IDs of nodes that did not apply them
+async fn push_settings(settings: &HashMap<String, String>, nodes: &[NodeInfo]) -> Vec<String> {
+    let mut failed = Vec::new();
+
+    for node in nodes {
+        let addr = common::nodeagent::connect_node_server(&node.ip_address);
+        let request = ConfigRequest {
+            config: settings.clone(),
+        };
+        match crate::grpc::sender::nodeagent::send_config(addr, request).await {
+            Ok(response) if response.get_ref().applied => {
+                println!("Pushed cluster config to node {}", node.node_id);
+            }
+            Ok(response) => {
+                println!(
+                    "Node {} rejected cluster config: {}",
+                    node.node_id,
+                    response.get_ref().message
+                );
+                failed.push(node.node_id.clone());
+            }
+            Err(e) => {
+                println!("Failed to push cluster config to {}: {}", node.node_id, e);
+                failed.push(node.node_id.clone());
+            }
+        }
+    }
+
+    failed
+}
+
+/// Fill master and sub nodes of a topology from registered nodes
+fn assign_members(topology: &mut ClusterTopology, nodes: Vec<NodeInfo>) {
+    let (masters, subs): (Vec<NodeInfo>, Vec<NodeInfo>) = nodes
+        .into_iter()
+        .partition(|node| node.role == i32::from(NodeRole::Master));
+    topology.master_nodes = masters;
+    topology.sub_nodes = subs;
+}
+
+/// Registered nodes with the roles requested by a topology update
+///
+/// Fails if a listed node is not registered or listed more than once.
+pub fn apply_roles(
+    topology: &ClusterTopology,
+    nodes: &[NodeInfo],
+) -> Result<Vec<NodeInfo>, String> {
+    let mut roles: HashMap<&str, NodeRole> = HashMap::new();
+    let requested = topology
+        .master_nodes
+        .iter()
+        .map(|node| (node, NodeRole::Master))
+        .chain(topology.sub_nodes.iter().map(|node| (node, NodeRole::Sub)));
+
+    for (node, role) in requested {
+        if !nodes.iter().any(|n| n.node_id == node.node_id) {
+            return Err(format!("Node {} is not registered", node.node_id));
+        }
+        if roles.insert(&node.node_id, role).is_some() {
+            return Err(format!("Node {} is listed more than once", node.node_id));
+        }
+    }
+
+    Ok(nodes
+        .iter()
+        .map(|node| {
+            let mut node = node.clone();
+            if let Some(role) = roles.get(node.node_id.as_str()) {
+                node.role = (*role).into();
+            }
+            node
+        })
+        .collect())
+}
+
+/// Check a topology against the nodes that will be its members
+///
+/// * the type must be a known topology type,
+/// * an `EMBEDDED` cluster has exactly one master node,
+/// * only a `MULTI_CLUSTER` topology may link to a parent cluster, and that
+///   parent must not be the cluster itself.
+pub fn validate_topology(topology: &ClusterTopology, members: &[NodeInfo]) -> Result<(), String> {
+    let topology_type = match TopologyType::try_from(topology.r#type) {
+        Ok(TopologyType::Unspecified) | Err(_) => {
+            return Err(format!("Invalid topology type {}", topology.r#type))
+        }
+        Ok(topology_type) => topology_type,
+    };
+
+    if topology_type == TopologyType::Embedded {
+        let masters = members
+            .iter()
+            .filter(|node| node.role == i32::from(NodeRole::Master))
+            .count();
+        if masters != 1 {
+            return Err(format!(
+                "Embedded topology needs exactly one master node, found {}",
+                masters
+            ));
+        }
+    }
+
+    if !topology.parent_cluster.is_empty() {
+        if topology_type != TopologyType::MultiCluster {
+            return Err("Only a multi-cluster topology can have a parent cluster".to_string());
+        }
+        if topology.parent_cluster == topology.cluster_id {
+            return Err("A cluster cannot be its own parent".to_string());
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn create_test_node(node_id: &str, role: NodeRole) -> NodeInfo {
+        NodeInfo {
+            node_id: node_id.to_string(),
+            hostname: node_id.to_string(),
+            role: role.into(),
+            ..Default::default()
+        }
+    }
+
+    fn create_topology(topology_type: TopologyType) -> ClusterTopology {
+        ClusterTopology {
+            r#type: topology_type.into(),
+            ..default_topology()
+        }
+    }
+
     #[tokio::test]
     async fn test_node_registry_operations() {
         let registry = NodeRegistry;
@@ -100,4 +364,106 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_cluster_settings_include_cluster_id() {
+        let mut topology = default_topology();
+        topology
+            .config
+            .insert("log_level".to_string(), "debug".to_string());
+
+        let settings = cluster_settings(&topology);
+        assert_eq!(settings.get("cluster_id").unwrap(), "default-cluster");
+        assert_eq!(settings.get("log_level").unwrap(), "debug");
+    }
+
+    #[tokio::test]
+    async fn test_push_settings_unreachable_node() {
+        let node = NodeInfo {
+            node_id: "node1".to_string(),
+            ip_address: "127.0.0.1".to_string(),
+            ..Default::default()
+        };
+        let failed = push_settings(&HashMap::new(), &[node]).await;
+        assert_eq!(failed, vec!["node1".to_string()]);
+    }
+
+    #[test]
+    fn test_assign_members_by_role() {
+        let mut topology = default_topology();
+        assign_members(
+            &mut topology,
+            vec![
+                create_test_node("master", NodeRole::Master),
+                create_test_node("sub", NodeRole::Sub),
+                create_test_node("unspecified", NodeRole::Unspecified),
+            ],
+        );
+
+        assert_eq!(topology.master_nodes.len(), 1);
+        assert_eq!(topology.master_nodes[0].node_id, "master");
+        assert_eq!(topology.sub_nodes.len(), 2);
+    }
+
+    #[test]
+    fn test_apply_roles_rejects_unknown_and_duplicate_nodes() {
+        let nodes = vec![
+            create_test_node("node1", NodeRole::Master),
+            create_test_node("node2", NodeRole::Sub),
+        ];
+
+        let mut topology = create_topology(TopologyType::Embedded);
+        topology.sub_nodes = vec![create_test_node("node3", NodeRole::Sub)];
+        assert!(apply_roles(&topology, &nodes).is_err());
+
+        topology.master_nodes = vec![create_test_node("node2", NodeRole::Master)];
+        topology.sub_nodes = vec![create_test_node("node2", NodeRole::Sub)];
+        assert!(apply_roles(&topology, &nodes).is_err());
+
+        topology.master_nodes = vec![create_test_node("node2", NodeRole::Master)];
+        topology.sub_nodes = vec![create_test_node("node1", NodeRole::Sub)];
+        let members = apply_roles(&topology, &nodes).unwrap();
+        assert_eq!(members[0].role(), NodeRole::Sub);
+        assert_eq!(members[1].role(), NodeRole::Master);
+    }
+
+    #[test]
+    fn test_validate_embedded_needs_one_master() {
+        let topology = create_topology(TopologyType::Embedded);
+        let one = vec![
+            create_test_node("node1", NodeRole::Master),
+            create_test_node("node2", NodeRole::Sub),
+        ];
+        assert!(validate_topology(&topology, &one).is_ok());
+
+        let none = vec![create_test_node("node1", NodeRole::Sub)];
+        assert!(validate_topology(&topology, &none).is_err());
+
+        let two = vec![
+            create_test_node("node1", NodeRole::Master),
+            create_test_node("node2", NodeRole::Master),
+        ];
+        assert!(validate_topology(&topology, &two).is_err());
+
+        // Other topology types may have several masters
+        let topology = create_topology(TopologyType::Distributed);
+        assert!(validate_topology(&topology, &two).is_ok());
+    }
+
+    #[test]
+    fn test_validate_parent_cluster() {
+        let mut topology = create_topology(TopologyType::MultiCluster);
+        topology.parent_cluster = "central-cluster".to_string();
+        assert!(validate_topology(&topology, &[]).is_ok());
+
+        topology.parent_cluster = topology.cluster_id.clone();
+        assert!(validate_topology(&topology, &[]).is_err());
+
+        let mut topology = create_topology(TopologyType::HybridCloud);
+        topology.parent_cluster = "central-cluster".to_string();
+        assert!(validate_topology(&topology, &[]).is_err());
+
+        let topology = create_topology(TopologyType::Unspecified);
+        assert!(validate_topology(&topology, &[]).is_err());
+    }
 }
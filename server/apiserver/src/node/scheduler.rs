@@ -0,0 +1,304 @@
+/*
+ * SPDX-FileCopyrightText: Copyright 2024 LG Electronics Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+//! Model placement onto registered cluster nodes
+
+use super::NodeManager;
+use common::apiserver::NodeInfo;
+use common::nodeagent::NodeStatus;
+use common::spec::artifact::{Package, Placement};
+use std::collections::HashMap;
+
+/// Current resource usage of a node as stored by monitoringserver
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct NodeUsage {
+    /// CPU usage in percent of all cores
+    pub cpu_usage: f64,
+    /// Used memory in bytes
+    pub used_memory: u64,
+}
+
+/// Scheduler choosing nodes for models that define a placement
+#[derive(Clone)]
+pub struct NodeScheduler;
+
+impl NodeScheduler {
+    /// Bind models without a node to a registered node
+    ///
+    /// Every model of a Package that has no `node` but a `placement` is bound
+    /// to the node chosen by `select_node()`. The binding is written into the
+    /// returned artifact so that it is stored in etcd and used by the other
+    /// components. Artifacts without such models are returned unchanged.
+    pub async fn schedule_artifact(
+        &self,
+        body: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let mut docs = Vec::new();
+        let mut changed = false;
+
+        for doc in body.split("---") {
+            match self.schedule_package(doc).await? {
+                Some(scheduled) => {
+                    docs.push(scheduled);
+                    changed = true;
+                }
+                None => docs.push(doc.to_string()),
+            }
+        }
+
+        if changed {
+            Ok(docs.join("---\n"))
+        } else {
+            Ok(body.to_string())
+        }
+    }
+
+    /// Choose the node for a placement among all registered nodes
+    pub async fn select_node(
+        &self,
+        placement: &Placement,
+    ) -> Result<NodeInfo, Box<dyn std::error::Error + Send + Sync>> {
+        let nodes = NodeManager.get_all_nodes().await?;
+
+        let mut usage = HashMap::new();
+        for node in &nodes {
+            if let Some(node_usage) = get_node_usage(&node.hostname).await {
+                usage.insert(node.node_id.clone(), node_usage);
+            }
+        }
+
+        pick_node(placement, &nodes, &usage)
+            .ok_or_else(|| "No registered node satisfies the placement".into())
+    }
+
+    async fn schedule_package(
+        &self,
+        doc: &str,
+    ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        // Documents that cannot be parsed are reported by artifact::apply()
+        let Ok(value) = serde_yaml::from_str::<serde_yaml::Value>(doc) else {
+            return Ok(None);
+        };
+        if value.get("kind").and_then(|k| k.as_str()) != Some("Package") {
+            return Ok(None);
+        }
+        let Ok(package) = serde_yaml::from_value::<Package>(value) else {
+            return Ok(None);
+        };
+
+        let mut scheduled: Option<String> = None;
+        for model in package.get_models() {
+            if !model.get_node().is_empty() {
+                continue;
+            }
+            let placement = model.get_placement().ok_or_else(|| {
+                format!("Model {} has neither node nor placement", model.get_name())
+            })?;
+            let node = self.select_node(&placement).await?;
+
+            let current = scheduled.take().unwrap_or_else(|| doc.to_string());
+            let updated =
+                crate::artifact::set_model_node(&current, &model.get_name(), &node.hostname)
+                    .map_err(|e| e.to_string())?;
+            scheduled = Some(updated);
+
+            println!("Model {} bound to node {}", model.get_name(), node.hostname);
+        }
+
+        Ok(scheduled)
+    }
+}
+
+/// Read the latest usage of a node reported to monitoringserver
+async fn get_node_usage(hostname: &str) -> Option<NodeUsage> {
+    let key = format!("monitoring/nodes/{}", hostname);
+    let json_data = common::etcd::get(&key).await.ok()?;
+    serde_json::from_str(&json_data).ok()
+}
+
+/// Free cpu cores and memory in MB of a node
+fn get_free_resources(node: &NodeInfo, usage: Option<&NodeUsage>) -> (f64, i64) {
+    let Some(resources) = &node.resources else {
+        return (0.0, 0);
+    };
+    let usage = usage.cloned().unwrap_or_default();
+
+    let cpu_usage = usage.cpu_usage.clamp(0.0, 100.0);
+    let free_cpu = resources.cpu_cores as f64 * (1.0 - cpu_usage / 100.0);
+    let free_memory = resources.memory_mb - (usage.used_memory / (1024 * 1024)) as i64;
+
+    (free_cpu, free_memory)
+}
+
+/// Pick the node with the most free memory that satisfies a placement
+///
+/// A node is a candidate when it accepts workloads, has every label of the
+/// node selector in its metadata and enough free resources for the requests.
+/// Ties are broken by free cpu and then by node ID.
+pub fn pick_node(
+    placement: &Placement,
+    nodes: &[NodeInfo],
+    usage: &HashMap<String, NodeUsage>,
+) -> Option<NodeInfo> {
+    let selector = placement.get_node_selector();
+    let requests = placement.get_requests();
+
+    nodes
+        .iter()
+        .filter(|node| {
+            NodeManager::is_schedulable(node) && node.status != i32::from(NodeStatus::NotReady)
+        })
+        .filter(|node| {
+            selector
+                .iter()
+                .all(|(key, value)| node.metadata.get(key) == Some(value))
+        })
+        .filter_map(|node| {
+            let (free_cpu, free_memory) = get_free_resources(node, usage.get(&node.node_id));
+            if free_cpu >= requests.get_cpu() && free_memory >= requests.get_memory() {
+                Some((node, free_cpu, free_memory))
+            } else {
+                None
+            }
+        })
+        .max_by(|a, b| {
+            a.2.cmp(&b.2)
+                .then(a.1.total_cmp(&b.1))
+                .then(b.0.node_id.cmp(&a.0.node_id))
+        })
+        .map(|(node, _, _)| node.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::nodeagent::{NodeRole, ResourceInfo};
+
+    fn create_test_node(node_id: &str, variant: &str, memory_mb: i64) -> NodeInfo {
+        let mut metadata = HashMap::new();
+        metadata.insert("variant".to_string(), variant.to_string());
+
+        NodeInfo {
+            node_id: node_id.to_string(),
+            hostname: format!("host-{}", node_id),
+            ip_address: "192.168.1.100".to_string(),
+            role: NodeRole::Sub.into(),
+            status: NodeStatus::Ready.into(),
+            resources: Some(ResourceInfo {
+                cpu_cores: 4,
+                memory_mb,
+                disk_gb: 100,
+                architecture: "x86_64".to_string(),
+                os_version: "Ubuntu 20.04".to_string(),
+            }),
+            last_heartbeat: 1234567890,
+            created_at: 1234567890,
+            metadata,
+        }
+    }
+
+    fn create_placement(yaml: &str) -> Placement {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_pick_node_by_selector() {
+        let nodes = vec![
+            create_test_node("node1", "base", 8192),
+            create_test_node("node2", "premium", 4096),
+        ];
+        let placement = create_placement("nodeSelector:\n  variant: premium\n");
+
+        let node = pick_node(&placement, &nodes, &HashMap::new()).unwrap();
+        assert_eq!(node.node_id, "node2");
+
+        let placement = create_placement("nodeSelector:\n  variant: sport\n");
+        assert!(pick_node(&placement, &nodes, &HashMap::new()).is_none());
+    }
+
+    #[test]
+    fn test_pick_node_by_resources() {
+        let nodes = vec![
+            create_test_node("node1", "base", 8192),
+            create_test_node("node2", "base", 4096),
+        ];
+        let placement = create_placement("requests:\n  cpu: 2\n  memory: 6000\n");
+        let node = pick_node(&placement, &nodes, &HashMap::new()).unwrap();
+        assert_eq!(node.node_id, "node1");
+
+        // node1 has only 2 GB left according to monitoring data
+        let mut usage = HashMap::new();
+        usage.insert(
+            "node1".to_string(),
+            NodeUsage {
+                cpu_usage: 10.0,
+                used_memory: 6144 * 1024 * 1024,
+            },
+        );
+        assert!(pick_node(&placement, &nodes, &usage).is_none());
+
+        let placement = create_placement("requests:\n  memory: 3000\n");
+        let node = pick_node(&placement, &nodes, &usage).unwrap();
+        assert_eq!(node.node_id, "node2");
+
+        let placement = create_placement("requests:\n  cpu: 8\n");
+        assert!(pick_node(&placement, &nodes, &HashMap::new()).is_none());
+    }
+
+    #[test]
+    fn test_pick_node_skips_unschedulable() {
+        let mut cordoned = create_test_node("node1", "base", 8192);
+        cordoned.status = NodeStatus::Maintenance.into();
+        let mut not_ready = create_test_node("node2", "base", 8192);
+        not_ready.status = NodeStatus::NotReady.into();
+        let nodes = vec![cordoned, not_ready, create_test_node("node3", "base", 1024)];
+
+        let node = pick_node(&Placement::default(), &nodes, &HashMap::new()).unwrap();
+        assert_eq!(node.node_id, "node3");
+    }
+
+    #[tokio::test]
+    async fn test_schedule_artifact_without_placement() {
+        let body = r#"
+apiVersion: v1
+kind: Package
+metadata:
+  label: null
+  name: helloworld
+spec:
+  pattern:
+    - type: plain
+  models:
+    - name: helloworld-core
+      node: HPC
+      resources:
+        volume:
+        network:
+"#;
+        let scheduled = NodeScheduler.schedule_artifact(body).await.unwrap();
+        assert_eq!(scheduled, body);
+    }
+
+    #[tokio::test]
+    async fn test_schedule_artifact_missing_node_and_placement() {
+        let body = r#"
+apiVersion: v1
+kind: Package
+metadata:
+  label: null
+  name: helloworld
+spec:
+  pattern:
+    - type: plain
+  models:
+    - name: helloworld-core
+      resources:
+        volume:
+        network:
+"#;
+        let result = NodeScheduler.schedule_artifact(body).await;
+        assert!(result.is_err());
+    }
+}
@@ -6,10 +6,16 @@
 //! Handler functions of Piccolo REST API
 
 use axum::{
-    response::Response,
-    routing::{delete, get, post},
-    Router,
+    extract::Path,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, get, post, put},
+    Json, Router,
 };
+use common::apiserver::{ClusterTopology, NodeInfo, TopologyType};
+use common::filtergateway::{FilterStatus, ScenarioFilterRequest};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Make router type for composing handler and Piccolo service
 ///
@@ -20,6 +26,15 @@ pub fn router() -> Router {
         .route("/api/notify", get(notify))
         .route("/api/artifact", post(apply_artifact))
         .route("/api/artifact", delete(withdraw_artifact))
+        .route("/api/node/:node_id/cordon", post(cordon_node))
+        .route("/api/node/:node_id/uncordon", post(uncordon_node))
+        .route("/api/node/:node_id/drain", post(drain_node))
+        .route("/api/topology", get(get_topology))
+        .route("/api/topology", put(update_topology))
+        .route("/api/config/push", post(push_config))
+        .route("/api/scenario/:name/pause", post(pause_scenario))
+        .route("/api/scenario/:name/resume", post(resume_scenario))
+        .route("/api/filters", get(list_filters))
 }
 
 /// Notify of new artifact release in the cloud
@@ -52,6 +67,241 @@ async fn withdraw_artifact(body: String) -> Response {
     super::status(result)
 }
 
+/// Cordon the node so that no new models are placed on it
+///
+/// ### Parameters
+/// * `node_id: String` - ID of the node to put in maintenance
+async fn cordon_node(Path(node_id): Path<String>) -> Response {
+    let result = crate::node::NodeManager
+        .cordon_node(&node_id)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string().into());
+
+    super::status(result)
+}
+
+/// Release the cordoned node
+///
+/// ### Parameters
+/// * `node_id: String` - ID of the node to release from maintenance
+async fn uncordon_node(Path(node_id): Path<String>) -> Response {
+    let result = crate::node::NodeManager
+        .uncordon_node(&node_id)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string().into());
+
+    super::status(result)
+}
+
+/// Cordon the node and stop or move its workloads
+///
+/// ### Parameters
+/// * `node_id: String` - ID of the node to drain
+async fn drain_node(Path(node_id): Path<String>) -> Response {
+    let result = crate::manager::drain_node(&node_id)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string().into());
+
+    super::status(result)
+}
+
+/// Push the cluster settings to nodes
+///
+/// ### Parameters
+/// * `body: String` - ID of the node, empty to push to all registered nodes
+async fn push_config(body: String) -> Response {
+    let node_id = Some(body.trim()).filter(|id| !id.is_empty());
+    let result = match crate::node::NodeRegistry.push_config(node_id).await {
+        Ok(failed) if failed.is_empty() => Ok(()),
+        Ok(failed) => Err(format!("Configuration not applied on {}", failed.join(", ")).into()),
+        Err(e) => Err(e.to_string().into()),
+    };
+
+    super::status(result)
+}
+
+/// Stop evaluating the condition of a scenario without withdrawing it
+///
+/// ### Parameters
+/// * `name: String` - name of the scenario to pause
+async fn pause_scenario(Path(name): Path<String>) -> Response {
+    let req = ScenarioFilterRequest {
+        scenario_name: name,
+    };
+    let result = crate::grpc::sender::filtergateway::pause_scenario(req)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.message().into());
+
+    super::status(result)
+}
+
+/// Evaluate the condition of a paused scenario again
+///
+/// ### Parameters
+/// * `name: String` - name of the scenario to resume
+async fn resume_scenario(Path(name): Path<String>) -> Response {
+    let req = ScenarioFilterRequest {
+        scenario_name: name,
+    };
+    let result = crate::grpc::sender::filtergateway::resume_scenario(req)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.message().into());
+
+    super::status(result)
+}
+
+/// Scenario filter as exchanged over REST
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct FilterSpec {
+    scenario_name: String,
+    topic: String,
+    active: bool,
+    /// Receive time of the last evaluated sample in unix milliseconds
+    last_sample_time: Option<i64>,
+    last_value: Option<String>,
+    last_result: Option<bool>,
+    trigger_count: u64,
+}
+
+impl From<FilterStatus> for FilterSpec {
+    fn from(status: FilterStatus) -> Self {
+        // No sample was evaluated yet if there is no sample time
+        let sampled = status.last_sample_time > 0;
+
+        FilterSpec {
+            scenario_name: status.scenario_name,
+            topic: status.topic,
+            active: status.active,
+            last_sample_time: sampled.then_some(status.last_sample_time),
+            last_value: sampled.then_some(status.last_value),
+            last_result: sampled.then_some(status.last_result),
+            trigger_count: status.trigger_count,
+        }
+    }
+}
+
+/// Get the scenario filters with their current condition state
+///
+/// ### Parameters
+/// None
+async fn list_filters() -> Response {
+    match crate::grpc::sender::filtergateway::list_filters().await {
+        Ok(response) => {
+            let filters: Vec<FilterSpec> = response
+                .into_inner()
+                .filters
+                .into_iter()
+                .map(FilterSpec::from)
+                .collect();
+            (StatusCode::OK, Json(filters)).into_response()
+        }
+        Err(e) => super::status(Err(e.message().into())),
+    }
+}
+
+/// Cluster topology as exchanged over REST, nodes are given by node ID
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct TopologySpec {
+    cluster_id: String,
+    cluster_name: String,
+    /// `embedded`, `hybrid_cloud`, `multi_cluster` or `distributed`
+    #[serde(rename = "type")]
+    topology_type: String,
+    #[serde(default)]
+    parent_cluster: String,
+    #[serde(default)]
+    master_nodes: Vec<String>,
+    #[serde(default)]
+    sub_nodes: Vec<String>,
+    #[serde(default)]
+    config: HashMap<String, String>,
+}
+
+impl From<ClusterTopology> for TopologySpec {
+    fn from(topology: ClusterTopology) -> Self {
+        let topology_type = TopologyType::try_from(topology.r#type)
+            .unwrap_or_default()
+            .as_str_name()
+            .trim_start_matches("TOPOLOGY_TYPE_")
+            .to_ascii_lowercase();
+        let node_ids = |nodes: Vec<NodeInfo>| nodes.into_iter().map(|n| n.node_id).collect();
+
+        TopologySpec {
+            cluster_id: topology.cluster_id,
+            cluster_name: topology.cluster_name,
+            topology_type,
+            parent_cluster: topology.parent_cluster,
+            master_nodes: node_ids(topology.master_nodes),
+            sub_nodes: node_ids(topology.sub_nodes),
+            config: topology.config,
+        }
+    }
+}
+
+impl TryFrom<TopologySpec> for ClusterTopology {
+    type Error = String;
+
+    fn try_from(spec: TopologySpec) -> Result<Self, Self::Error> {
+        let type_name = format!("TOPOLOGY_TYPE_{}", spec.topology_type.to_ascii_uppercase());
+        let topology_type = TopologyType::from_str_name(&type_name)
+            .ok_or_else(|| format!("Unknown topology type {}", spec.topology_type))?;
+        let nodes = |ids: Vec<String>| {
+            ids.into_iter()
+                .map(|node_id| NodeInfo {
+                    node_id,
+                    ..Default::default()
+                })
+                .collect()
+        };
+
+        Ok(ClusterTopology {
+            cluster_id: spec.cluster_id,
+            cluster_name: spec.cluster_name,
+            r#type: topology_type.into(),
+            master_nodes: nodes(spec.master_nodes),
+            sub_nodes: nodes(spec.sub_nodes),
+            parent_cluster: spec.parent_cluster,
+            config: spec.config,
+        })
+    }
+}
+
+/// Get the cluster topology with its current master and sub nodes
+///
+/// ### Parameters
+/// None
+async fn get_topology() -> Response {
+    match crate::node::NodeRegistry.get_topology().await {
+        Ok(topology) => (StatusCode::OK, Json(TopologySpec::from(topology))).into_response(),
+        Err(e) => super::status(Err(e.to_string().into())),
+    }
+}
+
+/// Update the cluster topology
+///
+/// ### Parameters
+/// * `body: String` - topology in yaml or json format
+async fn update_topology(body: String) -> Response {
+    let result = async {
+        let spec: TopologySpec = serde_yaml::from_str(&body).map_err(|e| e.to_string())?;
+        let topology = ClusterTopology::try_from(spec)?;
+        crate::node::NodeRegistry
+            .update_topology(topology)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok::<(), String>(())
+    }
+    .await
+    .map_err(|e| e.into());
+
+    super::status(result)
+}
+
 //UNIT TEST CASES
 #[cfg(test)]
 mod tests {
@@ -139,6 +389,106 @@ spec:
         status(Ok(()))
     }
 
+    /// Negative test: GET on node maintenance endpoints returns 405 Method Not Allowed
+    #[tokio::test]
+    async fn test_node_maintenance_invalid_method() {
+        for action in ["cordon", "uncordon", "drain"] {
+            let req = Request::builder()
+                .method("GET")
+                .uri(format!("/api/node/test-node/{}", action))
+                .body(Body::empty())
+                .unwrap();
+
+            let response = router().oneshot(req).await.unwrap();
+            assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_topology_unknown_type() {
+        let req = Request::builder()
+            .method("PUT")
+            .uri("/api/topology")
+            .body(Body::from(
+                r#"{"cluster_id": "c1", "cluster_name": "C1", "type": "mesh"}"#,
+            ))
+            .unwrap();
+
+        let response = router().oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        // The same status is returned for a missing route, check the reason
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let message: String = serde_json::from_slice(&body).unwrap();
+        assert_eq!(message, "Unknown topology type mesh");
+    }
+
+    #[test]
+    fn test_topology_spec_conversion() {
+        let spec: TopologySpec = serde_yaml::from_str(
+            r#"
+cluster_id: vehicle-1
+cluster_name: Vehicle 1
+type: multi_cluster
+parent_cluster: fleet
+master_nodes: [hpc-192.168.0.1]
+sub_nodes: [zone-192.168.0.2]
+"#,
+        )
+        .unwrap();
+
+        let topology = ClusterTopology::try_from(spec).unwrap();
+        assert_eq!(topology.r#type, i32::from(TopologyType::MultiCluster));
+        assert_eq!(topology.master_nodes[0].node_id, "hpc-192.168.0.1");
+        assert_eq!(topology.sub_nodes[0].node_id, "zone-192.168.0.2");
+
+        let spec = TopologySpec::from(topology);
+        assert_eq!(spec.topology_type, "multi_cluster");
+        assert_eq!(spec.parent_cluster, "fleet");
+        assert_eq!(spec.master_nodes, vec!["hpc-192.168.0.1".to_string()]);
+    }
+
+    #[test]
+    fn test_filter_spec_conversion() {
+        let spec = FilterSpec::from(FilterStatus {
+            scenario_name: "parking".to_string(),
+            topic: "rt/piccolo/gear_state".to_string(),
+            active: true,
+            ..Default::default()
+        });
+        assert_eq!(spec.last_sample_time, None);
+        assert_eq!(spec.last_result, None);
+
+        let spec = FilterSpec::from(FilterStatus {
+            scenario_name: "parking".to_string(),
+            active: false,
+            last_sample_time: 1_700_000_000_000,
+            last_value: "P".to_string(),
+            last_result: true,
+            trigger_count: 2,
+            ..Default::default()
+        });
+        assert_eq!(spec.last_value.as_deref(), Some("P"));
+        assert_eq!(spec.last_result, Some(true));
+        assert_eq!(spec.trigger_count, 2);
+    }
+
+    /// Negative test: GET on scenario pause and resume returns 405 Method Not Allowed
+    #[tokio::test]
+    async fn test_scenario_filter_invalid_method() {
+        for action in ["pause", "resume"] {
+            let req = Request::builder()
+                .method("GET")
+                .uri(format!("/api/scenario/helloworld/{}", action))
+                .body(Body::empty())
+                .unwrap();
+
+            let response = router().oneshot(req).await.unwrap();
+            assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        }
+    }
+
     /// Mock implementation of notify that just returns OK
     async fn mock_notify() -> Response {
         status(Ok(()))
@@ -3,7 +3,7 @@
  * SPDX-License-Identifier: Apache-2.0
  */
 
-use common::monitoringserver::NodeInfo;
+use common::monitoringserver::{ContainerInfo, ContainerList, NodeInfo};
 use std::collections::HashMap;
 use std::net::Ipv4Addr;
 use std::str::FromStr;
@@ -51,6 +51,8 @@ pub struct DataStore {
     pub nodes: HashMap<String, NodeInfo>,
     pub socs: HashMap<String, SocInfo>,
     pub boards: HashMap<String, BoardInfo>,
+    /// Containers of each node, keyed by node name and then container ID
+    pub containers: HashMap<String, HashMap<String, ContainerInfo>>,
 }
 
 impl DataStore {
@@ -59,9 +61,25 @@ impl DataStore {
             nodes: HashMap::new(),
             socs: HashMap::new(),
             boards: HashMap::new(),
+            containers: HashMap::new(),
         }
     }
 
+    /// Applies a ContainerList delta to the containers stored for its node
+    ///
+    /// Listed containers are added or replaced and removed IDs are dropped.
+    /// Returns the number of containers now stored for the node.
+    pub fn apply_container_list(&mut self, container_list: ContainerList) -> usize {
+        let containers = self.containers.entry(container_list.node_name).or_default();
+        for id in &container_list.removed_ids {
+            containers.remove(id);
+        }
+        for container in container_list.containers {
+            containers.insert(container.id.clone(), container);
+        }
+        containers.len()
+    }
+
     /// Stores NodeInfo and updates corresponding SocInfo and BoardInfo
     pub fn store_node_info(&mut self, node_info: NodeInfo) -> Result<(), String> {
         let node_name = node_info.node_name.clone();
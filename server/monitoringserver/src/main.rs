@@ -35,7 +35,7 @@ async fn launch_manager(rx_container: Receiver<ContainerList>, rx_node: Receiver
 ///
 /// Sets up the gRPC service and starts listening for incoming requests.
 async fn initialize(tx_container: Sender<ContainerList>, tx_node: Sender<NodeInfo>) {
-    use tonic::transport::Server;
+    use common::transport::Component;
 
     let server = grpc::receiver::MonitoringServerReceiver {
         tx_container,
@@ -47,7 +47,14 @@ async fn initialize(tx_container: Sender<ContainerList>, tx_node: Sender<NodeInf
         .expect("monitoringserver address parsing error");
     println!("MonitoringServer listening on {}", addr);
 
-    if let Err(e) = Server::builder()
+    let mut builder = match common::transport::server(Component::MonitoringServer) {
+        Ok(builder) => builder,
+        Err(e) => {
+            eprintln!("Failed to configure MonitoringServer gRPC server: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = builder
         .add_service(MonitoringServerConnectionServer::new(server))
         .serve(addr)
         .await
@@ -59,6 +66,7 @@ async fn initialize(tx_container: Sender<ContainerList>, tx_node: Sender<NodeInf
 #[tokio::main]
 async fn main() {
     println!("Starting MonitoringServer...");
+    common::transport::set_local_component(common::transport::Component::MonitoringServer);
 
     let (tx_container, rx_container) = channel::<ContainerList>(100);
     let (tx_node, rx_node) = channel::<NodeInfo>(100);
@@ -63,8 +63,21 @@ impl MonitoringServerManager {
                 container.id, container.names, container.image
             );
         }
+        for id in &container_list.removed_ids {
+            println!("  Removed container: ID={}", id);
+        }
 
-        // TODO: Add your container processing logic here
+        // The list only holds changes since the previous one of the node
+        let node_name = container_list.node_name.clone();
+        let stored = self
+            .data_store
+            .lock()
+            .await
+            .apply_container_list(container_list);
+        println!(
+            "[MonitoringServer] {} containers stored for {}",
+            stored, node_name
+        );
     }
 
     /// Processes NodeInfo messages from nodeagent.